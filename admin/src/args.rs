@@ -33,6 +33,36 @@ pub enum Command {
 
     /// Forcefully idle CCH projects.
     IdleCch,
+
+    /// View or override the container limits of a project
+    Limits(LimitsArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct LimitsArgs {
+    /// Project to manage the container limits of
+    pub project: String,
+
+    /// Set the limits to these values instead of the limits of the owner's tier. Takes effect the
+    /// next time the project is recreated
+    #[arg(long, requires_all = ["memory_mib", "pids", "disk_gib"], conflicts_with = "clear")]
+    pub cpu_millis: Option<u64>,
+
+    /// Hard memory limit, in MiB
+    #[arg(long, requires = "cpu_millis")]
+    pub memory_mib: Option<u64>,
+
+    /// Maximum number of processes and threads
+    #[arg(long, requires = "cpu_millis")]
+    pub pids: Option<u64>,
+
+    /// Size of the writable layer of the container, in GiB
+    #[arg(long, requires = "cpu_millis")]
+    pub disk_gib: Option<u64>,
+
+    /// Remove the override so the project goes back to the limits of its owner's tier
+    #[arg(long)]
+    pub clear: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -99,6 +129,9 @@ pub enum StatsCommand {
         #[arg(long)]
         clear: bool,
     },
+
+    /// View the certificates whose automatic renewal is failing
+    Certificates,
 }
 
 fn load_credentials(s: &str) -> Result<serde_json::Value, Error> {
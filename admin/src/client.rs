@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use serde::{de::DeserializeOwned, Serialize};
-use shuttle_common::models::{admin::ProjectResponse, stats, ToJson};
+use shuttle_common::models::{admin::ProjectResponse, project::ContainerLimits, stats, ToJson};
 use tracing::trace;
 
 pub struct Client {
@@ -77,11 +77,45 @@ impl Client {
         self.get("/admin/stats/load").await
     }
 
+    pub async fn get_certificate_renewal_failures(
+        &self,
+    ) -> Result<Vec<stats::CertificateRenewalFailure>> {
+        self.get("/admin/stats/certificates").await
+    }
+
     pub async fn clear_load(&self) -> Result<stats::LoadResponse> {
         self.delete("/admin/stats/load", Option::<String>::None)
             .await
     }
 
+    pub async fn get_container_limits(
+        &self,
+        project_name: &str,
+    ) -> Result<Option<ContainerLimits>> {
+        self.get(&format!("/admin/projects/{project_name}/limits"))
+            .await
+    }
+
+    pub async fn set_container_limits(
+        &self,
+        project_name: &str,
+        limits: Option<ContainerLimits>,
+    ) -> Result<Option<ContainerLimits>> {
+        reqwest::Client::new()
+            .put(format!(
+                "{}/admin/projects/{project_name}/limits",
+                self.api_url
+            ))
+            .bearer_auth(&self.api_key)
+            .json(&limits)
+            .send()
+            .await
+            .context("failed to make put request")?
+            .to_json()
+            .await
+            .context("failed to extract json body from put response")
+    }
+
     async fn post<T: Serialize, R: DeserializeOwned>(
         &self,
         path: &str,
@@ -1,9 +1,10 @@
 use clap::Parser;
 use shuttle_admin::{
-    args::{AcmeCommand, Args, Command, StatsCommand},
+    args::{AcmeCommand, Args, Command, LimitsArgs, StatsCommand},
     client::Client,
     config::get_api_key,
 };
+use shuttle_common::models::project::ContainerLimits;
 use std::{
     collections::{hash_map::RandomState, HashMap},
     fmt::Write,
@@ -161,10 +162,72 @@ async fn main() {
                 resp.builds_count, has_capacity
             )
         }
+        Command::Stats(StatsCommand::Certificates) => {
+            let failures = client
+                .get_certificate_renewal_failures()
+                .await
+                .expect("to get certificate renewal failures");
+
+            if failures.is_empty() {
+                "All certificate renewals succeeded".to_string()
+            } else {
+                let mut res = String::new();
+
+                for failure in failures {
+                    writeln!(
+                        res,
+                        "{}: failed {} times, last at {}: {}",
+                        failure.fqdn, failure.failures, failure.last_attempt_at, failure.last_error
+                    )
+                    .expect("to write certificate renewal failure");
+                }
+
+                res
+            }
+        }
         Command::IdleCch => {
             client.idle_cch().await.expect("cch projects to be idled");
             "Idled CCH projects".to_string()
         }
+        Command::Limits(LimitsArgs {
+            project,
+            cpu_millis,
+            memory_mib,
+            pids,
+            disk_gib,
+            clear,
+        }) => {
+            let limits = if clear {
+                client
+                    .set_container_limits(&project, None)
+                    .await
+                    .expect("to clear container limits")
+            } else if let (Some(cpu_millis), Some(memory_mib), Some(pids), Some(disk_gib)) =
+                (cpu_millis, memory_mib, pids, disk_gib)
+            {
+                let limits = ContainerLimits {
+                    cpu_millis,
+                    memory_mib,
+                    pids,
+                    disk_gib,
+                };
+
+                client
+                    .set_container_limits(&project, Some(limits))
+                    .await
+                    .expect("to set container limits")
+            } else {
+                client
+                    .get_container_limits(&project)
+                    .await
+                    .expect("to get container limits")
+            };
+
+            match limits {
+                Some(limits) => format!("{project} is limited to {limits}"),
+                None => format!("{project} uses the limits of its owner's tier"),
+            }
+        }
     };
 
     println!("{res}");
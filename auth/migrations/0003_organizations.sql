@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS organizations (
+  org_name TEXT PRIMARY KEY,
+  created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS organization_members (
+  org_name TEXT NOT NULL,
+  account_name TEXT NOT NULL,
+  role TEXT NOT NULL,
+  PRIMARY KEY (org_name, account_name),
+  FOREIGN KEY (org_name) REFERENCES organizations(org_name) ON DELETE CASCADE,
+  FOREIGN KEY (account_name) REFERENCES users(account_name)
+);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS api_keys (
+  key TEXT PRIMARY KEY,
+  account_name TEXT NOT NULL,
+  name TEXT NOT NULL,
+  -- NULL means the key is not restricted to a subset of the scopes or projects of the account
+  scopes TEXT[],
+  projects TEXT[],
+  created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  expires_at TIMESTAMPTZ,
+  last_used_at TIMESTAMPTZ,
+  UNIQUE (account_name, name),
+  FOREIGN KEY (account_name) REFERENCES users(account_name)
+);
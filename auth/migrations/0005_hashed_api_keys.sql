@@ -0,0 +1,24 @@
+-- Keys are stored as salted hashes, and found again by a short prefix of the key that is kept in
+-- the clear. Keys that are still in the clear are hashed by the auth service when it starts,
+-- since the database cannot derive the hashes itself.
+CREATE TABLE IF NOT EXISTS account_keys (
+  account_name TEXT NOT NULL,
+  key_prefix TEXT NOT NULL,
+  key_salt BYTEA NOT NULL,
+  key_hash BYTEA NOT NULL,
+  created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  -- Only set for keys that have been rotated, which remain valid for a grace period
+  expires_at TIMESTAMPTZ,
+  FOREIGN KEY (account_name) REFERENCES users(account_name)
+);
+
+CREATE INDEX IF NOT EXISTS account_keys_key_prefix ON account_keys (key_prefix);
+
+ALTER TABLE api_keys
+DROP CONSTRAINT api_keys_pkey,
+ALTER COLUMN key DROP NOT NULL,
+ADD COLUMN key_prefix TEXT,
+ADD COLUMN key_salt BYTEA,
+ADD COLUMN key_hash BYTEA;
+
+CREATE INDEX IF NOT EXISTS api_keys_key_prefix ON api_keys (key_prefix);
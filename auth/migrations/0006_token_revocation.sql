@@ -0,0 +1,23 @@
+-- Refresh tokens are stored hashed like keys, and are bound to the hash of the key that minted
+-- them so they stop working together with that key.
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+  account_name TEXT NOT NULL,
+  token_prefix TEXT NOT NULL,
+  token_salt BYTEA NOT NULL,
+  token_hash BYTEA NOT NULL,
+  api_key_hash BYTEA NOT NULL,
+  -- The organization the tokens are for, if any
+  org_name TEXT,
+  created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  expires_at TIMESTAMPTZ NOT NULL,
+  FOREIGN KEY (account_name) REFERENCES users(account_name)
+);
+
+CREATE INDEX IF NOT EXISTS refresh_tokens_token_prefix ON refresh_tokens (token_prefix);
+
+-- Tokens of an account issued before the time it was revoked are no longer valid
+CREATE TABLE IF NOT EXISTS token_revocations (
+  account_name TEXT PRIMARY KEY,
+  revoked_at TIMESTAMPTZ NOT NULL,
+  FOREIGN KEY (account_name) REFERENCES users(account_name)
+);
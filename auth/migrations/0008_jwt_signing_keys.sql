@@ -0,0 +1,8 @@
+-- Keys JWTs are signed with when signing keys are rotated, one for every rotation period. They are
+-- shared by all instances of the service, so each instance signs with and publishes the same keys.
+CREATE TABLE IF NOT EXISTS jwt_signing_keys (
+  period BIGINT PRIMARY KEY,
+  -- The private key, as a PKCS#8 v1 document
+  private_key BYTEA NOT NULL,
+  created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
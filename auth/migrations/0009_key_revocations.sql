@@ -0,0 +1,9 @@
+-- Tokens converted from a named key before the time the key was revoked are no longer valid. The
+-- tokens of the other keys of the account are kept.
+CREATE TABLE IF NOT EXISTS key_revocations (
+  account_name TEXT NOT NULL,
+  key_name TEXT NOT NULL,
+  revoked_at TIMESTAMPTZ NOT NULL,
+  PRIMARY KEY (account_name, key_name),
+  FOREIGN KEY (account_name) REFERENCES users(account_name)
+);
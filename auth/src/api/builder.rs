@@ -1,4 +1,4 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use axum::{
     extract::FromRef,
@@ -11,7 +11,7 @@ use shuttle_common::{
     request_span,
 };
 use sqlx::PgPool;
-use tracing::field;
+use tracing::{error, field};
 
 use crate::{
     secrets::{EdDsaManager, KeyManager},
@@ -19,10 +19,16 @@ use crate::{
 };
 
 use super::handlers::{
-    convert_key, delete_subscription, get_public_key, get_user, health_check, post_subscription,
-    post_user, put_user_reset_key, refresh_token,
+    convert_key, delete_api_key, delete_organization_member, delete_subscription, get_api_keys,
+    get_jwks, get_organization_members, get_organizations, get_public_key, get_revocations,
+    get_user, health_check, post_api_key, post_organization, post_stripe_webhook,
+    post_subscription, post_user, put_organization_member, put_user_reset_key, put_user_rotate_key,
+    refresh_token,
 };
 
+/// How often the jwt signing keys are reloaded when they are rotated
+const KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
 pub type UserManagerState = Arc<Box<dyn UserManagement>>;
 pub type KeyManagerState = Arc<Box<dyn KeyManager>>;
 
@@ -30,6 +36,8 @@ pub type KeyManagerState = Arc<Box<dyn KeyManager>>;
 pub struct RouterState {
     pub user_manager: UserManagerState,
     pub key_manager: KeyManagerState,
+    /// Secret Stripe signs webhook events with, if they are accepted
+    pub stripe_webhook_secret: Option<String>,
 }
 
 // Allow getting a user management state directly
@@ -50,7 +58,9 @@ pub struct ApiBuilder {
     router: Router<RouterState>,
     pool: Option<PgPool>,
     stripe_client: Option<stripe::Client>,
+    stripe_webhook_secret: Option<String>,
     jwt_signing_private_key: Option<String>,
+    jwt_signing_key_rotation: Option<Duration>,
 }
 
 impl Default for ApiBuilder {
@@ -65,15 +75,34 @@ impl ApiBuilder {
             .route("/", get(health_check))
             .route("/auth/key", get(convert_key))
             .route("/auth/refresh", post(refresh_token))
+            .route("/auth/revocations", get(get_revocations))
             .route("/public-key", get(get_public_key))
+            .route("/.well-known/jwks.json", get(get_jwks))
             .route("/users/:account_name", get(get_user))
             .route("/users/:account_name/:account_tier", post(post_user))
             .route("/users/reset-api-key", put(put_user_reset_key))
+            .route("/users/rotate-api-key", put(put_user_rotate_key))
+            .route("/users/api-keys", get(get_api_keys))
+            .route(
+                "/users/api-keys/:key_name",
+                post(post_api_key).delete(delete_api_key),
+            )
             .route("/users/:account_name/subscribe", post(post_subscription))
             .route(
                 "/users/:account_name/subscribe/:subscription_id",
                 delete(delete_subscription),
             )
+            .route("/webhooks/stripe", post(post_stripe_webhook))
+            .route("/organizations", get(get_organizations))
+            .route("/organizations/:org_name", post(post_organization))
+            .route(
+                "/organizations/:org_name/members",
+                get(get_organization_members),
+            )
+            .route(
+                "/organizations/:org_name/members/:account_name",
+                put(put_organization_member).delete(delete_organization_member),
+            )
             .route_layer(from_extractor::<Metrics>())
             .layer(
                 TraceLayer::new(|request| {
@@ -91,7 +120,9 @@ impl ApiBuilder {
             router,
             pool: None,
             stripe_client: None,
+            stripe_webhook_secret: None,
             jwt_signing_private_key: None,
+            jwt_signing_key_rotation: None,
         }
     }
 
@@ -105,26 +136,66 @@ impl ApiBuilder {
         self
     }
 
+    /// Accept Stripe webhook events signed with this secret
+    pub fn with_stripe_webhook_secret(mut self, secret: String) -> Self {
+        self.stripe_webhook_secret = Some(secret);
+        self
+    }
+
     pub fn with_jwt_signing_private_key(mut self, private_key: String) -> Self {
         self.jwt_signing_private_key = Some(private_key);
         self
     }
 
-    pub fn into_router(self) -> Router {
+    /// Sign JWTs with a new key every `interval`, instead of with the jwt signing private key. The
+    /// keys are stored in the database and published in the JWKS.
+    pub fn with_jwt_signing_key_rotation(mut self, interval: Duration) -> Self {
+        self.jwt_signing_key_rotation = Some(interval);
+        self
+    }
+
+    pub async fn into_router(self) -> Router {
         let pool = self.pool.expect("an sqlite pool is required");
         let stripe_client = self.stripe_client.expect("a stripe client is required");
-        let jwt_signing_private_key = self
-            .jwt_signing_private_key
-            .expect("a jwt signing private key");
+
+        let key_manager = match self.jwt_signing_key_rotation {
+            Some(interval) => {
+                let key_manager = EdDsaManager::rotating(&pool, interval)
+                    .await
+                    .expect("to load the jwt signing keys");
+
+                // Keep storing and publishing the keys of upcoming periods
+                let refreshed = key_manager.clone();
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(KEY_REFRESH_INTERVAL);
+
+                    loop {
+                        interval.tick().await;
+
+                        if let Err(error) = refreshed.refresh(&pool).await {
+                            error!(error = %error, "failed to refresh jwt signing keys");
+                        }
+                    }
+                });
+
+                key_manager
+            }
+            None => EdDsaManager::new(
+                self.jwt_signing_private_key
+                    .expect("a jwt signing private key"),
+            ),
+        };
+
         let user_manager = UserManager {
             pool,
             stripe_client,
         };
-        let key_manager = EdDsaManager::new(jwt_signing_private_key);
 
         let state = RouterState {
             user_manager: Arc::new(Box::new(user_manager)),
             key_manager: Arc::new(Box::new(key_manager)),
+            stripe_webhook_secret: self.stripe_webhook_secret,
         };
 
         self.router.with_state(state)
@@ -1,15 +1,31 @@
 use crate::{
     error::Error,
-    user::{AccountName, Admin, Key},
+    user::{AccountName, Admin, Key, NamedKey, User},
+    webhook::{self, Event},
 };
+use std::collections::HashMap;
+use std::str::FromStr;
+
 use axum::{
-    extract::{Path, State},
+    body::Bytes,
+    extract::{Path, Query, State},
+    headers::HeaderMapExt,
+    http::HeaderMap,
     Json,
 };
 use http::StatusCode;
 use shuttle_common::{
-    claims::{AccountTier, Claim},
-    models::user::{self, SubscriptionRequest},
+    backends::{
+        auth::{ConvertQuery, ConvertResponse, Jwks, RefreshRequest, Revocations},
+        headers::XShuttleOrganization,
+    },
+    claims::{AccountTier, Claim, Scope},
+    models::{
+        api_key,
+        organization::{self, MemberRequest, Role},
+        user::{self, SubscriptionRequest},
+    },
+    ApiKey,
 };
 use tracing::instrument;
 
@@ -49,6 +65,78 @@ pub(crate) async fn put_user_reset_key(
     user_manager.reset_key(account_name).await
 }
 
+#[instrument(skip_all, fields(account.name = %user.name))]
+pub(crate) async fn put_user_rotate_key(
+    user: User,
+    State(user_manager): State<UserManagerState>,
+) -> Result<Json<user::RotateKeyResponse>, Error> {
+    let (key, previous_key_expires_at) = user_manager.rotate_key(&user.name).await?;
+
+    Ok(Json(user::RotateKeyResponse {
+        key: key.as_ref().to_string(),
+        previous_key_expires_at,
+    }))
+}
+
+#[instrument(skip_all, fields(account.name = %user.name))]
+pub(crate) async fn get_api_keys(
+    user: User,
+    State(user_manager): State<UserManagerState>,
+) -> Result<Json<Vec<api_key::Response>>, Error> {
+    let api_keys = user_manager
+        .get_named_keys(&user.name)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    Ok(Json(api_keys))
+}
+
+#[instrument(skip_all, fields(account.name = %user.name, api_key.name = %key_name))]
+pub(crate) async fn post_api_key(
+    user: User,
+    State(user_manager): State<UserManagerState>,
+    Path(key_name): Path<String>,
+    Json(request): Json<api_key::CreateRequest>,
+) -> Result<Json<api_key::Response>, Error> {
+    let scopes = request
+        .scopes
+        .map(|scopes| {
+            scopes
+                .into_iter()
+                .map(|scope| Scope::from_str(&scope).map_err(|_| Error::InvalidScope(scope)))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?;
+
+    let (key, named_key) = user_manager
+        .create_named_key(
+            &user.name,
+            &key_name,
+            scopes,
+            request.projects,
+            request.expires_at,
+        )
+        .await?;
+
+    let mut response: api_key::Response = named_key.into();
+    response.key = Some(key.as_ref().to_string());
+
+    Ok(Json(response))
+}
+
+#[instrument(skip_all, fields(account.name = %user.name, api_key.name = %key_name))]
+pub(crate) async fn delete_api_key(
+    user: User,
+    State(user_manager): State<UserManagerState>,
+    Path(key_name): Path<String>,
+) -> Result<Json<api_key::Response>, Error> {
+    let named_key = user_manager.revoke_named_key(&user.name, &key_name).await?;
+
+    Ok(Json(named_key.into()))
+}
+
 pub(crate) async fn post_subscription(
     _: Admin,
     State(user_manager): State<UserManagerState>,
@@ -79,41 +167,323 @@ pub(crate) async fn delete_subscription(
     Ok(())
 }
 
+/// Handle a Stripe webhook event, keeping the tier of an account in sync with its subscriptions.
+pub(crate) async fn post_stripe_webhook(
+    State(RouterState {
+        user_manager,
+        stripe_webhook_secret,
+        ..
+    }): State<RouterState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(), Error> {
+    let secret = stripe_webhook_secret.ok_or(Error::InvalidWebhook)?;
+    let signature = headers
+        .get("Stripe-Signature")
+        .and_then(|signature| signature.to_str().ok())
+        .ok_or(Error::InvalidWebhook)?;
+
+    if !webhook::verify_signature(&secret, signature, &body) {
+        return Err(Error::InvalidWebhook);
+    }
+
+    let event: Event = serde_json::from_slice(&body).map_err(|_| Error::InvalidWebhook)?;
+
+    // Acknowledge the events we don't act on, so Stripe does not retry them
+    if let Some(subscription_id) = event.subscription_id() {
+        user_manager
+            .sync_subscription(&event.id, &event.r#type, subscription_id)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[instrument(skip_all, fields(account.name = %user.name))]
+pub(crate) async fn get_organizations(
+    user: User,
+    State(user_manager): State<UserManagerState>,
+) -> Result<Json<Vec<organization::Response>>, Error> {
+    let organizations = user_manager
+        .get_organizations(&user.name)
+        .await?
+        .into_iter()
+        .map(|(name, role)| organization::Response { name, role })
+        .collect();
+
+    Ok(Json(organizations))
+}
+
+#[instrument(skip_all, fields(account.name = %user.name, org.name = %org_name))]
+pub(crate) async fn post_organization(
+    user: User,
+    State(user_manager): State<UserManagerState>,
+    Path(org_name): Path<String>,
+) -> Result<Json<organization::Response>, Error> {
+    if !matches!(user.account_tier, AccountTier::Team | AccountTier::Admin) {
+        return Err(Error::Forbidden);
+    }
+
+    user_manager
+        .create_organization(&org_name, &user.name)
+        .await?;
+
+    Ok(Json(organization::Response {
+        name: org_name,
+        role: Role::Owner,
+    }))
+}
+
+#[instrument(skip_all, fields(account.name = %user.name, org.name = %org_name))]
+pub(crate) async fn get_organization_members(
+    user: User,
+    State(user_manager): State<UserManagerState>,
+    Path(org_name): Path<String>,
+) -> Result<Json<Vec<organization::MemberResponse>>, Error> {
+    member_role(&user_manager, &org_name, &user).await?;
+
+    let members = user_manager
+        .get_members(&org_name)
+        .await?
+        .into_iter()
+        .map(|(account_name, role)| organization::MemberResponse {
+            account_name: account_name.to_string(),
+            role,
+        })
+        .collect();
+
+    Ok(Json(members))
+}
+
+#[instrument(skip_all, fields(account.name = %user.name, org.name = %org_name, member.name = %account_name))]
+pub(crate) async fn put_organization_member(
+    user: User,
+    State(user_manager): State<UserManagerState>,
+    Path((org_name, account_name)): Path<(String, AccountName)>,
+    Json(MemberRequest { role }): Json<MemberRequest>,
+) -> Result<Json<organization::MemberResponse>, Error> {
+    let caller_role = member_role(&user_manager, &org_name, &user).await?;
+    let current_role = user_manager
+        .get_member_role(&org_name, &account_name)
+        .await?;
+
+    let can_manage = caller_role.can_manage(role)
+        && current_role.map_or(true, |current_role| caller_role.can_manage(current_role));
+    if !can_manage {
+        return Err(Error::Forbidden);
+    }
+
+    user_manager
+        .set_member(&org_name, &account_name, role)
+        .await?;
+
+    Ok(Json(organization::MemberResponse {
+        account_name: account_name.to_string(),
+        role,
+    }))
+}
+
+#[instrument(skip_all, fields(account.name = %user.name, org.name = %org_name, member.name = %account_name))]
+pub(crate) async fn delete_organization_member(
+    user: User,
+    State(user_manager): State<UserManagerState>,
+    Path((org_name, account_name)): Path<(String, AccountName)>,
+) -> Result<Json<organization::MemberResponse>, Error> {
+    let caller_role = member_role(&user_manager, &org_name, &user).await?;
+    let role = user_manager
+        .get_member_role(&org_name, &account_name)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    // Anyone can leave an organization, but only those managing it can remove others
+    if account_name != user.name && !caller_role.can_manage(role) {
+        return Err(Error::Forbidden);
+    }
+
+    user_manager.remove_member(&org_name, &account_name).await?;
+
+    Ok(Json(organization::MemberResponse {
+        account_name: account_name.to_string(),
+        role,
+    }))
+}
+
+/// Get the role of the calling user in an organization. Organizations the user is not a member
+/// of are reported as missing, so their names are not leaked.
+async fn member_role(
+    user_manager: &UserManagerState,
+    org_name: &str,
+    user: &User,
+) -> Result<Role, Error> {
+    user_manager
+        .get_member_role(org_name, &user.name)
+        .await?
+        .ok_or(Error::OrganizationNotFound)
+}
+
 // Dummy health-check returning 200 if the auth server is up.
 pub(crate) async fn health_check() -> Result<(), Error> {
     Ok(())
 }
 
-/// Convert a valid API-key bearer token to a JWT.
+/// Convert a valid API-key bearer token to a JWT, with a refresh token if asked for.
 pub(crate) async fn convert_key(
     _: Admin,
     State(RouterState {
         key_manager,
         user_manager,
+        ..
     }): State<RouterState>,
     key: Key,
-) -> Result<Json<shuttle_common::backends::auth::ConvertResponse>, StatusCode> {
-    let user = user_manager
-        .get_user_by_key(key.into())
+    headers: HeaderMap,
+    Query(query): Query<ConvertQuery>,
+) -> Result<Json<ConvertResponse>, StatusCode> {
+    let key = key.into();
+
+    // Fall back to the named keys, which can only be used for what they are restricted to
+    let (user, named_key) = match user_manager.get_user_by_key(key.clone()).await {
+        Ok(user) => (user, None),
+        Err(_) => user_manager
+            .get_user_by_named_key(key.clone())
+            .await
+            .map(|(user, named_key)| (user, Some(named_key)))
+            .map_err(|_| StatusCode::UNAUTHORIZED)?,
+    };
+
+    // The organization the user wants to act for, if any
+    let org_name = headers
+        .typed_try_get::<XShuttleOrganization>()
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+        .map(|XShuttleOrganization(org_name)| org_name);
+
+    let claim = user_claim(&user_manager, user, named_key, org_name.clone()).await?;
+
+    // Tokens converted on every request, like by the gateway, would only pile up unused ones
+    let refresh_token = if query.refresh_token {
+        Some(
+            user_manager
+                .create_refresh_token(&key, org_name.as_deref())
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        )
+    } else {
+        None
+    };
+
+    sign_claim(&key_manager, claim, refresh_token)
+}
+
+/// Exchange a refresh token for a new JWT, with the same access as the JWT it was issued with.
+pub(crate) async fn refresh_token(
+    State(RouterState {
+        key_manager,
+        user_manager,
+        ..
+    }): State<RouterState>,
+    Json(request): Json<RefreshRequest>,
+) -> Result<Json<ConvertResponse>, StatusCode> {
+    let refresh_token =
+        ApiKey::parse(&request.refresh_token).map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    let refreshed = user_manager
+        .refresh(&refresh_token)
         .await
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        .map_err(|error| match error {
+            Error::Unauthorized => StatusCode::UNAUTHORIZED,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    let claim = user_claim(
+        &user_manager,
+        refreshed.user,
+        refreshed.named_key,
+        refreshed.org_name,
+    )
+    .await?;
+
+    sign_claim(&key_manager, claim, Some(refreshed.refresh_token))
+}
+
+/// Get the accounts and named keys with tokens that were revoked before they expired.
+pub(crate) async fn get_revocations(
+    State(user_manager): State<UserManagerState>,
+) -> Result<Json<Revocations>, Error> {
+    let accounts = user_manager
+        .get_revocations()
+        .await?
+        .into_iter()
+        .map(|(account_name, revoked_at)| (account_name.to_string(), revoked_at))
+        .collect();
+
+    let mut keys: HashMap<String, HashMap<String, _>> = HashMap::new();
+    for (account_name, key_name, revoked_at) in user_manager.get_key_revocations().await? {
+        keys.entry(account_name.to_string())
+            .or_default()
+            .insert(key_name, revoked_at);
+    }
+
+    Ok(Json(Revocations { accounts, keys }))
+}
+
+/// Build the claim of a user, acting for an organization or restricted to a named key if given
+async fn user_claim(
+    user_manager: &UserManagerState,
+    user: User,
+    named_key: Option<NamedKey>,
+    org_name: Option<String>,
+) -> Result<Claim, StatusCode> {
+    let role = match org_name {
+        Some(ref org_name) => Some(
+            user_manager
+                .get_member_role(org_name, &user.name)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .ok_or(StatusCode::FORBIDDEN)?,
+        ),
+        None => None,
+    };
 
-    let claim = Claim::new(
+    let mut claim = Claim::new(
         user.name.to_string(),
         user.account_tier.into(),
         user.account_tier,
         user,
     );
 
-    let token = claim.into_token(key_manager.private_key())?;
+    if let (Some(org_name), Some(role)) = (org_name, role) {
+        claim = claim.with_organization(org_name, role);
+    }
 
-    let response = shuttle_common::backends::auth::ConvertResponse { token };
+    if let Some(named_key) = named_key {
+        claim = claim
+            .with_restrictions(named_key.scopes, named_key.projects)
+            .with_key(named_key.name);
+    }
 
-    Ok(Json(response))
+    Ok(claim)
 }
 
-pub(crate) async fn refresh_token() {}
+fn sign_claim(
+    key_manager: &KeyManagerState,
+    claim: Claim,
+    refresh_token: Option<ApiKey>,
+) -> Result<Json<ConvertResponse>, StatusCode> {
+    let signing_key = key_manager.private_key();
+    let token = claim.into_token_with_key_id(&signing_key.encoding_key, &signing_key.id)?;
+
+    let response = ConvertResponse {
+        token,
+        refresh_token: refresh_token.map(|refresh_token| refresh_token.as_ref().to_string()),
+    };
+
+    Ok(Json(response))
+}
 
 pub(crate) async fn get_public_key(State(key_manager): State<KeyManagerState>) -> Vec<u8> {
-    key_manager.public_key().to_vec()
+    key_manager.public_key()
+}
+
+/// Get the public keys JWTs can be verified with, which services pick from using the `kid` of a JWT
+pub(crate) async fn get_jwks(State(key_manager): State<KeyManagerState>) -> Json<Jwks> {
+    Json(key_manager.public_keys())
 }
@@ -0,0 +1,172 @@
+use std::num::NonZeroU32;
+
+use ring::{
+    digest::SHA256_OUTPUT_LEN,
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use shuttle_common::ApiKey;
+use sqlx::{query, PgConnection, PgPool, Row};
+use tracing::info;
+
+use crate::user::AccountName;
+
+/// How many characters at the start of a key are stored in the clear to find it again
+const PREFIX_LENGTH: usize = 6;
+const SALT_LENGTH: usize = 16;
+/// Keys are random, so they don't need as many iterations as a password would to be safe
+const ITERATIONS: u32 = 10_000;
+
+/// An API key as it is stored: a salted hash of the key, found by the prefix of the key
+pub struct HashedKey {
+    pub prefix: String,
+    pub salt: Vec<u8>,
+    pub hash: Vec<u8>,
+}
+
+impl HashedKey {
+    pub fn new(key: &ApiKey) -> Self {
+        let mut salt = vec![0; SALT_LENGTH];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .expect("to generate a salt for an api key");
+
+        let mut hash = vec![0; SHA256_OUTPUT_LEN];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations(),
+            &salt,
+            key.as_ref().as_bytes(),
+            &mut hash,
+        );
+
+        Self {
+            prefix: prefix(key).to_string(),
+            salt,
+            hash,
+        }
+    }
+}
+
+/// The part of a key that is stored in the clear
+pub fn prefix(key: &ApiKey) -> &str {
+    let key = key.as_ref();
+
+    &key[..PREFIX_LENGTH.min(key.len())]
+}
+
+/// Check if a key matches the salt and hash it was stored with
+pub fn verify(key: &ApiKey, salt: &[u8], hash: &[u8]) -> bool {
+    pbkdf2::verify(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations(),
+        salt,
+        key.as_ref().as_bytes(),
+        hash,
+    )
+    .is_ok()
+}
+
+/// Store a new key that an account can log in with
+pub(crate) async fn insert_account_key(
+    connection: &mut PgConnection,
+    account_name: &AccountName,
+    key: &ApiKey,
+) -> Result<(), sqlx::Error> {
+    let hashed_key = HashedKey::new(key);
+
+    query(
+        "INSERT INTO account_keys (account_name, key_prefix, key_salt, key_hash) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(account_name)
+    .bind(hashed_key.prefix)
+    .bind(hashed_key.salt)
+    .bind(hashed_key.hash)
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
+
+fn iterations() -> NonZeroU32 {
+    NonZeroU32::new(ITERATIONS).expect("iterations to not be zero")
+}
+
+/// Hash the keys that were stored in the clear before keys were hashed, and remove the clear
+/// keys. The database cannot derive the hashes itself, so this is done when the service starts.
+pub(crate) async fn hash_plaintext_keys(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    let users = query("SELECT account_name, key FROM users WHERE key IS NOT NULL")
+        .fetch_all(&mut *transaction)
+        .await?;
+
+    for row in users.iter() {
+        insert_account_key(
+            &mut transaction,
+            &row.try_get("account_name")?,
+            &row.try_get("key")?,
+        )
+        .await?;
+    }
+
+    query("UPDATE users SET key = NULL WHERE key IS NOT NULL")
+        .execute(&mut *transaction)
+        .await?;
+
+    let named_keys = query("SELECT account_name, name, key FROM api_keys WHERE key IS NOT NULL")
+        .fetch_all(&mut *transaction)
+        .await?;
+
+    for row in named_keys.iter() {
+        let hashed_key = HashedKey::new(&row.try_get("key")?);
+
+        query(
+            r#"UPDATE api_keys SET key = NULL, key_prefix = $3, key_salt = $4, key_hash = $5
+            WHERE account_name = $1 AND name = $2
+        "#,
+        )
+        .bind(row.try_get::<String, _>("account_name")?)
+        .bind(row.try_get::<String, _>("name")?)
+        .bind(hashed_key.prefix)
+        .bind(hashed_key.salt)
+        .bind(hashed_key.hash)
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    transaction.commit().await?;
+
+    if !users.is_empty() || !named_keys.is_empty() {
+        info!(
+            users = users.len(),
+            named_keys = named_keys.len(),
+            "hashed api keys that were stored in the clear"
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use shuttle_common::ApiKey;
+
+    use super::{prefix, verify, HashedKey};
+
+    #[test]
+    fn verify_hashed_key() {
+        let key = ApiKey::parse("ndh9z58jttoes3qv").unwrap();
+        let hashed_key = HashedKey::new(&key);
+
+        assert_eq!(hashed_key.prefix, "ndh9z5");
+        assert_eq!(prefix(&key), "ndh9z5");
+        assert!(verify(&key, &hashed_key.salt, &hashed_key.hash));
+
+        let other_key = ApiKey::parse("ndh9z58jttoes3qw").unwrap();
+        assert!(!verify(&other_key, &hashed_key.salt, &hashed_key.hash));
+
+        // The same key is salted differently every time it is hashed
+        assert_ne!(HashedKey::new(&key).hash, hashed_key.hash);
+    }
+}
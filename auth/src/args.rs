@@ -29,10 +29,21 @@ pub struct StartArgs {
     #[arg(long, default_value = "")]
     pub stripe_secret_key: String,
 
+    /// Secret Stripe signs webhook events with. Webhook events are
+    /// rejected when it is not set.
+    #[arg(long, default_value = "")]
+    pub stripe_webhook_secret: String,
+
     /// Auth JWT signing private key, as a base64 encoding of
     /// a PEM encoded PKCS#8 v1 formatted unencrypted private key.
     #[arg(long, default_value = "")]
     pub jwt_signing_private_key: String,
+
+    /// Hours after which JWTs are signed with a new randomly generated
+    /// key. Only services verifying JWTs with the JWKS keep accepting them
+    /// after a rotation. Set to 0 to always sign with the private key.
+    #[arg(long, default_value = "0")]
+    pub jwt_signing_key_rotation_hours: u64,
 }
 
 #[derive(clap::Args, Debug, Clone)]
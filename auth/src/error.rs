@@ -11,6 +11,18 @@ use stripe::StripeError;
 pub enum Error {
     #[error("User could not be found")]
     UserNotFound,
+    #[error("Organization could not be found")]
+    OrganizationNotFound,
+    #[error("An organization with this name already exists")]
+    OrganizationExists,
+    #[error("An organization needs at least one owner")]
+    LastOrganizationOwner,
+    #[error("API key could not be found")]
+    ApiKeyNotFound,
+    #[error("An API key with this name already exists")]
+    ApiKeyExists,
+    #[error("Unknown scope: {0}")]
+    InvalidScope(String),
     #[error("API key is missing.")]
     KeyMissing,
     #[error("Unauthorized.")]
@@ -21,6 +33,8 @@ pub enum Error {
     Database(#[from] sqlx::Error),
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
+    #[error("Invalid webhook event.")]
+    InvalidWebhook,
     #[error("Incomplete checkout session.")]
     Stripe(#[from] StripeError),
 }
@@ -43,8 +57,13 @@ impl IntoResponse for Error {
         let code = match self {
             Error::Forbidden => StatusCode::FORBIDDEN,
             Error::Unauthorized | Error::KeyMissing => StatusCode::UNAUTHORIZED,
-            Error::Database(sqlx::Error::RowNotFound) | Error::UserNotFound => {
-                StatusCode::NOT_FOUND
+            Error::Database(sqlx::Error::RowNotFound)
+            | Error::UserNotFound
+            | Error::OrganizationNotFound
+            | Error::ApiKeyNotFound => StatusCode::NOT_FOUND,
+            Error::OrganizationExists | Error::ApiKeyExists => StatusCode::CONFLICT,
+            Error::LastOrganizationOwner | Error::InvalidScope(_) | Error::InvalidWebhook => {
+                StatusCode::BAD_REQUEST
             }
             _ => {
                 // We only want to emit error events for internal errors, not e.g. 404s.
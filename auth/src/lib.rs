@@ -1,15 +1,18 @@
 mod api;
+mod api_key;
 mod args;
 mod error;
 mod secrets;
 mod user;
+mod webhook;
 
-use std::io;
+use std::{io, time::Duration};
 
 use args::StartArgs;
 use shuttle_common::{claims::AccountTier, ApiKey};
 use sqlx::{migrate::Migrator, query, PgPool};
 use tracing::info;
+use user::AccountName;
 
 use crate::api::serve;
 pub use api::ApiBuilder;
@@ -18,11 +21,22 @@ pub use args::{Args, Commands, InitArgs};
 pub static MIGRATIONS: Migrator = sqlx::migrate!("./migrations");
 
 pub async fn start(pool: PgPool, args: StartArgs) -> io::Result<()> {
-    let router = api::ApiBuilder::new()
+    let mut builder = api::ApiBuilder::new()
         .with_pg_pool(pool)
         .with_stripe_client(stripe::Client::new(args.stripe_secret_key))
-        .with_jwt_signing_private_key(args.jwt_signing_private_key)
-        .into_router();
+        .with_jwt_signing_private_key(args.jwt_signing_private_key);
+
+    if !args.stripe_webhook_secret.is_empty() {
+        builder = builder.with_stripe_webhook_secret(args.stripe_webhook_secret);
+    }
+
+    if args.jwt_signing_key_rotation_hours > 0 {
+        builder = builder.with_jwt_signing_key_rotation(Duration::from_secs(
+            args.jwt_signing_key_rotation_hours * 60 * 60,
+        ));
+    }
+
+    let router = builder.into_router().await;
 
     info!(address=%args.address, "Binding to and listening at address");
 
@@ -37,11 +51,28 @@ pub async fn init(pool: PgPool, args: InitArgs, tier: AccountTier) -> io::Result
         None => ApiKey::generate(),
     };
 
-    query("INSERT INTO users (account_name, key, account_tier) VALUES ($1, $2, $3)")
+    let mut transaction = pool
+        .begin()
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+    query("INSERT INTO users (account_name, account_tier) VALUES ($1, $2)")
         .bind(&args.name)
-        .bind(&key)
         .bind(tier.to_string())
-        .execute(&pool)
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+    api_key::insert_account_key(
+        &mut transaction,
+        &AccountName::from(args.name.clone()),
+        &key,
+    )
+    .await
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+    transaction
+        .commit()
         .await
         .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
@@ -66,6 +97,9 @@ pub async fn pgpool_init(db_uri: &str) -> io::Result<PgPool> {
         .run(&pool)
         .await
         .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    api_key::hash_plaintext_keys(&pool)
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
     Ok(pool)
 }
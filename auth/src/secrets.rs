@@ -1,22 +1,168 @@
-use base64::{engine::general_purpose, Engine};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use base64::{
+    engine::general_purpose::{self, URL_SAFE_NO_PAD},
+    Engine,
+};
+use chrono::Utc;
 use jsonwebtoken::EncodingKey;
-use ring::signature::{Ed25519KeyPair, KeyPair};
+use ring::{
+    digest,
+    rand::{SecureRandom, SystemRandom},
+    signature::{Ed25519KeyPair, KeyPair},
+};
+use shuttle_common::backends::auth::{Jwk, Jwks};
+use sqlx::{query, query_as, PgPool};
+use tracing::info;
+
+/// The start of a PKCS#8 v1 document holding an Ed25519 private key, which is followed by the
+/// 32 byte seed of the key
+const ED25519_PKCS8_V1_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
 
 pub trait KeyManager: Send + Sync {
-    /// Get a private key for signing secrets
-    fn private_key(&self) -> &EncodingKey;
+    /// Get the private key to sign new secrets with
+    fn private_key(&self) -> SigningKey;
+
+    /// Get the public key of the private key secrets are currently signed with
+    fn public_key(&self) -> Vec<u8>;
 
-    /// Get a public key to verify signed secrets
-    fn public_key(&self) -> &[u8];
+    /// Get all the public keys signed secrets can be verified with
+    fn public_keys(&self) -> Jwks;
 }
 
-pub struct EdDsaManager {
-    encoding_key: EncodingKey,
+#[derive(Clone)]
+pub struct SigningKey {
+    /// Set as the `kid` of signed secrets, so they can be matched to their public key
+    pub id: String,
+    pub encoding_key: EncodingKey,
+}
+
+#[derive(Clone)]
+struct Ed25519Key {
+    signing_key: SigningKey,
     public_key: Vec<u8>,
 }
 
+impl Ed25519Key {
+    fn from_pkcs8(pkcs8: &[u8]) -> Self {
+        let pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8)
+            .expect("to get PKCS#8 v1 formatted private key from pem encoded key");
+        let public_key = pair.public_key().as_ref().to_vec();
+
+        // Identify a key by a fingerprint of its public key
+        let fingerprint = digest::digest(&digest::SHA256, &public_key);
+
+        Self {
+            signing_key: SigningKey {
+                id: URL_SAFE_NO_PAD.encode(&fingerprint.as_ref()[..12]),
+                encoding_key: EncodingKey::from_ed_der(pkcs8),
+            },
+            public_key,
+        }
+    }
+
+    /// Generate a new private key, as a PKCS#8 v1 document
+    fn generate_pkcs8() -> Vec<u8> {
+        let mut seed = [0; 32];
+        SystemRandom::new()
+            .fill(&mut seed)
+            .expect("to generate a random ed25519 seed");
+
+        let mut pkcs8 = ED25519_PKCS8_V1_PREFIX.to_vec();
+        pkcs8.extend_from_slice(&seed);
+
+        pkcs8
+    }
+
+    fn jwk(&self) -> Jwk {
+        Jwk::ed25519(self.signing_key.id.clone(), &self.public_key)
+    }
+}
+
+/// The keys around a rotation period. Secrets are signed with the current key. The previous key is
+/// kept to verify secrets signed just before a rotation, and the next key is published ahead of
+/// time so services already know it when the rotation happens.
+struct RotatedKeys {
+    period: u64,
+    previous: Option<Ed25519Key>,
+    current: Ed25519Key,
+    next: Option<Ed25519Key>,
+}
+
+impl RotatedKeys {
+    /// Load the keys around `period`, storing new keys for the current and next periods if no
+    /// instance has stored them yet
+    async fn load(pool: &PgPool, period: u64) -> Result<Self, sqlx::Error> {
+        let period = period as i64;
+        let mut transaction = pool.begin().await?;
+
+        // The first instance to store the key of a period picks it for every instance
+        for period in [period, period + 1] {
+            query(
+                "INSERT INTO jwt_signing_keys (period, private_key) VALUES ($1, $2)
+                ON CONFLICT (period) DO NOTHING",
+            )
+            .bind(period)
+            .bind(Ed25519Key::generate_pkcs8())
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        // Keys from before the previous period are no longer published, so they can go
+        query("DELETE FROM jwt_signing_keys WHERE period < $1")
+            .bind(period - 1)
+            .execute(&mut *transaction)
+            .await?;
+
+        let rows: Vec<(i64, Vec<u8>)> =
+            query_as("SELECT period, private_key FROM jwt_signing_keys WHERE period >= $1")
+                .bind(period - 1)
+                .fetch_all(&mut *transaction)
+                .await?;
+
+        transaction.commit().await?;
+
+        let mut keys: HashMap<_, _> = rows
+            .into_iter()
+            .map(|(period, pkcs8)| (period, Ed25519Key::from_pkcs8(&pkcs8)))
+            .collect();
+
+        Ok(Self {
+            period: period as u64,
+            previous: keys.remove(&(period - 1)),
+            current: keys
+                .remove(&period)
+                .expect("the key of the current period to be stored"),
+            next: keys.remove(&(period + 1)),
+        })
+    }
+
+    /// Move on to the next period, signing with the key that was published as the next key
+    fn promote(&self) -> Option<Self> {
+        Some(Self {
+            period: self.period + 1,
+            previous: Some(self.current.clone()),
+            current: self.next.clone()?,
+            next: None,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct EdDsaManager {
+    rotation_interval: Option<Duration>,
+    keys: Arc<RwLock<Arc<RotatedKeys>>>,
+}
+
 impl EdDsaManager {
-    /// Create a new manager from a base64 PEM encoded private key. This key can be generated using:
+    /// Create a new manager from a base64 PEM encoded private key, which secrets are always signed
+    /// with. This key can be generated using:
     /// ```bash
     /// openssl genpkey -algorithm ED25519 -out auth_jwtsigning_private_key.pem
     /// base64 < auth_jwtsigning_private_key.pem
@@ -28,25 +174,146 @@ impl EdDsaManager {
             .expect("to decode base64 pem encoded private key");
 
         // Parse the pem file and the ed25519 private key contained.
-        let pem_keypair = pem::parse(pk_bytes.clone()).expect("to parse pem encoded private key");
-        let ed_keypair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pem_keypair.contents())
-            .expect("to get PKCS#8 v1 formatted private key from pem encoded key");
+        let pem_keypair = pem::parse(pk_bytes).expect("to parse pem encoded private key");
+
+        let keys = RotatedKeys {
+            period: 0,
+            previous: None,
+            current: Ed25519Key::from_pkcs8(pem_keypair.contents()),
+            next: None,
+        };
 
         Self {
-            // Wrap the private key as a jwt encoding key.
-            encoding_key: EncodingKey::from_ed_pem(pk_bytes.as_slice())
-                .expect("to get an encoding key from pem encoded ed25519 private key"),
-            public_key: ed_keypair.public_key().as_ref().to_vec(),
+            rotation_interval: None,
+            keys: Arc::new(RwLock::new(Arc::new(keys))),
         }
     }
+
+    /// Create a manager that signs secrets with a new key every `rotation_interval`. The keys are
+    /// generated randomly and stored in the database, so all instances of the service agree on them.
+    /// [EdDsaManager::refresh] should be called regularly to store and publish upcoming keys.
+    pub async fn rotating(pool: &PgPool, rotation_interval: Duration) -> Result<Self, sqlx::Error> {
+        let keys = RotatedKeys::load(pool, current_period(rotation_interval)).await?;
+
+        Ok(Self {
+            rotation_interval: Some(rotation_interval),
+            keys: Arc::new(RwLock::new(Arc::new(keys))),
+        })
+    }
+
+    /// Reload the keys of the current rotation period from the database
+    pub async fn refresh(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let Some(interval) = self.rotation_interval else {
+            return Ok(());
+        };
+
+        let keys = RotatedKeys::load(pool, current_period(interval)).await?;
+
+        *self
+            .keys
+            .write()
+            .expect("signing keys lock should not be poisoned") = Arc::new(keys);
+
+        Ok(())
+    }
+
+    /// Get the keys of the current rotation period, rotating them when a new period has started
+    fn keys(&self) -> Arc<RotatedKeys> {
+        let keys = self
+            .keys
+            .read()
+            .expect("signing keys lock should not be poisoned")
+            .clone();
+
+        let Some(interval) = self.rotation_interval else {
+            return keys;
+        };
+
+        let period = current_period(interval);
+        if keys.period >= period {
+            return keys;
+        }
+
+        let mut keys = self
+            .keys
+            .write()
+            .expect("signing keys lock should not be poisoned");
+
+        // The next key is stored before it is published, so it can be promoted without waiting for
+        // a refresh. Another request might have promoted it while we waited for the lock.
+        if keys.period + 1 == period {
+            if let Some(promoted) = keys.promote() {
+                *keys = Arc::new(promoted);
+
+                info!(
+                    key.id = %keys.current.signing_key.id,
+                    "rotated jwt signing key"
+                );
+            }
+        }
+
+        keys.clone()
+    }
 }
 
 impl KeyManager for EdDsaManager {
-    fn private_key(&self) -> &EncodingKey {
-        &self.encoding_key
+    fn private_key(&self) -> SigningKey {
+        self.keys().current.signing_key.clone()
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        self.keys().current.public_key.clone()
+    }
+
+    fn public_keys(&self) -> Jwks {
+        let keys = self.keys();
+
+        Jwks {
+            keys: [
+                Some(&keys.current),
+                keys.next.as_ref(),
+                keys.previous.as_ref(),
+            ]
+            .into_iter()
+            .flatten()
+            .map(Ed25519Key::jwk)
+            .collect(),
+        }
     }
+}
+
+/// The rotation period we are in, counted from the UNIX epoch
+fn current_period(interval: Duration) -> u64 {
+    Utc::now().timestamp() as u64 / interval.as_secs().max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ed25519Key, RotatedKeys};
+
+    #[test]
+    fn rotated_keys_overlap() {
+        let key = || Ed25519Key::from_pkcs8(&Ed25519Key::generate_pkcs8());
+        let id = |key: &Ed25519Key| key.signing_key.id.clone();
+
+        let keys = RotatedKeys {
+            period: 10,
+            previous: Some(key()),
+            current: key(),
+            next: Some(key()),
+        };
+        let rotated = keys.promote().unwrap();
+
+        // The key that is next becomes current, and the current key is kept as the previous key
+        assert_eq!(rotated.period, 11);
+        assert_eq!(id(keys.next.as_ref().unwrap()), id(&rotated.current));
+        assert_eq!(id(&keys.current), id(rotated.previous.as_ref().unwrap()));
+        assert_ne!(id(&keys.current), id(&rotated.current));
+
+        // Without a published next key there is nothing to rotate to until the keys are refreshed
+        assert!(rotated.promote().is_none());
 
-    fn public_key(&self) -> &[u8] {
-        &self.public_key
+        // Every generated key is new
+        assert_ne!(id(&key()), id(&key()));
     }
 }
@@ -7,16 +7,23 @@ use axum::{
     http::request::Parts,
     TypedHeader,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 use shuttle_common::{
-    backends::headers::XShuttleAdminSecret, claims::AccountTier, limits::Limits, models, ApiKey,
-    Secret,
+    backends::headers::XShuttleAdminSecret,
+    claims::{AccountTier, Scope, EXP_MINUTES},
+    limits::Limits,
+    models::{self, organization::Role},
+    ApiKey, Secret,
 };
 use sqlx::{postgres::PgRow, query, FromRow, PgPool, Row};
-use tracing::{debug, error, trace, Span};
+use tracing::{debug, trace, Span};
 
-use crate::{api::UserManagerState, error::Error};
+use crate::{
+    api::UserManagerState,
+    api_key::{self, HashedKey},
+    error::Error,
+};
 use stripe::{SubscriptionId, SubscriptionStatus};
 
 #[async_trait]
@@ -33,9 +40,13 @@ pub trait UserManagement: Send + Sync {
     /// Get a user by their api key
     async fn get_user_by_key(&self, key: ApiKey) -> Result<User, Error>;
 
-    /// Reset the key that belongs to an account
+    /// Reset the key that belongs to an account. Any previous keys stop working straight away
     async fn reset_key(&self, name: AccountName) -> Result<(), Error>;
 
+    /// Issue a new key for an account. The current key keeps working for a grace period, until
+    /// the returned time
+    async fn rotate_key(&self, name: &AccountName) -> Result<(ApiKey, DateTime<Utc>), Error>;
+
     /// Insert a subscription for an account
     async fn insert_subscription(
         &self,
@@ -51,8 +62,88 @@ pub trait UserManagement: Send + Sync {
         name: &AccountName,
         subscription_id: &str,
     ) -> Result<(), Error>;
+
+    /// Create an organization with the given account as its owner
+    async fn create_organization(&self, org_name: &str, owner: &AccountName) -> Result<(), Error>;
+
+    /// Get the organizations an account is a member of, with its role in each of them
+    async fn get_organizations(&self, name: &AccountName) -> Result<Vec<(String, Role)>, Error>;
+
+    /// Get the role of an account in an organization, if it is a member of it
+    async fn get_member_role(
+        &self,
+        org_name: &str,
+        name: &AccountName,
+    ) -> Result<Option<Role>, Error>;
+
+    /// Get the members of an organization
+    async fn get_members(&self, org_name: &str) -> Result<Vec<(AccountName, Role)>, Error>;
+
+    /// Add a member to an organization, or change the role of an existing member
+    async fn set_member(&self, org_name: &str, name: &AccountName, role: Role)
+        -> Result<(), Error>;
+
+    /// Remove a member from an organization
+    async fn remove_member(&self, org_name: &str, name: &AccountName) -> Result<(), Error>;
+
+    /// Create a named key for an account, optionally restricted to some scopes and projects
+    async fn create_named_key(
+        &self,
+        name: &AccountName,
+        key_name: &str,
+        scopes: Option<Vec<Scope>>,
+        projects: Option<Vec<String>>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(ApiKey, NamedKey), Error>;
+
+    /// Get the named keys of an account
+    async fn get_named_keys(&self, name: &AccountName) -> Result<Vec<NamedKey>, Error>;
+
+    /// Revoke a named key of an account
+    async fn revoke_named_key(&self, name: &AccountName, key_name: &str)
+        -> Result<NamedKey, Error>;
+
+    /// Get a user by one of their named keys that has not expired, together with the details of
+    /// that key. This records the key as used.
+    async fn get_user_by_named_key(&self, key: ApiKey) -> Result<(User, NamedKey), Error>;
+
+    /// Create a refresh token that is bound to a valid account or named key, and optionally to an
+    /// organization
+    async fn create_refresh_token(
+        &self,
+        key: &ApiKey,
+        org_name: Option<&str>,
+    ) -> Result<ApiKey, Error>;
+
+    /// Exchange a refresh token for a new one. A refresh token can only be used once, and stops
+    /// working when the key it is bound to does.
+    async fn refresh(&self, refresh_token: &ApiKey) -> Result<Refreshed, Error>;
+
+    /// Revoke all the tokens of an account that have been issued so far
+    async fn revoke_tokens(&self, name: &AccountName) -> Result<(), Error>;
+
+    /// Get the accounts that had their tokens revoked while those tokens could still be valid
+    async fn get_revocations(&self) -> Result<Vec<(AccountName, DateTime<Utc>)>, Error>;
+
+    /// Get the named keys that were revoked while tokens converted from them could still be valid
+    async fn get_key_revocations(&self)
+        -> Result<Vec<(AccountName, String, DateTime<Utc>)>, Error>;
+
+    /// Bring a subscription and the tier of its account in line with Stripe, after Stripe sent an
+    /// event about it. An event is only handled once, however often it is delivered.
+    async fn sync_subscription(
+        &self,
+        event_id: &str,
+        event_type: &str,
+        subscription_id: &str,
+    ) -> Result<(), Error>;
 }
 
+/// How long a key keeps working after it has been rotated
+const KEY_ROTATION_GRACE_HOURS: i64 = 24;
+/// How long a refresh token can be exchanged for new tokens
+const REFRESH_TOKEN_DAYS: i64 = 7;
+
 #[derive(Clone)]
 pub struct UserManager {
     pub pool: PgPool,
@@ -63,14 +154,18 @@ pub struct UserManager {
 impl UserManagement for UserManager {
     async fn create_user(&self, name: AccountName, tier: AccountTier) -> Result<User, Error> {
         let key = ApiKey::generate();
+        let mut transaction = self.pool.begin().await?;
 
-        query("INSERT INTO users (account_name, key, account_tier) VALUES ($1, $2, $3)")
+        query("INSERT INTO users (account_name, account_tier) VALUES ($1, $2)")
             .bind(&name)
-            .bind(&key)
             .bind(tier.to_string())
-            .execute(&self.pool)
+            .execute(&mut *transaction)
             .await?;
 
+        api_key::insert_account_key(&mut transaction, &name, &key).await?;
+
+        transaction.commit().await?;
+
         Ok(User::new(name, key, tier, vec![]))
     }
 
@@ -91,13 +186,12 @@ impl UserManagement for UserManager {
     }
 
     async fn get_user(&self, name: AccountName) -> Result<User, Error> {
-        let mut user: User = sqlx::query_as(
-            "SELECT account_name, key, account_tier FROM users WHERE account_name = $1",
-        )
-        .bind(&name)
-        .fetch_optional(&self.pool)
-        .await?
-        .ok_or(Error::UserNotFound)?;
+        let mut user: User =
+            sqlx::query_as("SELECT account_name, account_tier FROM users WHERE account_name = $1")
+                .bind(&name)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or(Error::UserNotFound)?;
 
         let subscriptions: Vec<Subscription> = sqlx::query_as(
             "SELECT subscription_id, type, quantity, created_at, updated_at FROM subscriptions WHERE account_name = $1",
@@ -110,59 +204,74 @@ impl UserManagement for UserManager {
             user.subscriptions = subscriptions;
         }
 
-        // Sync the user tier based on the subscription validity, if any.
-        if let Err(err) = user.sync_tier(self).await {
-            error!("failed syncing account");
-            return Err(err);
-        } else {
-            debug!("synced account");
-        }
-
         Ok(user)
     }
 
     async fn get_user_by_key(&self, key: ApiKey) -> Result<User, Error> {
-        let mut user: User =
-            sqlx::query_as("SELECT account_name, key, account_tier FROM users WHERE key = $1")
-                .bind(&key)
-                .fetch_optional(&self.pool)
-                .await?
-                .ok_or(Error::UserNotFound)?;
-
-        let subscriptions: Vec<Subscription> = sqlx::query_as(
-            "SELECT subscription_id, type, quantity, created_at, updated_at FROM subscriptions WHERE account_name = $1",
+        let candidates = query(
+            r#"SELECT account_name, key_salt, key_hash FROM account_keys
+            WHERE key_prefix = $1 AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+        "#,
         )
-        .bind(&user.name.to_string())
+        .bind(api_key::prefix(&key))
         .fetch_all(&self.pool)
         .await?;
 
-        if !subscriptions.is_empty() {
-            user.subscriptions = subscriptions;
-        }
-
-        // Sync the user tier based on the subscription validity, if any.
-        if user.sync_tier(self).await? {
-            debug!("synced account");
-        }
+        let row = candidates
+            .iter()
+            .find(|row| api_key::verify(&key, row.get("key_salt"), row.get("key_hash")))
+            .ok_or(Error::UserNotFound)?;
 
-        Ok(user)
+        self.get_user(row.try_get("account_name")?).await
     }
 
     async fn reset_key(&self, name: AccountName) -> Result<(), Error> {
         let key = ApiKey::generate();
+        let mut transaction = self.pool.begin().await?;
 
-        let rows_affected = query("UPDATE users SET key = $1 WHERE account_name = $2")
-            .bind(&key)
+        ensure_user_exists(&mut transaction, &name).await?;
+
+        query("DELETE FROM account_keys WHERE account_name = $1")
             .bind(&name)
-            .execute(&self.pool)
-            .await?
-            .rows_affected();
+            .execute(&mut *transaction)
+            .await?;
 
-        if rows_affected > 0 {
-            Ok(())
-        } else {
-            Err(Error::UserNotFound)
-        }
+        api_key::insert_account_key(&mut transaction, &name, &key).await?;
+        revoke_account_tokens(&mut transaction, &name).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn rotate_key(&self, name: &AccountName) -> Result<(ApiKey, DateTime<Utc>), Error> {
+        let key = ApiKey::generate();
+        let expires_at = Utc::now() + Duration::hours(KEY_ROTATION_GRACE_HOURS);
+        let mut transaction = self.pool.begin().await?;
+
+        ensure_user_exists(&mut transaction, name).await?;
+
+        query(
+            "DELETE FROM account_keys WHERE account_name = $1 AND expires_at <= CURRENT_TIMESTAMP",
+        )
+        .bind(name)
+        .execute(&mut *transaction)
+        .await?;
+
+        // Keys from earlier rotations keep their own, sooner, expiry
+        query(
+            "UPDATE account_keys SET expires_at = $2 WHERE account_name = $1 AND expires_at IS NULL",
+        )
+        .bind(name)
+        .bind(expires_at)
+        .execute(&mut *transaction)
+        .await?;
+
+        api_key::insert_account_key(&mut transaction, name, &key).await?;
+
+        transaction.commit().await?;
+
+        Ok((key, expires_at))
     }
 
     async fn insert_subscription(
@@ -238,12 +347,676 @@ impl UserManagement for UserManager {
 
         Ok(())
     }
+
+    async fn create_organization(&self, org_name: &str, owner: &AccountName) -> Result<(), Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let rows_affected =
+            query("INSERT INTO organizations (org_name) VALUES ($1) ON CONFLICT DO NOTHING")
+                .bind(org_name)
+                .execute(&mut *transaction)
+                .await?
+                .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(Error::OrganizationExists);
+        }
+
+        query(
+            "INSERT INTO organization_members (org_name, account_name, role) VALUES ($1, $2, $3)",
+        )
+        .bind(org_name)
+        .bind(owner)
+        .bind(Role::Owner.to_string())
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_organizations(&self, name: &AccountName) -> Result<Vec<(String, Role)>, Error> {
+        query("SELECT org_name, role FROM organization_members WHERE account_name = $1 ORDER BY org_name")
+            .bind(name)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| -> Result<_, Error> {
+                Ok((row.try_get("org_name")?, role_from_row(row)?))
+            })
+            .collect()
+    }
+
+    async fn get_member_role(
+        &self,
+        org_name: &str,
+        name: &AccountName,
+    ) -> Result<Option<Role>, Error> {
+        query("SELECT role FROM organization_members WHERE org_name = $1 AND account_name = $2")
+            .bind(org_name)
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|row| role_from_row(&row))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn get_members(&self, org_name: &str) -> Result<Vec<(AccountName, Role)>, Error> {
+        query("SELECT account_name, role FROM organization_members WHERE org_name = $1 ORDER BY account_name")
+            .bind(org_name)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| -> Result<_, Error> {
+                Ok((row.try_get("account_name")?, role_from_row(row)?))
+            })
+            .collect()
+    }
+
+    async fn set_member(
+        &self,
+        org_name: &str,
+        name: &AccountName,
+        role: Role,
+    ) -> Result<(), Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        ensure_user_exists(&mut transaction, name).await?;
+
+        query(
+            r#"INSERT INTO organization_members (org_name, account_name, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (org_name, account_name)
+            DO UPDATE SET role = EXCLUDED.role
+        "#,
+        )
+        .bind(org_name)
+        .bind(name)
+        .bind(role.to_string())
+        .execute(&mut *transaction)
+        .await?;
+
+        if role != Role::Owner {
+            ensure_owner_remains(&mut transaction, org_name).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_member(&self, org_name: &str, name: &AccountName) -> Result<(), Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let rows_affected =
+            query("DELETE FROM organization_members WHERE org_name = $1 AND account_name = $2")
+                .bind(org_name)
+                .bind(name)
+                .execute(&mut *transaction)
+                .await?
+                .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(Error::UserNotFound);
+        }
+
+        ensure_owner_remains(&mut transaction, org_name).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn create_named_key(
+        &self,
+        name: &AccountName,
+        key_name: &str,
+        scopes: Option<Vec<Scope>>,
+        projects: Option<Vec<String>>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(ApiKey, NamedKey), Error> {
+        let key = ApiKey::generate();
+        let hashed_key = HashedKey::new(&key);
+        let scopes: Option<Vec<String>> =
+            scopes.map(|scopes| scopes.iter().map(ToString::to_string).collect());
+
+        let named_key = sqlx::query_as(
+            r#"INSERT INTO api_keys (key_prefix, key_salt, key_hash, account_name, name, scopes, projects, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (account_name, name) DO NOTHING
+            RETURNING name, scopes, projects, created_at, expires_at, last_used_at
+        "#,
+        )
+        .bind(hashed_key.prefix)
+        .bind(hashed_key.salt)
+        .bind(hashed_key.hash)
+        .bind(name)
+        .bind(key_name)
+        .bind(scopes)
+        .bind(projects)
+        .bind(expires_at)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(Error::ApiKeyExists)?;
+
+        Ok((key, named_key))
+    }
+
+    async fn get_named_keys(&self, name: &AccountName) -> Result<Vec<NamedKey>, Error> {
+        let named_keys = sqlx::query_as(
+            "SELECT name, scopes, projects, created_at, expires_at, last_used_at FROM api_keys WHERE account_name = $1 ORDER BY name",
+        )
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(named_keys)
+    }
+
+    async fn revoke_named_key(
+        &self,
+        name: &AccountName,
+        key_name: &str,
+    ) -> Result<NamedKey, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let row = query(
+            "DELETE FROM api_keys WHERE account_name = $1 AND name = $2 RETURNING name, scopes, projects, created_at, expires_at, last_used_at, key_hash",
+        )
+        .bind(name)
+        .bind(key_name)
+        .fetch_optional(&mut *transaction)
+        .await?
+        .ok_or(Error::ApiKeyNotFound)?;
+        let named_key = NamedKey::from_row(&row)?;
+
+        query("DELETE FROM refresh_tokens WHERE api_key_hash = $1")
+            .bind(row.try_get::<Vec<u8>, _>("key_hash")?)
+            .execute(&mut *transaction)
+            .await?;
+
+        // Only the tokens converted from this key are revoked, the other keys of the account keep
+        // working
+        query(
+            r#"INSERT INTO key_revocations (account_name, key_name, revoked_at)
+            VALUES ($1, $2, CURRENT_TIMESTAMP)
+            ON CONFLICT (account_name, key_name) DO UPDATE SET revoked_at = EXCLUDED.revoked_at
+        "#,
+        )
+        .bind(name)
+        .bind(key_name)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(named_key)
+    }
+
+    async fn get_user_by_named_key(&self, key: ApiKey) -> Result<(User, NamedKey), Error> {
+        let candidates = query(
+            r#"SELECT account_name, name, key_salt, key_hash FROM api_keys
+            WHERE key_prefix = $1 AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+        "#,
+        )
+        .bind(api_key::prefix(&key))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let candidate = candidates
+            .iter()
+            .find(|row| api_key::verify(&key, row.get("key_salt"), row.get("key_hash")))
+            .ok_or(Error::UserNotFound)?;
+
+        let row = query(
+            r#"UPDATE api_keys SET last_used_at = CURRENT_TIMESTAMP
+            WHERE account_name = $1 AND name = $2
+            RETURNING account_name, name, scopes, projects, created_at, expires_at, last_used_at
+        "#,
+        )
+        .bind(candidate.try_get::<String, _>("account_name")?)
+        .bind(candidate.try_get::<String, _>("name")?)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let named_key = NamedKey::from_row(&row)?;
+        let user = self.get_user(row.try_get("account_name")?).await?;
+
+        Ok((user, named_key))
+    }
+
+    async fn create_refresh_token(
+        &self,
+        key: &ApiKey,
+        org_name: Option<&str>,
+    ) -> Result<ApiKey, Error> {
+        let (account_name, api_key_hash) = self.find_key(key).await?;
+        let mut transaction = self.pool.begin().await?;
+
+        let refresh_token =
+            insert_refresh_token(&mut transaction, &account_name, &api_key_hash, org_name).await?;
+
+        transaction.commit().await?;
+
+        Ok(refresh_token)
+    }
+
+    async fn refresh(&self, refresh_token: &ApiKey) -> Result<Refreshed, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let candidates = query(
+            r#"SELECT account_name, token_salt, token_hash, api_key_hash, org_name FROM refresh_tokens
+            WHERE token_prefix = $1 AND expires_at > CURRENT_TIMESTAMP
+        "#,
+        )
+        .bind(api_key::prefix(refresh_token))
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        let row = candidates
+            .iter()
+            .find(|row| {
+                api_key::verify(refresh_token, row.get("token_salt"), row.get("token_hash"))
+            })
+            .ok_or(Error::Unauthorized)?;
+
+        let account_name: AccountName = row.try_get("account_name")?;
+        let api_key_hash: Vec<u8> = row.try_get("api_key_hash")?;
+        let org_name: Option<String> = row.try_get("org_name")?;
+
+        query("DELETE FROM refresh_tokens WHERE token_hash = $1")
+            .bind(row.try_get::<Vec<u8>, _>("token_hash")?)
+            .execute(&mut *transaction)
+            .await?;
+
+        // The key the refresh token is bound to has to still be valid
+        let account_key = query(
+            r#"SELECT 1 FROM account_keys
+            WHERE key_hash = $1 AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+        "#,
+        )
+        .bind(&api_key_hash)
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        let named_key: Option<NamedKey> = match account_key {
+            Some(_) => None,
+            None => Some(
+                sqlx::query_as(
+                    r#"SELECT name, scopes, projects, created_at, expires_at, last_used_at FROM api_keys
+                    WHERE key_hash = $1 AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+                "#,
+                )
+                .bind(&api_key_hash)
+                .fetch_optional(&mut *transaction)
+                .await?
+                .ok_or(Error::Unauthorized)?,
+            ),
+        };
+
+        let refresh_token = insert_refresh_token(
+            &mut transaction,
+            &account_name,
+            &api_key_hash,
+            org_name.as_deref(),
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        let user = self.get_user(account_name).await?;
+
+        Ok(Refreshed {
+            user,
+            named_key,
+            org_name,
+            refresh_token,
+        })
+    }
+
+    async fn revoke_tokens(&self, name: &AccountName) -> Result<(), Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        revoke_account_tokens(&mut transaction, name).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_revocations(&self) -> Result<Vec<(AccountName, DateTime<Utc>)>, Error> {
+        // Tokens revoked before this have all expired by now
+        let oldest_valid = Utc::now() - Duration::minutes(EXP_MINUTES);
+
+        query("SELECT account_name, revoked_at FROM token_revocations WHERE revoked_at > $1")
+            .bind(oldest_valid)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| -> Result<_, Error> {
+                Ok((row.try_get("account_name")?, row.try_get("revoked_at")?))
+            })
+            .collect()
+    }
+
+    async fn get_key_revocations(
+        &self,
+    ) -> Result<Vec<(AccountName, String, DateTime<Utc>)>, Error> {
+        let oldest_valid = Utc::now() - Duration::minutes(EXP_MINUTES);
+
+        query(
+            "SELECT account_name, key_name, revoked_at FROM key_revocations WHERE revoked_at > $1",
+        )
+        .bind(oldest_valid)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| -> Result<_, Error> {
+            Ok((
+                row.try_get("account_name")?,
+                row.try_get("key_name")?,
+                row.try_get("revoked_at")?,
+            ))
+        })
+        .collect()
+    }
+
+    async fn sync_subscription(
+        &self,
+        event_id: &str,
+        event_type: &str,
+        subscription_id: &str,
+    ) -> Result<(), Error> {
+        let Some(row) =
+            query("SELECT account_name, type FROM subscriptions WHERE subscription_id = $1")
+                .bind(subscription_id)
+                .fetch_optional(&self.pool)
+                .await?
+        else {
+            // Stripe can tell us about a subscription before it is added to an account, in which
+            // case adding it sets the tier
+            debug!(
+                subscription.id = subscription_id,
+                "event for an unknown subscription"
+            );
+            return Ok(());
+        };
+
+        let name: AccountName = row.try_get("account_name")?;
+        let subscription_type = models::user::SubscriptionType::from_str(row.try_get("type")?)
+            .map_err(|err| anyhow::anyhow!("invalid subscription type: {err}"))?;
+
+        let already_handled = query("SELECT 1 FROM stripe_events WHERE event_id = $1")
+            .bind(event_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .is_some();
+
+        if already_handled {
+            return Ok(());
+        }
+
+        // Events can arrive out of order, so act on the latest state of the subscription rather
+        // than the state in the event
+        let subscription = stripe::Subscription::retrieve(
+            &self.stripe_client,
+            &SubscriptionId::from_str(subscription_id).map_err(|_| Error::InvalidWebhook)?,
+            &[],
+        )
+        .await?;
+
+        let mut transaction = self.pool.begin().await?;
+
+        let rows_affected = query(
+            "INSERT INTO stripe_events (event_id, type) VALUES ($1, $2) ON CONFLICT (event_id) DO NOTHING",
+        )
+        .bind(event_id)
+        .bind(event_type)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+
+        // Another delivery of the event got here first
+        if rows_affected == 0 {
+            return Ok(());
+        }
+
+        match subscription_type {
+            models::user::SubscriptionType::Pro => {
+                let tier: String =
+                    query("SELECT account_tier FROM users WHERE account_name = $1 FOR UPDATE")
+                        .bind(&name)
+                        .fetch_one(&mut *transaction)
+                        .await?
+                        .try_get("account_tier")?;
+                let tier = AccountTier::from_str(&tier)
+                    .map_err(|err| anyhow::anyhow!("invalid account tier: {err}"))?;
+
+                let new_tier = synced_tier(tier, &subscription);
+
+                if new_tier != tier {
+                    query("UPDATE users SET account_tier = $1 WHERE account_name = $2")
+                        .bind(new_tier.to_string())
+                        .bind(&name)
+                        .execute(&mut *transaction)
+                        .await?;
+
+                    // Tokens issued before a downgrade still carry the scopes and limits of the
+                    // old tier
+                    if new_tier != AccountTier::Pro {
+                        revoke_account_tokens(&mut transaction, &name).await?;
+                    }
+
+                    debug!(
+                        account.name = %name,
+                        from = %tier,
+                        to = %new_tier,
+                        "synced account tier"
+                    );
+                }
+            }
+            _ if subscription_has_ended(&subscription) => {
+                query("DELETE FROM subscriptions WHERE subscription_id = $1")
+                    .bind(subscription_id)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+            _ => {
+                let quantity = subscription
+                    .items
+                    .data
+                    .first()
+                    .and_then(|item| item.quantity)
+                    .unwrap_or(1);
+
+                query("UPDATE subscriptions SET quantity = $1 WHERE subscription_id = $2")
+                    .bind(quantity as i32)
+                    .bind(subscription_id)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
+
+impl UserManager {
+    /// Find the account and the hash of a valid account or named key
+    async fn find_key(&self, key: &ApiKey) -> Result<(AccountName, Vec<u8>), Error> {
+        let candidates = query(
+            r#"SELECT account_name, key_salt, key_hash FROM account_keys
+            WHERE key_prefix = $1 AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+            UNION ALL
+            SELECT account_name, key_salt, key_hash FROM api_keys
+            WHERE key_prefix = $1 AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+        "#,
+        )
+        .bind(api_key::prefix(key))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let row = candidates
+            .iter()
+            .find(|row| api_key::verify(key, row.get("key_salt"), row.get("key_hash")))
+            .ok_or(Error::Unauthorized)?;
+
+        Ok((row.try_get("account_name")?, row.try_get("key_hash")?))
+    }
+}
+
+/// Store a new refresh token for a key, clearing out the expired ones of the account
+async fn insert_refresh_token(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    name: &AccountName,
+    api_key_hash: &[u8],
+    org_name: Option<&str>,
+) -> Result<ApiKey, Error> {
+    let refresh_token = ApiKey::generate();
+    let hashed_token = HashedKey::new(&refresh_token);
+
+    query("DELETE FROM refresh_tokens WHERE account_name = $1 AND expires_at <= CURRENT_TIMESTAMP")
+        .bind(name)
+        .execute(&mut **transaction)
+        .await?;
+
+    query(
+        r#"INSERT INTO refresh_tokens (account_name, token_prefix, token_salt, token_hash, api_key_hash, org_name, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+    "#,
+    )
+    .bind(name)
+    .bind(hashed_token.prefix)
+    .bind(hashed_token.salt)
+    .bind(hashed_token.hash)
+    .bind(api_key_hash)
+    .bind(org_name)
+    .bind(Utc::now() + Duration::days(REFRESH_TOKEN_DAYS))
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(refresh_token)
+}
+
+/// Make all the tokens issued to an account so far invalid, including its refresh tokens
+async fn revoke_account_tokens(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    name: &AccountName,
+) -> Result<(), Error> {
+    record_token_revocation(transaction, name).await?;
+
+    query("DELETE FROM refresh_tokens WHERE account_name = $1")
+        .bind(name)
+        .execute(&mut **transaction)
+        .await?;
+
+    Ok(())
+}
+
+/// Reject the tokens of an account issued before now, without touching its refresh tokens
+async fn record_token_revocation(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    name: &AccountName,
+) -> Result<(), Error> {
+    query(
+        r#"INSERT INTO token_revocations (account_name, revoked_at)
+        VALUES ($1, CURRENT_TIMESTAMP)
+        ON CONFLICT (account_name) DO UPDATE SET revoked_at = EXCLUDED.revoked_at
+    "#,
+    )
+    .bind(name)
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// The tier a pro account should have given the state of its subscription
+fn synced_tier(tier: AccountTier, subscription: &stripe::Subscription) -> AccountTier {
+    let is_valid = matches!(
+        subscription.status,
+        SubscriptionStatus::Active | SubscriptionStatus::Trialing
+    );
+
+    match tier {
+        AccountTier::Pro | AccountTier::PendingPaymentPro
+            if subscription_has_ended(subscription) =>
+        {
+            AccountTier::Basic
+        }
+        AccountTier::CancelledPro if !is_valid => AccountTier::Basic,
+        // A cancelled subscription stays valid until the end of the period that was paid for
+        AccountTier::Pro if is_valid && subscription.cancel_at_period_end => {
+            AccountTier::CancelledPro
+        }
+        AccountTier::Pro if !is_valid => AccountTier::PendingPaymentPro,
+        AccountTier::PendingPaymentPro if is_valid => AccountTier::Pro,
+        tier => tier,
+    }
+}
+
+fn subscription_has_ended(subscription: &stripe::Subscription) -> bool {
+    matches!(
+        subscription.status,
+        SubscriptionStatus::Canceled | SubscriptionStatus::IncompleteExpired
+    )
+}
+
+/// Fail a change to an account that does not exist
+async fn ensure_user_exists(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    name: &AccountName,
+) -> Result<(), Error> {
+    let user_exists = query("SELECT 1 FROM users WHERE account_name = $1")
+        .bind(name)
+        .fetch_optional(&mut **transaction)
+        .await?
+        .is_some();
+
+    if user_exists {
+        Ok(())
+    } else {
+        Err(Error::UserNotFound)
+    }
+}
+
+/// Fail a change to the members of an organization if it would leave the organization without an
+/// owner. The change is rolled back when the transaction is dropped.
+async fn ensure_owner_remains(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    org_name: &str,
+) -> Result<(), Error> {
+    let owners: i64 =
+        query("SELECT COUNT(*) FROM organization_members WHERE org_name = $1 AND role = $2")
+            .bind(org_name)
+            .bind(Role::Owner.to_string())
+            .fetch_one(&mut **transaction)
+            .await?
+            .get(0);
+
+    if owners == 0 {
+        Err(Error::LastOrganizationOwner)
+    } else {
+        Ok(())
+    }
+}
+
+fn role_from_row(row: &PgRow) -> Result<Role, sqlx::Error> {
+    Role::from_str(row.try_get("role")?).map_err(|err| sqlx::Error::ColumnDecode {
+        index: "role".to_string(),
+        source: Box::new(std::io::Error::new(ErrorKind::Other, err.to_string())),
+    })
 }
 
 #[derive(Clone, Debug)]
 pub struct User {
     pub name: AccountName,
-    pub key: Secret<ApiKey>,
+    /// Only known when the user has just been created, since keys are stored hashed
+    pub key: Option<Secret<ApiKey>>,
     pub account_tier: AccountTier,
     pub subscriptions: Vec<Subscription>,
 }
@@ -257,18 +1030,32 @@ pub struct Subscription {
     pub updated_at: DateTime<Utc>,
 }
 
+/// What a refresh token was exchanged for
+pub struct Refreshed {
+    pub user: User,
+    /// The named key the refresh token is bound to, if it is not bound to the account key
+    pub named_key: Option<NamedKey>,
+    pub org_name: Option<String>,
+    /// The refresh token to use next
+    pub refresh_token: ApiKey,
+}
+
+/// An additional key of an account, which can be restricted to a subset of what the account can do
+#[derive(Clone, Debug)]
+pub struct NamedKey {
+    pub name: String,
+    pub scopes: Option<Vec<Scope>>,
+    pub projects: Option<Vec<String>>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
 impl User {
     pub fn is_admin(&self) -> bool {
         self.account_tier == AccountTier::Admin
     }
 
-    fn pro_subscription_id(&self) -> Option<&stripe::SubscriptionId> {
-        self.subscriptions
-            .iter()
-            .find(|sub| matches!(sub.r#type, models::user::SubscriptionType::Pro))
-            .map(|sub| &sub.id)
-    }
-
     pub fn new(
         name: AccountName,
         key: ApiKey,
@@ -277,71 +1064,18 @@ impl User {
     ) -> Self {
         Self {
             name,
-            key: Secret::new(key),
+            key: Some(Secret::new(key)),
             account_tier,
             subscriptions,
         }
     }
-
-    /// In case of an existing subscription, check if valid.
-    async fn subscription_is_valid(&self, client: &stripe::Client) -> Result<bool, Error> {
-        if let Some(subscription_id) = self.pro_subscription_id() {
-            let subscription = stripe::Subscription::retrieve(client, subscription_id, &[]).await?;
-            debug!("subscription: {:#?}", subscription);
-            return Ok(subscription.status == SubscriptionStatus::Active
-                || subscription.status == SubscriptionStatus::Trialing);
-        }
-
-        Ok(false)
-    }
-
-    // Synchronize the tiers with the subscription validity.
-    async fn sync_tier(&mut self, user_manager: &UserManager) -> Result<bool, Error> {
-        let has_pro_access = self.account_tier == AccountTier::Pro
-            || self.account_tier == AccountTier::CancelledPro
-            || self.account_tier == AccountTier::PendingPaymentPro;
-
-        if !has_pro_access {
-            return Ok(false);
-        }
-
-        let subscription_is_valid = self
-            .subscription_is_valid(&user_manager.stripe_client)
-            .await?;
-
-        if self.account_tier == AccountTier::CancelledPro && !subscription_is_valid {
-            self.account_tier = AccountTier::Basic;
-            user_manager
-                .update_tier(&self.name, self.account_tier)
-                .await?;
-            return Ok(true);
-        }
-
-        if self.account_tier == AccountTier::Pro && !subscription_is_valid {
-            self.account_tier = AccountTier::PendingPaymentPro;
-            user_manager
-                .update_tier(&self.name, self.account_tier)
-                .await?;
-            return Ok(true);
-        }
-
-        if self.account_tier == AccountTier::PendingPaymentPro && subscription_is_valid {
-            self.account_tier = AccountTier::Pro;
-            user_manager
-                .update_tier(&self.name, self.account_tier)
-                .await?;
-            return Ok(true);
-        }
-
-        Ok(false)
-    }
 }
 
 impl FromRow<'_, PgRow> for User {
     fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
         Ok(User {
             name: row.try_get("account_name").unwrap(),
-            key: Secret::new(row.try_get("key").unwrap()),
+            key: None,
             account_tier: AccountTier::from_str(row.try_get("account_tier").unwrap()).map_err(
                 |err| sqlx::Error::ColumnDecode {
                     index: "account_tier".to_string(),
@@ -373,6 +1107,33 @@ impl FromRow<'_, PgRow> for Subscription {
     }
 }
 
+impl FromRow<'_, PgRow> for NamedKey {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let scopes = row
+            .try_get::<Option<Vec<String>>, _>("scopes")?
+            .map(|scopes| {
+                scopes
+                    .iter()
+                    .map(|scope| Scope::from_str(scope))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()
+            .map_err(|err| sqlx::Error::ColumnDecode {
+                index: "scopes".to_string(),
+                source: Box::new(std::io::Error::new(ErrorKind::Other, err.to_string())),
+            })?;
+
+        Ok(NamedKey {
+            name: row.try_get("name")?,
+            scopes,
+            projects: row.try_get("projects")?,
+            created_at: row.try_get("created_at")?,
+            expires_at: row.try_get("expires_at")?,
+            last_used_at: row.try_get("last_used_at")?,
+        })
+    }
+}
+
 impl From<User> for Limits {
     fn from(user: User) -> Self {
         let mut limits: Limits = user.account_tier.into();
@@ -420,7 +1181,7 @@ impl From<User> for models::user::Response {
     fn from(user: User) -> Self {
         Self {
             name: user.name.to_string(),
-            key: user.key.expose().as_ref().to_owned(),
+            key: user.key.map(|key| key.expose().as_ref().to_owned()),
             account_tier: user.account_tier.to_string(),
             subscriptions: user.subscriptions.into_iter().map(Into::into).collect(),
         }
@@ -439,6 +1200,22 @@ impl From<Subscription> for models::user::Subscription {
     }
 }
 
+impl From<NamedKey> for models::api_key::Response {
+    fn from(named_key: NamedKey) -> Self {
+        Self {
+            name: named_key.name,
+            key: None,
+            scopes: named_key
+                .scopes
+                .map(|scopes| scopes.iter().map(ToString::to_string).collect()),
+            projects: named_key.projects,
+            created_at: named_key.created_at,
+            expires_at: named_key.expires_at,
+            last_used_at: named_key.last_used_at,
+        }
+    }
+}
+
 /// A wrapper around [ApiKey] so we can implement [FromRequestParts] for it.
 pub struct Key(ApiKey);
 
@@ -521,10 +1298,11 @@ where
     type Rejection = Error;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let user = User::from_request_parts(parts, state).await?;
-        if user.is_admin() {
-            return Ok(Self { user });
-        }
+        let user = match User::from_request_parts(parts, state).await {
+            Ok(user) if user.is_admin() => return Ok(Self { user }),
+            // Named keys do not authenticate as a user, but an admin can still convert them
+            user => user,
+        };
 
         match parts.headers.typed_try_get::<XShuttleAdminSecret>() {
             Ok(Some(secret)) => {
@@ -541,7 +1319,7 @@ where
                     Err(Error::Unauthorized)
                 }
             }
-            Ok(_) => Err(Error::Unauthorized),
+            Ok(_) => Err(user.err().unwrap_or(Error::Unauthorized)),
             // Returning forbidden for the cases where we don't understand why we can not authorize.
             Err(_) => Err(Error::Forbidden),
         }
@@ -688,4 +1466,68 @@ mod tests {
             );
         }
     }
+
+    mod convert_roles {
+        use shuttle_common::{claims::Scope, models::organization::Role};
+
+        #[test]
+        fn owner() {
+            let scopes: Vec<Scope> = Role::Owner.into();
+
+            assert_eq!(
+                scopes,
+                vec![
+                    Scope::Deployment,
+                    Scope::Logs,
+                    Scope::Service,
+                    Scope::Project,
+                    Scope::Resources,
+                    Scope::Secret,
+                    Scope::DeploymentPush,
+                    Scope::ServiceCreate,
+                    Scope::ResourcesWrite,
+                    Scope::SecretWrite,
+                    Scope::ProjectWrite,
+                ]
+            );
+        }
+
+        #[test]
+        fn developer() {
+            let scopes: Vec<Scope> = Role::Developer.into();
+
+            assert_eq!(
+                scopes,
+                vec![
+                    Scope::Deployment,
+                    Scope::Logs,
+                    Scope::Service,
+                    Scope::Project,
+                    Scope::Resources,
+                    Scope::Secret,
+                    Scope::DeploymentPush,
+                    Scope::ServiceCreate,
+                    Scope::ResourcesWrite,
+                    Scope::SecretWrite,
+                ]
+            );
+        }
+
+        #[test]
+        fn viewer() {
+            let scopes: Vec<Scope> = Role::Viewer.into();
+
+            assert_eq!(
+                scopes,
+                vec![
+                    Scope::Deployment,
+                    Scope::Logs,
+                    Scope::Service,
+                    Scope::Project,
+                    Scope::Resources,
+                    Scope::Secret,
+                ]
+            );
+        }
+    }
 }
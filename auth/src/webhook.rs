@@ -0,0 +1,124 @@
+use chrono::Utc;
+use ring::hmac;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// How old the timestamp of a signed event can be, so intercepted events cannot be replayed later
+const TOLERANCE_SECONDS: i64 = 5 * 60;
+
+/// A Stripe event, with only the fields needed to handle it
+#[derive(Deserialize, Debug)]
+pub struct Event {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub r#type: String,
+    pub data: EventData,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EventData {
+    pub object: Value,
+}
+
+impl Event {
+    /// The subscription this event is about, for the events we act on
+    pub fn subscription_id(&self) -> Option<&str> {
+        match self.r#type.as_str() {
+            "customer.subscription.created"
+            | "customer.subscription.updated"
+            | "customer.subscription.deleted" => self.data.object.get("id"),
+            "invoice.paid" | "invoice.payment_failed" => self.data.object.get("subscription"),
+            _ => None,
+        }
+        .and_then(Value::as_str)
+    }
+}
+
+/// Verify the `Stripe-Signature` header of a webhook request, which holds the time the event was
+/// sent and HMACs of that time and the payload:
+/// https://stripe.com/docs/webhooks#verify-manually
+pub fn verify_signature(secret: &str, signature_header: &str, payload: &[u8]) -> bool {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+
+    for part in signature_header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+
+    let Some(timestamp) = timestamp else {
+        return false;
+    };
+
+    if (Utc::now().timestamp() - timestamp).abs() > TOLERANCE_SECONDS {
+        return false;
+    }
+
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let mut message = format!("{timestamp}.").into_bytes();
+    message.extend_from_slice(payload);
+
+    signatures
+        .into_iter()
+        .filter_map(decode_hex)
+        .any(|signature| hmac::verify(&key, &message, &signature).is_ok())
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            value
+                .get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use ring::hmac;
+
+    use super::verify_signature;
+
+    fn sign(secret: &str, timestamp: i64, payload: &str) -> String {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+        let tag = hmac::sign(&key, format!("{timestamp}.{payload}").as_bytes());
+        let signature: String = tag
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        format!("t={timestamp},v1={signature}")
+    }
+
+    #[test]
+    fn verify_stripe_signature() {
+        let payload = r#"{"id":"evt_123"}"#;
+        let now = Utc::now().timestamp();
+
+        let header = sign("whsec_123", now, payload);
+        assert!(verify_signature("whsec_123", &header, payload.as_bytes()));
+
+        // Signed with another secret
+        assert!(!verify_signature("whsec_456", &header, payload.as_bytes()));
+
+        // The payload was changed
+        assert!(!verify_signature(
+            "whsec_123",
+            &header,
+            r#"{"id":"evt_456"}"#.as_bytes()
+        ));
+
+        // Signed too long ago
+        let header = sign("whsec_123", now - 60 * 60, payload);
+        assert!(!verify_signature("whsec_123", &header, payload.as_bytes()));
+
+        assert!(!verify_signature("whsec_123", "v1=abc", payload.as_bytes()));
+    }
+}
@@ -0,0 +1,147 @@
+mod needs_docker {
+    use http::header::{AUTHORIZATION, CONTENT_TYPE};
+    use http::{Request, StatusCode};
+    use hyper::Body;
+    use serde_json::{json, Value};
+    use shuttle_common::backends::auth::Revocations;
+    use shuttle_common::claims::Scope;
+
+    use crate::helpers::{app, TestApp, ADMIN_KEY};
+
+    async fn user_key(app: &TestApp, name: &str) -> String {
+        let response = app.post_user(name, "basic").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let user: Value = serde_json::from_slice(&body).unwrap();
+
+        user["key"].as_str().unwrap().to_string()
+    }
+
+    async fn create_api_key(app: &TestApp, key: &str, name: &str, request: Value) -> Value {
+        let request = Request::builder()
+            .uri(format!("/users/api-keys/{name}"))
+            .method("POST")
+            .header(AUTHORIZATION, format!("Bearer {key}"))
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&request).unwrap()))
+            .unwrap();
+
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn convert_restricted_api_key_to_jwt() {
+        let app = app().await;
+        let key = user_key(&app, "test-user").await;
+
+        let api_key = create_api_key(
+            &app,
+            &key,
+            "ci",
+            json!({
+                "scopes": ["deployment_push", "logs", "admin"],
+                "projects": ["my-project"],
+            }),
+        )
+        .await;
+        let ci_key = api_key["key"].as_str().unwrap();
+
+        // The key only gets the scopes of the account it was asked for
+        let claim = app.get_claim(ci_key).await;
+        assert_eq!(claim.sub, "test-user");
+        assert_eq!(claim.scopes, vec![Scope::DeploymentPush, Scope::Logs]);
+        assert_eq!(claim.projects, Some(vec!["my-project".to_string()]));
+        assert!(claim.allows_project("my-project"));
+        assert!(!claim.allows_project("other-project"));
+
+        // The key of the account is not restricted
+        let claim = app.get_claim(&key).await;
+        assert!(claim.scopes.contains(&Scope::ProjectWrite));
+        assert_eq!(claim.projects, None);
+
+        // Named keys cannot be used to manage the account
+        let request = Request::builder()
+            .uri("/users/api-keys")
+            .header(AUTHORIZATION, format!("Bearer {ci_key}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Using the key is recorded
+        let request = Request::builder()
+            .uri("/users/api-keys")
+            .header(AUTHORIZATION, format!("Bearer {key}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let api_keys: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(api_keys[0]["name"], "ci");
+        assert!(api_keys[0]["key"].is_null());
+        assert!(api_keys[0]["last_used_at"].is_string());
+    }
+
+    #[tokio::test]
+    async fn expired_and_revoked_api_keys_are_rejected() {
+        let app = app().await;
+        let key = user_key(&app, "test-user").await;
+
+        let api_key = create_api_key(
+            &app,
+            &key,
+            "old",
+            json!({ "expires_at": "2020-01-01T00:00:00Z" }),
+        )
+        .await;
+        let response = app
+            .get_jwt_from_api_key(api_key["key"].as_str().unwrap(), Some(ADMIN_KEY))
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let api_key = create_api_key(&app, &key, "laptop", json!({})).await;
+        let laptop_key = api_key["key"].as_str().unwrap();
+        let response = app.get_jwt_from_api_key(laptop_key, Some(ADMIN_KEY)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Tokens say which named key they were converted from
+        let claim = app.claim_from_response(response).await;
+        assert_eq!(claim.key.as_deref(), Some("laptop"));
+        assert_eq!(app.get_claim(&key).await.key, None);
+
+        let request = Request::builder()
+            .uri("/users/api-keys/laptop")
+            .method("DELETE")
+            .header(AUTHORIZATION, format!("Bearer {key}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.get_jwt_from_api_key(laptop_key, Some(ADMIN_KEY)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Tokens already converted from the revoked key are rejected too
+        let request = Request::builder()
+            .uri("/auth/revocations")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let revocations: Revocations = serde_json::from_slice(&body).unwrap();
+        assert!(revocations.keys["test-user"].contains_key("laptop"));
+
+        // While the tokens of the other keys are kept
+        assert!(!revocations.accounts.contains_key("test-user"));
+    }
+}
@@ -1,11 +1,13 @@
 mod needs_docker {
-    use http::header::AUTHORIZATION;
+    use http::header::{AUTHORIZATION, CONTENT_TYPE};
     use http::{Request, StatusCode};
     use hyper::Body;
-    use serde_json::Value;
-    use shuttle_common::claims::AccountTier;
+    use serde_json::{json, Value};
+    use shuttle_common::backends::auth::{Jwks, Revocations};
+    use shuttle_common::backends::headers::X_SHUTTLE_ADMIN_SECRET;
+    use shuttle_common::claims::{AccountTier, Claim};
 
-    use crate::helpers::{app, ADMIN_KEY};
+    use crate::helpers::{app, TestApp, ADMIN_KEY};
 
     #[tokio::test]
     async fn convert_api_key_to_jwt() {
@@ -82,4 +84,119 @@ mod needs_docker {
         assert_eq!(claim.tier, AccountTier::Basic);
         assert_eq!(claim.limits.project_limit(), 3);
     }
+
+    #[tokio::test]
+    async fn jwt_signed_by_published_key() {
+        let app = app().await;
+
+        let response = app.get_jwt_from_api_key(ADMIN_KEY, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let convert: Value = serde_json::from_slice(&body).unwrap();
+        let token = convert["token"].as_str().unwrap();
+
+        // The JWT names the key it was signed with
+        let key_id = jsonwebtoken::decode_header(token).unwrap().kid.unwrap();
+
+        let request = Request::builder()
+            .uri("/.well-known/jwks.json")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let jwks: Jwks = serde_json::from_slice(&body).unwrap();
+
+        // Which can be found in the published keys to verify the JWT
+        let public_key = jwks.public_key(&key_id).unwrap();
+        let claim = Claim::from_token(token, &public_key).unwrap();
+        assert_eq!(claim.sub, "admin");
+    }
+
+    #[tokio::test]
+    async fn refresh_and_revoke_tokens() {
+        let app = app().await;
+
+        let response = app.post_user("test-user", "basic").await;
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let user: Value = serde_json::from_slice(&body).unwrap();
+        let user_key = user["key"].as_str().unwrap();
+
+        // Refresh tokens are only issued when asked for
+        let response = app.get_jwt_from_api_key(user_key, Some(ADMIN_KEY)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let convert: Value = serde_json::from_slice(&body).unwrap();
+        assert!(convert.get("refresh_token").is_none());
+
+        let request = Request::builder()
+            .uri("/auth/key?refresh_token=true")
+            .header(AUTHORIZATION, format!("Bearer {user_key}"))
+            .header(X_SHUTTLE_ADMIN_SECRET.to_string(), ADMIN_KEY)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let convert: Value = serde_json::from_slice(&body).unwrap();
+        let refresh_token = convert["refresh_token"].as_str().unwrap().to_string();
+
+        let refresh = |refresh_token: String| {
+            Request::builder()
+                .uri("/auth/refresh")
+                .method("POST")
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::to_vec(&json!({ "refresh_token": refresh_token })).unwrap(),
+                ))
+                .unwrap()
+        };
+
+        // A refresh token gives a new token and a new refresh token
+        let response = app.send_request(refresh(refresh_token.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let convert: Value = serde_json::from_slice(&body).unwrap();
+        let next_refresh_token = convert["refresh_token"].as_str().unwrap().to_string();
+        assert_ne!(next_refresh_token, refresh_token);
+
+        // But only once
+        let response = app.send_request(refresh(refresh_token)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        assert!(!get_revocations(&app)
+            .await
+            .accounts
+            .contains_key("test-user"));
+
+        // Resetting the key revokes the tokens of the account
+        let request = Request::builder()
+            .uri("/users/reset-api-key")
+            .method("PUT")
+            .header(AUTHORIZATION, format!("Bearer {user_key}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        assert!(get_revocations(&app)
+            .await
+            .accounts
+            .contains_key("test-user"));
+
+        let response = app.send_request(refresh(next_refresh_token)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    async fn get_revocations(app: &TestApp) -> Revocations {
+        let request = Request::builder()
+            .uri("/auth/revocations")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        serde_json::from_slice(&body).unwrap()
+    }
 }
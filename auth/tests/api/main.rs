@@ -1,4 +1,6 @@
+mod api_keys;
 mod auth;
 mod helpers;
+mod organizations;
 mod stripe;
 mod users;
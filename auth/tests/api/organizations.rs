@@ -0,0 +1,209 @@
+mod needs_docker {
+    use http::header::{AUTHORIZATION, CONTENT_TYPE};
+    use http::{Request, StatusCode};
+    use hyper::Body;
+    use serde_json::{json, Value};
+    use shuttle_common::backends::headers::{X_SHUTTLE_ADMIN_SECRET, X_SHUTTLE_ORGANIZATION};
+    use shuttle_common::claims::Scope;
+
+    use crate::helpers::{app, TestApp, ADMIN_KEY};
+
+    async fn user_key(app: &TestApp, name: &str, tier: &str) -> String {
+        let response = app.post_user(name, tier).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let user: Value = serde_json::from_slice(&body).unwrap();
+
+        user["key"].as_str().unwrap().to_string()
+    }
+
+    fn organization_request(
+        method: &str,
+        uri: &str,
+        key: &str,
+        role: Option<&str>,
+    ) -> Request<Body> {
+        let builder = Request::builder()
+            .uri(uri)
+            .method(method)
+            .header(AUTHORIZATION, format!("Bearer {key}"));
+
+        match role {
+            Some(role) => builder
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::to_vec(&json!({ "role": role })).unwrap(),
+                ))
+                .unwrap(),
+            None => builder.body(Body::empty()).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn manage_organization_members() {
+        let app = app().await;
+
+        let basic_key = user_key(&app, "basic-user", "basic").await;
+        let team_key = user_key(&app, "team-user", "team").await;
+        let dev_key = user_key(&app, "dev-user", "basic").await;
+
+        // Only team accounts can create organizations
+        let response = app
+            .send_request(organization_request(
+                "POST",
+                "/organizations/acme",
+                &basic_key,
+                None,
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .send_request(organization_request(
+                "POST",
+                "/organizations/acme",
+                &team_key,
+                None,
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The name is taken now
+        let response = app
+            .send_request(organization_request(
+                "POST",
+                "/organizations/acme",
+                &team_key,
+                None,
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        // Non-members cannot see or change the members
+        let response = app
+            .send_request(organization_request(
+                "GET",
+                "/organizations/acme/members",
+                &dev_key,
+                None,
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app
+            .send_request(organization_request(
+                "PUT",
+                "/organizations/acme/members/dev-user",
+                &team_key,
+                Some("developer"),
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Developers cannot manage members
+        let response = app
+            .send_request(organization_request(
+                "PUT",
+                "/organizations/acme/members/basic-user",
+                &dev_key,
+                Some("viewer"),
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .send_request(organization_request(
+                "GET",
+                "/organizations/acme/members",
+                &dev_key,
+                None,
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let members: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            members,
+            json!([
+                { "account_name": "dev-user", "role": "developer" },
+                { "account_name": "team-user", "role": "owner" },
+            ])
+        );
+
+        // The last owner cannot leave
+        let response = app
+            .send_request(organization_request(
+                "DELETE",
+                "/organizations/acme/members/team-user",
+                &team_key,
+                None,
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // But other members can
+        let response = app
+            .send_request(organization_request(
+                "DELETE",
+                "/organizations/acme/members/dev-user",
+                &dev_key,
+                None,
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn convert_api_key_to_organization_jwt() {
+        let app = app().await;
+
+        let team_key = user_key(&app, "team-user", "team").await;
+        let viewer_key = user_key(&app, "viewer-user", "basic").await;
+
+        let response = app
+            .send_request(organization_request(
+                "POST",
+                "/organizations/acme",
+                &team_key,
+                None,
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .send_request(organization_request(
+                "PUT",
+                "/organizations/acme/members/viewer-user",
+                &team_key,
+                Some("viewer"),
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let jwt_request = |key: &str, org: &str| {
+            Request::builder()
+                .uri("/auth/key")
+                .header(AUTHORIZATION, format!("Bearer {key}"))
+                .header(X_SHUTTLE_ORGANIZATION.to_string(), org)
+                .header(X_SHUTTLE_ADMIN_SECRET.to_string(), ADMIN_KEY)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        // Viewers only get read scopes for the organization
+        let response = app.send_request(jwt_request(&viewer_key, "acme")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let claim = app.claim_from_response(response).await;
+        assert_eq!(claim.sub, "viewer-user");
+        assert_eq!(claim.org.as_deref(), Some("acme"));
+        assert!(claim.scopes.contains(&Scope::Logs));
+        assert!(!claim.scopes.contains(&Scope::DeploymentPush));
+
+        // Acting for an organization the user is not part of is forbidden
+        let response = app.send_request(jwt_request(&viewer_key, "other")).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
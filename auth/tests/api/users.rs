@@ -112,6 +112,9 @@ mod needs_docker {
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
         let persisted_user: Value = serde_json::from_slice(&body).unwrap();
 
+        // Keys are stored hashed, so only the response to creating the user has the key.
+        let mut user = user;
+        assert!(user.as_object_mut().unwrap().remove("key").is_some());
         assert_eq!(user, persisted_user);
     }
 
@@ -138,19 +141,22 @@ mod needs_docker {
             .await;
         assert_eq!(response.status(), StatusCode::OK);
 
-        // Next we're going to fetch the user, which will trigger a sync of the users tier. It will
-        // fetch the subscription from stripe using the previous subscription ID. This should return
-        // an active subscription, meaning the users tier should remain
-        // pro.
+        // Next Stripe tells us the subscription was paid for, which will trigger a sync of the
+        // users tier. It will fetch the subscription from stripe using the subscription ID. This
+        // should return an active subscription, meaning the users tier should remain pro.
         let response = app
-            .get_user_with_mocked_stripe(
+            .post_stripe_event(
+                "evt_paid",
+                "invoice.paid",
                 MOCKED_COMPLETED_CHECKOUT_SUBSCRIPTION_ID,
                 MOCKED_ACTIVE_SUBSCRIPTION,
-                "test-user",
             )
             .await;
+        assert_eq!(response.status(), StatusCode::OK);
 
+        let response = app.get_user("test-user").await;
         assert_eq!(response.status(), StatusCode::OK);
+
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
         let actual_user: Value = serde_json::from_slice(&body).unwrap();
 
@@ -159,10 +165,8 @@ mod needs_docker {
             actual_user.get("name").unwrap()
         );
 
-        assert_eq!(
-            expected_user.get("key").unwrap(),
-            actual_user.get("key").unwrap()
-        );
+        assert!(expected_user.get("key").is_some());
+        assert!(actual_user.get("key").is_none());
 
         assert_eq!(actual_user.get("account_tier").unwrap(), "pro");
 
@@ -201,16 +205,19 @@ mod needs_docker {
             .await;
         assert_eq!(response.status(), StatusCode::OK);
 
-        // The auth service should call stripe to fetch the subscription with the sub id, and return a subscription
-        // that is pending payment.
+        // Stripe tells us the payment failed. The auth service should call stripe to fetch the
+        // subscription with the sub id, and get back a subscription that is pending payment.
         let response = app
-            .get_user_with_mocked_stripe(
+            .post_stripe_event(
+                "evt_payment_failed",
+                "invoice.payment_failed",
                 MOCKED_OVERDUE_PAYMENT_CHECKOUT_SUBSCRIPTION_ID,
                 MOCKED_PAST_DUE_SUBSCRIPTION,
-                "test-user",
             )
             .await;
+        assert_eq!(response.status(), StatusCode::OK);
 
+        let response = app.get_user("test-user").await;
         assert_eq!(response.status(), StatusCode::OK);
 
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
@@ -310,6 +317,56 @@ mod needs_docker {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_rotate_key() {
+        let app = app().await;
+
+        let response = app.post_user("test-user", "basic").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let user: Value = serde_json::from_slice(&body).unwrap();
+        let old_key = user["key"].as_str().unwrap();
+
+        let request = Request::builder()
+            .uri("/users/rotate-api-key")
+            .method("PUT")
+            .header(AUTHORIZATION, format!("Bearer {old_key}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let rotated: Value = serde_json::from_slice(&body).unwrap();
+        let new_key = rotated["key"].as_str().unwrap();
+        assert_ne!(old_key, new_key);
+        assert!(rotated["previous_key_expires_at"].is_string());
+
+        // Both keys work during the grace period.
+        let claim = app.get_claim(old_key).await;
+        assert_eq!(claim.sub, "test-user");
+        let claim = app.get_claim(new_key).await;
+        assert_eq!(claim.sub, "test-user");
+
+        // Resetting the key invalidates all of them.
+        let request = Request::builder()
+            .uri("/users/reset-api-key")
+            .method("PUT")
+            .header(AUTHORIZATION, format!("Bearer {new_key}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send_request(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        for key in [old_key, new_key] {
+            let response = app
+                .get_jwt_from_api_key(key, Some(helpers::ADMIN_KEY))
+                .await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+    }
+
     #[tokio::test]
     async fn downgrade_from_cancelledpro() {
         let app = app().await;
@@ -335,37 +392,139 @@ mod needs_docker {
             .await;
         assert_eq!(response.status(), StatusCode::OK);
 
-        // Fetch the user to trigger a sync of the account tier to cancelled. The account should not
-        // be downgraded to basic right away, since when we cancel subscriptions we pass in the
+        // Stripe tells us about the update to the subscription. The account should not be
+        // downgraded to basic right away, since when we cancel subscriptions we pass in the
         // "cancel_at_period_end" end flag.
         let response = app
-            .get_user_with_mocked_stripe(
+            .post_stripe_event(
+                "evt_updated",
+                "customer.subscription.updated",
                 MOCKED_CANCELLEDPRO_CHECKOUT_SUBSCRIPTION_ID,
                 MOCKED_CANCELLEDPRO_SUBSCRIPTION_ACTIVE,
-                "test-user",
             )
             .await;
+        assert_eq!(response.status(), StatusCode::OK);
 
+        let response = app.get_user("test-user").await;
         assert_eq!(response.status(), StatusCode::OK);
 
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
         let user: Value = serde_json::from_slice(&body).unwrap();
         assert_eq!(user.get("account_tier").unwrap(), "cancelledpro");
 
-        // When called again at some later time, the subscription returned from stripe should be
-        // cancelled.
+        // At the end of the period, Stripe tells us the subscription was deleted.
         let response = app
-            .get_user_with_mocked_stripe(
+            .post_stripe_event(
+                "evt_deleted",
+                "customer.subscription.deleted",
                 MOCKED_CANCELLEDPRO_CHECKOUT_SUBSCRIPTION_ID,
                 MOCKED_CANCELLEDPRO_SUBSCRIPTION_CANCELLED,
-                "test-user",
             )
             .await;
         assert_eq!(response.status(), StatusCode::OK);
 
+        let response = app.get_user("test-user").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
         let user: Value = serde_json::from_slice(&body).unwrap();
 
         assert_eq!(user.get("account_tier").unwrap(), "basic");
     }
+
+    #[tokio::test]
+    async fn stripe_events_are_handled_once() {
+        let app = app().await;
+
+        let response = app.post_user("test-user", "basic").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .post_subscription(
+                "test-user",
+                MOCKED_OVERDUE_PAYMENT_CHECKOUT_SUBSCRIPTION_ID,
+                "pro",
+                1,
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .post_stripe_event(
+                "evt_payment_failed",
+                "invoice.payment_failed",
+                MOCKED_OVERDUE_PAYMENT_CHECKOUT_SUBSCRIPTION_ID,
+                MOCKED_PAST_DUE_SUBSCRIPTION,
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = app.get_user_typed("test-user").await;
+        assert_eq!(user.account_tier, "pendingpaymentpro");
+
+        // Stripe redelivers the event, which is acknowledged without being applied again.
+        let response = app
+            .post_stripe_event(
+                "evt_payment_failed",
+                "invoice.payment_failed",
+                MOCKED_OVERDUE_PAYMENT_CHECKOUT_SUBSCRIPTION_ID,
+                MOCKED_ACTIVE_SUBSCRIPTION,
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = app.get_user_typed("test-user").await;
+        assert_eq!(user.account_tier, "pendingpaymentpro");
+
+        // Paying the invoice restores the pro tier.
+        let response = app
+            .post_stripe_event(
+                "evt_paid",
+                "invoice.paid",
+                MOCKED_OVERDUE_PAYMENT_CHECKOUT_SUBSCRIPTION_ID,
+                MOCKED_ACTIVE_SUBSCRIPTION,
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = app.get_user_typed("test-user").await;
+        assert_eq!(user.account_tier, "pro");
+    }
+
+    #[tokio::test]
+    async fn stripe_events_need_a_valid_signature() {
+        let app = app().await;
+
+        let response = app.post_user("test-user", "basic").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .post_subscription(
+                "test-user",
+                MOCKED_OVERDUE_PAYMENT_CHECKOUT_SUBSCRIPTION_ID,
+                "pro",
+                1,
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let payload = serde_json::to_string(&serde_json::json!({
+            "id": "evt_forged",
+            "object": "event",
+            "type": "customer.subscription.deleted",
+            "data": { "object": { "id": MOCKED_OVERDUE_PAYMENT_CHECKOUT_SUBSCRIPTION_ID } }
+        }))
+        .unwrap();
+
+        for signature in [
+            helpers::sign_stripe_payload("whsec_another_secret", &payload),
+            "t=0,v1=00".to_string(),
+        ] {
+            let response = app.post_stripe_payload(payload.clone(), &signature).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let user = app.get_user_typed("test-user").await;
+        assert_eq!(user.account_tier, "pro");
+    }
 }
@@ -7,12 +7,16 @@ use std::{
 
 use anyhow::{bail, Context};
 use cargo_metadata::MetadataCommand;
+use chrono::{DateTime, Duration, Utc};
 use clap::{
     builder::{OsStringValueParser, PossibleValue, TypedValueParser},
     Parser, ValueEnum,
 };
 use clap_complete::Shell;
 use shuttle_common::constants::DEFAULT_IDLE_MINUTES;
+use shuttle_common::log::LogLevel;
+use shuttle_common::models::log::LogsQuery;
+use shuttle_common::models::organization::Role;
 use shuttle_common::resource;
 use uuid::Uuid;
 
@@ -33,6 +37,10 @@ pub struct ShuttleArgs {
     /// (allows targeting a custom deployed instance for this command only, mainly for development)
     #[arg(long, env = "SHUTTLE_API")]
     pub api_url: Option<String>,
+    /// Act for this organization instead of your own account, for example to work on the projects
+    /// shared with its members
+    #[arg(global = true, long, env = "SHUTTLE_ORG")]
+    pub org: Option<String>,
     #[command(subcommand)]
     pub cmd: Command,
 }
@@ -99,6 +107,14 @@ pub enum Command {
     /// Manage deployments of a Shuttle service
     #[command(subcommand)]
     Deployment(DeploymentCommand),
+    /// Roll back to a previous deployment of this Shuttle service without rebuilding
+    Rollback {
+        /// ID of the deployment to roll back to. Defaults to the most recent deployment
+        /// before the running one that can still be started
+        id: Option<Uuid>,
+        #[command(flatten)]
+        confirmation: ConfirmationArgs,
+    },
     /// View the status of a Shuttle service
     Status,
     /// Stop this Shuttle service
@@ -113,9 +129,14 @@ pub enum Command {
         #[arg(short, long)]
         /// Follow log output
         follow: bool,
+        #[arg(long, conflicts_with_all = ["id", "latest", "follow"])]
+        /// View the logs of all the deployments of this service, newest first, with the deployment ID of each line
+        all: bool,
         #[arg(long)]
         /// Don't display timestamps and log origin tags
         raw: bool,
+        #[command(flatten)]
+        filter: LogsFilterArgs,
     },
     /// List or manage projects on Shuttle
     #[command(subcommand)]
@@ -123,6 +144,18 @@ pub enum Command {
     /// Manage resources of a Shuttle project
     #[command(subcommand)]
     Resource(ResourceCommand),
+    /// Manage secrets of a Shuttle service without redeploying it
+    #[command(subcommand)]
+    Secrets(SecretsCommand),
+    /// Manage custom domains of a Shuttle project
+    #[command(subcommand)]
+    Domain(DomainCommand),
+    /// Manage organizations and their members
+    #[command(subcommand)]
+    Org(OrgCommand),
+    /// Manage your API keys, such as named keys restricted to deploying from CI
+    #[command(subcommand)]
+    ApiKey(ApiKeyCommand),
     /// Remove cargo build artifacts in the Shuttle environment
     Clean,
     /// Login to the Shuttle platform
@@ -171,6 +204,11 @@ pub enum DeploymentCommand {
         /// ID of deployment to get status for
         id: Uuid,
     },
+    /// Start a previous deployment again from its built executable, without rebuilding
+    Redeploy {
+        /// ID of the deployment to start
+        id: Uuid,
+    },
 }
 
 #[derive(Parser)]
@@ -194,9 +232,220 @@ pub enum ResourceCommand {
         /// Use the string in the 'Type' column as displayed in the `resource list` command.
         /// For example, 'database::shared::postgres'.
         resource_type: resource::Type,
+        /// Name of the resource to delete, as displayed in the `resource list` command.
+        /// Leave out to delete the default resource of the type.
+        #[arg(long)]
+        name: Option<String>,
+        #[command(flatten)]
+        confirmation: ConfirmationArgs,
+    },
+    /// Take a backup of a shared Postgres database
+    Backup {
+        /// Type of the database to back up, for example 'database::shared::postgres'
+        resource_type: resource::Type,
+        /// Name of the database, as displayed in the `resource list` command.
+        /// Leave out to back up the default database of the type.
+        #[arg(long)]
+        name: Option<String>,
+        /// Back up the database of a service running locally with `cargo shuttle run`
+        #[arg(long, default_value_t = false)]
+        local: bool,
+    },
+    /// List the backups kept for a shared Postgres database
+    ListBackups {
+        /// Type of the database, for example 'database::shared::postgres'
+        resource_type: resource::Type,
+        /// Name of the database, as displayed in the `resource list` command
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long, default_value_t = false)]
+        /// Output table in `raw` format
+        raw: bool,
+        /// List the backups of a database running locally
+        #[arg(long, default_value_t = false)]
+        local: bool,
+    },
+    /// Restore a shared Postgres database from one of its backups
+    Restore {
+        /// Type of the database to restore, for example 'database::shared::postgres'
+        resource_type: resource::Type,
+        /// ID of the backup to restore, as displayed in the `resource list-backups` command
+        backup_id: String,
+        /// Name of the database the backup was taken from
+        #[arg(long)]
+        name: Option<String>,
+        /// Restore into a new database with this name instead of overwriting the backed up one
+        #[arg(long)]
+        into: Option<String>,
+        /// Restore a database running locally
+        #[arg(long, default_value_t = false)]
+        local: bool,
         #[command(flatten)]
         confirmation: ConfirmationArgs,
     },
+    /// Issue new credentials for a shared Postgres database and restart the service with them.
+    /// The old credentials keep working for a grace period.
+    Rotate {
+        /// Type of the database, for example 'database::shared::postgres'
+        resource_type: resource::Type,
+        /// Name of the database, as displayed in the `resource list` command
+        #[arg(long)]
+        name: Option<String>,
+        #[command(flatten)]
+        confirmation: ConfirmationArgs,
+    },
+}
+
+#[derive(Parser)]
+pub enum SecretsCommand {
+    /// List the secrets of this service
+    List {
+        #[arg(long, default_value_t = false)]
+        /// Output table in `raw` format
+        raw: bool,
+
+        #[arg(long, default_value_t = false)]
+        /// Show the secret values instead of masking them
+        show_values: bool,
+    },
+    /// Add or update secrets
+    Set {
+        /// Secrets to set, as `KEY=VALUE` pairs
+        #[arg(required = true, value_parser = parse_secret)]
+        secrets: Vec<(String, String)>,
+        #[command(flatten)]
+        restart: SecretsRestartArgs,
+    },
+    /// Remove secrets
+    Unset {
+        /// Keys of the secrets to remove
+        #[arg(required = true)]
+        keys: Vec<String>,
+        #[command(flatten)]
+        restart: SecretsRestartArgs,
+    },
+    /// Add or update all the secrets in a TOML file
+    Import {
+        /// Path to the secrets file. Defaults to `Secrets.toml` in the working directory
+        path: Option<PathBuf>,
+        #[command(flatten)]
+        restart: SecretsRestartArgs,
+    },
+}
+
+#[derive(Parser)]
+pub enum DomainCommand {
+    /// List the custom domains of this project
+    List {
+        #[arg(long, default_value_t = false)]
+        /// Output table in `raw` format
+        raw: bool,
+    },
+    /// Add a custom domain to this project and request a certificate for it.
+    /// The domain must already have a DNS record pointing it to the project
+    Add {
+        /// The domain to add, for example 'www.example.com'
+        fqdn: String,
+    },
+    /// Remove a custom domain from this project
+    Remove {
+        /// The domain to remove
+        fqdn: String,
+        #[command(flatten)]
+        confirmation: ConfirmationArgs,
+    },
+}
+
+#[derive(Parser)]
+pub enum OrgCommand {
+    /// List the organizations you are a member of
+    List {
+        #[arg(long, default_value_t = false)]
+        /// Output table in `raw` format
+        raw: bool,
+    },
+    /// Create an organization with you as its owner. Only Team accounts can create organizations
+    Create {
+        /// The name of the organization
+        name: String,
+    },
+    /// List the members of an organization
+    Members {
+        /// The name of the organization
+        name: String,
+        #[arg(long, default_value_t = false)]
+        /// Output table in `raw` format
+        raw: bool,
+    },
+    /// Add a member to an organization, or change the role of an existing member
+    AddMember {
+        /// The name of the organization
+        name: String,
+        /// The account to add
+        account_name: String,
+        #[arg(long, default_value = "developer")]
+        /// The role of the member: 'owner', 'admin', 'developer' or 'viewer'
+        role: Role,
+    },
+    /// Remove a member from an organization
+    RemoveMember {
+        /// The name of the organization
+        name: String,
+        /// The account to remove
+        account_name: String,
+        #[command(flatten)]
+        confirmation: ConfirmationArgs,
+    },
+}
+
+#[derive(Parser)]
+pub enum ApiKeyCommand {
+    /// Create a named API key. The key is only shown once
+    Create {
+        /// The name of the key, for example 'ci'
+        name: String,
+        #[arg(long = "scope")]
+        /// Restrict the key to a scope, such as 'deployment_push' or 'logs'. Can be repeated.
+        /// The key has all the scopes of your account if none are given
+        scopes: Vec<String>,
+        #[arg(long = "project")]
+        /// Restrict the key to a project. Can be repeated.
+        /// The key can be used for all your projects if none are given
+        projects: Vec<String>,
+        #[arg(long)]
+        /// Make the key expire after this many days
+        expires_in_days: Option<u32>,
+    },
+    /// List your named API keys
+    List {
+        #[arg(long, default_value_t = false)]
+        /// Output table in `raw` format
+        raw: bool,
+    },
+    /// Revoke a named API key
+    Revoke {
+        /// The name of the key
+        name: String,
+        #[command(flatten)]
+        confirmation: ConfirmationArgs,
+    },
+    /// Issue a new key for your account and log in with it.
+    /// The previous key keeps working for a day, so it can be replaced wherever else it is used
+    Rotate,
+    /// Get a token that expires after 15 minutes, for tools which should not hold on to your key.
+    /// It comes with a refresh token to get the next token with
+    Token {
+        #[arg(long)]
+        /// Exchange this refresh token for a new token instead. A refresh token can only be used once
+        refresh: Option<String>,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct SecretsRestartArgs {
+    #[arg(long, default_value_t = false)]
+    /// Restart the running deployment so that the change takes effect immediately
+    pub restart: bool,
 }
 
 #[derive(Parser)]
@@ -280,6 +529,9 @@ pub struct RunArgs {
     /// Use release mode for building the project
     #[arg(long, short = 'r')]
     pub release: bool,
+    /// Rebuild and restart the services when a file of the workspace changes
+    #[arg(long)]
+    pub watch: bool,
 }
 
 #[derive(Parser, Clone, Debug, Default)]
@@ -389,6 +641,38 @@ impl InitTemplateArg {
     }
 }
 
+#[derive(Parser, Clone, Debug, Default)]
+pub struct LogsFilterArgs {
+    /// Only show logs at this level or above (trace, debug, info, warn, error)
+    #[arg(long)]
+    pub level: Option<LogLevel>,
+    /// Only show logs whose target (module path) starts with this
+    #[arg(long)]
+    pub target: Option<String>,
+    /// Only show logs from this long ago (e.g. 30s, 15m, 1h, 2d) or from this RFC 3339 time
+    #[arg(long, value_parser = parse_time_bound)]
+    pub since: Option<DateTime<Utc>>,
+    /// Only show logs from before this long ago (e.g. 30s, 15m, 1h, 2d) or this RFC 3339 time
+    #[arg(long, value_parser = parse_time_bound)]
+    pub until: Option<DateTime<Utc>>,
+    /// Only show logs containing this text, ignoring case
+    #[arg(long)]
+    pub grep: Option<String>,
+}
+
+impl LogsFilterArgs {
+    pub fn to_query(&self) -> LogsQuery {
+        LogsQuery {
+            level: self.level,
+            target: self.target.clone(),
+            since: self.since,
+            until: self.until,
+            search: self.grep.clone(),
+            ..Default::default()
+        }
+    }
+}
+
 /// Helper function to parse and return the absolute path
 fn parse_path(path: OsString) -> Result<PathBuf, String> {
     dunce::canonicalize(&path).map_err(|e| format!("could not turn {path:?} into a real path: {e}"))
@@ -407,6 +691,50 @@ pub(crate) fn parse_init_path(path: OsString) -> Result<PathBuf, io::Error> {
     parse_path(path).map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))
 }
 
+/// Parse a `KEY=VALUE` pair for a secret
+fn parse_secret(s: &str) -> anyhow::Result<(String, String)> {
+    let Some((key, value)) = s.split_once('=') else {
+        bail!("invalid secret `{s}`, expected KEY=VALUE");
+    };
+
+    if key.is_empty() {
+        bail!("invalid secret `{s}`, the key cannot be empty");
+    }
+
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Parse a point in time given either as a duration ago (`90s`, `15m`, `1h`, `2d`) or an RFC 3339 time
+fn parse_time_bound(s: &str) -> anyhow::Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    let invalid = || {
+        anyhow::anyhow!(
+            "invalid time `{s}`, expected a duration like 30s, 15m, 1h or 2d, or an RFC 3339 time"
+        )
+    };
+    let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (amount, unit) = s.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    let seconds = amount
+        .checked_mul(multiplier)
+        .filter(|seconds| *seconds <= i64::MAX / 1000)
+        .ok_or_else(invalid)?;
+
+    Utc::now()
+        .checked_sub_signed(Duration::seconds(seconds))
+        .ok_or_else(invalid)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -418,6 +746,36 @@ mod tests {
         ShuttleArgs::command().debug_assert();
     }
 
+    #[test]
+    fn secret_pairs() {
+        assert_eq!(
+            parse_secret("API_KEY=abc=123").unwrap(),
+            ("API_KEY".to_string(), "abc=123".to_string())
+        );
+        assert_eq!(
+            parse_secret("EMPTY=").unwrap(),
+            ("EMPTY".to_string(), String::new())
+        );
+        assert!(parse_secret("API_KEY").is_err());
+        assert!(parse_secret("=value").is_err());
+    }
+
+    #[test]
+    fn time_bounds() {
+        let before = Utc::now();
+        let since = parse_time_bound("1h").unwrap();
+        assert!(since <= before - Duration::hours(1));
+        assert!(since > before - Duration::hours(1) - Duration::minutes(1));
+
+        assert_eq!(
+            parse_time_bound("2024-01-02T03:04:05+02:00").unwrap(),
+            DateTime::parse_from_rfc3339("2024-01-02T01:04:05Z").unwrap()
+        );
+        assert!(parse_time_bound("1w").is_err());
+        assert!(parse_time_bound("h").is_err());
+        assert!(parse_time_bound("15").is_err());
+    }
+
     #[test]
     fn test_init_args_framework() {
         // pre-defined template (only hello world)
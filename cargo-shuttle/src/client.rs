@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use anyhow::{Context, Result};
 use headers::{Authorization, HeaderMapExt};
 use percent_encoding::utf8_percent_encode;
@@ -7,7 +9,11 @@ use reqwest_retry::policies::ExponentialBackoff;
 use reqwest_retry::RetryTransientMiddleware;
 use serde::{Deserialize, Serialize};
 use shuttle_common::models::deployment::DeploymentRequest;
-use shuttle_common::models::{deployment, project, service, ToJson};
+use shuttle_common::models::{
+    api_key, deployment, domain,
+    log::{LogsQuery, ServiceLogsResponse},
+    organization, project, resource as resource_models, secret, service, user, ToJson,
+};
 use shuttle_common::secrets::Secret;
 use shuttle_common::{resource, ApiKey, ApiUrl, LogItem, VersionInfo};
 use tokio::net::TcpStream;
@@ -16,10 +22,14 @@ use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
 use tracing::error;
 use uuid::Uuid;
 
+/// Header telling the API which organization the user is acting for
+const ORG_HEADER: &str = "x-shuttle-org";
+
 #[derive(Clone)]
 pub struct Client {
     api_url: ApiUrl,
     api_key: Option<Secret<ApiKey>>,
+    org: Option<String>,
     client: reqwest::Client,
     retry_client: ClientWithMiddleware,
 }
@@ -35,6 +45,7 @@ impl Client {
         Self {
             api_url,
             api_key: None,
+            org: None,
             client,
             retry_client,
         }
@@ -44,6 +55,11 @@ impl Client {
         self.api_key = Some(Secret::new(api_key));
     }
 
+    /// Make requests on behalf of an organization the user is a member of
+    pub fn set_org(&mut self, org: String) {
+        self.org = Some(org);
+    }
+
     pub async fn get_api_versions(&self) -> Result<VersionInfo> {
         let url = format!("{}/versions", self.api_url);
 
@@ -114,8 +130,9 @@ impl Client {
         &self,
         project: &str,
         resource_type: &resource::Type,
+        name: Option<&str>,
     ) -> Result<()> {
-        let path = format!(
+        let mut path = format!(
             "/projects/{project}/services/{project}/resources/{}",
             utf8_percent_encode(
                 &resource_type.to_string(),
@@ -123,6 +140,170 @@ impl Client {
             ),
         );
 
+        if let Some(name) = name {
+            path.push_str(&format!(
+                "?name={}",
+                utf8_percent_encode(name, percent_encoding::NON_ALPHANUMERIC)
+            ));
+        }
+
+        self.delete(path).await
+    }
+
+    pub async fn backup_service_resource(
+        &self,
+        project: &str,
+        resource_type: &resource::Type,
+        name: Option<&str>,
+    ) -> Result<resource_models::Backup> {
+        let path = format!(
+            "{}{}",
+            resource_backups_path(project, resource_type),
+            name_query(name)
+        );
+
+        self.post(path, Option::<()>::None)
+            .await
+            .context("failed to make backup request")?
+            .to_json()
+            .await
+    }
+
+    pub async fn get_service_resource_backups(
+        &self,
+        project: &str,
+        resource_type: &resource::Type,
+        name: Option<&str>,
+    ) -> Result<Vec<resource_models::Backup>> {
+        let path = format!(
+            "{}{}",
+            resource_backups_path(project, resource_type),
+            name_query(name)
+        );
+
+        self.get(path).await
+    }
+
+    pub async fn restore_service_resource(
+        &self,
+        project: &str,
+        resource_type: &resource::Type,
+        name: Option<&str>,
+        backup_id: &str,
+        into: Option<&str>,
+    ) -> Result<()> {
+        let mut path = format!(
+            "{}/{}/restore{}",
+            resource_backups_path(project, resource_type),
+            utf8_percent_encode(backup_id, percent_encoding::NON_ALPHANUMERIC),
+            name_query(name)
+        );
+
+        if let Some(into) = into {
+            path.push(if name.is_some() { '&' } else { '?' });
+            path.push_str(&format!(
+                "into={}",
+                utf8_percent_encode(into, percent_encoding::NON_ALPHANUMERIC)
+            ));
+        }
+
+        self.post(path, Option::<()>::None)
+            .await
+            .context("failed to make restore request")?
+            .to_json()
+            .await
+    }
+
+    pub async fn rotate_service_resource(
+        &self,
+        project: &str,
+        resource_type: &resource::Type,
+        name: Option<&str>,
+    ) -> Result<resource_models::RotateResponse> {
+        let path = format!(
+            "/projects/{project}/services/{project}/resources/{}/rotate{}",
+            utf8_percent_encode(
+                &resource_type.to_string(),
+                percent_encoding::NON_ALPHANUMERIC
+            ),
+            name_query(name)
+        );
+
+        self.post(path, Option::<()>::None)
+            .await
+            .context("failed to make rotate request")?
+            .to_json()
+            .await
+    }
+
+    pub async fn get_secrets(
+        &self,
+        project: &str,
+        show_values: bool,
+    ) -> Result<Vec<secret::Response>> {
+        let path =
+            format!("/projects/{project}/services/{project}/secrets?show_values={show_values}");
+
+        self.get(path).await
+    }
+
+    pub async fn set_secrets(
+        &self,
+        project: &str,
+        secrets: BTreeMap<String, String>,
+        restart: bool,
+    ) -> Result<Vec<secret::Response>> {
+        let path = format!("/projects/{project}/services/{project}/secrets");
+
+        self.put(path, Some(secret::SetRequest { secrets, restart }))
+            .await
+            .context("failed to set secrets")?
+            .to_json()
+            .await
+    }
+
+    pub async fn unset_secrets(
+        &self,
+        project: &str,
+        keys: Vec<String>,
+        restart: bool,
+    ) -> Result<()> {
+        let path = format!("/projects/{project}/services/{project}/secrets/unset");
+
+        self.post(path, Some(secret::UnsetRequest { keys, restart }))
+            .await
+            .context("failed to unset secrets")?
+            .to_json()
+            .await
+    }
+
+    pub async fn get_domains(&self, project: &str) -> Result<Vec<domain::Response>> {
+        let path = format!("/projects/{project}/domains");
+
+        self.get(path).await
+    }
+
+    pub async fn add_domain(&self, project: &str, fqdn: &str) -> Result<domain::Response> {
+        let path = format!("/projects/{project}/domains");
+
+        self.post(
+            path,
+            Some(domain::AddRequest {
+                fqdn: fqdn.to_string(),
+            }),
+        )
+        .await
+        .context("failed to make add domain request")?
+        .to_json()
+        .await
+    }
+
+    pub async fn remove_domain(&self, project: &str, fqdn: &str) -> Result<domain::Response> {
+        let path = format!(
+            "/projects/{project}/domains/{}",
+            utf8_percent_encode(fqdn, percent_encoding::NON_ALPHANUMERIC),
+        );
+
         self.delete(path).await
     }
 
@@ -174,8 +355,31 @@ impl Client {
         self.delete(path).await
     }
 
-    pub async fn get_logs(&self, project: &str, deployment_id: &Uuid) -> Result<Vec<LogItem>> {
-        let path = format!("/projects/{project}/deployments/{deployment_id}/logs");
+    pub async fn get_logs(
+        &self,
+        project: &str,
+        deployment_id: &Uuid,
+        query: &LogsQuery,
+    ) -> Result<Vec<LogItem>> {
+        let path = format!(
+            "/projects/{project}/deployments/{deployment_id}/logs?{}",
+            query.to_query_string()
+        );
+
+        self.get(path)
+            .await
+            .context("Failed parsing logs. Is your cargo-shuttle outdated?")
+    }
+
+    pub async fn get_service_logs(
+        &self,
+        project: &str,
+        query: &LogsQuery,
+    ) -> Result<ServiceLogsResponse> {
+        let path = format!(
+            "/projects/{project}/services/{project}/logs?{}",
+            query.to_query_string()
+        );
 
         self.get(path)
             .await
@@ -186,8 +390,12 @@ impl Client {
         &self,
         project: &str,
         deployment_id: &Uuid,
+        query: &LogsQuery,
     ) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
-        let path = format!("/projects/{project}/ws/deployments/{deployment_id}/logs");
+        let path = format!(
+            "/projects/{project}/ws/deployments/{deployment_id}/logs?{}",
+            query.to_query_string()
+        );
 
         self.ws_get(path).await
     }
@@ -217,6 +425,121 @@ impl Client {
         self.get(path).await
     }
 
+    pub async fn redeploy(
+        &self,
+        project: &str,
+        deployment_id: &Uuid,
+    ) -> Result<deployment::Response> {
+        let path = format!("/projects/{project}/deployments/{deployment_id}");
+
+        self.put(path, Option::<()>::None)
+            .await
+            .context("failed to start the deployment")?
+            .to_json()
+            .await
+    }
+
+    pub async fn get_available_executables(&self, project: &str) -> Result<Vec<Uuid>> {
+        let path = format!("/projects/{project}/executables");
+
+        self.get(path).await
+    }
+
+    pub async fn get_organizations(&self) -> Result<Vec<organization::Response>> {
+        self.get("/organizations".to_string()).await
+    }
+
+    pub async fn create_organization(&self, name: &str) -> Result<organization::Response> {
+        let path = format!("/organizations/{name}");
+
+        self.post(path, Option::<()>::None)
+            .await
+            .context("failed to make create organization request")?
+            .to_json()
+            .await
+    }
+
+    pub async fn get_organization_members(
+        &self,
+        name: &str,
+    ) -> Result<Vec<organization::MemberResponse>> {
+        let path = format!("/organizations/{name}/members");
+
+        self.get(path).await
+    }
+
+    pub async fn set_organization_member(
+        &self,
+        name: &str,
+        account_name: &str,
+        role: organization::Role,
+    ) -> Result<organization::MemberResponse> {
+        let path = format!("/organizations/{name}/members/{account_name}");
+
+        self.put(path, Some(organization::MemberRequest { role }))
+            .await
+            .context("failed to make add member request")?
+            .to_json()
+            .await
+    }
+
+    pub async fn remove_organization_member(
+        &self,
+        name: &str,
+        account_name: &str,
+    ) -> Result<organization::MemberResponse> {
+        let path = format!("/organizations/{name}/members/{account_name}");
+
+        self.delete(path).await
+    }
+
+    pub async fn get_api_keys(&self) -> Result<Vec<api_key::Response>> {
+        self.get("/users/api-keys".to_string()).await
+    }
+
+    pub async fn create_api_key(
+        &self,
+        name: &str,
+        request: api_key::CreateRequest,
+    ) -> Result<api_key::Response> {
+        let path = format!("/users/api-keys/{name}");
+
+        self.post(path, Some(request))
+            .await
+            .context("failed to make create API key request")?
+            .to_json()
+            .await
+    }
+
+    pub async fn revoke_api_key(&self, name: &str) -> Result<api_key::Response> {
+        let path = format!("/users/api-keys/{name}");
+
+        self.delete(path).await
+    }
+
+    pub async fn rotate_api_key(&self) -> Result<user::RotateKeyResponse> {
+        self.put("/users/rotate-api-key".into(), Option::<()>::None)
+            .await
+            .context("failed to make rotate API key request")?
+            .to_json()
+            .await
+    }
+
+    pub async fn get_token(&self) -> Result<user::TokenResponse> {
+        self.get("/auth/key".to_string()).await
+    }
+
+    pub async fn refresh_token(&self, refresh_token: String) -> Result<user::TokenResponse> {
+        self.post(
+            "/auth/refresh".to_string(),
+            Some(user::RefreshTokenRequest { refresh_token }),
+        )
+        .await
+        .context("failed to make refresh token request")?
+        .to_json()
+        .await
+    }
+
     pub async fn reset_api_key(&self) -> Result<Response> {
         self.put("/users/reset-api-key".into(), Option::<()>::None)
             .await
@@ -232,6 +555,10 @@ impl Client {
             request.headers_mut().typed_insert(auth_header);
         }
 
+        if let Some(ref org) = self.org {
+            request.headers_mut().insert(ORG_HEADER, org.parse()?);
+        }
+
         let (stream, _) = connect_async(request).await.with_context(|| {
             error!("failed to connect to websocket");
             "could not connect to websocket"
@@ -308,11 +635,35 @@ impl Client {
             .await
     }
 
-    fn set_builder_auth(&self, builder: RequestBuilder) -> RequestBuilder {
+    fn set_builder_auth(&self, mut builder: RequestBuilder) -> RequestBuilder {
         if let Some(ref api_key) = self.api_key {
-            builder.bearer_auth(api_key.expose().as_ref())
-        } else {
-            builder
+            builder = builder.bearer_auth(api_key.expose().as_ref());
         }
+
+        if let Some(ref org) = self.org {
+            builder = builder.header(ORG_HEADER, org);
+        }
+
+        builder
     }
 }
+
+fn resource_backups_path(project: &str, resource_type: &resource::Type) -> String {
+    format!(
+        "/projects/{project}/services/{project}/resources/{}/backups",
+        utf8_percent_encode(
+            &resource_type.to_string(),
+            percent_encoding::NON_ALPHANUMERIC
+        ),
+    )
+}
+
+fn name_query(name: Option<&str>) -> String {
+    name.map(|name| {
+        format!(
+            "?name={}",
+            utf8_percent_encode(name, percent_encoding::NON_ALPHANUMERIC)
+        )
+    })
+    .unwrap_or_default()
+}
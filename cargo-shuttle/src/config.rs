@@ -4,7 +4,11 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
-use shuttle_common::{constants::API_URL_DEFAULT, ApiKey, ApiUrl};
+use shuttle_common::{
+    constants::API_URL_DEFAULT,
+    models::deployment::{HealthCheck, RestartPolicy},
+    ApiKey, ApiUrl,
+};
 use tracing::trace;
 
 use crate::args::ProjectArgs;
@@ -148,6 +152,13 @@ impl GlobalConfig {
 pub struct ProjectConfig {
     pub name: Option<String>,
     pub assets: Option<Vec<String>>,
+    /// Endpoint polled to know if a deployment is healthy. Read by the deployer from the
+    /// Shuttle.toml in the deployed archive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<HealthCheck>,
+    /// When a deployment that stopped by itself is started again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<RestartPolicy>,
 }
 
 /// A handler for configuration files. The type parameter `M` is the [`ConfigManager`] which handles
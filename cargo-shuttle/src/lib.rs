@@ -4,6 +4,7 @@ pub mod config;
 mod init;
 mod provisioner_server;
 mod suggestions;
+mod watch;
 
 use std::collections::{BTreeMap, HashMap};
 use std::ffi::OsString;
@@ -15,7 +16,7 @@ use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::str::FromStr;
 
-use args::{ConfirmationArgs, GenerateCommand};
+use args::{ConfirmationArgs, GenerateCommand, LogsFilterArgs, SecretsRestartArgs};
 use clap_mangen::Man;
 
 use shuttle_common::{
@@ -25,15 +26,21 @@ use shuttle_common::{
         SHUTTLE_GH_ISSUE_URL, SHUTTLE_IDLE_DOCS_URL, SHUTTLE_INSTALL_DOCS_URL, SHUTTLE_LOGIN_URL,
         STORAGE_DIRNAME,
     },
+    database,
     deployment::{DEPLOYER_END_MESSAGES_BAD, DEPLOYER_END_MESSAGES_GOOD},
     models::{
+        api_key::{self, get_api_keys_table},
         deployment::{
             get_deployments_table, DeploymentRequest, CREATE_SERVICE_BODY_LIMIT,
             GIT_STRINGS_MAX_LENGTH,
         },
+        domain::get_domains_table,
         error::ApiError,
+        log::LogsQuery,
+        organization::{get_members_table, get_organizations_table, Role},
         project,
-        resource::get_resource_tables,
+        resource::{get_backups_table, get_resource_tables},
+        secret::get_secrets_table,
     },
     resource, semvers_are_compatible, ApiKey, LogItem, VersionInfo,
 };
@@ -72,13 +79,14 @@ use tonic::Status;
 use tracing::{debug, error, trace, warn};
 use uuid::Uuid;
 
-pub use crate::args::{Command, ProjectArgs, RunArgs, ShuttleArgs};
 use crate::args::{
-    DeployArgs, DeploymentCommand, InitArgs, LoginArgs, LogoutArgs, ProjectCommand,
-    ProjectStartArgs, ResourceCommand, EXAMPLES_REPO,
+    ApiKeyCommand, DeployArgs, DeploymentCommand, DomainCommand, InitArgs, LoginArgs, LogoutArgs,
+    OrgCommand, ProjectCommand, ProjectStartArgs, ResourceCommand, SecretsCommand, EXAMPLES_REPO,
 };
+pub use crate::args::{Command, ProjectArgs, RunArgs, ShuttleArgs};
 use crate::client::Client;
 use crate::provisioner_server::LocalProvisioner;
+use crate::watch::WorkspaceWatcher;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
@@ -163,6 +171,8 @@ impl Shuttle {
             Command::Deploy(..)
                 | Command::Deployment(..)
                 | Command::Resource(..)
+                | Command::Secrets(..)
+                | Command::Domain(..)
                 | Command::Project(
                     // ProjectCommand::List does not need to know which project we are in
                     ProjectCommand::Start { .. }
@@ -171,6 +181,7 @@ impl Shuttle {
                         | ProjectCommand::Status { .. }
                         | ProjectCommand::Delete { .. }
                 )
+                | Command::Rollback { .. }
                 | Command::Stop
                 | Command::Clean
                 | Command::Status
@@ -189,7 +200,12 @@ impl Shuttle {
                 | Command::Logs { .. }
                 | Command::Logout(..)
                 | Command::Deployment(..)
+                | Command::Rollback { .. }
                 | Command::Resource(..)
+                | Command::Secrets(..)
+                | Command::Domain(..)
+                | Command::Org(..)
+                | Command::ApiKey(..)
                 | Command::Stop
                 | Command::Clean
                 | Command::Project(..)
@@ -199,6 +215,9 @@ impl Shuttle {
                 // init command will handle this by itself (log in and set key) if there is no key yet
                 client.set_api_key(self.ctx.api_key()?);
             }
+            if let Some(org) = args.org {
+                client.set_org(org);
+            }
             self.client = Some(client);
             self.check_api_versions().await?;
         }
@@ -215,6 +234,7 @@ impl Shuttle {
             Command::Login(login_args) => self.login(login_args).await,
             Command::Logout(logout_args) => self.logout(logout_args).await,
             Command::Feedback => self.feedback(),
+            Command::Run(run_args) if run_args.watch => self.local_run_watch(run_args).await,
             Command::Run(run_args) => self.local_run(run_args).await,
             Command::Deploy(deploy_args) => self.deploy(deploy_args).await,
             Command::Status => self.status().await,
@@ -222,12 +242,27 @@ impl Shuttle {
                 id,
                 latest,
                 follow,
+                all,
                 raw,
-            } => self.logs(id, latest, follow, raw).await,
+                filter,
+            } => {
+                if all {
+                    self.service_logs(raw, filter).await
+                } else {
+                    self.logs(id, latest, follow, raw, filter).await
+                }
+            }
             Command::Deployment(DeploymentCommand::List { page, limit, raw }) => {
                 self.deployments_list(page, limit, raw).await
             }
             Command::Deployment(DeploymentCommand::Status { id }) => self.deployment_get(id).await,
+            Command::Deployment(DeploymentCommand::Redeploy { id }) => {
+                self.deployment_redeploy(id).await
+            }
+            Command::Rollback {
+                id,
+                confirmation: ConfirmationArgs { yes },
+            } => self.rollback(id, yes).await,
             Command::Resource(ResourceCommand::List { raw, show_secrets }) => {
                 self.resources_list(raw, show_secrets).await
             }
@@ -235,8 +270,116 @@ impl Shuttle {
             Command::Clean => self.clean().await,
             Command::Resource(ResourceCommand::Delete {
                 resource_type,
+                name,
                 confirmation: ConfirmationArgs { yes },
-            }) => self.resource_delete(&resource_type, yes).await,
+            }) => {
+                self.resource_delete(&resource_type, name.as_deref(), yes)
+                    .await
+            }
+            Command::Resource(ResourceCommand::Backup {
+                resource_type,
+                name,
+                local,
+            }) => {
+                self.resource_backup(&resource_type, name.as_deref(), local)
+                    .await
+            }
+            Command::Resource(ResourceCommand::ListBackups {
+                resource_type,
+                name,
+                raw,
+                local,
+            }) => {
+                self.resource_list_backups(&resource_type, name.as_deref(), raw, local)
+                    .await
+            }
+            Command::Resource(ResourceCommand::Restore {
+                resource_type,
+                backup_id,
+                name,
+                into,
+                local,
+                confirmation: ConfirmationArgs { yes },
+            }) => {
+                self.resource_restore(
+                    &resource_type,
+                    &backup_id,
+                    name.as_deref(),
+                    into.as_deref(),
+                    local,
+                    yes,
+                )
+                .await
+            }
+            Command::Resource(ResourceCommand::Rotate {
+                resource_type,
+                name,
+                confirmation: ConfirmationArgs { yes },
+            }) => {
+                self.resource_rotate(&resource_type, name.as_deref(), yes)
+                    .await
+            }
+            Command::Secrets(SecretsCommand::List { raw, show_values }) => {
+                self.secrets_list(raw, show_values).await
+            }
+            Command::Secrets(SecretsCommand::Set {
+                secrets,
+                restart: SecretsRestartArgs { restart },
+            }) => {
+                self.secrets_set(secrets.into_iter().collect(), restart)
+                    .await
+            }
+            Command::Secrets(SecretsCommand::Unset {
+                keys,
+                restart: SecretsRestartArgs { restart },
+            }) => self.secrets_unset(keys, restart).await,
+            Command::Secrets(SecretsCommand::Import {
+                path,
+                restart: SecretsRestartArgs { restart },
+            }) => self.secrets_import(path, restart).await,
+            Command::Domain(DomainCommand::List { raw }) => self.domains_list(raw).await,
+            Command::Domain(DomainCommand::Add { fqdn }) => self.domain_add(&fqdn).await,
+            Command::Domain(DomainCommand::Remove {
+                fqdn,
+                confirmation: ConfirmationArgs { yes },
+            }) => self.domain_remove(&fqdn, yes).await,
+            Command::Org(OrgCommand::List { raw }) => self.organizations_list(raw).await,
+            Command::Org(OrgCommand::Create { name }) => self.organization_create(&name).await,
+            Command::Org(OrgCommand::Members { name, raw }) => {
+                self.organization_members(&name, raw).await
+            }
+            Command::Org(OrgCommand::AddMember {
+                name,
+                account_name,
+                role,
+            }) => {
+                self.organization_add_member(&name, &account_name, role)
+                    .await
+            }
+            Command::Org(OrgCommand::RemoveMember {
+                name,
+                account_name,
+                confirmation: ConfirmationArgs { yes },
+            }) => {
+                self.organization_remove_member(&name, &account_name, yes)
+                    .await
+            }
+            Command::ApiKey(ApiKeyCommand::Create {
+                name,
+                scopes,
+                projects,
+                expires_in_days,
+            }) => {
+                self.api_key_create(&name, scopes, projects, expires_in_days)
+                    .await
+            }
+            Command::ApiKey(ApiKeyCommand::List { raw }) => self.api_keys_list(raw).await,
+            Command::ApiKey(ApiKeyCommand::Revoke {
+                name,
+                confirmation: ConfirmationArgs { yes },
+            }) => self.api_key_revoke(&name, yes).await,
+            Command::ApiKey(ApiKeyCommand::Rotate) => self.api_key_rotate().await,
+            Command::ApiKey(ApiKeyCommand::Token { refresh }) => self.api_key_token(refresh).await,
             Command::Project(ProjectCommand::Start(ProjectStartArgs { idle_minutes })) => {
                 self.project_start(idle_minutes).await
             }
@@ -741,8 +884,10 @@ impl Shuttle {
         latest: bool,
         follow: bool,
         raw: bool,
+        filter: LogsFilterArgs,
     ) -> Result<CommandOutcome> {
         let client = self.client.as_ref().unwrap();
+        let query = filter.to_query();
         let id = if let Some(id) = id {
             id
         } else {
@@ -777,7 +922,7 @@ impl Shuttle {
 
         if follow {
             let mut stream = client
-                .get_logs_ws(self.ctx.project_name(), &id)
+                .get_logs_ws(self.ctx.project_name(), &id, &query)
                 .await
                 .map_err(|err| {
                     suggestions::logs::get_logs_failure(err, "Connecting to the logs stream failed")
@@ -809,7 +954,7 @@ impl Shuttle {
             }
         } else {
             let logs = client
-                .get_logs(self.ctx.project_name(), &id)
+                .get_logs(self.ctx.project_name(), &id, &query)
                 .await
                 .map_err(|err| {
                     suggestions::logs::get_logs_failure(err, "Fetching the deployment failed")
@@ -827,69 +972,714 @@ impl Shuttle {
         Ok(CommandOutcome::Ok)
     }
 
-    async fn deployments_list(&self, page: u32, limit: u32, raw: bool) -> Result<CommandOutcome> {
+    async fn service_logs(&self, raw: bool, filter: LogsFilterArgs) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let mut query = filter.to_query();
+
+        // Pages come newest first, and are printed as they arrive so a long history does not have
+        // to be held in memory before anything shows up
+        loop {
+            let page = client
+                .get_service_logs(self.ctx.project_name(), &query)
+                .await
+                .map_err(|err| {
+                    suggestions::logs::get_logs_failure(err, "Fetching the service logs failed")
+                })?;
+
+            for log in page.logs {
+                if raw {
+                    println!("{} {}", log.id, log.get_raw_line());
+                } else {
+                    println!("{} {log}", log.id.to_string().dim());
+                }
+            }
+
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn deployments_list(&self, page: u32, limit: u32, raw: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        if limit == 0 {
+            println!();
+            return Ok(CommandOutcome::Ok);
+        }
+        let limit = limit + 1;
+
+        let proj_name = self.ctx.project_name();
+        let mut deployments = client
+            .get_deployments(proj_name, page, limit)
+            .await
+            .map_err(suggestions::deployment::get_deployments_list_failure)?;
+        let page_hint = if deployments.len() == limit as usize {
+            deployments.pop();
+            true
+        } else {
+            false
+        };
+        let table = get_deployments_table(&deployments, proj_name, page, raw, page_hint);
+
+        println!("{table}");
+
+        if deployments.is_empty() {
+            println!("Run `cargo shuttle deploy` to deploy your project.");
+        } else {
+            println!("Run `cargo shuttle logs <id>` to get logs for a given deployment.");
+        }
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn deployment_get(&self, deployment_id: Uuid) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let deployment = client
+            .get_deployment_details(self.ctx.project_name(), &deployment_id)
+            .await
+            .map_err(suggestions::deployment::get_deployment_status_failure)?;
+
+        println!("{deployment}");
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn deployment_redeploy(&self, deployment_id: Uuid) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let proj_name = self.ctx.project_name();
+
+        let available = client.get_available_executables(proj_name).await?;
+        if !available.contains(&deployment_id) {
+            bail!(
+                "the executable of deployment {deployment_id} is no longer available. Run `cargo shuttle deploy` to build it again"
+            );
+        }
+
+        let deployment = client
+            .redeploy(proj_name, &deployment_id)
+            .await
+            .map_err(suggestions::deployment::redeploy_failure)?;
+
+        println!("Starting deployment {} again", deployment.id);
+        println!("Run `cargo shuttle logs {}` to follow it.", deployment.id);
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn rollback(&self, id: Option<Uuid>, no_confirm: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let proj_name = self.ctx.project_name();
+
+        let available = client.get_available_executables(proj_name).await?;
+        let deployments = client
+            .get_deployments(proj_name, 1, u32::MAX)
+            .await
+            .map_err(suggestions::deployment::get_deployments_list_failure)?;
+        // Deployments come in descending order, so the ones after the running one are older
+        let running = deployments.iter().position(|d| {
+            matches!(
+                d.state,
+                shuttle_common::deployment::State::Running
+                    | shuttle_common::deployment::State::Unhealthy
+            )
+        });
+        let deployments_ids: Vec<_> = deployments.iter().map(|d| d.id).collect();
+        let candidates: Vec<_> = deployments
+            .into_iter()
+            .enumerate()
+            .filter(|(index, d)| Some(*index) != running && available.contains(&d.id))
+            .collect();
+
+        if candidates.is_empty() {
+            println!("No previous deployment can be started again, since their executables have been cleaned up.");
+            println!("Run `cargo shuttle deploy` to deploy your project.");
+
+            return Ok(CommandOutcome::Ok);
+        }
+
+        let target = match id {
+            Some(id) if running.is_some_and(|running| deployments_ids[running] == id) => {
+                bail!("deployment {id} is already running")
+            }
+            Some(id) => id,
+            None => match candidates
+                .iter()
+                .find(|(index, _)| running.map_or(true, |running| *index > running))
+            {
+                Some((_, deployment)) => deployment.id,
+                None => bail!(
+                    "no deployment older than the running one can be started again, pass a deployment id to pick one"
+                ),
+            },
+        };
+
+        let candidates: Vec<_> = candidates.into_iter().map(|(_, d)| d).collect();
+        println!("These deployments can be started again without rebuilding:");
+        println!(
+            "{}",
+            get_deployments_table(&candidates, proj_name, 1, false, false)
+        );
+
+        if !no_confirm
+            && !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Roll back to deployment {target}?"))
+                .default(true)
+                .interact()
+                .unwrap()
+        {
+            return Ok(CommandOutcome::Ok);
+        }
+
+        self.deployment_redeploy(target).await
+    }
+
+    async fn resources_list(&self, raw: bool, show_secrets: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let resources = client
+            .get_service_resources(self.ctx.project_name())
+            .await
+            .map_err(suggestions::resources::get_service_resources_failure)?;
+        let table = get_resource_tables(&resources, self.ctx.project_name(), raw, show_secrets);
+
+        println!("{table}");
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn secrets_list(&self, raw: bool, show_values: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let secrets = client
+            .get_secrets(self.ctx.project_name(), show_values)
+            .await?;
+
+        println!(
+            "{}",
+            get_secrets_table(&secrets, self.ctx.project_name(), raw)
+        );
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn secrets_set(
+        &self,
+        secrets: BTreeMap<String, String>,
+        restart: bool,
+    ) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let set = client
+            .set_secrets(self.ctx.project_name(), secrets, restart)
+            .await?;
+
+        for secret in set {
+            println!("Set secret {}", secret.key);
+        }
+        self.secrets_restart_hint(restart);
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn secrets_unset(&self, keys: Vec<String>, restart: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+
+        client
+            .unset_secrets(self.ctx.project_name(), keys.clone(), restart)
+            .await?;
+
+        for key in keys {
+            println!("Unset secret {key}");
+        }
+        self.secrets_restart_hint(restart);
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn secrets_import(&self, path: Option<PathBuf>, restart: bool) -> Result<CommandOutcome> {
+        let path = path.unwrap_or_else(|| self.ctx.working_directory().join("Secrets.toml"));
+        let secrets: BTreeMap<String, String> = read_to_string(&path)
+            .with_context(|| format!("failed to read secrets from {}", path.display()))?
+            .parse::<toml::Value>()?
+            .try_into()
+            .context("secrets file should only contain string values")?;
+
+        if secrets.is_empty() {
+            println!("No secrets found in {}", path.display());
+
+            return Ok(CommandOutcome::Ok);
+        }
+
+        self.secrets_set(secrets, restart).await
+    }
+
+    fn secrets_restart_hint(&self, restart: bool) {
+        if restart {
+            println!("Restarting the running deployment so the change takes effect");
+        } else {
+            println!(
+                "{}",
+                formatdoc! {"
+                    Note:
+                        The running deployment keeps its current secrets until it is restarted.
+                        Use `--restart` to restart it now, or deploy again."
+                }
+                .yellow(),
+            );
+        }
+    }
+
+    async fn domains_list(&self, raw: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let domains = client.get_domains(self.ctx.project_name()).await?;
+
+        println!(
+            "{}",
+            get_domains_table(&domains, self.ctx.project_name(), raw)
+        );
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn domain_add(&self, fqdn: &str) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+
+        println!("Verifying {fqdn} and requesting a certificate for it. This can take a minute.");
+        let domain = client
+            .add_domain(self.ctx.project_name(), fqdn)
+            .await
+            .map_err(suggestions::domain::add_domain_failure)?;
+
+        println!("Added custom domain {}", domain.fqdn);
+        println!(
+            "{}",
+            formatdoc! {"
+                Note:
+                    The project environment is restarted to serve the new domain.
+                    Your service will be reachable at https://{} once it is running again.",
+                domain.fqdn
+            }
+            .yellow(),
+        );
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn domain_remove(&self, fqdn: &str, no_confirm: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+
+        if !no_confirm {
+            println!(
+                "{}",
+                formatdoc!(
+                    "
+                WARNING:
+                    Are you sure you want to remove {} from this project?
+                    Its certificate is deleted and the domain stops being served.",
+                    fqdn
+                )
+                .bold()
+                .red()
+            );
+            if !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Are you sure?")
+                .default(false)
+                .interact()
+                .unwrap()
+            {
+                return Ok(CommandOutcome::Ok);
+            }
+        }
+
+        let domain = client.remove_domain(self.ctx.project_name(), fqdn).await?;
+
+        println!("Removed custom domain {}", domain.fqdn);
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn organizations_list(&self, raw: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let organizations = client.get_organizations().await?;
+
+        println!("{}", get_organizations_table(&organizations, raw));
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn organization_create(&self, name: &str) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let organization = client.create_organization(name).await?;
+
+        println!("Created organization {}", organization.name);
+        println!(
+            "Use `--org {}` with other commands to work on the projects of the organization",
+            organization.name
+        );
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn organization_members(&self, name: &str, raw: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let members = client.get_organization_members(name).await?;
+
+        println!("{}", get_members_table(&members, name, raw));
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn organization_add_member(
+        &self,
+        name: &str,
+        account_name: &str,
+        role: Role,
+    ) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let member = client
+            .set_organization_member(name, account_name, role)
+            .await?;
+
+        println!(
+            "{} is now a member of {name} with the {} role",
+            member.account_name, member.role
+        );
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn organization_remove_member(
+        &self,
+        name: &str,
+        account_name: &str,
+        no_confirm: bool,
+    ) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+
+        if !no_confirm {
+            println!(
+                "{}",
+                formatdoc!(
+                    "
+                WARNING:
+                    Are you sure you want to remove {} from {}?
+                    They lose access to the projects of the organization.",
+                    account_name,
+                    name
+                )
+                .bold()
+                .red()
+            );
+            if !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Are you sure?")
+                .default(false)
+                .interact()
+                .unwrap()
+            {
+                return Ok(CommandOutcome::Ok);
+            }
+        }
+
+        let member = client
+            .remove_organization_member(name, account_name)
+            .await?;
+
+        println!("Removed {} from {name}", member.account_name);
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn api_key_create(
+        &self,
+        name: &str,
+        scopes: Vec<String>,
+        projects: Vec<String>,
+        expires_in_days: Option<u32>,
+    ) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let request = api_key::CreateRequest {
+            scopes: (!scopes.is_empty()).then_some(scopes),
+            projects: (!projects.is_empty()).then_some(projects),
+            expires_at: expires_in_days
+                .map(|days| chrono::Utc::now() + chrono::Duration::days(days.into())),
+        };
+        let api_key = client.create_api_key(name, request).await?;
+
+        println!("Created API key {}", api_key.name);
+        if let Some(key) = api_key.key {
+            println!("{}", key.bold());
+            println!("Store it somewhere safe, it will not be shown again.");
+        }
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn api_keys_list(&self, raw: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let api_keys = client.get_api_keys().await?;
+
+        println!("{}", get_api_keys_table(&api_keys, raw));
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn api_key_revoke(&self, name: &str, no_confirm: bool) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+
+        if !no_confirm {
+            println!(
+                "{}",
+                formatdoc!(
+                    "
+                WARNING:
+                    Are you sure you want to revoke the API key {}?
+                    Anything still using it will no longer be able to reach Shuttle.",
+                    name
+                )
+                .bold()
+                .red()
+            );
+            if !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Are you sure?")
+                .default(false)
+                .interact()
+                .unwrap()
+            {
+                return Ok(CommandOutcome::Ok);
+            }
+        }
+
+        let api_key = client.revoke_api_key(name).await?;
+
+        println!("Revoked API key {}", api_key.name);
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn api_key_rotate(&mut self) -> Result<CommandOutcome> {
         let client = self.client.as_ref().unwrap();
-        if limit == 0 {
-            println!();
-            return Ok(CommandOutcome::Ok);
+        let rotated = client.rotate_api_key().await?;
+        let api_key = ApiKey::parse(&rotated.key)?;
+
+        self.ctx.set_api_key(api_key.clone())?;
+
+        if let Some(client) = self.client.as_mut() {
+            client.set_api_key(api_key);
         }
-        let limit = limit + 1;
 
-        let proj_name = self.ctx.project_name();
-        let mut deployments = client
-            .get_deployments(proj_name, page, limit)
-            .await
-            .map_err(suggestions::deployment::get_deployments_list_failure)?;
-        let page_hint = if deployments.len() == limit as usize {
-            deployments.pop();
-            true
-        } else {
-            false
+        println!("Rotated the API key of your account and logged in with the new key.");
+        println!(
+            "The previous key keeps working until {}. Replace it wherever else it is used before then.",
+            rotated
+                .previous_key_expires_at
+                .format("%Y-%m-%d %H:%M:%S UTC")
+        );
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn api_key_token(&self, refresh_token: Option<String>) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let token = match refresh_token {
+            Some(refresh_token) => client.refresh_token(refresh_token).await?,
+            None => client.get_token().await?,
         };
-        let table = get_deployments_table(&deployments, proj_name, page, raw, page_hint);
 
-        println!("{table}");
+        println!("Token: {}", token.token);
+        println!("Refresh token: {}", token.refresh_token);
 
-        if deployments.is_empty() {
-            println!("Run `cargo shuttle deploy` to deploy your project.");
-        } else {
-            println!("Run `cargo shuttle logs <id>` to get logs for a given deployment.");
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn resource_delete(
+        &self,
+        resource_type: &resource::Type,
+        name: Option<&str>,
+        no_confirm: bool,
+    ) -> Result<CommandOutcome> {
+        let client = self.client.as_ref().unwrap();
+        let resource = resource_description(resource_type, name);
+
+        if !no_confirm {
+            println!(
+                "{}",
+                formatdoc!(
+                    "
+                WARNING:
+                    Are you sure you want to delete this project's {}?
+                    This action is permanent.",
+                    resource
+                )
+                .bold()
+                .red()
+            );
+            if !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Are you sure?")
+                .default(false)
+                .interact()
+                .unwrap()
+            {
+                return Ok(CommandOutcome::Ok);
+            }
         }
 
+        client
+            .delete_service_resource(self.ctx.project_name(), resource_type, name)
+            .await?;
+
+        println!("Deleted resource {resource}");
+        println!(
+            "{}",
+            formatdoc! {"
+                Note:
+                    Remember to remove the resource annotation from your #[shuttle_runtime::main] function.
+                    Otherwise, it will be provisioned again during the next deployment."
+            }
+            .yellow(),
+        );
+
         Ok(CommandOutcome::Ok)
     }
 
-    async fn deployment_get(&self, deployment_id: Uuid) -> Result<CommandOutcome> {
-        let client = self.client.as_ref().unwrap();
-        let deployment = client
-            .get_deployment_details(self.ctx.project_name(), &deployment_id)
-            .await
-            .map_err(suggestions::deployment::get_deployment_status_failure)?;
+    async fn resource_backup(
+        &self,
+        resource_type: &resource::Type,
+        name: Option<&str>,
+        local: bool,
+    ) -> Result<CommandOutcome> {
+        let backup = if local {
+            check_local_backup_type(resource_type)?;
 
-        println!("{deployment}");
+            LocalProvisioner::new()?
+                .backup_shared_pg(self.ctx.project_name(), name)
+                .await?
+        } else {
+            self.client
+                .as_ref()
+                .unwrap()
+                .backup_service_resource(self.ctx.project_name(), resource_type, name)
+                .await?
+        };
+
+        println!(
+            "Backed up {} as '{}'",
+            resource_description(resource_type, name),
+            backup.id
+        );
 
         Ok(CommandOutcome::Ok)
     }
 
-    async fn resources_list(&self, raw: bool, show_secrets: bool) -> Result<CommandOutcome> {
-        let client = self.client.as_ref().unwrap();
-        let resources = client
-            .get_service_resources(self.ctx.project_name())
-            .await
-            .map_err(suggestions::resources::get_service_resources_failure)?;
-        let table = get_resource_tables(&resources, self.ctx.project_name(), raw, show_secrets);
+    async fn resource_list_backups(
+        &self,
+        resource_type: &resource::Type,
+        name: Option<&str>,
+        raw: bool,
+        local: bool,
+    ) -> Result<CommandOutcome> {
+        let backups = if local {
+            check_local_backup_type(resource_type)?;
+
+            LocalProvisioner::list_shared_pg_backups(self.ctx.project_name(), name)?
+        } else {
+            self.client
+                .as_ref()
+                .unwrap()
+                .get_service_resource_backups(self.ctx.project_name(), resource_type, name)
+                .await?
+        };
+
+        let table = get_backups_table(&backups, &resource_description(resource_type, name), raw);
 
         println!("{table}");
 
         Ok(CommandOutcome::Ok)
     }
 
-    async fn resource_delete(
+    async fn resource_restore(
+        &self,
+        resource_type: &resource::Type,
+        backup_id: &str,
+        name: Option<&str>,
+        into: Option<&str>,
+        local: bool,
+        no_confirm: bool,
+    ) -> Result<CommandOutcome> {
+        let resource = resource_description(resource_type, name);
+
+        // Restoring into a new database leaves the backed up one as it is
+        if into.is_none() && !no_confirm {
+            println!(
+                "{}",
+                formatdoc!(
+                    "
+                WARNING:
+                    Are you sure you want to restore this project's {} from '{}'?
+                    Everything written to it after the backup was taken will be lost.",
+                    resource,
+                    backup_id
+                )
+                .bold()
+                .red()
+            );
+            if !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Are you sure?")
+                .default(false)
+                .interact()
+                .unwrap()
+            {
+                return Ok(CommandOutcome::Ok);
+            }
+        }
+
+        if local {
+            check_local_backup_type(resource_type)?;
+
+            LocalProvisioner::new()?
+                .restore_shared_pg(self.ctx.project_name(), name, backup_id, into)
+                .await?;
+        } else {
+            self.client
+                .as_ref()
+                .unwrap()
+                .restore_service_resource(
+                    self.ctx.project_name(),
+                    resource_type,
+                    name,
+                    backup_id,
+                    into,
+                )
+                .await?;
+        }
+
+        match into {
+            Some(into) => {
+                println!(
+                    "Restored backup '{backup_id}' of {resource} into a new database named '{into}'"
+                );
+                println!(
+                    "{}",
+                    formatdoc! {"
+                        Note:
+                            Add a resource annotation with `name = \"{into}\"` to your #[shuttle_runtime::main] function to use it."
+                    }
+                    .yellow(),
+                );
+            }
+            None => println!("Restored {resource} from backup '{backup_id}'"),
+        }
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    async fn resource_rotate(
         &self,
         resource_type: &resource::Type,
+        name: Option<&str>,
         no_confirm: bool,
     ) -> Result<CommandOutcome> {
         let client = self.client.as_ref().unwrap();
+        let resource = resource_description(resource_type, name);
 
         if !no_confirm {
             println!(
@@ -897,9 +1687,9 @@ impl Shuttle {
                 formatdoc!(
                     "
                 WARNING:
-                    Are you sure you want to delete this project's {}?
-                    This action is permanent.",
-                    resource_type
+                    Are you sure you want to rotate the credentials of this project's {}?
+                    The running deployment will be restarted to use the new credentials.",
+                    resource
                 )
                 .bold()
                 .red()
@@ -914,17 +1704,20 @@ impl Shuttle {
             }
         }
 
-        client
-            .delete_service_resource(self.ctx.project_name(), resource_type)
+        let response = client
+            .rotate_service_resource(self.ctx.project_name(), resource_type, name)
             .await?;
 
-        println!("Deleted resource {resource_type}");
+        println!("Rotated the credentials of {resource}");
         println!(
             "{}",
             formatdoc! {"
                 Note:
-                    Remember to remove the resource annotation from your #[shuttle_runtime::main] function.
-                    Otherwise, it will be provisioned again during the next deployment."
+                    The old credentials stop working at {}.
+                    Update anything outside of this project that connects with them before then.",
+                response
+                    .old_expires_at
+                    .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
             }
             .yellow(),
         );
@@ -938,6 +1731,7 @@ impl Shuttle {
         provisioner_server: &JoinHandle<Result<(), tonic::transport::Error>>,
         idx: u16,
         provisioner_port: u16,
+        cached_resources: &mut HashMap<String, Vec<Vec<u8>>>,
     ) -> Result<
         Option<(
             Child,
@@ -1051,6 +1845,7 @@ impl Shuttle {
             Environment::Local,
             &format!("http://localhost:{provisioner_port}"),
             None,
+            None,
             portpicker::pick_unused_port().expect("unable to find available port for gRPC server"),
             runtime_executable,
             service.workspace_path.as_path(),
@@ -1091,7 +1886,12 @@ impl Shuttle {
                 .into_string()
                 .expect("to convert path to string"),
             service_name: service_name.to_string(),
-            resources: Default::default(),
+            project_name: Default::default(),
+            // Resources provisioned by an earlier run in this session are reused as they are
+            resources: cached_resources
+                .get(service_name.as_str())
+                .cloned()
+                .unwrap_or_default(),
             secrets,
         });
 
@@ -1111,6 +1911,8 @@ impl Shuttle {
             return Ok(None);
         }
 
+        cached_resources.insert(service_name.to_string(), response.resources.clone());
+
         let resources = response
             .resources
             .into_iter()
@@ -1276,12 +2078,13 @@ impl Shuttle {
 
         Shuttle::find_available_port(&mut run_args, services.len());
 
+        let mut resources = HashMap::new();
         let mut signal_received = false;
         for (i, service) in services.iter().enumerate() {
             // We must cover the case of starting multiple workspace services and receiving a signal in parallel.
             // This must stop all the existing runtimes and creating new ones.
             signal_received = tokio::select! {
-                res = Shuttle::spin_local_runtime(&run_args, service, &provisioner_server, i as u16, provisioner_port) => {
+                res = Shuttle::spin_local_runtime(&run_args, service, &provisioner_server, i as u16, provisioner_port, &mut resources) => {
                     match res {
                         Ok(runtime) => {
                             Shuttle::add_runtime_info(runtime, &mut runtimes, &[&provisioner_server]).await?;
@@ -1377,6 +2180,27 @@ impl Shuttle {
         Ok(CommandOutcome::Ok)
     }
 
+    #[cfg(target_family = "unix")]
+    async fn handle_signals() -> bool {
+        let mut sigterm_notif =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+                .expect("Can not get the SIGTERM signal receptor");
+        let mut sigint_notif =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())
+                .expect("Can not get the SIGINT signal receptor");
+
+        tokio::select! {
+            _ = sigterm_notif.recv() => {
+                println!("cargo-shuttle received SIGTERM.");
+                true
+            },
+            _ = sigint_notif.recv() => {
+                println!("cargo-shuttle received SIGINT.");
+                true
+            }
+        }
+    }
+
     #[cfg(target_family = "windows")]
     async fn handle_signals() -> bool {
         let mut ctrl_break_notif = tokio::signal::windows::ctrl_break()
@@ -1430,10 +2254,11 @@ impl Shuttle {
 
         Shuttle::find_available_port(&mut run_args, services.len());
 
+        let mut resources = HashMap::new();
         let mut signal_received = false;
         for (i, service) in services.iter().enumerate() {
             signal_received = tokio::select! {
-                res = Shuttle::spin_local_runtime(&run_args, service, &provisioner_server, i as u16, provisioner_port) => {
+                res = Shuttle::spin_local_runtime(&run_args, service, &provisioner_server, i as u16, provisioner_port, &mut resources) => {
                     Shuttle::add_runtime_info(res.unwrap(), &mut runtimes, &[&provisioner_server]).await?;
                     false
                 },
@@ -1508,6 +2333,140 @@ impl Shuttle {
         Ok(CommandOutcome::Ok)
     }
 
+    /// Run the services of the workspace, then build and start them again every time a file of
+    /// the workspace changes. A failed build is reported and leaves the previous services running,
+    /// and a service that exits is reported and started again by the next change.
+    async fn local_run_watch(&self, mut run_args: RunArgs) -> Result<CommandOutcome> {
+        debug!("starting local run in watch mode");
+        let working_directory = self.ctx.working_directory();
+        let mut watcher = WorkspaceWatcher::new(working_directory).await?;
+        let (mut provisioner_server, mut provisioner_port) =
+            Shuttle::setup_local_provisioner().await?;
+
+        let mut runtimes: Vec<(
+            Child,
+            RuntimeClient<ClaimService<InjectPropagation<Channel>>>,
+        )> = Vec::new();
+        let mut resources = HashMap::new();
+        let mut ports_picked = false;
+
+        'watch: loop {
+            // Signals are also handled while building, since that is where most of the time goes
+            let started = async {
+                let services = match self.pre_local_run(&run_args).await {
+                    Ok(services) => services,
+                    Err(err) => {
+                        println!("{} {err:?}", "Build failed:".bold().red());
+                        return Ok(());
+                    }
+                };
+
+                for (mut rt, mut rt_client) in runtimes.drain(..) {
+                    Shuttle::stop_runtime(&mut rt, &mut rt_client)
+                        .await
+                        .unwrap_or_else(|err| {
+                            trace!(status = ?err, "stopping the runtime errored out");
+                        });
+                    // Make sure the old runtime is gone before a new one binds its port
+                    let _ = rt.kill().await;
+                }
+
+                // Keep the services on the same ports across rebuilds
+                if !ports_picked {
+                    Shuttle::find_available_port(&mut run_args, services.len());
+                    ports_picked = true;
+                }
+
+                // The provisioner is aborted when a runtime fails to start
+                if provisioner_server.is_finished() {
+                    (provisioner_server, provisioner_port) =
+                        Shuttle::setup_local_provisioner().await?;
+                }
+
+                for (i, service) in services.iter().enumerate() {
+                    match Shuttle::spin_local_runtime(
+                        &run_args,
+                        service,
+                        &provisioner_server,
+                        i as u16,
+                        provisioner_port,
+                        &mut resources,
+                    )
+                    .await
+                    {
+                        Ok(Some(runtime)) => runtimes.push(runtime),
+                        // The load error was already logged
+                        Ok(None) => {}
+                        Err(e) => println!("Runtime error: {e:?}"),
+                    }
+                }
+
+                anyhow::Ok(())
+            };
+
+            tokio::select! {
+                res = started => res?,
+                _ = Shuttle::handle_signals() => break 'watch,
+            }
+
+            println!(
+                "{} {} for changes",
+                "    Watching".bold().green(),
+                working_directory.display()
+            );
+
+            loop {
+                tokio::select! {
+                    res = watcher.changed() => {
+                        res?;
+                        println!("{}", "    Files changed, rebuilding".bold().green());
+                        break;
+                    },
+                    (index, res) = Shuttle::runtime_exited(&mut runtimes) => {
+                        runtimes.remove(index);
+
+                        let status = res.map_or_else(|err| err.to_string(), |status| status.to_string());
+                        println!(
+                            "{} {status}. It is started again on the next change.",
+                            "A service exited:".bold().red()
+                        );
+                    },
+                    _ = Shuttle::handle_signals() => break 'watch,
+                }
+            }
+        }
+
+        println!("Killing all the runtimes...");
+        provisioner_server.abort();
+        for (mut rt, mut rt_client) in runtimes {
+            Shuttle::stop_runtime(&mut rt, &mut rt_client)
+                .await
+                .unwrap_or_else(|err| {
+                    trace!(status = ?err, "stopping the runtime errored out");
+                });
+        }
+
+        Ok(CommandOutcome::Ok)
+    }
+
+    /// Wait for one of the runtimes to exit, giving its index. Never completes without runtimes.
+    async fn runtime_exited(
+        runtimes: &mut [(
+            Child,
+            RuntimeClient<ClaimService<InjectPropagation<Channel>>>,
+        )],
+    ) -> (usize, std::io::Result<std::process::ExitStatus>) {
+        if runtimes.is_empty() {
+            return futures::future::pending().await;
+        }
+
+        let (res, index, _) =
+            futures::future::select_all(runtimes.iter_mut().map(|(rt, _)| Box::pin(rt.wait())))
+                .await;
+
+        (index, res)
+    }
+
     async fn deploy(&mut self, args: DeployArgs) -> Result<CommandOutcome> {
         let client = self.client.as_ref().unwrap();
         let working_directory = self.ctx.working_directory();
@@ -1563,7 +2522,11 @@ impl Shuttle {
             .map_err(suggestions::deploy::deploy_request_failure)?;
 
         let mut stream = client
-            .get_logs_ws(self.ctx.project_name(), &deployment.id)
+            .get_logs_ws(
+                self.ctx.project_name(),
+                &deployment.id,
+                &LogsQuery::default(),
+            )
             .await
             .map_err(|err| {
                 suggestions::deploy::deployment_setup_failure(
@@ -1679,7 +2642,11 @@ impl Shuttle {
                 // the terminal isn't completely spammed
                 sleep(Duration::from_millis(100)).await;
                 stream = client
-                    .get_logs_ws(self.ctx.project_name(), &deployment.id)
+                    .get_logs_ws(
+                        self.ctx.project_name(),
+                        &deployment.id,
+                        &LogsQuery::default(),
+                    )
                     .await
                     .map_err(|err| {
                         suggestions::deploy::deployment_setup_failure(
@@ -1705,8 +2672,13 @@ impl Shuttle {
                 )
             })?;
 
-        // A deployment will only exist if there is currently one in the running state
-        if deployment.state != shuttle_common::deployment::State::Running {
+        // A deployment will only exist if there is currently one in the running state. An unhealthy
+        // deployment is still serving, and is restarted if it does not recover.
+        if !matches!(
+            deployment.state,
+            shuttle_common::deployment::State::Running
+                | shuttle_common::deployment::State::Unhealthy
+        ) {
             println!("{}", "Deployment has not entered the running state".red());
             println!();
 
@@ -1742,6 +2714,14 @@ impl Shuttle {
             return Ok(CommandOutcome::DeploymentFailure);
         }
 
+        if deployment.state == shuttle_common::deployment::State::Unhealthy {
+            println!(
+                "{}",
+                "Deployment is running, but failing its health checks".yellow()
+            );
+            println!();
+        }
+
         let service = client.get_service(self.ctx.project_name()).await?;
         let resources = client
             .get_service_resources(self.ctx.project_name())
@@ -1892,6 +2872,9 @@ impl Shuttle {
                     .map(|i| i.to_string())
                     .unwrap_or("<unknown>".to_owned())
             );
+            if let Some(limits) = project.limits {
+                println!("Limits: {limits}");
+            }
         }
 
         Ok(CommandOutcome::Ok)
@@ -2238,6 +3221,23 @@ pub enum CommandOutcome {
     DeploymentFailure,
 }
 
+fn resource_description(resource_type: &resource::Type, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{resource_type} named '{name}'"),
+        None => resource_type.to_string(),
+    }
+}
+
+/// Only shared Postgres databases can be backed up, both locally and when deployed
+fn check_local_backup_type(resource_type: &resource::Type) -> Result<()> {
+    if *resource_type
+        != resource::Type::Database(database::Type::Shared(database::SharedEngine::Postgres))
+    {
+        bail!("only shared Postgres databases can be backed up");
+    }
+
+    Ok(())
+}
 #[cfg(test)]
 mod tests {
     use flate2::read::GzDecoder;
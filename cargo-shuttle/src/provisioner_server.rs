@@ -1,15 +1,18 @@
-use std::{collections::HashMap, io::stdout, net::SocketAddr, time::Duration};
+use std::{
+    collections::HashMap, ffi::OsStr, io::stdout, net::SocketAddr, path::PathBuf, time::Duration,
+};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
 use bollard::{
-    container::{Config, CreateContainerOptions, StartContainerOptions},
-    exec::{CreateExecOptions, CreateExecResults},
+    container::{Config, CreateContainerOptions, LogOutput, StartContainerOptions},
+    exec::{CreateExecOptions, CreateExecResults, StartExecResults},
     image::CreateImageOptions,
     models::{CreateImageInfo, HostConfig, PortBinding, ProgressDetail},
     service::ContainerInspectResponse,
     Docker,
 };
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use crossterm::{
     cursor::{MoveDown, MoveUp},
     terminal::{Clear, ClearType},
@@ -17,23 +20,30 @@ use crossterm::{
 };
 use futures::StreamExt;
 use portpicker::pick_unused_port;
+use rand::Rng;
 use shuttle_common::{
     database::{AwsRdsEngine, SharedEngine},
+    models::resource,
     Secret,
 };
 use shuttle_proto::provisioner::{
     provisioner_server::{Provisioner, ProvisionerServer},
-    ContainerRequest, ContainerResponse, DatabaseDeletionResponse, DatabaseRequest,
-    DatabaseResponse, Ping, Pong,
+    Backup, ContainerRequest, ContainerResponse, DatabaseDeletionResponse, DatabaseRequest,
+    DatabaseResponse, ListBackupsResponse, ObjectStoreDeletionResponse, ObjectStoreRequest,
+    ObjectStoreResponse, Ping, Pong, RestoreRequest, RestoreResponse, RotateCredentialsResponse,
 };
 use shuttle_service::database::Type;
-use tokio::{task::JoinHandle, time::sleep};
+use tokio::{fs::File, io::AsyncWriteExt, task::JoinHandle, time::sleep};
 use tonic::{
     transport::{self, Server},
     Request, Response, Status,
 };
 use tracing::{error, trace};
 
+/// Backups are named after the time they were taken and a random suffix, the same as for deployed
+/// databases
+const BACKUP_ID_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
 /// A provisioner for local runs
 /// It uses Docker to create Databases
 pub struct LocalProvisioner {
@@ -169,6 +179,7 @@ impl LocalProvisioner {
     async fn get_db_connection_string(
         &self,
         project_name: &str,
+        name: &str,
         db_type: Type,
     ) -> Result<DatabaseResponse, Status> {
         trace!("getting sql string for project '{project_name}'");
@@ -184,7 +195,7 @@ impl LocalProvisioner {
             env,
             is_ready_cmd,
         } = db_type_to_config(db_type);
-        let container_name = format!("shuttle_{project_name}_{type}");
+        let container_name = db_container_name(project_name, &r#type, name);
 
         let container = self
             .get_container(&container_name, &image, &port, env)
@@ -216,6 +227,229 @@ impl LocalProvisioner {
         Ok(res)
     }
 
+    /// Dump the local shared Postgres database of a project to a backup on this machine
+    pub async fn backup_shared_pg(
+        &self,
+        project_name: &str,
+        name: Option<&str>,
+    ) -> Result<resource::Backup> {
+        let container_name = self.running_shared_pg_container(project_name, name).await?;
+
+        let dump = self
+            .exec(
+                &container_name,
+                vec!["pg_dump", "-U", "postgres", "-Fc", "postgres"],
+                None,
+            )
+            .await?;
+
+        let created_at = Utc::now();
+        let id = format!(
+            "{}-{:08x}",
+            created_at.format(BACKUP_ID_FORMAT),
+            rand::thread_rng().gen::<u32>()
+        );
+        let backups_dir = local_backups_dir(&container_name)?;
+
+        std::fs::create_dir_all(&backups_dir)?;
+        std::fs::write(backups_dir.join(format!("{id}.dump")), &dump)?;
+
+        Ok(resource::Backup {
+            id,
+            created_at,
+            size: dump.len() as u64,
+        })
+    }
+
+    /// List the backups taken of the local shared Postgres database of a project, newest first
+    pub fn list_shared_pg_backups(
+        project_name: &str,
+        name: Option<&str>,
+    ) -> Result<Vec<resource::Backup>> {
+        let r#type = db_type_to_config(Type::Shared(SharedEngine::Postgres)).r#type;
+        let container_name = db_container_name(project_name, &r#type, name.unwrap_or_default());
+        let backups_dir = local_backups_dir(&container_name)?;
+
+        if !backups_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups = Vec::new();
+
+        for entry in std::fs::read_dir(backups_dir)? {
+            let path = entry?.path();
+
+            if path.extension().and_then(OsStr::to_str) != Some("dump") {
+                continue;
+            }
+
+            let Some(id) = path.file_stem().and_then(OsStr::to_str) else {
+                continue;
+            };
+            let Some(created_at) = parse_backup_id(id) else {
+                continue;
+            };
+
+            backups.push(resource::Backup {
+                id: id.to_string(),
+                created_at,
+                size: path.metadata()?.len(),
+            });
+        }
+
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+        Ok(backups)
+    }
+
+    /// Restore a local backup over the database it was taken from, or into a new database named `into`
+    pub async fn restore_shared_pg(
+        &self,
+        project_name: &str,
+        name: Option<&str>,
+        backup_id: &str,
+        into: Option<&str>,
+    ) -> Result<()> {
+        let r#type = db_type_to_config(Type::Shared(SharedEngine::Postgres)).r#type;
+        let source_container_name =
+            db_container_name(project_name, &r#type, name.unwrap_or_default());
+        let backup_path =
+            local_backups_dir(&source_container_name)?.join(format!("{backup_id}.dump"));
+
+        if parse_backup_id(backup_id).is_none() || !backup_path.exists() {
+            bail!("there is no backup with ID '{backup_id}' for this database");
+        }
+
+        let dump = File::open(backup_path).await?;
+
+        let (container_name, into_existing) = match into {
+            Some(into) => {
+                let container_name = db_container_name(project_name, &r#type, into);
+
+                if self
+                    .docker
+                    .inspect_container(&container_name, None)
+                    .await
+                    .is_ok()
+                {
+                    bail!("a database named '{into}' already exists");
+                }
+
+                self.get_db_connection_string(
+                    project_name,
+                    into,
+                    Type::Shared(SharedEngine::Postgres),
+                )
+                .await?;
+
+                (container_name, false)
+            }
+            None => (
+                self.running_shared_pg_container(project_name, name).await?,
+                true,
+            ),
+        };
+
+        let mut cmd = vec![
+            "pg_restore",
+            "-U",
+            "postgres",
+            "-d",
+            "postgres",
+            "--no-owner",
+            "--single-transaction",
+            "--exit-on-error",
+        ];
+
+        // Drop what is in the database first so the restore replaces it
+        if into_existing {
+            cmd.extend(["--clean", "--if-exists"]);
+        }
+
+        self.exec(&container_name, cmd, Some(dump)).await?;
+
+        Ok(())
+    }
+
+    /// Get the name of the container of a local shared Postgres database, making sure it is running
+    async fn running_shared_pg_container(
+        &self,
+        project_name: &str,
+        name: Option<&str>,
+    ) -> Result<String> {
+        let r#type = db_type_to_config(Type::Shared(SharedEngine::Postgres)).r#type;
+        let container_name = db_container_name(project_name, &r#type, name.unwrap_or_default());
+
+        let running = self
+            .docker
+            .inspect_container(&container_name, None)
+            .await
+            .ok()
+            .and_then(|container| container.state)
+            .and_then(|state| state.running)
+            .unwrap_or_default();
+
+        if !running {
+            bail!(
+                "the database container '{container_name}' is not running, start it with `cargo shuttle run` first"
+            );
+        }
+
+        Ok(container_name)
+    }
+
+    /// Run a command in a container and return its stdout, failing if it exits unsuccessfully. The
+    /// `stdin` file is streamed into the command.
+    async fn exec(
+        &self,
+        container_name: &str,
+        cmd: Vec<&str>,
+        stdin: Option<File>,
+    ) -> Result<Vec<u8>> {
+        let config = CreateExecOptions {
+            cmd: Some(cmd),
+            attach_stdin: Some(stdin.is_some()),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            ..Default::default()
+        };
+
+        let CreateExecResults { id } = self.docker.create_exec(container_name, config).await?;
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        if let StartExecResults::Attached {
+            mut output,
+            mut input,
+        } = self.docker.start_exec(&id, None).await?
+        {
+            if let Some(mut stdin) = stdin {
+                tokio::io::copy(&mut stdin, &mut input).await?;
+                input.shutdown().await?;
+            }
+
+            while let Some(line) = output.next().await {
+                match line? {
+                    LogOutput::StdOut { message } => stdout.extend_from_slice(&message),
+                    LogOutput::StdErr { message } => stderr.extend_from_slice(&message),
+                    _ => {}
+                }
+            }
+        }
+
+        let exit_code = self.docker.inspect_exec(&id).await?.exit_code;
+
+        if exit_code != Some(0) {
+            bail!(
+                "command in '{container_name}' failed: {}",
+                String::from_utf8_lossy(&stderr).trim()
+            );
+        }
+
+        Ok(stdout)
+    }
+
     async fn start_container(&self, req: ContainerRequest) -> Result<ContainerResponse, Status> {
         let ContainerRequest {
             project_name,
@@ -329,13 +563,14 @@ impl Provisioner for LocalProvisioner {
     ) -> Result<Response<DatabaseResponse>, Status> {
         let DatabaseRequest {
             project_name,
+            name,
             db_type,
         } = request.into_inner();
 
         let db_type: Option<Type> = db_type.unwrap().into();
 
         let res = self
-            .get_db_connection_string(&project_name, db_type.unwrap())
+            .get_db_connection_string(&project_name, &name, db_type.unwrap())
             .await?;
 
         Ok(Response::new(res))
@@ -348,6 +583,20 @@ impl Provisioner for LocalProvisioner {
         panic!("local runner should not try to delete databases");
     }
 
+    async fn provision_object_store(
+        &self,
+        _request: Request<ObjectStoreRequest>,
+    ) -> Result<Response<ObjectStoreResponse>, Status> {
+        panic!("local runner should start a container instead of provisioning an object store");
+    }
+
+    async fn delete_object_store(
+        &self,
+        _request: Request<ObjectStoreRequest>,
+    ) -> Result<Response<ObjectStoreDeletionResponse>, Status> {
+        panic!("local runner should not try to delete object stores");
+    }
+
     async fn provision_arbitrary_container(
         &self,
         request: Request<ContainerRequest>,
@@ -357,6 +606,34 @@ impl Provisioner for LocalProvisioner {
         ))
     }
 
+    async fn backup_database(
+        &self,
+        _request: Request<DatabaseRequest>,
+    ) -> Result<Response<Backup>, Status> {
+        panic!("local runner should not try to back up databases");
+    }
+
+    async fn list_backups(
+        &self,
+        _request: Request<DatabaseRequest>,
+    ) -> Result<Response<ListBackupsResponse>, Status> {
+        panic!("local runner should not try to list backups");
+    }
+
+    async fn restore_database(
+        &self,
+        _request: Request<RestoreRequest>,
+    ) -> Result<Response<RestoreResponse>, Status> {
+        panic!("local runner should not try to restore databases");
+    }
+
+    async fn rotate_database_credentials(
+        &self,
+        _request: Request<DatabaseRequest>,
+    ) -> Result<Response<RotateCredentialsResponse>, Status> {
+        panic!("local runner should not try to rotate database credentials");
+    }
+
     async fn health_check(&self, _request: Request<Ping>) -> Result<Response<Pong>, Status> {
         panic!("local runner should not try to do a health check");
     }
@@ -400,6 +677,44 @@ fn print_layers(layers: &Vec<CreateImageInfo>) {
         .expect("to reset cursor position");
 }
 
+/// Named databases get a container of their own next to the default one
+fn db_container_name(project_name: &str, r#type: &str, name: &str) -> String {
+    if name.is_empty() {
+        format!("shuttle_{project_name}_{type}")
+    } else {
+        format!("shuttle_{project_name}_{type}_{name}")
+    }
+}
+
+/// Backups of local databases are kept on the host, so they survive the container being removed
+/// Get when a local backup was taken from its id. Backups taken before ids got a random suffix
+/// are named after the time only.
+fn parse_backup_id(backup_id: &str) -> Option<DateTime<Utc>> {
+    let created_at = match backup_id.split_once('-') {
+        Some((created_at, suffix)) => {
+            if suffix.len() != 8 || !suffix.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+
+            created_at
+        }
+        None => backup_id,
+    };
+
+    NaiveDateTime::parse_from_str(created_at, BACKUP_ID_FORMAT)
+        .ok()
+        .map(|created_at| Utc.from_utc_datetime(&created_at))
+}
+
+fn local_backups_dir(container_name: &str) -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir().context("could not find a local data directory")?;
+
+    Ok(data_dir
+        .join("shuttle")
+        .join("backups")
+        .join(container_name))
+}
+
 struct EngineConfig {
     r#type: String,
     image: String,
@@ -448,6 +763,23 @@ fn db_type_to_config(db_type: Type) -> EngineConfig {
                 "db".to_string(),
             ],
         },
+        Type::Shared(SharedEngine::Redis) => EngineConfig {
+            r#type: "shared_redis".to_string(),
+            // This image can set a password from the environment
+            image: "docker.io/bitnami/redis:7.2".to_string(),
+            engine: "redis".to_string(),
+            username: "default".to_string(),
+            password: "redis".to_string().into(),
+            database_name: "0".to_string(),
+            port: "6379/tcp".to_string(),
+            env: Some(vec!["REDIS_PASSWORD=redis".to_string()]),
+            is_ready_cmd: vec![
+                "redis-cli".to_string(),
+                "-a".to_string(),
+                "redis".to_string(),
+                "ping".to_string(),
+            ],
+        },
         Type::AwsRds(AwsRdsEngine::Postgres) => EngineConfig {
             r#type: "aws_rds_postgres".to_string(),
             image: "docker.io/library/postgres:13.4".to_string(),
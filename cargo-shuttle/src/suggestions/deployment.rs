@@ -50,3 +50,20 @@ pub fn stop_deployment_failure(err: anyhow::Error) -> anyhow::Error {
     println!("cargo shuttle project restart");
     err
 }
+
+/// Used in case starting a previous deployment again fails.
+pub fn redeploy_failure(err: anyhow::Error) -> anyhow::Error {
+    println!();
+    println!("{}", "Starting the deployment again failed".red());
+    println!();
+    println!("Please check that the deployment exists and your project is running:");
+    println!();
+    println!("1. cargo shuttle deployment list");
+    println!();
+    println!("2. cargo shuttle project status");
+    println!();
+    println!(
+        "If its executable is no longer available, deploy your project again with `cargo shuttle deploy`."
+    );
+    err
+}
@@ -0,0 +1,16 @@
+use crossterm::style::Stylize;
+
+/// Suggestions in case adding a custom domain fails.
+pub fn add_domain_failure(err: anyhow::Error) -> anyhow::Error {
+    println!();
+    println!("{}", "Adding the custom domain failed".red());
+    println!();
+    println!("Make sure the domain has a CNAME record pointing to your project, for example:");
+    println!();
+    println!("www.example.com.  CNAME  <project-name>.shuttleapp.rs.");
+    println!();
+    println!(
+        "DNS changes can take a while to propagate. If adding the domain fails repeatedly once the record resolves, please reach out to the team on the Discord server."
+    );
+    err
+}
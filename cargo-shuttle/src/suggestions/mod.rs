@@ -1,6 +1,7 @@
 pub mod api_key;
 pub mod deploy;
 pub mod deployment;
+pub mod domain;
 pub mod logs;
 pub mod project;
 pub mod resources;
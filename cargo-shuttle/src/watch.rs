@@ -0,0 +1,171 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+use ignore::{overrides::Override, overrides::OverrideBuilder, WalkBuilder};
+use shuttle_common::constants::{EXECUTABLE_DIRNAME, STORAGE_DIRNAME};
+use tokio::time::{sleep, Duration};
+use tracing::trace;
+
+/// How often the workspace is scanned for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Swap, backup and lock files editors write next to the files being edited
+const EDITOR_FILES: [&str; 7] = ["*.swp", "*.swo", "*.swx", "*~", ".#*", "#*#", "4913"];
+
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+/// Watches the files of a workspace which are not ignored by its `.gitignore` files
+pub struct WorkspaceWatcher {
+    root: PathBuf,
+    overrides: Override,
+    snapshot: Snapshot,
+}
+
+impl WorkspaceWatcher {
+    pub async fn new(root: &Path) -> Result<Self> {
+        // Build output, files written by a running service and the swap files of editors should
+        // not trigger a rebuild
+        let dirs =
+            [".git", "target", EXECUTABLE_DIRNAME, STORAGE_DIRNAME].map(|dir| format!("{dir}/"));
+        let mut overrides = OverrideBuilder::new(root);
+        for glob in dirs.iter().map(String::as_str).chain(EDITOR_FILES) {
+            overrides
+                .add(&format!("!{glob}"))
+                .with_context(|| format!("adding override `!{glob}`"))?;
+        }
+        let overrides = overrides.build().context("building watch override rules")?;
+
+        let mut watcher = Self {
+            root: root.to_path_buf(),
+            overrides,
+            snapshot: Default::default(),
+        };
+        watcher.snapshot = watcher.take_snapshot().await?;
+
+        Ok(watcher)
+    }
+
+    /// Wait for a file to be added, removed or modified. Only returns once the files stopped
+    /// changing, so that saving many files at once leads to a single rebuild.
+    pub async fn changed(&mut self) -> Result<()> {
+        let mut current = loop {
+            sleep(POLL_INTERVAL).await;
+
+            let current = self.take_snapshot().await?;
+            if current != self.snapshot {
+                break current;
+            }
+        };
+
+        loop {
+            sleep(POLL_INTERVAL).await;
+
+            let next = self.take_snapshot().await?;
+            if next == current {
+                trace!(files = next.len(), "workspace changed");
+                self.snapshot = next;
+
+                return Ok(());
+            }
+            current = next;
+        }
+    }
+
+    async fn take_snapshot(&self) -> Result<Snapshot> {
+        let root = self.root.clone();
+        let overrides = self.overrides.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut snapshot = Snapshot::new();
+
+            for entry in WalkBuilder::new(root)
+                .hidden(false)
+                // Projects that are not in a repository still have their ignore files followed
+                .require_git(false)
+                .overrides(overrides)
+                .build()
+            {
+                // Files can be removed while the workspace is being walked
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                if !matches!(entry.file_type(), Some(file_type) if file_type.is_file()) {
+                    continue;
+                }
+                if let Some(modified) = entry.metadata().ok().and_then(|m| m.modified().ok()) {
+                    snapshot.insert(entry.into_path(), modified);
+                }
+            }
+
+            snapshot
+        })
+        .await
+        .context("walking the workspace")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+    use tokio::time::timeout;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn snapshot_skips_ignored_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir_all(root.join("ignored")).unwrap();
+        fs::write(root.join(".gitignore"), "ignored/\n").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("src/.main.rs.swp"), "").unwrap();
+        fs::write(root.join("src/main.rs~"), "").unwrap();
+        fs::write(root.join("src/.#main.rs"), "").unwrap();
+        fs::write(root.join("target/debug/app"), "").unwrap();
+        fs::write(root.join("ignored/file"), "").unwrap();
+
+        let watcher = WorkspaceWatcher::new(root).await.unwrap();
+        let files: Vec<_> = watcher
+            .snapshot
+            .keys()
+            .map(|path| path.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+
+        assert_eq!(
+            files,
+            vec![PathBuf::from(".gitignore"), PathBuf::from("src/main.rs")]
+        );
+    }
+
+    #[tokio::test]
+    async fn changed_waits_for_a_change() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+
+        let mut watcher = WorkspaceWatcher::new(&root).await.unwrap();
+
+        // Nothing changed
+        assert!(timeout(POLL_INTERVAL * 3, watcher.changed()).await.is_err());
+
+        // Saving a swap file is not a change either
+        fs::write(root.join(".main.rs.swp"), "").unwrap();
+        assert!(timeout(POLL_INTERVAL * 3, watcher.changed()).await.is_err());
+
+        fs::write(root.join("lib.rs"), "").unwrap();
+        timeout(POLL_INTERVAL * 6, watcher.changed())
+            .await
+            .expect("a new file to be a change")
+            .unwrap();
+        assert!(watcher.snapshot.contains_key(&root.join("lib.rs")));
+    }
+}
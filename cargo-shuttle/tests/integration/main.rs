@@ -16,6 +16,7 @@ async fn cargo_shuttle_command(
         .run(
             ShuttleArgs {
                 api_url: Some("http://shuttle.invalid:80".to_string()),
+                org: None,
                 project_args: ProjectArgs {
                     working_directory,
                     name: None,
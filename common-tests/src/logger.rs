@@ -8,7 +8,10 @@ use shuttle_common::claims::{ClaimLayer, InjectPropagationLayer};
 use shuttle_proto::logger::{
     logger_client::LoggerClient,
     logger_server::{Logger, LoggerServer},
-    LogLine, LogsRequest, LogsResponse, StoreLogsRequest, StoreLogsResponse,
+    AddDrainRequest, DeleteLogsRequest, DeleteLogsResponse, DeploymentTierRequest,
+    DeploymentTierResponse, Drain, DrainsRequest, DrainsResponse, LogLine, LogsRequest,
+    LogsResponse, RemoveDrainRequest, RemoveDrainResponse, ServiceLogsRequest, ServiceLogsResponse,
+    StoreLogsRequest, StoreLogsResponse,
 };
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
@@ -45,6 +48,45 @@ impl Logger for MockedLogger {
         let (_, rx) = mpsc::channel(1);
         Ok(Response::new(ReceiverStream::new(rx)))
     }
+
+    async fn get_service_logs(
+        &self,
+        _: Request<ServiceLogsRequest>,
+    ) -> Result<Response<ServiceLogsResponse>, Status> {
+        Ok(Response::new(ServiceLogsResponse::default()))
+    }
+
+    async fn set_deployment_tier(
+        &self,
+        _: Request<DeploymentTierRequest>,
+    ) -> Result<Response<DeploymentTierResponse>, Status> {
+        Ok(Response::new(DeploymentTierResponse {}))
+    }
+
+    async fn delete_logs(
+        &self,
+        _: Request<DeleteLogsRequest>,
+    ) -> Result<Response<DeleteLogsResponse>, Status> {
+        Ok(Response::new(DeleteLogsResponse { deleted: 0 }))
+    }
+
+    async fn add_drain(&self, _: Request<AddDrainRequest>) -> Result<Response<Drain>, Status> {
+        Ok(Response::new(Default::default()))
+    }
+
+    async fn get_drains(
+        &self,
+        _: Request<DrainsRequest>,
+    ) -> Result<Response<DrainsResponse>, Status> {
+        Ok(Response::new(Default::default()))
+    }
+
+    async fn remove_drain(
+        &self,
+        _: Request<RemoveDrainRequest>,
+    ) -> Result<Response<RemoveDrainResponse>, Status> {
+        Ok(Response::new(RemoveDrainResponse {}))
+    }
 }
 
 pub async fn get_mocked_logger_client(
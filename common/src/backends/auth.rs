@@ -1,7 +1,11 @@
-use std::{convert::Infallible, future::Future, pin::Pin, sync::Arc, task::Poll};
+use std::{
+    collections::HashMap, convert::Infallible, future::Future, pin::Pin, sync::Arc, task::Poll,
+};
 
 use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use headers::{authorization::Bearer, Authorization, HeaderMapExt};
 use http::{Request, Response, StatusCode, Uri};
 use http_body::combinators::UnsyncBoxBody;
@@ -9,7 +13,7 @@ use hyper::{body, Body, Client};
 use opentelemetry::global;
 use opentelemetry_http::HeaderInjector;
 use pin_project::pin_project;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use thiserror::Error;
 use tower::{Layer, Service};
 use tracing::{error, trace, Span};
@@ -23,7 +27,11 @@ use super::{
     headers::XShuttleAdminSecret,
 };
 
-const PUBLIC_KEY_CACHE_KEY: &str = "shuttle.public-key";
+const JWKS_CACHE_KEY: &str = "shuttle.jwks";
+/// Only set for a short while after the public keys were fetched, so tokens with unknown key ids
+/// cannot make us fetch the keys on every request
+const JWKS_REFRESHED_CACHE_KEY: &str = "shuttle.jwks.refreshed";
+const REVOCATIONS_CACHE_KEY: &str = "shuttle.revocations";
 
 /// Layer to check the admin secret set by deployer is correct
 #[derive(Clone)]
@@ -85,10 +93,105 @@ where
     }
 }
 
+#[derive(Deserialize, Serialize, Default)]
+/// Query of a request to convert an API key to a JWT token
+pub struct ConvertQuery {
+    /// Also issue a refresh token, for clients which keep the token instead of converting the key
+    /// on every request
+    #[serde(default)]
+    pub refresh_token: bool,
+}
+
 #[derive(Deserialize, Serialize)]
 /// Response used internally to pass around JWT token
 pub struct ConvertResponse {
     pub token: String,
+    /// Can be exchanged for a new token, for as long as the key the token was converted from is
+    /// valid. Only set when asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+/// Request to exchange a refresh token for a new JWT token
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Accounts and named keys with tokens that were revoked before they expired
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Revocations {
+    /// Tokens of an account issued before this time are no longer valid
+    pub accounts: HashMap<String, DateTime<Utc>>,
+    /// Tokens converted from a named key of an account, by key name, issued before this time are
+    /// no longer valid
+    #[serde(default)]
+    pub keys: HashMap<String, HashMap<String, DateTime<Utc>>>,
+}
+
+impl Revocations {
+    /// Tokens are issued with a precision of seconds, so a token issued in the same second as a
+    /// revocation is kept. Otherwise a token issued straight after the revocation would be lost.
+    pub fn is_revoked(&self, claim: &Claim) -> bool {
+        let key_revoked_at = claim.key.as_ref().and_then(|key| {
+            self.keys
+                .get(&claim.sub)
+                .and_then(|account_keys| account_keys.get(key))
+        });
+
+        self.accounts
+            .get(&claim.sub)
+            .into_iter()
+            .chain(key_revoked_at)
+            .any(|revoked_at| (claim.issued_at() as i64) < revoked_at.timestamp())
+    }
+}
+
+/// The public keys tokens can be verified with, as a JSON Web Key Set (RFC 7517). The first key is
+/// the one currently used to sign new tokens.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+impl Jwks {
+    /// Get the public key with the given id
+    pub fn public_key(&self, key_id: &str) -> Option<Vec<u8>> {
+        self.keys
+            .iter()
+            .find(|key| key.kid == key_id)
+            .and_then(Jwk::public_key)
+    }
+}
+
+/// An Ed25519 public key as a JSON Web Key (RFC 8037)
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Jwk {
+    pub kty: String,
+    pub crv: String,
+    pub alg: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    pub kid: String,
+    /// The base64url encoded public key
+    pub x: String,
+}
+
+impl Jwk {
+    pub fn ed25519(key_id: String, public_key: &[u8]) -> Self {
+        Self {
+            kty: "OKP".to_string(),
+            crv: "Ed25519".to_string(),
+            alg: "EdDSA".to_string(),
+            key_use: "sig".to_string(),
+            kid: key_id,
+            x: URL_SAFE_NO_PAD.encode(public_key),
+        }
+    }
+
+    pub fn public_key(&self) -> Option<Vec<u8>> {
+        URL_SAFE_NO_PAD.decode(&self.x).ok()
+    }
 }
 
 /// Trait to get a public key asynchronously
@@ -96,7 +199,13 @@ pub struct ConvertResponse {
 pub trait PublicKeyFn: Send + Sync + Clone {
     type Error: std::error::Error + Send;
 
-    async fn public_key(&self) -> Result<Vec<u8>, Self::Error>;
+    /// Get the public key with the given id, or the current public key when no id is given
+    async fn public_key(&self, key_id: Option<&str>) -> Result<Vec<u8>, Self::Error>;
+
+    /// Get the tokens that are no longer valid, even though they have not expired
+    async fn revocations(&self) -> Result<Revocations, Self::Error> {
+        Ok(Revocations::default())
+    }
 }
 
 #[async_trait]
@@ -107,7 +216,7 @@ where
 {
     type Error = Infallible;
 
-    async fn public_key(&self) -> Result<Vec<u8>, Self::Error> {
+    async fn public_key(&self, _key_id: Option<&str>) -> Result<Vec<u8>, Self::Error> {
         Ok((self)().await)
     }
 }
@@ -115,53 +224,111 @@ where
 #[derive(Clone)]
 pub struct AuthPublicKey {
     auth_uri: Uri,
-    cache_manager: Arc<Box<dyn CacheManagement<Value = Vec<u8>>>>,
+    cache_manager: Arc<Box<dyn CacheManagement<Value = Jwks>>>,
+    revocations_cache_manager: Arc<Box<dyn CacheManagement<Value = Revocations>>>,
 }
 
 impl AuthPublicKey {
     pub fn new(auth_uri: Uri) -> Self {
-        let public_key_cache_manager = CacheManager::new(1);
+        let jwks_cache_manager = CacheManager::new(2);
+        let revocations_cache_manager = CacheManager::new(1);
         Self {
             auth_uri,
-            cache_manager: Arc::new(Box::new(public_key_cache_manager)),
+            cache_manager: Arc::new(Box::new(jwks_cache_manager)),
+            revocations_cache_manager: Arc::new(Box::new(revocations_cache_manager)),
         }
     }
+
+    async fn fetch_jwks(&self) -> Result<Jwks, PublicKeyFnError> {
+        let jwks: Jwks = self.get(".well-known/jwks.json").await?;
+
+        trace!("inserting public keys from auth service into cache");
+        self.cache_manager.insert(
+            JWKS_CACHE_KEY,
+            jwks.clone(),
+            std::time::Duration::from_secs(60),
+        );
+        self.cache_manager.insert(
+            JWKS_REFRESHED_CACHE_KEY,
+            jwks.clone(),
+            std::time::Duration::from_secs(10),
+        );
+
+        Ok(jwks)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, PublicKeyFnError> {
+        let client = Client::new();
+        let uri: Uri = format!("{}{path}", self.auth_uri).parse()?;
+        let mut request = Request::builder().uri(uri);
+
+        // Safe to unwrap since we just build it
+        let headers = request.headers_mut().unwrap();
+
+        let cx = Span::current().context();
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, &mut HeaderInjector(headers))
+        });
+
+        let res = client.request(request.body(Body::empty())?).await?;
+        let buf = body::to_bytes(res).await?;
+
+        Ok(serde_json::from_slice(&buf)?)
+    }
 }
 
 #[async_trait]
 impl PublicKeyFn for AuthPublicKey {
     type Error = PublicKeyFnError;
 
-    async fn public_key(&self) -> Result<Vec<u8>, Self::Error> {
-        if let Some(public_key) = self.cache_manager.get(PUBLIC_KEY_CACHE_KEY) {
-            trace!("found public key in the cache, returning it");
+    async fn public_key(&self, key_id: Option<&str>) -> Result<Vec<u8>, Self::Error> {
+        let jwks = match self.cache_manager.get(JWKS_CACHE_KEY) {
+            Some(jwks) => {
+                trace!("found public keys in the cache");
+                jwks
+            }
+            None => self.fetch_jwks().await?,
+        };
 
-            Ok(public_key)
-        } else {
-            let client = Client::new();
-            let uri: Uri = format!("{}public-key", self.auth_uri).parse()?;
-            let mut request = Request::builder().uri(uri);
+        let jwks = match key_id {
+            // The auth service has likely rotated to a key we don't know yet
+            Some(key_id)
+                if jwks.public_key(key_id).is_none()
+                    && self.cache_manager.get(JWKS_REFRESHED_CACHE_KEY).is_none() =>
+            {
+                trace!(
+                    key_id,
+                    "token is signed by an unknown key, refreshing public keys"
+                );
+                self.fetch_jwks().await?
+            }
+            _ => jwks,
+        };
 
-            // Safe to unwrap since we just build it
-            let headers = request.headers_mut().unwrap();
+        // Tokens without a key id, or with one that is still unknown, are checked against the
+        // current key. An unknown key will then fail the signature check.
+        key_id
+            .and_then(|key_id| jwks.public_key(key_id))
+            .or_else(|| jwks.keys.first().and_then(Jwk::public_key))
+            .ok_or(PublicKeyFnError::NoPublicKey)
+    }
 
-            let cx = Span::current().context();
-            global::get_text_map_propagator(|propagator| {
-                propagator.inject_context(&cx, &mut HeaderInjector(headers))
-            });
+    async fn revocations(&self) -> Result<Revocations, Self::Error> {
+        if let Some(revocations) = self.revocations_cache_manager.get(REVOCATIONS_CACHE_KEY) {
+            return Ok(revocations);
+        }
 
-            let res = client.request(request.body(Body::empty())?).await?;
-            let buf = body::to_bytes(res).await?;
+        let revocations: Revocations = self.get("auth/revocations").await?;
 
-            trace!("inserting public key from auth service into cache");
-            self.cache_manager.insert(
-                PUBLIC_KEY_CACHE_KEY,
-                buf.to_vec(),
-                std::time::Duration::from_secs(60),
-            );
+        // Kept short, since this is how long a revoked token can still be used for
+        trace!("inserting token revocations from auth service into cache");
+        self.revocations_cache_manager.insert(
+            REVOCATIONS_CACHE_KEY,
+            revocations.clone(),
+            std::time::Duration::from_secs(10),
+        );
 
-            Ok(buf.to_vec())
-        }
+        Ok(revocations)
     }
 }
 
@@ -175,6 +342,12 @@ pub enum PublicKeyFnError {
 
     #[error("http error: {0}")]
     Http(#[from] http::Error),
+
+    #[error("invalid public keys: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("auth service has no public keys")]
+    NoPublicKey,
 }
 
 /// Layer to validate JWT tokens with a public key. Valid claims are added to the request extension
@@ -233,7 +406,7 @@ pub enum JwtAuthenticationFuture<
         bearer: Authorization<Bearer>,
         request: Request<Body>,
         #[pin]
-        public_key_future: AsyncTraitFuture<Result<Vec<u8>, PubKeyFn::Error>>,
+        public_key_future: AsyncTraitFuture<Result<(Vec<u8>, Revocations), PubKeyFn::Error>>,
         service: TService,
     },
 }
@@ -275,8 +448,16 @@ where
 
                         Poll::Ready(Ok(response))
                     }
-                    Poll::Ready(Ok(public_key)) => {
-                        let claim_result = Claim::from_token(bearer.token().trim(), &public_key);
+                    Poll::Ready(Ok((public_key, revocations))) => {
+                        let claim_result = Claim::from_token(bearer.token().trim(), &public_key)
+                            .and_then(|claim| {
+                                if revocations.is_revoked(&claim) {
+                                    trace!(sub = %claim.sub, "token has been revoked");
+                                    Err(StatusCode::UNAUTHORIZED)
+                                } else {
+                                    Ok(claim)
+                                }
+                            });
                         match claim_result {
                             Err(code) => {
                                 error!(code = %code, "failed to decode JWT");
@@ -337,7 +518,16 @@ where
         match req.headers().typed_try_get::<Authorization<Bearer>>() {
             Ok(Some(bearer)) => {
                 let public_key_fn = self.public_key_fn.clone();
-                let public_key_future = Box::pin(async move { public_key_fn.public_key().await });
+                // Invalid tokens are rejected when they are decoded, so the header can be optional here
+                let key_id = jsonwebtoken::decode_header(bearer.token().trim())
+                    .ok()
+                    .and_then(|header| header.kid);
+                let public_key_future = Box::pin(async move {
+                    let public_key = public_key_fn.public_key(key_id.as_deref()).await?;
+                    let revocations = public_key_fn.revocations().await?;
+
+                    Ok((public_key, revocations))
+                });
                 Self::Future::HasTokenWaitingForPublicKey {
                     bearer,
                     request: req,
@@ -470,8 +660,12 @@ impl<B> VerifyClaim for tonic::Request<B> {
 
 #[cfg(test)]
 mod tests {
+    use std::{collections::HashMap, convert::Infallible};
+
+    use async_trait::async_trait;
     use axum::{routing::get, Extension, Router};
     use base64::Engine;
+    use chrono::{DateTime, Utc};
     use http::{Request, StatusCode};
     use hyper::{body, Body};
     use jsonwebtoken::EncodingKey;
@@ -484,7 +678,7 @@ mod tests {
 
     use crate::claims::{AccountTier, Claim, Scope};
 
-    use super::{JwtAuthenticationLayer, ScopedLayer};
+    use super::{Jwk, Jwks, JwtAuthenticationLayer, PublicKeyFn, Revocations, ScopedLayer};
 
     const BASE64_URL_SAFE_ENGINE: base64::engine::GeneralPurpose =
         base64::engine::GeneralPurpose::new(
@@ -540,6 +734,50 @@ mod tests {
         assert_eq!(claim, new);
     }
 
+    #[test]
+    fn to_token_with_key_id_and_back() {
+        let claim = Claim::new(
+            "ferries".to_string(),
+            vec![Scope::Deployment, Scope::Project],
+            Default::default(),
+            AccountTier::default(),
+        );
+
+        let docs: Vec<_> = (0..2)
+            .map(|_| signature::Ed25519KeyPair::generate_pkcs8(&rand::SystemRandom::new()).unwrap())
+            .collect();
+        let jwks = Jwks {
+            keys: docs
+                .iter()
+                .enumerate()
+                .map(|(id, doc)| {
+                    let pair = Ed25519KeyPair::from_pkcs8(doc.as_ref()).unwrap();
+                    Jwk::ed25519(id.to_string(), pair.public_key().as_ref())
+                })
+                .collect(),
+        };
+
+        let encoding_key = EncodingKey::from_ed_der(docs[1].as_ref());
+        let token = claim.into_token_with_key_id(&encoding_key, "1").unwrap();
+
+        let key_id = jsonwebtoken::decode_header(&token).unwrap().kid.unwrap();
+        assert_eq!(key_id, "1");
+
+        let public_key = jwks.public_key(&key_id).unwrap();
+        assert_eq!(
+            Claim::from_token(&token, &public_key).unwrap().sub,
+            "ferries"
+        );
+
+        // The token cannot be verified with the other key
+        let other_public_key = jwks.public_key("0").unwrap();
+        assert_eq!(
+            Claim::from_token(&token, &other_public_key),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(jwks.public_key("2"), None);
+    }
+
     #[tokio::test]
     async fn authorization_layer() {
         let claim = Claim::new(
@@ -638,6 +876,94 @@ mod tests {
         assert_eq!(&body[..], b"Hello, ferries");
     }
 
+    #[derive(Clone)]
+    struct RevokingPublicKey {
+        public_key: Vec<u8>,
+        revocations: Revocations,
+    }
+
+    #[async_trait]
+    impl PublicKeyFn for RevokingPublicKey {
+        type Error = Infallible;
+
+        async fn public_key(&self, _key_id: Option<&str>) -> Result<Vec<u8>, Self::Error> {
+            Ok(self.public_key.clone())
+        }
+
+        async fn revocations(&self) -> Result<Revocations, Self::Error> {
+            Ok(self.revocations.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn authorization_layer_revoked_token() {
+        let claim = Claim::new(
+            "ferries".to_string(),
+            vec![Scope::Deployment, Scope::Project],
+            Default::default(),
+            AccountTier::default(),
+        );
+
+        let doc = signature::Ed25519KeyPair::generate_pkcs8(&rand::SystemRandom::new()).unwrap();
+        let encoding_key = EncodingKey::from_ed_der(doc.as_ref());
+        let pair = Ed25519KeyPair::from_pkcs8(doc.as_ref()).unwrap();
+        let public_key = pair.public_key().as_ref().to_vec();
+        let token = claim.into_token(&encoding_key).unwrap();
+
+        let status = |revoked_at: DateTime<Utc>| {
+            let router =
+                Router::new()
+                    .route("/", get(|| async {}))
+                    .layer(JwtAuthenticationLayer::new(RevokingPublicKey {
+                        public_key: public_key.clone(),
+                        revocations: Revocations {
+                            accounts: HashMap::from([("ferries".to_string(), revoked_at)]),
+                            ..Default::default()
+                        },
+                    }));
+            let request = Request::builder()
+                .uri("/")
+                .header("authorization", format!("Bearer {token}"))
+                .body(Body::empty())
+                .unwrap();
+
+            async move { router.oneshot(request).await.unwrap().status() }
+        };
+
+        // Tokens issued before the revocation are rejected
+        assert_eq!(
+            status(Utc::now() + chrono::Duration::seconds(1)).await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        // But tokens issued after it are still valid
+        assert_eq!(
+            status(Utc::now() - chrono::Duration::minutes(1)).await,
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn revoked_key_only_revokes_its_tokens() {
+        let claim = Claim::new(
+            "ferries".to_string(),
+            vec![Scope::Deployment],
+            Default::default(),
+            AccountTier::default(),
+        );
+        let revocations = Revocations {
+            keys: HashMap::from([(
+                "ferries".to_string(),
+                HashMap::from([("ci".to_string(), Utc::now() + chrono::Duration::seconds(1))]),
+            )]),
+            ..Default::default()
+        };
+
+        assert!(revocations.is_revoked(&claim.clone().with_key("ci".to_string())));
+        assert!(!revocations.is_revoked(&claim.clone().with_key("other".to_string())));
+        assert!(!revocations.is_revoked(&claim));
+    }
+
     // Test changing to a symmetric key is not possible
     #[test]
     #[should_panic(expected = "value: 400")]
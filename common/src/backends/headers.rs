@@ -96,3 +96,35 @@ impl Header for XShuttleProject {
         }
     }
 }
+
+pub static X_SHUTTLE_ORGANIZATION: HeaderName = HeaderName::from_static("x-shuttle-org");
+
+/// Typed header for the organization a user is acting for
+pub struct XShuttleOrganization(pub String);
+
+impl Header for XShuttleOrganization {
+    fn name() -> &'static HeaderName {
+        &X_SHUTTLE_ORGANIZATION
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        Self: Sized,
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values
+            .next()
+            .ok_or_else(headers::Error::invalid)?
+            .to_str()
+            .map_err(|_| headers::Error::invalid())?
+            .to_string();
+
+        Ok(Self(value))
+    }
+
+    fn encode<E: Extend<http::HeaderValue>>(&self, values: &mut E) {
+        if let Ok(value) = HeaderValue::from_str(self.0.as_str()) {
+            values.extend(std::iter::once(value));
+        }
+    }
+}
@@ -15,12 +15,14 @@ use opentelemetry::global;
 use opentelemetry_http::HeaderInjector;
 use pin_project::pin_project;
 use serde::{Deserialize, Serialize};
-use strum::{EnumMessage, EnumString};
+use strum::{Display, EnumMessage, EnumString};
 use tower::{Layer, Service};
 use tracing::{error, trace, Span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::limits::Limits;
+#[cfg(feature = "models")]
+use crate::models::organization::Role;
 
 /// Minutes before a claim expires
 ///
@@ -31,8 +33,9 @@ const ISS: &str = "shuttle";
 
 /// The scope of operations that can be performed on shuttle
 /// Every scope defaults to read and will use a suffix for updating tasks
-#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, EnumMessage)]
+#[derive(Clone, Debug, Deserialize, Display, Serialize, Eq, PartialEq, EnumMessage, EnumString)]
 #[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 pub enum Scope {
     /// Read the details, such as status and address, of a deployment
     Deployment,
@@ -54,6 +57,7 @@ pub enum Scope {
 
     /// Create and delete projects
     #[serde(rename = "project_create")] // compatibility
+    #[strum(serialize = "project_create")]
     ProjectWrite,
 
     /// Create more projects than the free tier default
@@ -142,6 +146,31 @@ impl ScopeBuilder {
         self
     }
 
+    /// Extend the current scopes with the rights to read, but not change, projects.
+    pub fn with_read_only(mut self) -> Self {
+        self.0.extend(vec![
+            Scope::Deployment,
+            Scope::Logs,
+            Scope::Service,
+            Scope::Project,
+            Scope::Resources,
+            Scope::Secret,
+        ]);
+        self
+    }
+
+    /// Extend the current scopes with the rights to deploy to, and change the resources and
+    /// secrets of, existing projects.
+    pub fn with_deploy(mut self) -> Self {
+        self.0.extend(vec![
+            Scope::DeploymentPush,
+            Scope::ServiceCreate,
+            Scope::ResourcesWrite,
+            Scope::SecretWrite,
+        ]);
+        self
+    }
+
     /// Extend the current scopes with those needed by a deployer machine / user.
     pub fn with_deploy_rights(mut self) -> Self {
         self.0.extend(vec![
@@ -207,6 +236,24 @@ impl From<AccountTier> for Vec<Scope> {
     }
 }
 
+/// The scopes of a member acting for an organization
+#[cfg(feature = "models")]
+impl From<Role> for Vec<Scope> {
+    fn from(role: Role) -> Self {
+        let builder = ScopeBuilder::new().with_read_only();
+
+        match role {
+            Role::Owner | Role::Admin => {
+                let mut scopes = builder.with_deploy().build();
+                scopes.push(Scope::ProjectWrite);
+                scopes
+            }
+            Role::Developer => builder.with_deploy().build(),
+            Role::Viewer => builder.build(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
 pub struct Claim {
     /// Expiration time (as UTC timestamp).
@@ -227,6 +274,15 @@ pub struct Claim {
     pub limits: Limits,
     /// The account tier of the subject.
     pub tier: AccountTier,
+    /// The organization the subject is acting for, if any.
+    #[serde(default)]
+    pub org: Option<String>,
+    /// The projects this token is restricted to. All the projects of the subject when `None`.
+    #[serde(default)]
+    pub projects: Option<Vec<String>>,
+    /// The named key this token was converted from, if any.
+    #[serde(default)]
+    pub key: Option<String>,
 }
 
 impl Claim {
@@ -250,19 +306,78 @@ impl Claim {
             token: None,
             limits: limits.into(),
             tier,
+            org: None,
+            projects: None,
+            key: None,
         }
     }
 
+    /// Act for an organization, with only the scopes of the subject's role in it.
+    #[cfg(feature = "models")]
+    pub fn with_organization(mut self, org: String, role: Role) -> Self {
+        self.scopes = role.into();
+        self.org = Some(org);
+        self
+    }
+
+    /// Restrict the claim to the scopes and projects of an API key. A `None` restriction keeps
+    /// everything the subject already has access to.
+    pub fn with_restrictions(
+        mut self,
+        scopes: Option<Vec<Scope>>,
+        projects: Option<Vec<String>>,
+    ) -> Self {
+        if let Some(scopes) = scopes {
+            self.scopes.retain(|scope| scopes.contains(scope));
+        }
+        self.projects = projects;
+        self
+    }
+
+    /// Record the named key this claim was converted from, so its tokens can be revoked with it
+    pub fn with_key(mut self, key: String) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// When this claim was issued (as UTC timestamp)
+    pub fn issued_at(&self) -> usize {
+        self.iat
+    }
+
+    /// Whether this claim can be used for the given project
+    pub fn allows_project(&self, project: &str) -> bool {
+        self.projects
+            .as_ref()
+            .map_or(true, |projects| projects.iter().any(|p| p == project))
+    }
+
     pub fn into_token(self, encoding_key: &EncodingKey) -> Result<String, StatusCode> {
+        self.into_signed_token(Header::new(jsonwebtoken::Algorithm::EdDSA), encoding_key)
+    }
+
+    /// Convert to a token signed by one of several keys, recording the id of the key in the `kid`
+    /// header so the receiver knows which public key to verify it with
+    pub fn into_token_with_key_id(
+        self,
+        encoding_key: &EncodingKey,
+        key_id: &str,
+    ) -> Result<String, StatusCode> {
+        let mut header = Header::new(jsonwebtoken::Algorithm::EdDSA);
+        header.kid = Some(key_id.to_string());
+
+        self.into_signed_token(header, encoding_key)
+    }
+
+    fn into_signed_token(
+        self,
+        header: Header,
+        encoding_key: &EncodingKey,
+    ) -> Result<String, StatusCode> {
         if let Some(token) = self.token {
             Ok(token)
         } else {
-            encode(
-                &Header::new(jsonwebtoken::Algorithm::EdDSA),
-                &self,
-                encoding_key,
-            )
-            .map_err(|err| {
+            encode(&header, &self, encoding_key).map_err(|err| {
                 error!(
                     error = &err as &dyn std::error::Error,
                     "failed to convert claim to token"
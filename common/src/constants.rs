@@ -37,4 +37,13 @@ pub const fn default_idle_minutes() -> u64 {
 pub mod limits {
     pub const MAX_PROJECTS_DEFAULT: u32 = 3;
     pub const MAX_PROJECTS_EXTRA: u32 = 15;
+
+    pub const CONTAINER_CPU_MILLIS_DEFAULT: u64 = 4000;
+    pub const CONTAINER_CPU_MILLIS_EXTRA: u64 = 8000;
+    pub const CONTAINER_MEMORY_MIB_DEFAULT: u64 = 6144;
+    pub const CONTAINER_MEMORY_MIB_EXTRA: u64 = 12288;
+    pub const CONTAINER_PIDS_DEFAULT: u64 = 2048;
+    pub const CONTAINER_PIDS_EXTRA: u64 = 4096;
+    pub const CONTAINER_DISK_GIB_DEFAULT: u64 = 20;
+    pub const CONTAINER_DISK_GIB_EXTRA: u64 = 50;
 }
@@ -25,6 +25,7 @@ pub enum AwsRdsEngine {
 pub enum SharedEngine {
     Postgres,
     MongoDb,
+    Redis,
 }
 
 impl Display for Type {
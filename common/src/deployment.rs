@@ -13,6 +13,8 @@ pub enum State {
     Built,
     Loading,
     Running,
+    /// Running, but failing its health check
+    Unhealthy,
     Completed,
     Stopped,
     Crashed,
@@ -89,6 +89,9 @@ impl AsRef<str> for ApiKey {
 #[derive(Deserialize, Serialize, Default)]
 pub struct DbInput {
     pub local_uri: Option<String>,
+    /// Name of the database when a service uses several of the same type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 
 /// The output produced by Shuttle DB resources
@@ -163,6 +166,22 @@ impl DatabaseInfo {
     }
 }
 
+/// Holds the location of an S3-compatible object store and the credentials scoped to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectStoreInfo {
+    pub bucket: String,
+    /// Key prefix the objects of this store are kept under, since the stores of a project share
+    /// its bucket. Empty when the store has the whole bucket to itself.
+    #[serde(default)]
+    pub prefix: String,
+    pub region: String,
+    /// Custom endpoint for S3-compatible stores, such as a local MinIO container.
+    /// `None` means AWS S3 itself.
+    pub endpoint: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: Secret<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VersionInfo {
     /// Version of gateway
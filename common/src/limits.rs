@@ -4,6 +4,14 @@ use crate::{
     claims::AccountTier,
     constants::limits::{MAX_PROJECTS_DEFAULT, MAX_PROJECTS_EXTRA},
 };
+#[cfg(feature = "models")]
+use crate::{
+    constants::limits::{
+        CONTAINER_CPU_MILLIS_EXTRA, CONTAINER_DISK_GIB_EXTRA, CONTAINER_MEMORY_MIB_EXTRA,
+        CONTAINER_PIDS_EXTRA,
+    },
+    models::project::ContainerLimits,
+};
 
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 pub struct Limits {
@@ -69,3 +77,21 @@ impl From<AccountTier> for Limits {
         }
     }
 }
+
+#[cfg(feature = "models")]
+impl From<AccountTier> for ContainerLimits {
+    fn from(value: AccountTier) -> Self {
+        match value {
+            AccountTier::Admin
+            | AccountTier::Basic
+            | AccountTier::PendingPaymentPro
+            | AccountTier::Deployer => Self::default(),
+            AccountTier::Pro | AccountTier::CancelledPro | AccountTier::Team => Self {
+                cpu_millis: CONTAINER_CPU_MILLIS_EXTRA,
+                memory_mib: CONTAINER_MEMORY_MIB_EXTRA,
+                pids: CONTAINER_PIDS_EXTRA,
+                disk_gib: CONTAINER_DISK_GIB_EXTRA,
+            },
+        }
+    }
+}
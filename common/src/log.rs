@@ -1,4 +1,4 @@
-use std::fmt::Write;
+use std::{collections::BTreeMap, fmt::Write};
 
 use chrono::{DateTime, Utc};
 #[cfg(feature = "display")]
@@ -31,6 +31,128 @@ pub enum Backend {
     Runtime(String),
 }
 
+/// Severity of a log line, ordered from the most to the least verbose
+#[derive(
+    Clone, Copy, Debug, EnumString, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    const ALL: [LogLevel; 5] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+
+    /// All the levels that are at least as severe as this one
+    pub fn and_above(self) -> impl Iterator<Item = LogLevel> {
+        LogLevel::ALL
+            .into_iter()
+            .filter(move |level| *level >= self)
+    }
+
+    /// Try to find the level of a line printed by a formatting subscriber, such as the
+    /// `tracing_subscriber::fmt` output of a runtime. Only the first few words are looked at.
+    pub fn detect(line: &str) -> Option<Self> {
+        strip_ansi_escapes(line)
+            .split_whitespace()
+            .take(4)
+            .find_map(|word| match word {
+                "TRACE" => Some(LogLevel::Trace),
+                "DEBUG" => Some(LogLevel::Debug),
+                "INFO" => Some(LogLevel::Info),
+                "WARN" => Some(LogLevel::Warn),
+                "ERROR" => Some(LogLevel::Error),
+                _ => None,
+            })
+    }
+}
+
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&Level> for LogLevel {
+    fn from(level: &Level) -> Self {
+        match *level {
+            Level::TRACE => LogLevel::Trace,
+            Level::DEBUG => LogLevel::Debug,
+            Level::INFO => LogLevel::Info,
+            Level::WARN => LogLevel::Warn,
+            Level::ERROR => LogLevel::Error,
+        }
+    }
+}
+
+/// Kind of external sink the logs of a project can be drained to
+#[derive(Clone, Copy, Debug, EnumString, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum DrainKind {
+    /// Batches of JSON lines POSTed to an HTTP endpoint
+    Http,
+    /// RFC 5424 messages over TCP
+    Syslog,
+}
+
+impl DrainKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DrainKind::Http => "http",
+            DrainKind::Syslog => "syslog",
+        }
+    }
+}
+
+impl std::fmt::Display for DrainKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Remove the terminal color codes from a line
+pub fn strip_ansi_escapes(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip the whole `ESC [ ... <letter>` sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct LogItem {
     /// Deployment id
@@ -44,6 +166,18 @@ pub struct LogItem {
 
     /// The log line
     pub line: String,
+
+    /// Level of the event that produced this line, if it is known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<LogLevel>,
+
+    /// Target (usually the module path) of the event that produced this line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+
+    /// Fields of the event and of the deployment span it happened in
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, serde_json::Value>,
 }
 
 const LOGLINE_MAX_CHARS: usize = 2048;
@@ -60,9 +194,26 @@ impl LogItem {
             internal_origin,
             timestamp: Utc::now(),
             line,
+            level: None,
+            target: None,
+            fields: Default::default(),
         }
     }
 
+    /// Attach the level, target and fields of the event this line was formatted from
+    pub fn with_metadata(
+        mut self,
+        level: LogLevel,
+        target: impl Into<String>,
+        fields: BTreeMap<String, serde_json::Value>,
+    ) -> Self {
+        self.level = Some(level);
+        self.target = Some(target.into());
+        self.fields = fields;
+
+        self
+    }
+
     fn truncate_line(line: &mut String) {
         // Check if it can be over the limit (assuming ascii only), no iteration
         if line.len() > LOGLINE_MAX_CHARS {
@@ -191,11 +342,29 @@ where
             let extensions = span.extensions();
 
             if let Some(details) = extensions.get::<ScopeDetails>() {
-                self.log_recorder.record(LogItem::new(
-                    details.deployment_id,
-                    self.internal_service.clone(),
-                    format_event(event),
-                ));
+                let metadata = event.metadata();
+                let mut visitor = JsonVisitor::default();
+                event.record(&mut visitor);
+
+                let target = visitor
+                    .target
+                    .unwrap_or_else(|| metadata.target().to_string());
+                let mut fields = details.fields.clone();
+                fields.extend(
+                    visitor
+                        .fields
+                        .into_iter()
+                        .filter(|(key, _)| key != "message"),
+                );
+
+                self.log_recorder.record(
+                    LogItem::new(
+                        details.deployment_id,
+                        self.internal_service.clone(),
+                        format_event(event),
+                    )
+                    .with_metadata(metadata.level().into(), target, fields),
+                );
                 break;
             }
         }
@@ -212,7 +381,11 @@ where
         }
         let mut visitor = DeploymentIdVisitor::default();
         attrs.record(&mut visitor);
-        let details = visitor.details;
+        let mut details = visitor.details;
+
+        let mut fields_visitor = JsonVisitor::default();
+        attrs.record(&mut fields_visitor);
+        details.fields = fields_visitor.fields.into_iter().collect();
 
         if details.deployment_id.is_nil() {
             warn!("scope details does not have a valid deployment_id");
@@ -227,11 +400,18 @@ where
 
         let message = format!("{} {}", metadata.level().colored(), metadata.name().blue());
 
-        self.log_recorder.record(LogItem::new(
-            details.deployment_id,
-            self.internal_service.clone(),
-            message,
-        ));
+        self.log_recorder.record(
+            LogItem::new(
+                details.deployment_id,
+                self.internal_service.clone(),
+                message,
+            )
+            .with_metadata(
+                metadata.level().into(),
+                metadata.target(),
+                details.fields.clone(),
+            ),
+        );
 
         extensions.insert::<ScopeDetails>(details);
     }
@@ -240,6 +420,8 @@ where
 #[derive(Debug, Default)]
 struct ScopeDetails {
     deployment_id: Uuid,
+    /// Fields recorded on the deployment span, attached to every log in it
+    fields: BTreeMap<String, serde_json::Value>,
 }
 /// To extract `deployment_id` field for scopes that have it
 #[derive(Default)]
@@ -331,4 +513,33 @@ mod tests {
         assert_eq!(l.len(), 3 + 4 * (LOGLINE_MAX_CHARS - 1) + TRUNC_MSG.len());
         assert_eq!(l.chars().count(), LOGLINE_MAX_CHARS + TRUNC_MSG.len());
     }
+
+    #[test]
+    fn log_level_detect() {
+        assert_eq!(
+            LogLevel::detect("2024-01-01T00:00:00.000000Z  WARN my_app: slow request"),
+            Some(LogLevel::Warn)
+        );
+        assert_eq!(
+            LogLevel::detect(
+                "\u{1b}[2m2024-01-01T00:00:00Z\u{1b}[0m \u{1b}[31mERROR\u{1b}[0m boom"
+            ),
+            Some(LogLevel::Error)
+        );
+        assert_eq!(LogLevel::detect("plain println output"), None);
+        assert_eq!(
+            LogLevel::detect("this line talks about an ERROR later"),
+            None
+        );
+    }
+
+    #[test]
+    fn log_level_and_above() {
+        assert_eq!(
+            LogLevel::Warn.and_above().collect::<Vec<_>>(),
+            vec![LogLevel::Warn, LogLevel::Error]
+        );
+        assert_eq!(LogLevel::Trace.and_above().count(), 5);
+        assert_eq!("WARN".parse::<LogLevel>().unwrap(), LogLevel::Warn);
+    }
 }
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use comfy_table::{
+    modifiers::UTF8_ROUND_CORNERS,
+    presets::{NOTHING, UTF8_FULL},
+    Attribute, Cell, CellAlignment, ContentArrangement, Table,
+};
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+
+/// A named API key of an account
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub name: String,
+    /// The key itself. Only returned when the key is created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// The scopes the key is restricted to, such as `deployment_push` or `logs`.
+    /// All the scopes of the account when `None`
+    pub scopes: Option<Vec<String>>,
+    /// The projects the key is restricted to. All the projects of the account when `None`
+    pub projects: Option<Vec<String>>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct CreateRequest {
+    /// The scopes to restrict the key to. Unrestricted when `None`
+    pub scopes: Option<Vec<String>>,
+    /// The projects to restrict the key to. Unrestricted when `None`
+    pub projects: Option<Vec<String>>,
+    /// When the key stops being valid. Never when `None`
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+pub fn get_api_keys_table(api_keys: &[Response], raw: bool) -> String {
+    if api_keys.is_empty() {
+        let s = "No named API keys have been created\n";
+
+        return if raw {
+            s.to_string()
+        } else {
+            s.bold().to_string()
+        };
+    }
+
+    let mut table = Table::new();
+    let header = [
+        "Name",
+        "Scopes",
+        "Projects",
+        "Created",
+        "Expires",
+        "Last used",
+    ];
+
+    if raw {
+        table
+            .load_preset(NOTHING)
+            .set_content_arrangement(ContentArrangement::Disabled)
+            .set_header(
+                header
+                    .into_iter()
+                    .map(|title| Cell::new(title).set_alignment(CellAlignment::Left)),
+            );
+    } else {
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::DynamicFullWidth)
+            .set_header(header.into_iter().map(|title| {
+                Cell::new(title)
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold)
+            }));
+    }
+
+    let restriction = |values: &Option<Vec<String>>| {
+        values
+            .as_ref()
+            .map(|values| values.join(", "))
+            .unwrap_or_else(|| "all".to_string())
+    };
+    let date = |date: &Option<DateTime<Utc>>, none: &str| {
+        date.map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| none.to_string())
+    };
+
+    for api_key in api_keys {
+        table.add_row(vec![
+            api_key.name.clone(),
+            restriction(&api_key.scopes),
+            restriction(&api_key.projects),
+            api_key.created_at.format("%Y-%m-%d").to_string(),
+            date(&api_key.expires_at, "never"),
+            date(&api_key.last_used_at, "never"),
+        ]);
+    }
+
+    format!("Named API keys\n{table}\n")
+}
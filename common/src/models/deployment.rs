@@ -6,7 +6,7 @@ use comfy_table::{
 };
 use crossterm::style::Stylize;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, str::FromStr, time::Duration};
 use uuid::Uuid;
 
 use crate::deployment::State;
@@ -57,7 +57,7 @@ impl State {
             State::Running => "green",
             State::Completed | State::Stopped => "blue",
             State::Crashed => "red",
-            State::Unknown => "yellow",
+            State::Unhealthy | State::Unknown => "yellow",
         }
     }
 }
@@ -209,3 +209,135 @@ pub struct DeploymentRequest {
     pub git_branch: Option<String>,
     pub git_dirty: Option<bool>,
 }
+
+/// Settings of the `Shuttle.toml` of a project that change how its deployments are run
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RunConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<HealthCheck>,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+}
+
+/// HTTP endpoint a running service is polled on to know whether it is healthy
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HealthCheck {
+    /// Path requested on the service, such as `/health`. Any 2xx response is healthy.
+    pub path: String,
+    /// Seconds between two checks
+    #[serde(default = "HealthCheck::default_interval")]
+    pub interval: u64,
+    /// Seconds a check can take before it counts as failed
+    #[serde(default = "HealthCheck::default_timeout")]
+    pub timeout: u64,
+    /// Number of failed checks in a row after which the deployment is unhealthy
+    #[serde(default = "HealthCheck::default_unhealthy_threshold")]
+    pub unhealthy_threshold: u32,
+}
+
+impl HealthCheck {
+    fn default_interval() -> u64 {
+        10
+    }
+
+    fn default_timeout() -> u64 {
+        5
+    }
+
+    fn default_unhealthy_threshold() -> u32 {
+        3
+    }
+}
+
+/// When a deployment that stopped by itself is started again
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Restart {
+    #[default]
+    Never,
+    /// After it crashed or became unhealthy
+    OnCrash,
+    /// Also after it completed
+    Always,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RestartPolicy {
+    #[serde(default)]
+    pub policy: Restart,
+    /// Number of restarts in a row before the deployment is left crashed
+    #[serde(default = "RestartPolicy::default_max_retries")]
+    pub max_retries: u32,
+    /// Seconds to wait before the first restart, doubled for every restart after it
+    #[serde(default = "RestartPolicy::default_backoff")]
+    pub backoff: u64,
+}
+
+impl RestartPolicy {
+    /// Longest wait between two restarts
+    const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+    fn default_max_retries() -> u32 {
+        3
+    }
+
+    fn default_backoff() -> u64 {
+        1
+    }
+
+    /// Wait before the next restart of a deployment that was already restarted `restarts`
+    /// times in a row. `None` when it should not be restarted anymore.
+    pub fn backoff(&self, restarts: u32) -> Option<Duration> {
+        if self.policy == Restart::Never || restarts >= self.max_retries {
+            return None;
+        }
+
+        let backoff = Duration::from_secs(self.backoff)
+            .saturating_mul(2u32.saturating_pow(restarts))
+            .min(Self::MAX_BACKOFF);
+
+        Some(backoff)
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            policy: Default::default(),
+            max_retries: Self::default_max_retries(),
+            backoff: Self::default_backoff(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restart_backoff() {
+        let policy = RestartPolicy {
+            policy: Restart::OnCrash,
+            max_retries: 3,
+            backoff: 2,
+        };
+
+        assert_eq!(policy.backoff(0), Some(Duration::from_secs(2)));
+        assert_eq!(policy.backoff(2), Some(Duration::from_secs(8)));
+        assert_eq!(policy.backoff(3), None);
+
+        let never = RestartPolicy {
+            policy: Restart::Never,
+            ..policy
+        };
+        assert_eq!(never.backoff(0), None);
+
+        let config: RunConfig = serde_json::from_str(
+            r#"{"health_check": {"path": "/health"}, "restart": {"policy": "on-crash"}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.health_check.unwrap().interval, 10);
+        assert_eq!(config.restart.policy, Restart::OnCrash);
+        assert_eq!(config.restart.max_retries, 3);
+    }
+}
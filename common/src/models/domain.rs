@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use comfy_table::{
+    modifiers::UTF8_ROUND_CORNERS,
+    presets::{NOTHING, UTF8_FULL},
+    Attribute, Cell, CellAlignment, ContentArrangement, Table,
+};
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub fqdn: String,
+    /// When the certificate currently served for this domain expires
+    pub certificate_expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AddRequest {
+    /// The domain to add. It must already resolve to the project
+    pub fqdn: String,
+}
+
+pub fn get_domains_table(domains: &[Response], project_name: &str, raw: bool) -> String {
+    if domains.is_empty() {
+        let s = format!("No custom domains are set up for {project_name}\n");
+
+        return if raw { s } else { s.bold().to_string() };
+    }
+
+    let mut table = Table::new();
+
+    if raw {
+        table
+            .load_preset(NOTHING)
+            .set_content_arrangement(ContentArrangement::Disabled)
+            .set_header(vec![
+                Cell::new("Domain").set_alignment(CellAlignment::Left),
+                Cell::new("Certificate expires").set_alignment(CellAlignment::Left),
+            ]);
+    } else {
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::DynamicFullWidth)
+            .set_header(vec![
+                Cell::new("Domain")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Certificate expires")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+            ]);
+    }
+
+    for domain in domains {
+        let expires_at = domain
+            .certificate_expires_at
+            .map(|expires_at| expires_at.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        table.add_row(vec![domain.fqdn.clone(), expires_at]);
+    }
+
+    format!("Custom domains of {project_name}\n{table}\n")
+}
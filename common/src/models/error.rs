@@ -56,6 +56,9 @@ pub enum ErrorKind {
     CustomDomainNotFound,
     InvalidCustomDomain,
     CustomDomainAlreadyExists,
+    /// Contains a message describing why the domain could not be verified
+    CustomDomainNotVerified(String),
+    ProjectHasCustomDomain,
     InvalidOperation,
     Internal,
     NotReady,
@@ -130,6 +133,16 @@ impl From<ErrorKind> for ApiError {
             ErrorKind::InvalidCustomDomain => (StatusCode::BAD_REQUEST, "Invalid custom domain"),
             ErrorKind::CustomDomainNotFound => (StatusCode::NOT_FOUND, "Custom domain not found"),
             ErrorKind::CustomDomainAlreadyExists => (StatusCode::BAD_REQUEST, "Custom domain already in use"),
+            ErrorKind::CustomDomainNotVerified(message) => {
+                return Self {
+                    message,
+                    status_code: StatusCode::BAD_REQUEST.as_u16(),
+                }
+            }
+            ErrorKind::ProjectHasCustomDomain => (
+                StatusCode::BAD_REQUEST,
+                "Project already has a custom domain. Remove it with `cargo shuttle domain remove` before adding another one."
+            ),
             ErrorKind::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
             ErrorKind::Forbidden => (StatusCode::FORBIDDEN, "Forbidden"),
             ErrorKind::NotReady => (StatusCode::INTERNAL_SERVER_ERROR, "Service not ready"),
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::log::{DrainKind, LogItem, LogLevel};
+
+/// Filters for fetching the logs of a deployment. Everything is optional, leaving all of
+/// them out returns all the logs of the deployment.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogsQuery {
+    /// Only return logs at this level or above
+    pub level: Option<LogLevel>,
+    /// Only return logs whose target starts with this
+    pub target: Option<String>,
+    /// Only return logs from this time onwards
+    pub since: Option<DateTime<Utc>>,
+    /// Only return logs from before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only return logs containing this text, ignoring case
+    pub search: Option<String>,
+    /// Maximum number of logs to return
+    pub limit: Option<u32>,
+    /// Number of matching logs to skip. Only used for the logs of a single deployment
+    pub offset: Option<u32>,
+    /// Continue from the `next_cursor` of a previous [`ServiceLogsResponse`]
+    pub cursor: Option<String>,
+}
+
+/// Page of the logs of all the deployments of a service, newest first
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ServiceLogsResponse {
+    pub logs: Vec<LogItem>,
+    /// Set when there are older logs to get
+    pub next_cursor: Option<String>,
+}
+
+/// Sink to forward the logs of all the deployments of a project to
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DrainRequest {
+    pub kind: DrainKind,
+    /// `http(s)://` endpoint for an HTTP drain, `tcp://host:port` for a syslog drain
+    pub url: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DrainResponse {
+    pub id: i64,
+    pub kind: DrainKind,
+    pub url: String,
+}
+
+impl LogsQuery {
+    /// Encode the filters that are set as a url query string, without the leading `?`
+    pub fn to_query_string(&self) -> String {
+        let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+
+        if let Some(level) = self.level {
+            serializer.append_pair("level", level.as_str());
+        }
+        if let Some(target) = &self.target {
+            serializer.append_pair("target", target);
+        }
+        if let Some(since) = self.since {
+            serializer.append_pair("since", &since.to_rfc3339());
+        }
+        if let Some(until) = self.until {
+            serializer.append_pair("until", &until.to_rfc3339());
+        }
+        if let Some(search) = &self.search {
+            serializer.append_pair("search", search);
+        }
+        if let Some(limit) = self.limit {
+            serializer.append_pair("limit", &limit.to_string());
+        }
+        if let Some(offset) = self.offset {
+            serializer.append_pair("offset", &offset.to_string());
+        }
+        if let Some(cursor) = &self.cursor {
+            serializer.append_pair("cursor", cursor);
+        }
+
+        serializer.finish()
+    }
+}
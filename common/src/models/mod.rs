@@ -1,8 +1,13 @@
 pub mod admin;
+pub mod api_key;
 pub mod deployment;
+pub mod domain;
 pub mod error;
+pub mod log;
+pub mod organization;
 pub mod project;
 pub mod resource;
+pub mod secret;
 pub mod service;
 pub mod stats;
 pub mod user;
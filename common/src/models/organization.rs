@@ -0,0 +1,147 @@
+use comfy_table::{
+    modifiers::UTF8_ROUND_CORNERS,
+    presets::{NOTHING, UTF8_FULL},
+    Attribute, Cell, CellAlignment, ContentArrangement, Table,
+};
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// The role of a member in an organization. Every role can do what the roles after it can do.
+#[derive(
+    Clone, Copy, Debug, Deserialize, Display, EnumString, Eq, Ord, PartialEq, PartialOrd, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Role {
+    /// Manage the members, including other owners
+    Owner,
+    /// Manage the members, except for owners
+    Admin,
+    /// Deploy to and manage the resources of the projects of the organization
+    Developer,
+    /// Read the status, logs and resources of the projects of the organization
+    Viewer,
+}
+
+impl Role {
+    /// Whether members with this role can add, change or remove members with the `other` role
+    pub fn can_manage(&self, other: Role) -> bool {
+        match self {
+            Self::Owner => true,
+            Self::Admin => other != Self::Owner,
+            Self::Developer | Self::Viewer => false,
+        }
+    }
+}
+
+/// An organization, as seen by one of its members
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub name: String,
+    /// The role of the calling user in this organization
+    pub role: Role,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct MemberResponse {
+    pub account_name: String,
+    pub role: Role,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MemberRequest {
+    pub role: Role,
+}
+
+pub fn get_organizations_table(organizations: &[Response], raw: bool) -> String {
+    if organizations.is_empty() {
+        let s = "You are not a member of any organizations\n";
+
+        return if raw {
+            s.to_string()
+        } else {
+            s.bold().to_string()
+        };
+    }
+
+    let mut table = Table::new();
+
+    if raw {
+        table
+            .load_preset(NOTHING)
+            .set_content_arrangement(ContentArrangement::Disabled)
+            .set_header(vec![
+                Cell::new("Organization").set_alignment(CellAlignment::Left),
+                Cell::new("Role").set_alignment(CellAlignment::Left),
+            ]);
+    } else {
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::DynamicFullWidth)
+            .set_header(vec![
+                Cell::new("Organization")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Role")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+            ]);
+    }
+
+    for organization in organizations {
+        table.add_row(vec![
+            organization.name.clone(),
+            organization.role.to_string(),
+        ]);
+    }
+
+    format!("Organizations you are a member of\n{table}\n")
+}
+
+pub fn get_members_table(members: &[MemberResponse], organization: &str, raw: bool) -> String {
+    let mut table = Table::new();
+
+    if raw {
+        table
+            .load_preset(NOTHING)
+            .set_content_arrangement(ContentArrangement::Disabled)
+            .set_header(vec![
+                Cell::new("Account").set_alignment(CellAlignment::Left),
+                Cell::new("Role").set_alignment(CellAlignment::Left),
+            ]);
+    } else {
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::DynamicFullWidth)
+            .set_header(vec![
+                Cell::new("Account")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Role")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+            ]);
+    }
+
+    for member in members {
+        table.add_row(vec![member.account_name.clone(), member.role.to_string()]);
+    }
+
+    format!("Members of {organization}\n{table}\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Role;
+
+    #[test]
+    fn role_can_manage() {
+        assert!(Role::Owner.can_manage(Role::Owner));
+        assert!(Role::Admin.can_manage(Role::Developer));
+        assert!(!Role::Admin.can_manage(Role::Owner));
+        assert!(!Role::Developer.can_manage(Role::Viewer));
+    }
+}
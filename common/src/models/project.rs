@@ -11,12 +11,58 @@ use crossterm::style::Stylize;
 use serde::{Deserialize, Serialize};
 use strum::EnumString;
 
+use crate::constants::limits::{
+    CONTAINER_CPU_MILLIS_DEFAULT, CONTAINER_DISK_GIB_DEFAULT, CONTAINER_MEMORY_MIB_DEFAULT,
+    CONTAINER_PIDS_DEFAULT,
+};
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Response {
     pub id: String,
     pub name: String,
     pub state: State,
     pub idle_minutes: Option<u64>,
+    /// Resources the container of the project can use. Older gateways do not send them
+    #[serde(default)]
+    pub limits: Option<ContainerLimits>,
+}
+
+/// Resources the container of a project can use on its node
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct ContainerLimits {
+    /// CPU time, in thousandths of a CPU
+    pub cpu_millis: u64,
+    /// Hard memory limit, in MiB
+    pub memory_mib: u64,
+    /// Maximum number of processes and threads
+    pub pids: u64,
+    /// Size of the writable layer of the container and of the volume of the project, each in GiB.
+    /// Only enforced on nodes which support disk quotas, and on volumes created after it was set
+    pub disk_gib: u64,
+}
+
+impl Default for ContainerLimits {
+    fn default() -> Self {
+        Self {
+            cpu_millis: CONTAINER_CPU_MILLIS_DEFAULT,
+            memory_mib: CONTAINER_MEMORY_MIB_DEFAULT,
+            pids: CONTAINER_PIDS_DEFAULT,
+            disk_gib: CONTAINER_DISK_GIB_DEFAULT,
+        }
+    }
+}
+
+impl Display for ContainerLimits {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} CPUs, {} GiB memory, {} processes, {} GiB disk",
+            self.cpu_millis as f64 / 1000.0,
+            self.memory_mib as f64 / 1024.0,
+            self.pids,
+            self.disk_gib
+        )
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, EnumString)]
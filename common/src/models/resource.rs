@@ -1,18 +1,112 @@
 use std::collections::HashMap;
 
+use chrono::{DateTime, SecondsFormat, Utc};
 use comfy_table::{
     modifiers::UTF8_ROUND_CORNERS,
     presets::{NOTHING, UTF8_FULL},
     Attribute, Cell, CellAlignment, ContentArrangement, Table,
 };
 use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     resource::{Response, Type},
     secrets::SecretStore,
-    DatabaseResource,
+    DatabaseResource, ObjectStoreInfo,
 };
 
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct DeleteQuery {
+    /// Name of the resource to delete when the service has several of the same type
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct BackupQuery {
+    /// Name of the database when the service has several of the same type
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct RestoreQuery {
+    /// Name of the database the backup was taken from
+    pub name: Option<String>,
+    /// Name of a new database to restore into, instead of replacing the contents of the original one
+    pub into: Option<String>,
+}
+
+/// A snapshot of a database kept by Shuttle
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Backup {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    /// Size of the dump in bytes
+    pub size: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct RotateQuery {
+    /// Name of the database when the service has several of the same type
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RotateResponse {
+    /// When the credentials in use before the rotation stop working
+    pub old_expires_at: DateTime<Utc>,
+}
+
+pub fn get_backups_table(backups: &[Backup], database: &str, raw: bool) -> String {
+    if backups.is_empty() {
+        let message = format!("No backups are kept for {database}");
+
+        return if raw {
+            format!("{message}\n")
+        } else {
+            format!("{}\n", message.bold())
+        };
+    }
+
+    let mut table = Table::new();
+
+    if raw {
+        table
+            .load_preset(NOTHING)
+            .set_content_arrangement(ContentArrangement::Disabled)
+            .set_header(vec![
+                Cell::new("ID").set_alignment(CellAlignment::Left),
+                Cell::new("Created at").set_alignment(CellAlignment::Left),
+                Cell::new("Size").set_alignment(CellAlignment::Left),
+            ]);
+    } else {
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::DynamicFullWidth)
+            .set_header(vec![
+                Cell::new("ID")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Created at")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Size")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+            ]);
+    }
+
+    for backup in backups {
+        table.add_row(vec![
+            backup.id.clone(),
+            backup.created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            format!("{:.1} MiB", backup.size as f64 / (1024.0 * 1024.0)),
+        ]);
+    }
+
+    format!("These backups are kept for {database}\n{table}\n")
+}
+
 pub fn get_resource_tables(
     resources: &Vec<Response>,
     service_name: &str,
@@ -33,6 +127,7 @@ pub fn get_resource_tables(
                 Type::StaticFolder => "Static Folder",
                 Type::Persist => "Persist",
                 Type::Turso => "Turso",
+                Type::ObjectStore => "Object Stores",
                 Type::Metadata => "Metadata",
                 Type::Custom => "Custom",
             };
@@ -66,6 +161,10 @@ pub fn get_resource_tables(
             output.push(get_persist_table(persist, service_name, raw));
         };
 
+        if let Some(object_stores) = resource_groups.get("Object Stores") {
+            output.push(get_object_stores_table(object_stores, service_name, raw));
+        };
+
         if let Some(custom) = resource_groups.get("Custom") {
             output.push(get_custom_resources_table(custom, service_name, raw));
         };
@@ -88,6 +187,7 @@ fn get_databases_table(
             .set_content_arrangement(ContentArrangement::Disabled)
             .set_header(vec![
                 Cell::new("Type").set_alignment(CellAlignment::Left),
+                Cell::new("Name").set_alignment(CellAlignment::Left),
                 Cell::new("Connection string").set_alignment(CellAlignment::Left),
             ]);
     } else {
@@ -99,6 +199,9 @@ fn get_databases_table(
                 Cell::new("Type")
                     .set_alignment(CellAlignment::Center)
                     .add_attribute(Attribute::Bold),
+                Cell::new("Name")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
                 Cell::new("Connection string")
                     .add_attribute(Attribute::Bold)
                     .set_alignment(CellAlignment::Center),
@@ -135,7 +238,14 @@ fn get_databases_table(
                 }
             }
         };
-        table.add_row(vec![database.r#type.to_string(), conn_string]);
+        table.add_row(vec![
+            database.r#type.to_string(),
+            database
+                .name
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            conn_string,
+        ]);
     }
 
     let show_secret_hint = if databases.is_empty() || show_secrets {
@@ -220,6 +330,56 @@ fn get_persist_table(persist_instances: &[&Response], service_name: &str, raw: b
     format!("These persist instances are linked to {service_name}\n{table}\n")
 }
 
+fn get_object_stores_table(object_stores: &[&Response], service_name: &str, raw: bool) -> String {
+    let mut table = Table::new();
+
+    if raw {
+        table
+            .load_preset(NOTHING)
+            .set_content_arrangement(ContentArrangement::Disabled)
+            .set_header(vec![
+                Cell::new("Name").set_alignment(CellAlignment::Left),
+                Cell::new("Bucket").set_alignment(CellAlignment::Left),
+                Cell::new("Endpoint").set_alignment(CellAlignment::Left),
+            ]);
+    } else {
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::DynamicFullWidth)
+            .set_header(vec![
+                Cell::new("Name")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Bucket")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Endpoint")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+            ]);
+    }
+
+    for object_store in object_stores {
+        let info = serde_json::from_value::<ObjectStoreInfo>(object_store.data.clone())
+            .expect("resource data to be a valid object store");
+        let endpoint = info
+            .endpoint
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", info.region));
+
+        table.add_row(vec![
+            object_store
+                .name
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            format!("{}/{}", info.bucket, info.prefix),
+            endpoint,
+        ]);
+    }
+
+    format!("These object stores are linked to {service_name}\n{table}\n")
+}
+
 fn get_custom_resources_table(
     custom_resource_instances: &[&Response],
     service_name: &str,
@@ -240,11 +400,13 @@ fn get_custom_resources_table(
                 .add_attribute(Attribute::Bold)]);
     }
 
-    for (idx, _) in custom_resource_instances.iter().enumerate() {
-        // TODO: add some information that would make the custom resources identifiable.
-        // This requires changing the backend resource list response to include a resource identifier
-        // that can be used to query for more info related to a resource.
-        table.add_row(vec![format!("custom-resource-{}", idx.to_string())]);
+    for (idx, custom) in custom_resource_instances.iter().enumerate() {
+        let name = custom
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("custom-resource-{idx}"));
+
+        table.add_row(vec![name]);
     }
 
     format!("These custom resource instances are linked to {service_name}\n{table}\n")
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use comfy_table::{
+    modifiers::UTF8_ROUND_CORNERS,
+    presets::{NOTHING, UTF8_FULL},
+    Attribute, Cell, CellAlignment, ContentArrangement, Table,
+};
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+
+/// Placeholder shown instead of a secret value when the values are not revealed
+pub const MASKED_VALUE: &str = "********";
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub key: String,
+    /// The secret value, or [`MASKED_VALUE`] unless the values were asked for
+    pub value: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct SetRequest {
+    /// Secrets to add or overwrite. Secrets not listed here are kept as is
+    pub secrets: BTreeMap<String, String>,
+    /// Restart the running deployment so that it picks up the new values
+    #[serde(default)]
+    pub restart: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct ListQuery {
+    /// Reveal the secret values instead of masking them
+    #[serde(default)]
+    pub show_values: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct UnsetRequest {
+    /// Keys of the secrets to remove. Nothing is removed if one of them is not set
+    pub keys: Vec<String>,
+    /// Restart the running deployment so that the secrets are no longer available to it
+    #[serde(default)]
+    pub restart: bool,
+}
+
+pub fn get_secrets_table(secrets: &[Response], service_name: &str, raw: bool) -> String {
+    if secrets.is_empty() {
+        let s = format!("No secrets are set for {service_name}\n");
+
+        return if raw { s } else { s.bold().to_string() };
+    }
+
+    let mut table = Table::new();
+
+    if raw {
+        table
+            .load_preset(NOTHING)
+            .set_content_arrangement(ContentArrangement::Disabled)
+            .set_header(vec![
+                Cell::new("Key").set_alignment(CellAlignment::Left),
+                Cell::new("Value").set_alignment(CellAlignment::Left),
+            ]);
+    } else {
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::DynamicFullWidth)
+            .set_header(vec![
+                Cell::new("Key")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Value")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+            ]);
+    }
+
+    for secret in secrets {
+        table.add_row(vec![&secret.key, &secret.value]);
+    }
+
+    let show_values_hint = if secrets.iter().all(|s| s.value == MASKED_VALUE) {
+        "Hint: you can show the values of these secrets using `cargo shuttle secrets list --show-values`\n"
+    } else {
+        ""
+    };
+
+    format!("These secrets can be accessed by {service_name}\n{table}\n{show_values_hint}")
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -11,3 +12,13 @@ pub struct LoadResponse {
     pub builds_count: usize,
     pub has_capacity: bool,
 }
+
+#[derive(Deserialize, Serialize)]
+pub struct CertificateRenewalFailure {
+    /// Domain of the certificate, `*.<public domain>` for the gateway certificate
+    pub fqdn: String,
+    /// Number of renewals in a row which failed
+    pub failures: u32,
+    pub last_error: String,
+    pub last_attempt_at: DateTime<Utc>,
+}
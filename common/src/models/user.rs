@@ -5,11 +5,35 @@ use strum::{Display, EnumString};
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Response {
     pub name: String,
-    pub key: String,
+    /// The API key of the user. Only returned when the user is created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
     pub account_tier: String,
     pub subscriptions: Vec<Subscription>,
 }
 
+/// A new API key of a user, issued while their previous key keeps working for a while
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RotateKeyResponse {
+    pub key: String,
+    /// When the previous key stops working
+    pub previous_key_expires_at: DateTime<Utc>,
+}
+
+/// A short-lived token for a key, for clients which keep the token instead of sending the key on
+/// every request
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TokenResponse {
+    pub token: String,
+    /// Exchanged for the next token once this one expires. Can only be used once
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Subscription {
     pub id: String,
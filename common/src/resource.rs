@@ -11,6 +11,11 @@ pub struct Response {
     /// The type of this resource.
     pub r#type: Type,
 
+    /// The name given to this resource, to tell it apart from other resources of the same type.
+    /// Resources without a name are the default resource of their type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
     /// The config used when creating this resource. Use the [Self::r#type] to know how to parse this data.
     pub config: Value,
 
@@ -26,6 +31,7 @@ pub enum Type {
     StaticFolder,
     Persist,
     Turso,
+    ObjectStore,
     Metadata,
     Custom,
 }
@@ -46,6 +52,7 @@ impl FromStr for Type {
                 "metadata" => Ok(Self::Metadata),
                 "persist" => Ok(Self::Persist),
                 "turso" => Ok(Self::Turso),
+                "object_store" => Ok(Self::ObjectStore),
                 "custom" => Ok(Self::Custom),
                 _ => Err(format!("'{s}' is an unknown resource type")),
             }
@@ -53,6 +60,18 @@ impl FromStr for Type {
     }
 }
 
+/// Check that a resource name is safe to use in the identifiers of what gets provisioned for it,
+/// such as the name of a database
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 32
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+        && name
+            .bytes()
+            .all(|byte| matches!(byte, b'a'..=b'z' | b'0'..=b'9' | b'-'))
+}
+
 impl Response {
     pub fn into_bytes(self) -> Vec<u8> {
         self.to_bytes()
@@ -75,6 +94,7 @@ impl Display for Type {
             Type::StaticFolder => write!(f, "static_folder"),
             Type::Persist => write!(f, "persist"),
             Type::Turso => write!(f, "turso"),
+            Type::ObjectStore => write!(f, "object_store"),
             Type::Metadata => write!(f, "metadata"),
             Type::Custom => write!(f, "custom"),
         }
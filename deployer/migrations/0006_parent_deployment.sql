@@ -0,0 +1,2 @@
+ALTER TABLE deployments
+ADD COLUMN parent_id TEXT; -- Deployment of the workspace's primary service this deployment was built with
@@ -0,0 +1,2 @@
+ALTER TABLE deployments
+ADD COLUMN run_config TEXT; -- Health check and restart policy from the Shuttle.toml the deployment was built with, as JSON
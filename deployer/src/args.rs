@@ -67,6 +67,10 @@ pub struct Args {
     #[clap(long, default_value = "/tmp")]
     pub artifacts_path: PathBuf,
 
+    /// Seconds a service gets to finish its in-flight work when its deployment is stopped
+    #[clap(long, default_value = "10")]
+    pub drain_timeout: u64,
+
     /// Add an auth layer to deployer for local development
     #[arg(long)]
     pub local: bool,
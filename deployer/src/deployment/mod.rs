@@ -3,7 +3,7 @@ use std::{
     sync::Arc,
 };
 
-use shuttle_common::log::LogRecorder;
+use shuttle_common::{constants::EXECUTABLE_DIRNAME, log::LogRecorder};
 use shuttle_proto::{builder::builder_client::BuilderClient, logger::logger_client::LoggerClient};
 use tokio::{
     sync::{mpsc, Mutex},
@@ -172,6 +172,7 @@ where
         // Run queue. Waits for built deployments and runs them.
         set.spawn(run::task(
             run_recv,
+            run_send.clone(),
             runtime_manager.clone(),
             deployment_updater,
             active_deployment_getter,
@@ -216,7 +217,9 @@ pub struct DeploymentManager {
 ///       |
 ///       v
 ///    run task     tasks enter the State::Running state and begin
-///                 executing
+///                 executing. Traffic is only switched to them, and the
+///                 old deployment of the service stopped, once they are
+///                 ready for it
 /// ```
 impl DeploymentManager {
     /// Create a new deployment manager. Manages one or more 'pipelines' for
@@ -259,6 +262,35 @@ impl DeploymentManager {
         self.builds_path.as_path()
     }
 
+    /// Path the built executable of a deployment is kept at until the project is cleaned
+    pub fn executable_path(&self, project_name: &str, id: &Uuid) -> PathBuf {
+        self.builds_path
+            .join(project_name)
+            .join(EXECUTABLE_DIRNAME)
+            .join(id.to_string())
+    }
+
+    /// Ids of the deployments of a project that still have a built executable to start from
+    pub async fn available_executables(&self, project_name: &str) -> std::io::Result<Vec<Uuid>> {
+        let mut entries =
+            match tokio::fs::read_dir(self.builds_path.join(project_name).join(EXECUTABLE_DIRNAME))
+                .await
+            {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(err) => return Err(err),
+            };
+
+        let mut ids = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if let Ok(id) = Uuid::parse_str(&entry.file_name().to_string_lossy()) {
+                ids.push(id);
+            }
+        }
+
+        Ok(ids)
+    }
+
     pub fn logs_fetcher(
         &self,
     ) -> &LoggerClient<
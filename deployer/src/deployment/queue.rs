@@ -12,6 +12,7 @@ use shuttle_common::{
     constants::{EXECUTABLE_DIRNAME, STORAGE_DIRNAME},
     deployment::DEPLOYER_END_MSG_BUILD_ERR,
     log::LogRecorder,
+    models::deployment::RunConfig,
     LogItem,
 };
 use shuttle_proto::builder::builder_client::BuilderClient;
@@ -118,7 +119,9 @@ pub async fn task(
                         {
                             Ok(built) => {
                                 remove_from_queue(queue_client, id).await;
-                                promote_to_run(built, run_send_cloned).await
+                                for built in built {
+                                    promote_to_run(built, run_send_cloned.clone()).await
+                                }
                             }
                             Err(err) => {
                                 remove_from_queue(queue_client, id).await;
@@ -158,6 +161,11 @@ fn build_failed(_id: &Uuid, error: impl std::error::Error + 'static) {
     );
 }
 
+#[instrument(name = "Build failed", skip(_id), fields(deployment_id = %_id, state = %State::Crashed))]
+fn child_build_failed(_id: &Uuid, parent_id: &Uuid) {
+    error!(%parent_id, "{DEPLOYER_END_MSG_BUILD_ERR}");
+}
+
 #[instrument(name = "Waiting for queue slot", skip(queue_client), fields(deployment_id = %id, state = %State::Queued))]
 async fn wait_for_queue(queue_client: impl BuildQueueClient, id: Uuid) -> Result<()> {
     loop {
@@ -202,6 +210,7 @@ async fn promote_to_run(mut built: Built, run_send: RunSender) {
 pub struct Queued {
     pub id: Uuid,
     pub service_name: String,
+    pub project_name: String,
     pub service_id: Ulid,
     pub project_id: Ulid,
     pub data: Vec<u8>,
@@ -221,8 +230,8 @@ impl Queued {
         deployment_updater: impl DeploymentUpdater,
         log_recorder: impl LogRecorder,
         builds_path: &Path,
-    ) -> Result<Built> {
-        let project_path = builds_path.join(&self.service_name);
+    ) -> Result<Vec<Built>> {
+        let project_path = builds_path.join(&self.project_name);
 
         info!("Extracting files");
         fs::create_dir_all(&project_path).await?;
@@ -242,45 +251,99 @@ impl Queued {
             }
         });
         let project_path = project_path.canonicalize()?;
-        // Currently returns the first found shuttle service in a given workspace.
-        let built_service = build_deployment(&project_path, tx.clone()).await?;
-
-        // Get the Secrets.toml from the shuttle service in the workspace.
-        let secrets = get_secrets(built_service.crate_directory()).await?;
+        let mut built_services = build_deployment(&project_path, tx.clone()).await?;
 
         if self.will_run_tests {
             info!("Running tests before starting up");
             run_pre_deploy_tests(&project_path, tx).await?;
         }
 
-        info!("Moving built executable");
-        copy_executable(
-            built_service.executable_path.as_path(),
-            built_service
-                .workspace_path
-                .join(EXECUTABLE_DIRNAME)
-                .as_path(),
-            &self.id,
-        )
-        .await?;
+        // The service named after the deployed service keeps this deployment's id. Any
+        // other service in the workspace gets its own service and deployment recorded.
+        let primary = built_services
+            .iter()
+            .position(
+                |service| matches!(service.service_name(), Ok(name) if name == self.service_name),
+            )
+            .unwrap_or_default();
+        built_services.swap(0, primary);
+
+        let run_config = get_run_config(&project_path).await?;
+        let mut built = Vec::with_capacity(built_services.len());
+        let mut children = Vec::new();
+
+        let recorded = async {
+            for (index, built_service) in built_services.into_iter().enumerate() {
+                let (id, service_name, service_id) = if index == 0 {
+                    (self.id, self.service_name.clone(), self.service_id)
+                } else {
+                    let service_name = built_service
+                        .service_name()
+                        .map_err(|e| Error::Build(e.into()))?;
+                    let (id, service_id) = deployment_updater
+                        .add_child_deployment(&self.id, &service_name)
+                        .await
+                        .map_err(|e| Error::Build(Box::new(e)))?;
 
-        let is_next = built_service.is_wasm;
+                    info!(%id, "Recorded deployment for service {service_name}");
+                    children.push(id);
 
-        deployment_updater
-            .set_is_next(&self.id, is_next)
-            .await
-            .map_err(|e| Error::Build(Box::new(e)))?;
-
-        let built = Built {
-            id: self.id,
-            service_name: self.service_name,
-            service_id: self.service_id,
-            project_id: self.project_id,
-            tracing_context: Default::default(),
-            is_next,
-            claim: self.claim,
-            secrets,
-        };
+                    (id, service_name, service_id)
+                };
+
+                // Get the Secrets.toml from the shuttle service in the workspace.
+                let secrets = get_secrets(built_service.crate_directory()).await?;
+
+                info!("Moving built executable for service {service_name}");
+                copy_executable(
+                    built_service.executable_path.as_path(),
+                    built_service
+                        .workspace_path
+                        .join(EXECUTABLE_DIRNAME)
+                        .as_path(),
+                    &id,
+                )
+                .await?;
+
+                let is_next = built_service.is_wasm;
+
+                deployment_updater
+                    .set_is_next(&id, is_next)
+                    .await
+                    .map_err(|e| Error::Build(Box::new(e)))?;
+                deployment_updater
+                    .set_run_config(&id, &run_config)
+                    .await
+                    .map_err(|e| Error::Build(Box::new(e)))?;
+
+                built.push(Built {
+                    id,
+                    service_name,
+                    project_name: self.project_name.clone(),
+                    service_id,
+                    project_id: self.project_id,
+                    tracing_context: Default::default(),
+                    is_next,
+                    claim: self.claim.clone(),
+                    secrets,
+                    run_config: run_config.clone(),
+                    restarts: 0,
+                });
+            }
+
+            Ok::<_, Error>(())
+        }
+        .await;
+
+        // Nothing gets to run when any service fails, so the deployments already recorded
+        // for the other services would otherwise be stuck in the building state
+        if let Err(error) = recorded {
+            for id in children {
+                child_build_failed(&id, &self.id);
+            }
+
+            return Err(error);
+        }
 
         Ok(built)
     }
@@ -291,6 +354,7 @@ impl fmt::Debug for Queued {
         f.debug_struct("Queued")
             .field("id", &self.id)
             .field("service_name", &self.service_name)
+            .field("project_name", &self.project_name)
             .field("service_id", &self.service_id)
             .field("will_run_tests", &self.will_run_tests)
             .finish_non_exhaustive()
@@ -314,6 +378,20 @@ async fn get_secrets(project_path: &Path) -> Result<HashMap<String, String>> {
     }
 }
 
+/// Read the health check and restart policy from the Shuttle.toml at the root of the project
+#[instrument(skip(project_path))]
+async fn get_run_config(project_path: &Path) -> Result<RunConfig> {
+    let config_file = project_path.join("Shuttle.toml");
+
+    if config_file.exists() && config_file.is_file() {
+        let config_str = fs::read_to_string(config_file).await?;
+
+        toml::from_str(&config_str).map_err(Error::RunConfigParse)
+    } else {
+        Ok(Default::default())
+    }
+}
+
 /// Akin to the command: `tar -xzf --strip-components 1`
 #[instrument(skip(data, dest))]
 async fn extract_tar_gz_data(data: impl Read, dest: impl AsRef<Path>) -> Result<()> {
@@ -365,13 +443,19 @@ async fn extract_tar_gz_data(data: impl Read, dest: impl AsRef<Path>) -> Result<
 async fn build_deployment(
     project_path: &Path,
     tx: tokio::sync::mpsc::Sender<String>,
-) -> Result<BuiltService> {
+) -> Result<Vec<BuiltService>> {
     // Build in release mode, except for when testing, such as in CI
     let runtimes = build_workspace(project_path, cfg!(not(test)), tx, true)
         .await
         .map_err(|e| Error::Build(e.into()))?;
 
-    Ok(runtimes[0].clone())
+    if runtimes.is_empty() {
+        return Err(Error::Build(
+            anyhow::anyhow!("no shuttle services were found in the workspace").into(),
+        ));
+    }
+
+    Ok(runtimes)
 }
 
 #[instrument(skip(project_path, tx))]
@@ -445,6 +529,7 @@ async fn copy_executable(
 mod tests {
     use std::{collections::HashMap, fs::File, io::Write, path::Path};
 
+    use shuttle_common::models::deployment::{Restart, RunConfig};
     use tempfile::Builder;
     use tokio::fs;
     use uuid::Uuid;
@@ -608,4 +693,27 @@ ff0e55bda1ff01000000000000000000e0079c01ff12a55500280000",
 
         assert!(!secret_p.exists(), "the secrets file should be deleted");
     }
+
+    #[tokio::test]
+    async fn get_run_config() {
+        let temp = Builder::new().prefix("run-config").tempdir().unwrap();
+        let temp_p = temp.path();
+
+        let actual = super::get_run_config(temp_p).await.unwrap();
+        assert_eq!(actual, RunConfig::default());
+
+        let config_p = temp_p.join("Shuttle.toml");
+        let mut config_file = File::create(config_p.clone()).unwrap();
+        config_file
+            .write_all(
+                b"name = 'my-project'\n\n[health_check]\npath = '/health'\n\n[restart]\npolicy = 'always'\n",
+            )
+            .unwrap();
+
+        let actual = super::get_run_config(temp_p).await.unwrap();
+
+        assert_eq!(actual.health_check.unwrap().path, "/health");
+        assert_eq!(actual.restart.policy, Restart::Always);
+        assert!(config_p.exists(), "the project config should be kept");
+    }
 }
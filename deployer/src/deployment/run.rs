@@ -3,9 +3,11 @@ use std::{
     net::{Ipv4Addr, SocketAddr},
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use async_trait::async_trait;
+use hyper::client::HttpConnector;
 use opentelemetry::global;
 use portpicker::pick_unused_port;
 use shuttle_common::{
@@ -15,26 +17,29 @@ use shuttle_common::{
         DEPLOYER_END_MSG_COMPLETED, DEPLOYER_END_MSG_CRASHED, DEPLOYER_END_MSG_STARTUP_ERR,
         DEPLOYER_END_MSG_STOPPED, DEPLOYER_RUNTIME_START_RESPONSE,
     },
+    models::deployment::{HealthCheck, Restart, RunConfig},
     resource, SecretStore,
 };
 use shuttle_proto::{
     resource_recorder::record_request,
     runtime::{
-        runtime_client::RuntimeClient, LoadRequest, StartRequest, StopReason, SubscribeStopRequest,
-        SubscribeStopResponse,
+        runtime_client::RuntimeClient, LoadRequest, StartRequest, StartResponse, StopReason,
+        SubscribeStopRequest, SubscribeStopResponse,
     },
 };
 use tokio::{
+    net::TcpStream,
     sync::Mutex,
     task::{JoinHandle, JoinSet},
+    time::{interval, sleep, timeout, MissedTickBehavior},
 };
-use tonic::{transport::Channel, Code};
+use tonic::{transport::Channel, Code, Status};
 use tracing::{debug, debug_span, error, info, instrument, warn, Instrument};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use ulid::Ulid;
 use uuid::Uuid;
 
-use super::{RunReceiver, State};
+use super::{RunReceiver, RunSender, State};
 use crate::{
     error::{Error, Result},
     persistence::{resource::ResourceManager, DeploymentUpdater},
@@ -43,8 +48,10 @@ use crate::{
 
 /// Run a task which takes runnable deploys from a channel and starts them up on our runtime
 /// A deploy is killed when it receives a signal from the kill channel
+/// A deploy which stopped by itself is pushed back on the run channel when its restart policy says so
 pub async fn task(
     mut recv: RunReceiver,
+    run_send: RunSender,
     runtime_manager: Arc<Mutex<RuntimeManager>>,
     deployment_updater: impl DeploymentUpdater,
     active_deployment_getter: impl ActiveDeploymentsGetter,
@@ -72,11 +79,16 @@ pub async fn task(
                     runtime_manager.clone(),
                 );
                 let runtime_manager_clone = runtime_manager.clone();
+                let mut restartable = built.clone();
+                let run_send = run_send.clone();
+                let restart_deployment_getter = active_deployment_getter.clone();
+                let started = Instant::now();
                 let cleanup = move |response: Option<SubscribeStopResponse>| {
                     debug!(response = ?response,  "stop client response: ");
 
-                    if let Some(response) = response {
-                        match StopReason::try_from(response.reason).unwrap_or_default() {
+                    let reason = if let Some(response) = response {
+                        let reason = StopReason::try_from(response.reason).unwrap_or_default();
+                        match reason {
                             StopReason::Request => stopped_cleanup(&id),
                             StopReason::End => completed_cleanup(&id),
                             StopReason::Crash => crashed_cleanup(
@@ -85,6 +97,8 @@ pub async fn task(
                                 Error::Run(anyhow::Error::msg(response.message).into()),
                             )
                         }
+
+                        reason
                     } else {
                         crashed_cleanup(
                             &id,
@@ -93,8 +107,18 @@ pub async fn task(
                                 "stop subscribe channel stopped unexpectedly"
                             )),
                         );
-                    }
 
+                        StopReason::Crash
+                    };
+
+                    if let Some(delay) = restartable.restart_delay(reason, started.elapsed()) {
+                        tokio::spawn(restart_deployment(
+                            restartable,
+                            delay,
+                            run_send,
+                            restart_deployment_getter,
+                        ));
+                    }
                 };
 
                 let runtime_manager = runtime_manager.clone();
@@ -206,6 +230,46 @@ fn start_crashed_cleanup(_id: &Uuid, error: impl std::error::Error + 'static) {
     );
 }
 
+/// Push a deployment which stopped by itself back on the run queue once its backoff is over
+#[instrument(name = "Restarting deployment", skip(built, delay, run_send, active_deployment_getter), fields(deployment_id = %built.id))]
+async fn restart_deployment(
+    mut built: Built,
+    delay: Duration,
+    run_send: RunSender,
+    active_deployment_getter: impl ActiveDeploymentsGetter,
+) {
+    info!(
+        restarts = built.restarts,
+        "restarting deployment in {}s",
+        delay.as_secs()
+    );
+    sleep(delay).await;
+
+    // Do not take over from a deployment that was started while this one was down
+    match active_deployment_getter
+        .get_active_deployments(&built.service_id)
+        .await
+    {
+        Ok(active) if !active.is_empty() => {
+            info!("not restarting since another deployment of the service is running");
+            return;
+        }
+        Ok(_) => {}
+        Err(error) => {
+            error!(
+                error = &error as &dyn std::error::Error,
+                "failed to get the active deployments before restarting"
+            );
+            return;
+        }
+    }
+
+    built.restarts += 1;
+    if run_send.send(built).await.is_err() {
+        error!("run queue is closed, cannot restart deployment");
+    }
+}
+
 #[async_trait]
 pub trait ActiveDeploymentsGetter: Clone + Send + Sync + 'static {
     type Err: std::error::Error + Send;
@@ -220,15 +284,43 @@ pub trait ActiveDeploymentsGetter: Clone + Send + Sync + 'static {
 pub struct Built {
     pub id: Uuid, // Deployment id
     pub service_name: String,
+    /// Name of the project the service was built in, which is also its build directory
+    pub project_name: String,
     pub service_id: Ulid,
     pub project_id: Ulid,
     pub tracing_context: HashMap<String, String>,
     pub is_next: bool,
     pub claim: Claim,
     pub secrets: HashMap<String, String>,
+    pub run_config: RunConfig,
+    /// How many times in a row this deployment was restarted by its restart policy
+    pub restarts: u32,
 }
 
+/// A deployment that ran for this long before stopping gets all its restart retries back
+const RESTARTS_RESET_AFTER: Duration = Duration::from_secs(10 * 60);
+
 impl Built {
+    /// How long to wait before restarting this deployment after it stopped for `reason`, or
+    /// `None` when its restart policy does not restart it
+    fn restart_delay(&mut self, reason: StopReason, ran_for: Duration) -> Option<Duration> {
+        let restart = match reason {
+            StopReason::Request => false,
+            StopReason::Crash => self.run_config.restart.policy != Restart::Never,
+            StopReason::End => self.run_config.restart.policy == Restart::Always,
+        };
+
+        if !restart {
+            return None;
+        }
+
+        if ran_for >= RESTARTS_RESET_AFTER {
+            self.restarts = 0;
+        }
+
+        self.run_config.restart.backoff(self.restarts)
+    }
+
     #[instrument(
         name = "Loading resources",
         skip(self, resource_manager, runtime_manager, deployment_updater, kill_old_deployments, cleanup),
@@ -240,11 +332,11 @@ impl Built {
         resource_manager: impl ResourceManager,
         runtime_manager: Arc<Mutex<RuntimeManager>>,
         deployment_updater: impl DeploymentUpdater,
-        kill_old_deployments: impl futures::Future<Output = Result<()>>,
+        kill_old_deployments: impl futures::Future<Output = Result<()>> + Send + 'static,
         cleanup: impl FnOnce(Option<SubscribeStopResponse>) + Send + 'static,
         builds_path: &Path,
     ) -> Result<JoinHandle<()>> {
-        let project_path = builds_path.join(&self.service_name);
+        let project_path = builds_path.join(&self.project_name);
         // For alpha this is the path to the users project with an embedded runtime.
         // For shuttle-next this is the path to the compiled .wasm file, which will be
         // used in the load request.
@@ -263,6 +355,13 @@ impl Built {
 
         let address = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port);
 
+        // An address left from an earlier run of this deployment must not get traffic before
+        // the service is ready on its new one
+        deployment_updater
+            .clear_address(&self.id)
+            .await
+            .map_err(|err| Error::PrepareRun(err.to_string()))?;
+
         let alpha_runtime_path = if self.is_next {
             // The runtime client for next is the installed shuttle-next bin
             None
@@ -282,10 +381,10 @@ impl Built {
             .await
             .map_err(Error::Runtime)?;
 
-        kill_old_deployments.await?;
         // Execute loaded service
         load(
             self.service_name.clone(),
+            self.project_name,
             self.service_id,
             executable_path.clone(),
             resource_manager,
@@ -295,12 +394,16 @@ impl Built {
         )
         .await?;
 
+        let restart_unhealthy = self.run_config.restart.policy != Restart::Never;
         let handler = tokio::spawn(run(
             self.id,
             self.service_name,
             runtime_client,
             address,
             deployment_updater,
+            kill_old_deployments,
+            self.run_config.health_check,
+            restart_unhealthy,
             cleanup,
         ));
 
@@ -308,8 +411,10 @@ impl Built {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn load(
     service_name: String,
+    project_name: String,
     service_id: Ulid,
     executable_path: PathBuf,
     mut resource_manager: impl ResourceManager,
@@ -361,16 +466,17 @@ async fn load(
             .into_string()
             .unwrap_or_default(),
         service_name: service_name.clone(),
+        project_name: project_name.clone(),
         resources,
         secrets,
     });
 
     load_request.extensions_mut().insert(claim.clone());
 
-    debug!(shuttle.project.name = %service_name, shuttle.service.name = %service_name, "loading service");
+    debug!(shuttle.project.name = %project_name, shuttle.service.name = %service_name, "loading service");
     let response = runtime_client.load(load_request).await;
 
-    debug!(shuttle.project.name = %service_name, shuttle.service.name = %service_name, "service loaded");
+    debug!(shuttle.project.name = %project_name, shuttle.service.name = %service_name, "service loaded");
     match response {
         Ok(response) => {
             let response = response.into_inner();
@@ -388,6 +494,7 @@ async fn load(
                         r#type: resource.r#type.to_string(),
                         config: resource.config.to_string().into_bytes(),
                         data: resource.data.to_string().into_bytes(),
+                        name: resource.name.unwrap_or_default(),
                     }
                 })
                 .collect();
@@ -410,24 +517,19 @@ async fn load(
     }
 }
 
-#[instrument(name = "Starting service", skip(runtime_client, deployment_updater, cleanup), fields(deployment_id = %id, state = %State::Running))]
+#[instrument(name = "Starting service", skip(runtime_client, deployment_updater, kill_old_deployments, health_check, cleanup), fields(deployment_id = %id, state = %State::Running))]
+#[allow(clippy::too_many_arguments)]
 async fn run(
     id: Uuid,
     service_name: String,
     mut runtime_client: RuntimeClient<ClaimService<InjectPropagation<Channel>>>,
     address: SocketAddr,
     deployment_updater: impl DeploymentUpdater,
+    kill_old_deployments: impl futures::Future<Output = Result<()>>,
+    health_check: Option<HealthCheck>,
+    restart_unhealthy: bool,
     cleanup: impl FnOnce(Option<SubscribeStopResponse>) + Send + 'static,
 ) {
-    if let Err(err) = deployment_updater.set_address(&id, &address).await {
-        // Clean up based on a stop response built outside the runtime
-        cleanup(Some(SubscribeStopResponse {
-            reason: StopReason::Crash as i32,
-            message: format!("errored while setting the new deployer address: {}", err),
-        }));
-        return;
-    }
-
     let start_request = tonic::Request::new(StartRequest {
         ip: address.to_string(),
     });
@@ -452,18 +554,60 @@ async fn run(
 
     match response {
         Ok(response) => {
-            if response.into_inner().success {
+            let response = response.into_inner();
+            if response.success {
                 info!("{}", DEPLOYER_RUNTIME_START_RESPONSE);
             }
 
-            // Wait for stop reason
-            match stream.message().await {
-                Ok(reason) => cleanup(reason),
-                // Stream closed abruptly, most probably runtime crashed.
-                Err(err) => cleanup(Some(SubscribeStopResponse {
+            let health_probe =
+                health_check.and_then(|health_check| HealthProbe::new(address, health_check));
+
+            // The old deployments keep serving until this one is ready for traffic
+            let ready = tokio::select! {
+                message = stream.message() => Err(stop_response(message)),
+                ready = wait_until_ready(
+                    address,
+                    health_probe.as_ref(),
+                    accepts_connections(&response),
+                    READY_TIMEOUT,
+                ) => {
+                    ready.map_err(|message| Some(SubscribeStopResponse {
+                        reason: StopReason::Crash as i32,
+                        message,
+                    }))
+                }
+            };
+            if let Err(response) = ready {
+                cleanup(response);
+                return;
+            }
+
+            if let Err(err) = deployment_updater.set_address(&id, &address).await {
+                // Clean up based on a stop response built outside the runtime
+                cleanup(Some(SubscribeStopResponse {
                     reason: StopReason::Crash as i32,
-                    message: format!("runtime StopSubscribe channel errored: {}", err),
-                })),
+                    message: format!("errored while setting the new deployer address: {}", err),
+                }));
+                return;
+            }
+
+            // Traffic now goes to this deployment, so the old ones are drained and stopped
+            if let Err(error) = kill_old_deployments.await {
+                error!(
+                    error = &error as &dyn std::error::Error,
+                    "failed to stop the old deployments of the service"
+                );
+            }
+
+            // Wait for stop reason, or for an unhealthy service to be stopped for a restart
+            tokio::select! {
+                message = stream.message() => cleanup(stop_response(message)),
+                message = check_health(id, health_probe.as_ref(), restart_unhealthy) => {
+                    cleanup(Some(SubscribeStopResponse {
+                        reason: StopReason::Crash as i32,
+                        message,
+                    }))
+                }
             }
         }
         Err(ref status) if status.code() == Code::InvalidArgument => {
@@ -481,3 +625,475 @@ async fn run(
         }
     }
 }
+
+/// Whether a started service is waited on to accept connections. Runtimes from before the
+/// flag existed do not send it, and their services might never listen on their address.
+fn accepts_connections(response: &StartResponse) -> bool {
+    response.no_connections == Some(false)
+}
+
+/// Stop response for a message of the runtime's stop stream
+fn stop_response(
+    message: std::result::Result<Option<SubscribeStopResponse>, Status>,
+) -> Option<SubscribeStopResponse> {
+    match message {
+        Ok(response) => response,
+        // Stream closed abruptly, most probably runtime crashed.
+        Err(err) => Some(SubscribeStopResponse {
+            reason: StopReason::Crash as i32,
+            message: format!("runtime StopSubscribe channel errored: {}", err),
+        }),
+    }
+}
+
+/// Longest a started service can take to be ready for traffic
+const READY_TIMEOUT: Duration = Duration::from_secs(60);
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Wait for a started service to be ready for traffic. That is when its health check passes, or
+/// when it accepts connections if it has no health check. Services which never accept connections
+/// are ready as soon as they start.
+async fn wait_until_ready(
+    address: SocketAddr,
+    health_probe: Option<&HealthProbe>,
+    accepts_connections: bool,
+    ready_timeout: Duration,
+) -> std::result::Result<(), String> {
+    if health_probe.is_none() && !accepts_connections {
+        return Ok(());
+    }
+
+    let ready = async {
+        loop {
+            let ready = match health_probe {
+                Some(health_probe) => health_probe.check().await.is_ok(),
+                None => TcpStream::connect(address).await.is_ok(),
+            };
+
+            if ready {
+                break;
+            }
+
+            sleep(READY_POLL_INTERVAL).await;
+        }
+    };
+
+    if timeout(ready_timeout, ready).await.is_ok() {
+        info!("service is ready for traffic");
+        return Ok(());
+    }
+
+    match health_probe {
+        Some(health_probe) => Err(format!(
+            "health check on {} did not pass within {}s of the service starting",
+            health_probe.health_check.path,
+            ready_timeout.as_secs()
+        )),
+        None => Err(format!(
+            "service did not accept connections on {address} within {}s of starting",
+            ready_timeout.as_secs()
+        )),
+    }
+}
+
+/// Requests the health check path of a service
+struct HealthProbe {
+    health_check: HealthCheck,
+    client: hyper::Client<HttpConnector>,
+    uri: hyper::Uri,
+}
+
+impl HealthProbe {
+    fn new(address: SocketAddr, health_check: HealthCheck) -> Option<Self> {
+        match format!("http://{address}{}", health_check.path).parse() {
+            Ok(uri) => Some(Self {
+                health_check,
+                client: hyper::Client::new(),
+                uri,
+            }),
+            Err(error) => {
+                warn!(%error, path = %health_check.path, "invalid health check path, not checking the service's health");
+                None
+            }
+        }
+    }
+
+    async fn check(&self) -> std::result::Result<(), String> {
+        let check_timeout = Duration::from_secs(self.health_check.timeout.max(1));
+
+        match timeout(check_timeout, self.client.get(self.uri.clone())).await {
+            Ok(Ok(response)) if response.status().is_success() => Ok(()),
+            Ok(Ok(response)) => Err(format!("responded with {}", response.status())),
+            Ok(Err(error)) => Err(error.to_string()),
+            Err(_) => Err(format!(
+                "did not respond within {}s",
+                check_timeout.as_secs()
+            )),
+        }
+    }
+}
+
+/// Poll the health check of a running service. Only returns, with the reason to stop the
+/// service, when it became unhealthy and `restart_unhealthy` is set.
+async fn check_health(
+    id: Uuid,
+    health_probe: Option<&HealthProbe>,
+    restart_unhealthy: bool,
+) -> String {
+    let Some(health_probe) = health_probe else {
+        return futures::future::pending().await;
+    };
+    let health_check = &health_probe.health_check;
+    let threshold = health_check.unhealthy_threshold.max(1);
+
+    let mut checks = interval(Duration::from_secs(health_check.interval.max(1)));
+    checks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately, and the service was just found ready
+    checks.tick().await;
+
+    let mut failures = 0;
+
+    loop {
+        checks.tick().await;
+
+        match health_probe.check().await {
+            Ok(()) => {
+                if failures >= threshold {
+                    healthy_again(&id);
+                }
+                failures = 0;
+            }
+            Err(error) => {
+                failures += 1;
+                debug!(%error, failures, "health check failed");
+
+                if failures == threshold {
+                    unhealthy(&id, &error);
+
+                    if restart_unhealthy {
+                        return format!(
+                            "health check on {} failed {failures} times in a row: {error}",
+                            health_check.path
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[instrument(name = "Service is unhealthy", skip(_id), fields(deployment_id = %_id, state = %State::Unhealthy))]
+fn unhealthy(_id: &Uuid, error: &str) {
+    warn!(error, "service failed its health check");
+}
+
+#[instrument(name = "Service is healthy again", skip(_id), fields(deployment_id = %_id, state = %State::Running))]
+fn healthy_again(_id: &Uuid) {
+    info!("service passed its health check");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        io::{Read, Write},
+        net::{Ipv4Addr, SocketAddr},
+        sync::{
+            atomic::{AtomicU16, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use portpicker::pick_unused_port;
+    use shuttle_common::{
+        claims::{AccountTier, Claim},
+        models::deployment::{HealthCheck, Restart, RestartPolicy, RunConfig},
+    };
+    use shuttle_proto::runtime::{StartResponse, StopReason};
+    use tokio::{
+        net::TcpListener,
+        sync::mpsc,
+        time::{sleep, timeout},
+    };
+    use ulid::Ulid;
+    use uuid::Uuid;
+
+    use super::{
+        accepts_connections, check_health, restart_deployment, wait_until_ready,
+        ActiveDeploymentsGetter, Built, HealthProbe, RESTARTS_RESET_AFTER,
+    };
+
+    fn unused_address() -> SocketAddr {
+        SocketAddr::new(Ipv4Addr::LOCALHOST.into(), pick_unused_port().unwrap())
+    }
+
+    /// Serve every request with the status currently in `status`
+    fn serve_status(status: Arc<AtomicU16>) -> SocketAddr {
+        let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request);
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status.load(Ordering::SeqCst)
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        address
+    }
+
+    fn health_check() -> HealthCheck {
+        HealthCheck {
+            path: "/health".to_string(),
+            interval: 1,
+            timeout: 1,
+            unhealthy_threshold: 2,
+        }
+    }
+
+    #[derive(Clone)]
+    struct StubActiveDeploymentGetter(Vec<Uuid>);
+
+    #[async_trait]
+    impl ActiveDeploymentsGetter for StubActiveDeploymentGetter {
+        type Err = std::io::Error;
+
+        async fn get_active_deployments(
+            &self,
+            _service_id: &Ulid,
+        ) -> std::result::Result<Vec<Uuid>, Self::Err> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn get_built(restart: RestartPolicy) -> Built {
+        Built {
+            id: Uuid::new_v4(),
+            service_name: "restarts".to_string(),
+            project_name: "restarts".to_string(),
+            service_id: Ulid::new(),
+            project_id: Ulid::new(),
+            tracing_context: HashMap::new(),
+            is_next: false,
+            claim: Claim::new(
+                "test-user".to_string(),
+                AccountTier::Basic.into(),
+                AccountTier::Basic,
+                AccountTier::Basic,
+            ),
+            secrets: HashMap::new(),
+            run_config: RunConfig {
+                health_check: Some(health_check()),
+                restart,
+            },
+            restarts: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn ready_once_accepting_connections() {
+        let address = unused_address();
+
+        tokio::spawn(async move {
+            // Services usually take a moment to bind to their address
+            sleep(Duration::from_millis(500)).await;
+            let listener = TcpListener::bind(address).await.unwrap();
+
+            loop {
+                let _ = listener.accept().await;
+            }
+        });
+
+        wait_until_ready(address, None, true, Duration::from_secs(5))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn not_ready_without_accepting_connections() {
+        let error = wait_until_ready(unused_address(), None, true, Duration::from_secs(1))
+            .await
+            .unwrap_err();
+
+        assert!(error.contains("did not accept connections"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn ready_when_runtime_does_not_say() {
+        // What a runtime built before the flag existed sends back
+        let response = StartResponse {
+            success: true,
+            no_connections: None,
+        };
+        assert!(!accepts_connections(&response));
+
+        timeout(
+            Duration::from_millis(100),
+            wait_until_ready(
+                unused_address(),
+                None,
+                accepts_connections(&response),
+                Duration::from_secs(60),
+            ),
+        )
+        .await
+        .expect("services of older runtimes should not be waited on")
+        .unwrap();
+
+        assert!(accepts_connections(&StartResponse {
+            success: true,
+            no_connections: Some(false),
+        }));
+    }
+
+    #[tokio::test]
+    async fn ready_when_not_accepting_connections() {
+        timeout(
+            Duration::from_millis(100),
+            wait_until_ready(unused_address(), None, false, Duration::from_secs(60)),
+        )
+        .await
+        .expect("services without connections should not be waited on")
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn failing_health_check_stops_service_for_restart() {
+        let status = Arc::new(AtomicU16::new(200));
+        let address = serve_status(status.clone());
+        let probe = HealthProbe::new(address, health_check()).unwrap();
+
+        // A healthy service keeps running
+        assert!(
+            timeout(
+                Duration::from_secs(3),
+                check_health(Uuid::new_v4(), Some(&probe), true)
+            )
+            .await
+            .is_err(),
+            "a healthy service should not be stopped"
+        );
+
+        status.store(500, Ordering::SeqCst);
+
+        let message = timeout(
+            Duration::from_secs(5),
+            check_health(Uuid::new_v4(), Some(&probe), true),
+        )
+        .await
+        .expect("an unhealthy service to be stopped");
+
+        assert!(message.contains("failed 2 times in a row"), "{message}");
+    }
+
+    #[tokio::test]
+    async fn failing_health_check_without_restarts_keeps_service() {
+        let address = serve_status(Arc::new(AtomicU16::new(500)));
+        let probe = HealthProbe::new(address, health_check()).unwrap();
+
+        assert!(
+            timeout(
+                Duration::from_secs(4),
+                check_health(Uuid::new_v4(), Some(&probe), false)
+            )
+            .await
+            .is_err(),
+            "an unhealthy service should only be stopped when it is restarted"
+        );
+    }
+
+    #[tokio::test]
+    async fn crashes_are_restarted_until_the_retry_limit() {
+        let mut built = get_built(RestartPolicy {
+            policy: Restart::OnCrash,
+            max_retries: 2,
+            backoff: 0,
+        });
+        let (run_send, mut run_recv) = mpsc::channel(1);
+
+        for restarts in 1..=2 {
+            let delay = built
+                .restart_delay(StopReason::Crash, Duration::from_secs(1))
+                .expect("to restart within the retry limit");
+
+            restart_deployment(
+                built,
+                delay,
+                run_send.clone(),
+                StubActiveDeploymentGetter(vec![]),
+            )
+            .await;
+
+            built = run_recv.try_recv().expect("the deployment to be run again");
+            assert_eq!(built.restarts, restarts);
+        }
+
+        assert_eq!(
+            built.restart_delay(StopReason::Crash, Duration::from_secs(1)),
+            None,
+            "the deployment should be left crashed after its retries"
+        );
+
+        // Running for long enough gives the retries back
+        assert!(built
+            .restart_delay(StopReason::Crash, RESTARTS_RESET_AFTER)
+            .is_some());
+        assert_eq!(built.restarts, 0);
+    }
+
+    #[tokio::test]
+    async fn restart_policy_decides_what_is_restarted() {
+        let mut never = get_built(RestartPolicy::default());
+        assert_eq!(never.restart_delay(StopReason::Crash, Duration::ZERO), None);
+
+        let mut on_crash = get_built(RestartPolicy {
+            policy: Restart::OnCrash,
+            ..Default::default()
+        });
+        assert!(on_crash
+            .restart_delay(StopReason::Crash, Duration::ZERO)
+            .is_some());
+        assert_eq!(
+            on_crash.restart_delay(StopReason::End, Duration::ZERO),
+            None
+        );
+        assert_eq!(
+            on_crash.restart_delay(StopReason::Request, Duration::ZERO),
+            None
+        );
+
+        let mut always = get_built(RestartPolicy {
+            policy: Restart::Always,
+            ..Default::default()
+        });
+        assert!(always
+            .restart_delay(StopReason::End, Duration::ZERO)
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn restart_is_skipped_when_another_deployment_runs() {
+        let built = get_built(RestartPolicy {
+            policy: Restart::OnCrash,
+            ..Default::default()
+        });
+        let (run_send, mut run_recv) = mpsc::channel(1);
+
+        restart_deployment(
+            built,
+            Duration::ZERO,
+            run_send,
+            StubActiveDeploymentGetter(vec![Uuid::new_v4()]),
+        )
+        .await;
+
+        assert!(run_recv.try_recv().is_err());
+    }
+}
@@ -146,20 +146,25 @@ mod tests {
     use ctor::ctor;
     use flate2::{write::GzEncoder, Compression};
     use portpicker::pick_unused_port;
-    use shuttle_common::claims::Claim;
+    use shuttle_common::{claims::Claim, models::deployment::RunConfig};
     use shuttle_common_tests::{
         builder::get_mocked_builder_client, logger::get_mocked_logger_client,
     };
     use shuttle_proto::{
         builder::{builder_server::Builder, BuildRequest, BuildResponse},
         logger::{
-            logger_client::LoggerClient, logger_server::Logger, Batcher, LogLine, LogsRequest,
-            LogsResponse, StoreLogsRequest, StoreLogsResponse,
+            logger_client::LoggerClient, logger_server::Logger, AddDrainRequest, Batcher,
+            DeleteLogsRequest, DeleteLogsResponse, DeploymentTierRequest, DeploymentTierResponse,
+            Drain, DrainsRequest, DrainsResponse, LogLine, LogsRequest, LogsResponse,
+            RemoveDrainRequest, RemoveDrainResponse, ServiceLogsRequest, ServiceLogsResponse,
+            StoreLogsRequest, StoreLogsResponse,
         },
         provisioner::{
             provisioner_server::{Provisioner, ProvisionerServer},
-            ContainerRequest, ContainerResponse, DatabaseDeletionResponse, DatabaseRequest,
-            DatabaseResponse, Ping, Pong,
+            Backup, ContainerRequest, ContainerResponse, DatabaseDeletionResponse, DatabaseRequest,
+            DatabaseResponse, ListBackupsResponse, ObjectStoreDeletionResponse, ObjectStoreRequest,
+            ObjectStoreResponse, Ping, Pong, RestoreRequest, RestoreResponse,
+            RotateCredentialsResponse,
         },
         resource_recorder::{ResourceResponse, ResourcesResponse, ResultResponse},
     };
@@ -324,6 +329,45 @@ mod tests {
             let (_, rx) = mpsc::channel(1);
             Ok(Response::new(ReceiverStream::new(rx)))
         }
+
+        async fn get_service_logs(
+            &self,
+            _: Request<ServiceLogsRequest>,
+        ) -> Result<Response<ServiceLogsResponse>, Status> {
+            Ok(Response::new(ServiceLogsResponse::default()))
+        }
+
+        async fn set_deployment_tier(
+            &self,
+            _: Request<DeploymentTierRequest>,
+        ) -> Result<Response<DeploymentTierResponse>, Status> {
+            Ok(Response::new(DeploymentTierResponse {}))
+        }
+
+        async fn delete_logs(
+            &self,
+            _: Request<DeleteLogsRequest>,
+        ) -> Result<Response<DeleteLogsResponse>, Status> {
+            Ok(Response::new(DeleteLogsResponse { deleted: 0 }))
+        }
+
+        async fn add_drain(&self, _: Request<AddDrainRequest>) -> Result<Response<Drain>, Status> {
+            Ok(Response::new(Default::default()))
+        }
+
+        async fn get_drains(
+            &self,
+            _: Request<DrainsRequest>,
+        ) -> Result<Response<DrainsResponse>, Status> {
+            Ok(Response::new(Default::default()))
+        }
+
+        async fn remove_drain(
+            &self,
+            _: Request<RemoveDrainRequest>,
+        ) -> Result<Response<RemoveDrainResponse>, Status> {
+            Ok(Response::new(RemoveDrainResponse {}))
+        }
     }
 
     struct ProvisionerMock;
@@ -351,6 +395,48 @@ mod tests {
             panic!("no deploy layer tests should request delete a db");
         }
 
+        async fn provision_object_store(
+            &self,
+            _request: tonic::Request<ObjectStoreRequest>,
+        ) -> Result<tonic::Response<ObjectStoreResponse>, tonic::Status> {
+            panic!("no deploy layer tests should request an object store");
+        }
+
+        async fn delete_object_store(
+            &self,
+            _request: tonic::Request<ObjectStoreRequest>,
+        ) -> Result<tonic::Response<ObjectStoreDeletionResponse>, tonic::Status> {
+            panic!("no deploy layer tests should request delete an object store");
+        }
+
+        async fn backup_database(
+            &self,
+            _request: tonic::Request<DatabaseRequest>,
+        ) -> Result<tonic::Response<Backup>, tonic::Status> {
+            panic!("no deploy layer tests should request a backup");
+        }
+
+        async fn list_backups(
+            &self,
+            _request: tonic::Request<DatabaseRequest>,
+        ) -> Result<tonic::Response<ListBackupsResponse>, tonic::Status> {
+            panic!("no deploy layer tests should request a list of backups");
+        }
+
+        async fn restore_database(
+            &self,
+            _request: tonic::Request<RestoreRequest>,
+        ) -> Result<tonic::Response<RestoreResponse>, tonic::Status> {
+            panic!("no deploy layer tests should request a restore");
+        }
+
+        async fn rotate_database_credentials(
+            &self,
+            _request: tonic::Request<DatabaseRequest>,
+        ) -> Result<tonic::Response<RotateCredentialsResponse>, tonic::Status> {
+            panic!("no deploy layer tests should rotate db credentials");
+        }
+
         async fn health_check(
             &self,
             _request: tonic::Request<Ping>,
@@ -379,7 +465,12 @@ mod tests {
                 .unwrap();
         });
 
-        RuntimeManager::new(format!("http://{}", provisioner_addr), logger_client, None)
+        RuntimeManager::new(
+            format!("http://{}", provisioner_addr),
+            logger_client,
+            None,
+            None,
+        )
     }
 
     #[derive(Clone)]
@@ -393,9 +484,29 @@ mod tests {
             Ok(())
         }
 
+        async fn clear_address(&self, _id: &Uuid) -> Result<(), Self::Err> {
+            Ok(())
+        }
+
         async fn set_is_next(&self, _id: &Uuid, _is_next: bool) -> Result<(), Self::Err> {
             Ok(())
         }
+
+        async fn set_run_config(
+            &self,
+            _id: &Uuid,
+            _run_config: &RunConfig,
+        ) -> Result<(), Self::Err> {
+            Ok(())
+        }
+
+        async fn add_child_deployment(
+            &self,
+            _parent_id: &Uuid,
+            _service_name: &str,
+        ) -> Result<(Uuid, Ulid), Self::Err> {
+            Ok((Uuid::new_v4(), Ulid::new()))
+        }
     }
 
     #[derive(Clone)]
@@ -467,6 +578,7 @@ mod tests {
             &mut self,
             _service_id: &ulid::Ulid,
             _type: shuttle_common::resource::Type,
+            _name: Option<String>,
             _claim: Claim,
         ) -> Result<ResourceResponse, Self::Err> {
             Ok(ResourceResponse {
@@ -481,6 +593,7 @@ mod tests {
             _project_name: String,
             _service_id: &ulid::Ulid,
             _type: shuttle_common::resource::Type,
+            _name: Option<String>,
             _claim: Claim,
         ) -> Result<ResultResponse, Self::Err> {
             Ok(ResultResponse {
@@ -743,12 +856,15 @@ mod tests {
             .run_push(Built {
                 id,
                 service_name: "run-test".to_string(),
+                project_name: "run-test".to_string(),
                 service_id: Ulid::new(),
                 project_id: Ulid::new(),
                 tracing_context: Default::default(),
                 is_next: false,
                 claim: Default::default(),
                 secrets: Default::default(),
+                run_config: Default::default(),
+                restarts: 0,
             })
             .await;
 
@@ -788,6 +904,7 @@ mod tests {
             .queue_push(Queued {
                 id,
                 service_name: "nil_id".to_string(),
+                project_name: "nil_id".to_string(),
                 service_id: Ulid::new(),
                 project_id: Ulid::new(),
                 data: Bytes::from("violets are red").to_vec(),
@@ -849,6 +966,7 @@ mod tests {
         Queued {
             id: Uuid::new_v4(),
             service_name: format!("deploy-layer-{name}"),
+            project_name: format!("deploy-layer-{name}"),
             service_id: Ulid::new(),
             project_id: Ulid::new(),
             data: bytes,
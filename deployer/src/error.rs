@@ -21,6 +21,8 @@ pub enum Error {
     PreDeployTestFailure(#[from] TestError),
     #[error("Failed to parse secrets: {0}")]
     SecretsParse(#[from] toml::de::Error),
+    #[error("Failed to parse the run config in Shuttle.toml: {0}")]
+    RunConfigParse(#[source] toml::de::Error),
     #[error("Failed to set secrets: {0}")]
     SecretsSet(#[source] Box<dyn StdError + Send>),
     #[error("Failed to get secrets: {0}")]
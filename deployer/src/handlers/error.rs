@@ -25,6 +25,8 @@ pub enum Error {
     Internal(#[from] anyhow::Error),
     #[error("Missing header: {0}")]
     MissingHeader(String),
+    #[error("{0}")]
+    BadRequest(String),
 }
 
 impl Serialize for Error {
@@ -44,6 +46,7 @@ impl IntoResponse for Error {
     fn into_response(self) -> Response {
         let code = match self {
             Error::NotFound(_) => StatusCode::NOT_FOUND,
+            Error::BadRequest(_) => StatusCode::BAD_REQUEST,
             _ => {
                 // We only want to emit error events for internal errors, not e.g. 404s.
                 error!(
@@ -1,4 +1,6 @@
+use std::collections::BTreeMap;
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
@@ -13,7 +15,7 @@ use axum::middleware::{self, from_extractor};
 use axum::routing::{delete, get, post, Router};
 use axum::Json;
 use bytes::Bytes;
-use chrono::{SecondsFormat, Utc};
+use chrono::{NaiveDateTime, SecondsFormat, TimeZone, Utc};
 use fqdn::FQDN;
 use hyper::{Request, StatusCode, Uri};
 use serde::{de::DeserializeOwned, Deserialize};
@@ -32,13 +34,22 @@ use shuttle_common::{
     models::{
         deployment::{DeploymentRequest, CREATE_SERVICE_BODY_LIMIT, GIT_STRINGS_MAX_LENGTH},
         error::axum::CustomErrorPath,
+        log::{DrainRequest, DrainResponse, LogsQuery, ServiceLogsResponse},
         project::ProjectName,
+        resource, secret,
     },
-    request_span, LogItem,
+    request_span, LogItem, Secret, SecretStore,
+};
+use shuttle_proto::{
+    logger::{
+        self, AddDrainRequest, DeleteLogsRequest, DeploymentTierRequest, DrainsRequest,
+        LogsRequest, RemoveDrainRequest, ServiceLogsRequest,
+    },
+    provisioner,
+    resource_recorder::record_request,
 };
-use shuttle_proto::logger::LogsRequest;
 
-use crate::persistence::{Deployment, Persistence, State};
+use crate::persistence::{Deployment, Persistence, PersistenceError, Service, State};
 use crate::{
     deployment::{Built, DeploymentManager, Queued},
     persistence::resource::ResourceManager,
@@ -97,10 +108,50 @@ impl RouterBuilder {
                 delete(delete_service_resource)
                     .layer(ScopedLayer::new(vec![Scope::ResourcesWrite])),
             )
+            .route(
+                "/projects/:project_name/services/:service_name/resources/:resource_type/rotate",
+                post(rotate_service_resource.layer(ScopedLayer::new(vec![Scope::ResourcesWrite]))),
+            )
+            .route(
+                "/projects/:project_name/services/:service_name/resources/:resource_type/backups",
+                get(list_service_resource_backups.layer(ScopedLayer::new(vec![Scope::Resources])))
+                    .post(
+                        backup_service_resource
+                            .layer(ScopedLayer::new(vec![Scope::ResourcesWrite])),
+                    ),
+            )
+            .route(
+                "/projects/:project_name/services/:service_name/resources/:resource_type/backups/:backup_id/restore",
+                post(restore_service_resource.layer(ScopedLayer::new(vec![Scope::ResourcesWrite]))),
+            )
+            .route(
+                "/projects/:project_name/services/:service_name/secrets",
+                get(get_secrets.layer(ScopedLayer::new(vec![Scope::Secret]))).put(
+                    set_secrets
+                        .layer(Extension(project_id))
+                        .layer(ScopedLayer::new(vec![Scope::SecretWrite])),
+                ),
+            )
+            .route(
+                "/projects/:project_name/services/:service_name/logs",
+                get(get_service_logs.layer(ScopedLayer::new(vec![Scope::Logs]))),
+            )
+            .route(
+                "/projects/:project_name/services/:service_name/secrets/unset",
+                post(
+                    unset_secrets
+                        .layer(Extension(project_id))
+                        .layer(ScopedLayer::new(vec![Scope::SecretWrite])),
+                ),
+            )
             .route(
                 "/projects/:project_name/deployments",
                 get(get_deployments).layer(ScopedLayer::new(vec![Scope::Service])),
             )
+            .route(
+                "/projects/:project_name/executables",
+                get(get_executables).layer(ScopedLayer::new(vec![Scope::Service])),
+            )
             .route(
                 "/projects/:project_name/deployments/:deployment_id",
                 get(get_deployment.layer(ScopedLayer::new(vec![Scope::Deployment])))
@@ -119,6 +170,19 @@ impl RouterBuilder {
                 "/projects/:project_name/deployments/:deployment_id/logs",
                 get(get_logs.layer(ScopedLayer::new(vec![Scope::Logs]))),
             )
+            .route(
+                "/projects/:project_name/logs",
+                delete(delete_project_logs.layer(ScopedLayer::new(vec![Scope::ProjectWrite]))),
+            )
+            .route(
+                "/projects/:project_name/drains",
+                get(get_drains.layer(ScopedLayer::new(vec![Scope::Logs])))
+                    .post(add_drain.layer(ScopedLayer::new(vec![Scope::ProjectWrite]))),
+            )
+            .route(
+                "/projects/:project_name/drains/:drain_id",
+                delete(remove_drain.layer(ScopedLayer::new(vec![Scope::ProjectWrite]))),
+            )
             .route(
                 "/projects/:project_name/clean",
                 post(clean_project.layer(ScopedLayer::new(vec![Scope::DeploymentPush]))),
@@ -259,6 +323,7 @@ pub async fn delete_service_resource(
         String,
         String,
     )>,
+    Query(resource::DeleteQuery { name }): Query<resource::DeleteQuery>,
 ) -> Result<Json<()>> {
     let service = persistence
         .get_service_by_name(&service_name)
@@ -275,7 +340,7 @@ pub async fn delete_service_resource(
         })?;
 
     let get_resource_response = persistence
-        .get_resource(&service.id, r#type, claim.clone())
+        .get_resource(&service.id, r#type, name.clone(), claim.clone())
         .await?;
 
     if get_resource_response.resource.is_none() {
@@ -283,7 +348,7 @@ pub async fn delete_service_resource(
     }
 
     let delete_resource_response = persistence
-        .delete_resource(project_name, &service.id, r#type, claim)
+        .delete_resource(project_name, &service.id, r#type, name, claim)
         .await?;
 
     if !delete_resource_response.success {
@@ -293,6 +358,478 @@ pub async fn delete_service_resource(
     Ok(Json(()))
 }
 
+/// Get a database of a service, along with the service
+async fn get_service_database(
+    persistence: &mut Persistence,
+    service_name: &str,
+    resource_type: &str,
+    name: Option<String>,
+    claim: Claim,
+) -> Result<(Service, shuttle_common::database::Type)> {
+    let service = persistence
+        .get_service_by_name(service_name)
+        .await?
+        .ok_or_else(|| Error::NotFound("service not found".to_string()))?;
+
+    let r#type = shuttle_common::resource::Type::from_str(resource_type).map_err(|err| {
+        error::Error::Convert {
+            from: "str".to_string(),
+            to: "shuttle_common::resource::Type".to_string(),
+            message: format!("Not a valid resource type representation: {}", err).to_string(),
+        }
+    })?;
+
+    let shuttle_common::resource::Type::Database(db_type) = r#type else {
+        return Err(Error::BadRequest("resource is not a database".to_string()));
+    };
+
+    let get_resource_response = persistence
+        .get_resource(&service.id, r#type, name, claim)
+        .await?;
+
+    if get_resource_response.resource.is_none() {
+        return Err(Error::NotFound("resource not found".to_string()));
+    }
+
+    Ok((service, db_type))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, shuttle.service.name = %service_name))]
+pub async fn backup_service_resource(
+    Extension(mut persistence): Extension<Persistence>,
+    Extension(claim): Extension<Claim>,
+    CustomErrorPath((project_name, service_name, resource_type)): CustomErrorPath<(
+        String,
+        String,
+        String,
+    )>,
+    Query(resource::BackupQuery { name }): Query<resource::BackupQuery>,
+) -> Result<Json<resource::Backup>> {
+    let (_, db_type) = get_service_database(
+        &mut persistence,
+        &service_name,
+        &resource_type,
+        name.clone(),
+        claim.clone(),
+    )
+    .await?;
+
+    let backup = persistence
+        .backup_database(project_name, db_type, name, claim)
+        .await
+        .map_err(provisioner_error)?;
+
+    Ok(Json(backup_response(backup)))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, shuttle.service.name = %service_name))]
+pub async fn list_service_resource_backups(
+    Extension(mut persistence): Extension<Persistence>,
+    Extension(claim): Extension<Claim>,
+    CustomErrorPath((project_name, service_name, resource_type)): CustomErrorPath<(
+        String,
+        String,
+        String,
+    )>,
+    Query(resource::BackupQuery { name }): Query<resource::BackupQuery>,
+) -> Result<Json<Vec<resource::Backup>>> {
+    let (_, db_type) = get_service_database(
+        &mut persistence,
+        &service_name,
+        &resource_type,
+        name.clone(),
+        claim.clone(),
+    )
+    .await?;
+
+    let backups = persistence
+        .list_backups(project_name, db_type, name, claim)
+        .await
+        .map_err(provisioner_error)?
+        .backups
+        .into_iter()
+        .map(backup_response)
+        .collect();
+
+    Ok(Json(backups))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, shuttle.service.name = %service_name))]
+pub async fn restore_service_resource(
+    Extension(mut persistence): Extension<Persistence>,
+    Extension(claim): Extension<Claim>,
+    CustomErrorPath((project_name, service_name, resource_type, backup_id)): CustomErrorPath<(
+        String,
+        String,
+        String,
+        String,
+    )>,
+    Query(resource::RestoreQuery { name, into }): Query<resource::RestoreQuery>,
+) -> Result<Json<()>> {
+    let (_, db_type) = get_service_database(
+        &mut persistence,
+        &service_name,
+        &resource_type,
+        name.clone(),
+        claim.clone(),
+    )
+    .await?;
+
+    persistence
+        .restore_database(project_name, db_type, name, backup_id, into, claim)
+        .await
+        .map_err(provisioner_error)?;
+
+    Ok(Json(()))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, shuttle.service.name = %service_name))]
+pub async fn rotate_service_resource(
+    Extension(mut persistence): Extension<Persistence>,
+    Extension(deployment_manager): Extension<DeploymentManager>,
+    Extension(claim): Extension<Claim>,
+    Extension(project_id): Extension<Ulid>,
+    CustomErrorPath((project_name, service_name, resource_type)): CustomErrorPath<(
+        String,
+        String,
+        String,
+    )>,
+    Query(resource::RotateQuery { name }): Query<resource::RotateQuery>,
+) -> Result<Json<resource::RotateResponse>> {
+    let (_, db_type) = get_service_database(
+        &mut persistence,
+        &service_name,
+        &resource_type,
+        name.clone(),
+        claim.clone(),
+    )
+    .await?;
+    let r#type = shuttle_common::resource::Type::Database(db_type);
+
+    // Workspace services share the databases of their project, so all of them need the new
+    // credentials
+    let mut services = Vec::new();
+    for service in persistence.get_all_services().await? {
+        let get_resource_response = persistence
+            .get_resource(&service.id, r#type, name.clone(), claim.clone())
+            .await?;
+
+        if get_resource_response.resource.is_some() {
+            services.push(service);
+        }
+    }
+
+    // The recorded outputs hold the old credentials, so make the next loads ask for new ones.
+    // This happens first so that a failed rotation only has them provisioned again.
+    for service in &services {
+        let invalidate_response = persistence
+            .invalidate_resource(&service.id, r#type, name.clone(), claim.clone())
+            .await?;
+
+        if !invalidate_response.success {
+            return Err(anyhow!("Unable to invalidate resource in resource recorder").into());
+        }
+    }
+
+    let response = persistence
+        .rotate_database_credentials(project_name.clone(), db_type, name, claim.clone())
+        .await
+        .map_err(provisioner_error)?;
+    let old_expires_at = from_timestamp(response.old_expires_at.unwrap_or_default());
+
+    info!("database credentials were rotated");
+
+    for service in &services {
+        if let Err(error) = restart_active_deployment(
+            &persistence,
+            &deployment_manager,
+            service,
+            project_name.clone(),
+            project_id,
+            claim.clone(),
+        )
+        .await
+        {
+            return Err(anyhow!(
+                "the credentials were rotated, but restarting {} failed: {error}. Redeploy it before the old credentials expire at {old_expires_at}",
+                service.name
+            )
+            .into());
+        }
+    }
+
+    Ok(Json(resource::RotateResponse { old_expires_at }))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, shuttle.service.name = %service_name))]
+pub async fn get_secrets(
+    Extension(mut persistence): Extension<Persistence>,
+    Extension(claim): Extension<Claim>,
+    CustomErrorPath((project_name, service_name)): CustomErrorPath<(String, String)>,
+    Query(secret::ListQuery { show_values }): Query<secret::ListQuery>,
+) -> Result<Json<Vec<secret::Response>>> {
+    let service = persistence
+        .get_service_by_name(&service_name)
+        .await?
+        .ok_or_else(|| Error::NotFound("service not found".to_string()))?;
+
+    let secrets = get_secret_store(&mut persistence, &service.id, claim)
+        .await?
+        .into_iter()
+        .map(|(key, value)| secret::Response {
+            key,
+            value: if show_values {
+                value
+            } else {
+                secret::MASKED_VALUE.to_string()
+            },
+        })
+        .collect();
+
+    Ok(Json(secrets))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, shuttle.service.name = %service_name))]
+pub async fn set_secrets(
+    Extension(mut persistence): Extension<Persistence>,
+    Extension(deployment_manager): Extension<DeploymentManager>,
+    Extension(claim): Extension<Claim>,
+    Extension(project_id): Extension<Ulid>,
+    CustomErrorPath((project_name, service_name)): CustomErrorPath<(String, String)>,
+    Json(secret::SetRequest { secrets, restart }): Json<secret::SetRequest>,
+) -> Result<Json<Vec<secret::Response>>> {
+    let service = persistence
+        .get_service_by_name(&service_name)
+        .await?
+        .ok_or_else(|| Error::NotFound("service not found".to_string()))?;
+
+    let keys = store_secrets(&mut persistence, &service.id, secrets, claim.clone()).await?;
+    info!(?keys, "secrets were set");
+
+    if restart {
+        restart_active_deployment(
+            &persistence,
+            &deployment_manager,
+            &service,
+            project_name,
+            project_id,
+            claim,
+        )
+        .await?;
+    }
+
+    let secrets = keys
+        .into_iter()
+        .map(|key| secret::Response {
+            key,
+            value: secret::MASKED_VALUE.to_string(),
+        })
+        .collect();
+
+    Ok(Json(secrets))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, shuttle.service.name = %service_name))]
+pub async fn unset_secrets(
+    Extension(mut persistence): Extension<Persistence>,
+    Extension(deployment_manager): Extension<DeploymentManager>,
+    Extension(claim): Extension<Claim>,
+    Extension(project_id): Extension<Ulid>,
+    CustomErrorPath((project_name, service_name)): CustomErrorPath<(String, String)>,
+    Json(secret::UnsetRequest { keys, restart }): Json<secret::UnsetRequest>,
+) -> Result<Json<()>> {
+    let service = persistence
+        .get_service_by_name(&service_name)
+        .await?
+        .ok_or_else(|| Error::NotFound("service not found".to_string()))?;
+
+    remove_secrets(&mut persistence, &service.id, &keys, claim.clone()).await?;
+    info!(?keys, "secrets were unset");
+
+    if restart {
+        restart_active_deployment(
+            &persistence,
+            &deployment_manager,
+            &service,
+            project_name,
+            project_id,
+            claim,
+        )
+        .await?;
+    }
+
+    Ok(Json(()))
+}
+
+/// Add or overwrite secrets of a service, keeping its other secrets. Gives the keys that were set.
+async fn store_secrets(
+    persistence: &mut Persistence,
+    service_id: &Ulid,
+    secrets: BTreeMap<String, String>,
+    claim: Claim,
+) -> Result<Vec<String>> {
+    let mut stored: BTreeMap<String, String> =
+        get_secret_store(persistence, service_id, claim.clone())
+            .await?
+            .into_iter()
+            .collect();
+    let keys = secrets.keys().cloned().collect();
+    stored.extend(secrets);
+
+    record_secret_store(persistence, service_id, stored, claim).await?;
+
+    Ok(keys)
+}
+
+/// Remove secrets of a service in one update, failing without removing any when one is not set
+async fn remove_secrets(
+    persistence: &mut Persistence,
+    service_id: &Ulid,
+    keys: &[String],
+    claim: Claim,
+) -> Result<()> {
+    let mut stored: BTreeMap<String, String> =
+        get_secret_store(persistence, service_id, claim.clone())
+            .await?
+            .into_iter()
+            .collect();
+
+    for key in keys {
+        if stored.remove(key).is_none() {
+            return Err(Error::NotFound(format!("secret not found: {key}")));
+        }
+    }
+
+    record_secret_store(persistence, service_id, stored, claim).await
+}
+
+/// Get the secrets stored for a service, which live in its secrets resource
+async fn get_secret_store(
+    persistence: &mut Persistence,
+    service_id: &Ulid,
+    claim: Claim,
+) -> Result<SecretStore> {
+    let response = persistence
+        .get_resource(
+            service_id,
+            shuttle_common::resource::Type::Secrets,
+            None,
+            claim,
+        )
+        .await?;
+
+    let Some(resource) = response.resource else {
+        return Ok(SecretStore::new(Default::default()));
+    };
+
+    let resource = shuttle_common::resource::Response::try_from(resource)?;
+
+    serde_json::from_value(resource.data).map_err(|err| Error::Convert {
+        from: "serde_json::Value".to_string(),
+        to: "SecretStore".to_string(),
+        message: err.to_string(),
+    })
+}
+
+/// Overwrite the secrets resource of a service. The next load of the service picks these up.
+async fn record_secret_store(
+    persistence: &mut Persistence,
+    service_id: &Ulid,
+    secrets: BTreeMap<String, String>,
+    claim: Claim,
+) -> Result<()> {
+    let store = SecretStore::new(
+        secrets
+            .into_iter()
+            .map(|(key, value)| (key, Secret::new(value)))
+            .collect(),
+    );
+    let data = serde_json::to_vec(&store).map_err(|err| Error::Convert {
+        from: "SecretStore".to_string(),
+        to: "serde_json::Value".to_string(),
+        message: err.to_string(),
+    })?;
+
+    let response = persistence
+        .insert_resources(
+            vec![record_request::Resource {
+                r#type: shuttle_common::resource::Type::Secrets.to_string(),
+                config: serde_json::Value::Null.to_string().into_bytes(),
+                data,
+                name: String::new(),
+            }],
+            service_id,
+            claim,
+        )
+        .await?;
+
+    if !response.success {
+        return Err(anyhow!("Unable to record secrets in resource recorder").into());
+    }
+
+    Ok(())
+}
+
+/// How long a killed deployment gets to record that it stopped before it is started again
+const STOPPED_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Stop the running deployment of a service and start it again, so that it is loaded with the
+/// latest resources
+async fn restart_active_deployment(
+    persistence: &Persistence,
+    deployment_manager: &DeploymentManager,
+    service: &Service,
+    project_name: String,
+    project_id: Ulid,
+    claim: Claim,
+) -> Result<()> {
+    let Some(deployment) = persistence.get_active_deployment(&service.id).await? else {
+        info!("no running deployment to restart");
+        return Ok(());
+    };
+
+    let run_config = persistence.get_run_config(&deployment.id).await?;
+
+    deployment_manager.kill(deployment.id).await;
+    // The old run records the stop once it sees it, which would otherwise land on the new run
+    wait_until_stopped(persistence, &deployment.id, STOPPED_TIMEOUT).await?;
+    deployment_manager
+        .run_push(Built {
+            id: deployment.id,
+            service_name: service.name.clone(),
+            project_name,
+            service_id: service.id,
+            project_id,
+            tracing_context: Default::default(),
+            is_next: deployment.is_next,
+            claim,
+            secrets: Default::default(),
+            run_config,
+            restarts: 0,
+        })
+        .await;
+
+    Ok(())
+}
+
+/// Wait for a deployment to no longer be recorded as running, failing after `timeout`
+async fn wait_until_stopped(persistence: &Persistence, id: &Uuid, timeout: Duration) -> Result<()> {
+    let stopped = async {
+        loop {
+            let state = persistence.get_deployment(id).await?.map(|d| d.state);
+            if !matches!(state, Some(State::Running | State::Unhealthy)) {
+                return Ok::<_, Error>(());
+            }
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    };
+
+    tokio::time::timeout(timeout, stopped)
+        .await
+        .map_err(|_| anyhow!("deployment {id} did not stop within {timeout:?}"))?
+}
+
 #[instrument(skip_all, fields(shuttle.project.name = %project_name, shuttle.service.name = %service_name))]
 pub async fn create_service(
     Extension(persistence): Extension<Persistence>,
@@ -339,12 +876,30 @@ pub async fn create_service(
             .git_branch
             .map(|s| s.chars().take(GIT_STRINGS_MAX_LENGTH).collect()),
         git_dirty: deployment_req.git_dirty,
+        parent_id: None,
     };
 
     persistence.insert_deployment(&deployment).await?;
+
+    // Logs are kept for as long as the tier of the account allows, and go to the project's drains
+    let mut tier_request = tonic::Request::new(DeploymentTierRequest {
+        deployment_id: id.to_string(),
+        project_name: project_name.clone(),
+    });
+    tier_request.extensions_mut().insert(claim.clone());
+    if let Err(error) = deployment_manager
+        .logs_fetcher()
+        .clone()
+        .set_deployment_tier(tier_request)
+        .await
+    {
+        warn!(error = %error, "failed to set the retention tier of the deployment logs");
+    }
+
     let queued = Queued {
         id: deployment.id,
         service_name: service.name,
+        project_name,
         service_id: deployment.service_id,
         project_id: pid,
         data: deployment_req.data,
@@ -373,6 +928,7 @@ pub async fn stop_service(
         return Err(Error::NotFound("no running deployment found".to_string()));
     };
     deployment_manager.kill(deployment.id).await;
+    kill_child_deployments(&persistence, &deployment_manager, &deployment.id).await?;
 
     let response = shuttle_common::models::service::Summary {
         name: service.name,
@@ -383,6 +939,21 @@ pub async fn stop_service(
     Ok(Json(response))
 }
 
+/// Stop the other services that were deployed from the same workspace
+async fn kill_child_deployments(
+    persistence: &Persistence,
+    deployment_manager: &DeploymentManager,
+    parent_id: &Uuid,
+) -> Result<()> {
+    for child in persistence.get_child_deployments(parent_id).await? {
+        if matches!(child.state, State::Running | State::Unhealthy) {
+            deployment_manager.kill(child.id).await;
+        }
+    }
+
+    Ok(())
+}
+
 #[instrument(skip_all, fields(shuttle.project.name = %project_name, page, limit))]
 pub async fn get_deployments(
     Extension(persistence): Extension<Persistence>,
@@ -425,6 +996,7 @@ pub async fn delete_deployment(
 ) -> Result<Json<shuttle_common::models::deployment::Response>> {
     if let Some(deployment) = persistence.get_deployment(&deployment_id).await? {
         deployment_manager.kill(deployment.id).await;
+        kill_child_deployments(&persistence, &deployment_manager, &deployment.id).await?;
 
         Ok(Json(deployment.into()))
     } else {
@@ -439,35 +1011,87 @@ pub async fn start_deployment(
     Extension(claim): Extension<Claim>,
     Extension(project_id): Extension<Ulid>,
     CustomErrorPath((project_name, deployment_id)): CustomErrorPath<(String, Uuid)>,
-) -> Result<()> {
+) -> Result<Json<shuttle_common::models::deployment::Response>> {
     if let Some(deployment) = persistence.get_runnable_deployment(&deployment_id).await? {
-        let built = Built {
-            id: deployment.id,
-            service_name: deployment.service_name,
-            service_id: deployment.service_id,
-            project_id,
-            tracing_context: Default::default(),
-            is_next: deployment.is_next,
-            claim,
-            secrets: Default::default(),
-        };
-        deployment_manager.run_push(built).await;
+        if !deployment_manager
+            .executable_path(&project_name, &deployment.id)
+            .exists()
+        {
+            return Err(Error::NotFound(format!(
+                "the executable of deployment {deployment_id} has been cleaned up and is no longer available"
+            )));
+        }
 
-        Ok(())
+        // Services deployed from the same workspace are started together
+        let mut deployments = vec![deployment];
+        for child in persistence.get_child_deployments(&deployment_id).await? {
+            if let Some(child) = persistence.get_runnable_deployment(&child.id).await? {
+                if deployment_manager
+                    .executable_path(&project_name, &child.id)
+                    .exists()
+                {
+                    deployments.push(child);
+                } else {
+                    warn!(
+                        deployment_id = %child.id,
+                        "not starting {} since its executable is no longer available",
+                        child.service_name
+                    );
+                }
+            }
+        }
+
+        for deployment in deployments {
+            let run_config = persistence.get_run_config(&deployment.id).await?;
+            let built = Built {
+                id: deployment.id,
+                service_name: deployment.service_name,
+                project_name: project_name.clone(),
+                service_id: deployment.service_id,
+                project_id,
+                tracing_context: Default::default(),
+                is_next: deployment.is_next,
+                claim: claim.clone(),
+                secrets: Default::default(),
+                run_config,
+                restarts: 0,
+            };
+            deployment_manager.run_push(built).await;
+        }
+
+        let deployment = persistence
+            .get_deployment(&deployment_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("deployment not found".to_string()))?;
+
+        Ok(Json(deployment.into()))
     } else {
         Err(Error::NotFound("deployment not found".to_string()))
     }
 }
 
+#[instrument(skip_all, fields(shuttle.project.name = %project_name))]
+pub async fn get_executables(
+    Extension(deployment_manager): Extension<DeploymentManager>,
+    CustomErrorPath(project_name): CustomErrorPath<String>,
+) -> Result<Json<Vec<Uuid>>> {
+    let ids = deployment_manager
+        .available_executables(&project_name)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    Ok(Json(ids))
+}
+
 #[instrument(skip_all, fields(shuttle.project.name = %project_name, %deployment_id))]
 pub async fn get_logs(
     Extension(deployment_manager): Extension<DeploymentManager>,
     Extension(claim): Extension<Claim>,
     CustomErrorPath((project_name, deployment_id)): CustomErrorPath<(String, Uuid)>,
+    Query(query): Query<LogsQuery>,
 ) -> Result<Json<Vec<LogItem>>> {
-    let mut logs_request: tonic::Request<LogsRequest> = tonic::Request::new(LogsRequest {
-        deployment_id: deployment_id.to_string(),
-    });
+    let mut logs_request: tonic::Request<LogsRequest> =
+        tonic::Request::new(logs_request(deployment_id, query));
 
     logs_request.extensions_mut().insert(claim);
 
@@ -481,34 +1105,277 @@ pub async fn get_logs(
                 .map(|l| l.to_log_item_with_id(deployment_id))
                 .collect(),
         )),
+        Err(status) => Err(logs_error(status, "failed to retrieve logs for deployment")),
+    }
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, shuttle.service.name = %service_name))]
+pub async fn get_service_logs(
+    Extension(persistence): Extension<Persistence>,
+    Extension(deployment_manager): Extension<DeploymentManager>,
+    Extension(claim): Extension<Claim>,
+    CustomErrorPath((project_name, service_name)): CustomErrorPath<(String, String)>,
+    Query(query): Query<LogsQuery>,
+) -> Result<Json<ServiceLogsResponse>> {
+    let service = persistence
+        .get_service_by_name(&service_name)
+        .await?
+        .ok_or_else(|| Error::NotFound("service not found".to_string()))?;
+    let deployment_ids = persistence
+        .get_deployments(&service.id, 0, u32::MAX)
+        .await?
+        .into_iter()
+        .map(|deployment| deployment.id.to_string())
+        .collect();
+
+    let mut logs_request: tonic::Request<ServiceLogsRequest> =
+        tonic::Request::new(ServiceLogsRequest {
+            deployment_ids,
+            level: query
+                .level
+                .map(|level| level.to_string())
+                .unwrap_or_default(),
+            target: query.target.unwrap_or_default(),
+            since: query.since.map(to_timestamp),
+            until: query.until.map(to_timestamp),
+            search: query.search.unwrap_or_default(),
+            limit: query.limit.unwrap_or_default(),
+            cursor: query.cursor.unwrap_or_default(),
+        });
+
+    logs_request.extensions_mut().insert(claim);
+
+    let mut client = deployment_manager.logs_fetcher().clone();
+
+    match client.get_service_logs(logs_request).await {
+        Ok(response) => {
+            let response = response.into_inner();
+
+            Ok(Json(ServiceLogsResponse {
+                logs: response.log_items.into_iter().map(Into::into).collect(),
+                next_cursor: (!response.next_cursor.is_empty()).then_some(response.next_cursor),
+            }))
+        }
+        Err(status) => Err(logs_error(status, "failed to retrieve logs for service")),
+    }
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name))]
+pub async fn delete_project_logs(
+    Extension(persistence): Extension<Persistence>,
+    Extension(deployment_manager): Extension<DeploymentManager>,
+    Extension(claim): Extension<Claim>,
+    CustomErrorPath(project_name): CustomErrorPath<String>,
+) -> Result<Json<u64>> {
+    let deployment_ids = persistence
+        .get_all_deployment_ids()
+        .await?
+        .into_iter()
+        .map(|id| id.to_string())
+        .collect();
+
+    let mut logs_request = tonic::Request::new(DeleteLogsRequest { deployment_ids });
+    logs_request.extensions_mut().insert(claim);
+
+    let mut client = deployment_manager.logs_fetcher().clone();
+
+    match client.delete_logs(logs_request).await {
+        Ok(response) => Ok(Json(response.into_inner().deleted)),
         Err(error) => {
-            error!(error = %error, "failed to retrieve logs for deployment");
-            Err(anyhow!("failed to retrieve logs for deployment").into())
+            error!(error = %error, "failed to delete logs of project");
+            Err(anyhow!("failed to delete logs of project").into())
+        }
+    }
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name))]
+pub async fn get_drains(
+    Extension(deployment_manager): Extension<DeploymentManager>,
+    Extension(claim): Extension<Claim>,
+    CustomErrorPath(project_name): CustomErrorPath<String>,
+) -> Result<Json<Vec<DrainResponse>>> {
+    let mut drains_request = tonic::Request::new(DrainsRequest { project_name });
+    drains_request.extensions_mut().insert(claim);
+
+    let mut client = deployment_manager.logs_fetcher().clone();
+    let drains = client
+        .get_drains(drains_request)
+        .await
+        .map_err(drain_error)?
+        .into_inner()
+        .drains
+        .into_iter()
+        .map(drain_response)
+        .collect::<Result<_>>()?;
+
+    Ok(Json(drains))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, kind = %request.kind))]
+pub async fn add_drain(
+    Extension(persistence): Extension<Persistence>,
+    Extension(deployment_manager): Extension<DeploymentManager>,
+    Extension(claim): Extension<Claim>,
+    CustomErrorPath(project_name): CustomErrorPath<String>,
+    Json(request): Json<DrainRequest>,
+) -> Result<Json<DrainResponse>> {
+    let deployment_ids = persistence
+        .get_all_deployment_ids()
+        .await?
+        .into_iter()
+        .map(|id| id.to_string())
+        .collect();
+
+    let mut drain_request = tonic::Request::new(AddDrainRequest {
+        project_name,
+        kind: request.kind.to_string(),
+        url: request.url,
+        deployment_ids,
+    });
+    drain_request.extensions_mut().insert(claim);
+
+    let mut client = deployment_manager.logs_fetcher().clone();
+    let drain = client
+        .add_drain(drain_request)
+        .await
+        .map_err(drain_error)?
+        .into_inner();
+
+    Ok(Json(drain_response(drain)?))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, %drain_id))]
+pub async fn remove_drain(
+    Extension(deployment_manager): Extension<DeploymentManager>,
+    Extension(claim): Extension<Claim>,
+    CustomErrorPath((project_name, drain_id)): CustomErrorPath<(String, i64)>,
+) -> Result<()> {
+    let mut drain_request = tonic::Request::new(RemoveDrainRequest {
+        project_name,
+        id: drain_id,
+    });
+    drain_request.extensions_mut().insert(claim);
+
+    let mut client = deployment_manager.logs_fetcher().clone();
+    client
+        .remove_drain(drain_request)
+        .await
+        .map_err(drain_error)?;
+
+    Ok(())
+}
+
+fn drain_response(drain: logger::Drain) -> Result<DrainResponse> {
+    Ok(DrainResponse {
+        id: drain.id,
+        kind: drain
+            .kind
+            .parse()
+            .map_err(|_| anyhow!("logger returned an unknown drain kind: {}", drain.kind))?,
+        url: drain.url,
+    })
+}
+
+/// The logger rejects filters it cannot parse, which is passed on to the user with its reason
+fn logs_error(status: tonic::Status, message: &'static str) -> Error {
+    match status.code() {
+        tonic::Code::InvalidArgument => Error::BadRequest(status.message().to_string()),
+        _ => {
+            error!(error = %status, "{message}");
+            anyhow!(message).into()
         }
     }
 }
 
+fn drain_error(status: tonic::Status) -> Error {
+    match status.code() {
+        tonic::Code::InvalidArgument => Error::BadRequest(status.message().to_string()),
+        tonic::Code::NotFound => Error::NotFound("drain not found".to_string()),
+        _ => {
+            error!(error = %status, "failed to manage the drains of project");
+            anyhow!("failed to manage the drains of project").into()
+        }
+    }
+}
+
+fn backup_response(backup: provisioner::Backup) -> resource::Backup {
+    resource::Backup {
+        id: backup.id,
+        created_at: from_timestamp(backup.created_at.unwrap_or_default()),
+        size: backup.size,
+    }
+}
+
+fn provisioner_error(error: PersistenceError) -> Error {
+    match error {
+        PersistenceError::Provisioner(status)
+            if matches!(
+                status.code(),
+                tonic::Code::InvalidArgument | tonic::Code::NotFound | tonic::Code::AlreadyExists
+            ) =>
+        {
+            Error::BadRequest(status.message().to_string())
+        }
+        error => error.into(),
+    }
+}
+
+fn to_timestamp(time: chrono::DateTime<Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn from_timestamp(timestamp: prost_types::Timestamp) -> chrono::DateTime<Utc> {
+    Utc.from_utc_datetime(
+        &NaiveDateTime::from_timestamp_opt(
+            timestamp.seconds,
+            timestamp.nanos.try_into().unwrap_or_default(),
+        )
+        .unwrap_or_default(),
+    )
+}
+
+fn logs_request(deployment_id: Uuid, query: LogsQuery) -> LogsRequest {
+    LogsRequest {
+        deployment_id: deployment_id.to_string(),
+        level: query
+            .level
+            .map(|level| level.to_string())
+            .unwrap_or_default(),
+        target: query.target.unwrap_or_default(),
+        since: query.since.map(to_timestamp),
+        until: query.until.map(to_timestamp),
+        search: query.search.unwrap_or_default(),
+        limit: query.limit.unwrap_or_default(),
+        offset: query.offset.unwrap_or_default(),
+    }
+}
+
 // don't instrument id to prevent it from showing up in deployment log
 #[instrument(skip_all, fields(shuttle.project.name = %project_name))]
 pub async fn get_logs_subscribe(
     Extension(deployment_manager): Extension<DeploymentManager>,
     Extension(claim): Extension<Claim>,
     CustomErrorPath((project_name, deployment_id)): CustomErrorPath<(String, Uuid)>,
+    Query(query): Query<LogsQuery>,
     ws_upgrade: ws::WebSocketUpgrade,
 ) -> axum::response::Response {
-    ws_upgrade
-        .on_upgrade(move |s| logs_websocket_handler(s, deployment_manager, deployment_id, claim))
+    ws_upgrade.on_upgrade(move |s| {
+        logs_websocket_handler(s, deployment_manager, deployment_id, query, claim)
+    })
 }
 
 async fn logs_websocket_handler(
     mut s: WebSocket,
     deployment_manager: DeploymentManager,
     deployment_id: Uuid,
+    query: LogsQuery,
     claim: Claim,
 ) {
-    let mut logs_request: tonic::Request<LogsRequest> = tonic::Request::new(LogsRequest {
-        deployment_id: deployment_id.to_string(),
-    });
+    let mut logs_request: tonic::Request<LogsRequest> =
+        tonic::Request::new(logs_request(deployment_id, query));
 
     logs_request.extensions_mut().insert(claim);
 
@@ -617,3 +1484,311 @@ where
         Ok(Self(t))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use shuttle_common::backends::client::gateway;
+    use shuttle_common::claims::AccountTier;
+    use shuttle_common_tests::logger::{get_mocked_logger_client, MockedLogger};
+    use shuttle_proto::logger::Batcher;
+    use shuttle_proto::test_utils::resource_recorder::get_mocked_resource_recorder;
+
+    use super::*;
+    use crate::RuntimeManager;
+
+    async fn get_persistence() -> Persistence {
+        let port = get_mocked_resource_recorder().await;
+        let (persistence, _) = Persistence::new_in_memory().await;
+
+        persistence
+            .with_resource_recorder(format!("http://localhost:{port}").parse().unwrap())
+            .await
+    }
+
+    async fn get_deployment_manager(
+        persistence: &Persistence,
+        artifacts_path: &Path,
+    ) -> DeploymentManager {
+        let logger_client = get_mocked_logger_client(MockedLogger).await;
+        let gateway_uri: Uri = "http://localhost:8001".parse().unwrap();
+
+        DeploymentManager::builder()
+            .build_log_recorder(Batcher::wrap(logger_client.clone()))
+            .active_deployment_getter(persistence.clone())
+            .artifacts_path(artifacts_path.to_path_buf())
+            .runtime(RuntimeManager::new(
+                "http://localhost:5000".to_string(),
+                Batcher::wrap(logger_client.clone()),
+                None,
+                None,
+            ))
+            .deployment_updater(persistence.clone())
+            .resource_manager(persistence.clone())
+            .queue_client(gateway::Client::new(gateway_uri.clone(), gateway_uri))
+            .log_fetcher(logger_client)
+            .build()
+    }
+
+    /// Insert a stopped deployment of `service_id`, optionally with a built executable to start
+    async fn insert_stopped_deployment(
+        persistence: &Persistence,
+        deployment_manager: &DeploymentManager,
+        service_id: Ulid,
+        with_executable: bool,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        persistence
+            .insert_deployment(&Deployment {
+                id,
+                service_id,
+                state: State::Stopped,
+                last_update: Utc::now(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        if with_executable {
+            let path = deployment_manager.executable_path("rollback", &id);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        id
+    }
+
+    fn get_claim() -> Claim {
+        Claim::new(
+            "test-user".to_string(),
+            AccountTier::Basic.into(),
+            AccountTier::Basic,
+            AccountTier::Basic,
+        )
+    }
+
+    async fn get_stored(persistence: &mut Persistence, service_id: &Ulid) -> Vec<(String, String)> {
+        get_secret_store(persistence, service_id, get_claim())
+            .await
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    fn secrets(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn set_secrets_keeps_other_secrets() {
+        let mut persistence = get_persistence().await;
+        let service = persistence.get_or_create_service("secrets").await.unwrap();
+
+        let keys = store_secrets(
+            &mut persistence,
+            &service.id,
+            secrets(&[("API_KEY", "first"), ("DB_URL", "postgres://")]),
+            get_claim(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(keys, vec!["API_KEY", "DB_URL"]);
+
+        let keys = store_secrets(
+            &mut persistence,
+            &service.id,
+            secrets(&[("API_KEY", "second"), ("TOKEN", "abc")]),
+            get_claim(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(keys, vec!["API_KEY", "TOKEN"]);
+
+        assert_eq!(
+            get_stored(&mut persistence, &service.id).await,
+            vec![
+                ("API_KEY".to_string(), "second".to_string()),
+                ("DB_URL".to_string(), "postgres://".to_string()),
+                ("TOKEN".to_string(), "abc".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unset_secrets_removes_all_or_nothing() {
+        let mut persistence = get_persistence().await;
+        let service = persistence.get_or_create_service("secrets").await.unwrap();
+
+        store_secrets(
+            &mut persistence,
+            &service.id,
+            secrets(&[
+                ("API_KEY", "key"),
+                ("DB_URL", "postgres://"),
+                ("TOKEN", "abc"),
+            ]),
+            get_claim(),
+        )
+        .await
+        .unwrap();
+
+        // Nothing is removed when one of the keys is not set
+        let error = remove_secrets(
+            &mut persistence,
+            &service.id,
+            &["API_KEY".to_string(), "MISSING".to_string()],
+            get_claim(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, Error::NotFound(_)));
+        assert_eq!(get_stored(&mut persistence, &service.id).await.len(), 3);
+
+        remove_secrets(
+            &mut persistence,
+            &service.id,
+            &["API_KEY".to_string(), "TOKEN".to_string()],
+            get_claim(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            get_stored(&mut persistence, &service.id).await,
+            vec![("DB_URL".to_string(), "postgres://".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn restarts_wait_for_the_old_run_to_stop() {
+        let persistence = get_persistence().await;
+        let service = persistence.get_or_create_service("restart").await.unwrap();
+        let id = Uuid::new_v4();
+        persistence
+            .insert_deployment(&Deployment {
+                id,
+                service_id: service.id,
+                state: State::Running,
+                last_update: Utc::now(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let result = wait_until_stopped(&persistence, &id, Duration::from_millis(300)).await;
+        assert!(
+            result.is_err(),
+            "a deployment which keeps running should time out"
+        );
+
+        let runnable = persistence
+            .get_runnable_deployment(&id)
+            .await
+            .unwrap()
+            .unwrap();
+        let stopper = persistence.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            stopper.stop_running_deployment(runnable).await.unwrap();
+        });
+
+        wait_until_stopped(&persistence, &id, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(
+            persistence
+                .get_deployment(&id)
+                .await
+                .unwrap()
+                .unwrap()
+                .state,
+            State::Stopped
+        );
+    }
+
+    #[tokio::test]
+    async fn get_executables_without_builds() {
+        let persistence = get_persistence().await;
+        let artifacts = tempfile::tempdir().unwrap();
+        let deployment_manager = get_deployment_manager(&persistence, artifacts.path()).await;
+
+        // Nothing has been built for the project yet, so its executables directory is missing
+        let Json(ids) = get_executables(
+            Extension(deployment_manager.clone()),
+            CustomErrorPath("rollback".to_string()),
+        )
+        .await
+        .unwrap();
+        assert!(ids.is_empty());
+
+        let service = persistence.get_or_create_service("rollback").await.unwrap();
+        let built =
+            insert_stopped_deployment(&persistence, &deployment_manager, service.id, true).await;
+        insert_stopped_deployment(&persistence, &deployment_manager, service.id, false).await;
+
+        let Json(ids) = get_executables(
+            Extension(deployment_manager),
+            CustomErrorPath("rollback".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids, vec![built]);
+    }
+
+    #[tokio::test]
+    async fn start_deployment_rolls_back_to_a_built_deployment() {
+        let persistence = get_persistence().await;
+        let artifacts = tempfile::tempdir().unwrap();
+        let deployment_manager = get_deployment_manager(&persistence, artifacts.path()).await;
+        let service = persistence.get_or_create_service("rollback").await.unwrap();
+
+        let cleaned_up =
+            insert_stopped_deployment(&persistence, &deployment_manager, service.id, false).await;
+        let result = start_deployment(
+            Extension(persistence.clone()),
+            Extension(deployment_manager.clone()),
+            Extension(get_claim()),
+            Extension(Ulid::new()),
+            CustomErrorPath(("rollback".to_string(), cleaned_up)),
+        )
+        .await;
+        assert!(
+            matches!(result, Err(Error::NotFound(_))),
+            "a deployment without an executable cannot be started again"
+        );
+
+        let built =
+            insert_stopped_deployment(&persistence, &deployment_manager, service.id, true).await;
+        let Json(deployment) = start_deployment(
+            Extension(persistence.clone()),
+            Extension(deployment_manager),
+            Extension(get_claim()),
+            Extension(Ulid::new()),
+            CustomErrorPath(("rollback".to_string(), built)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(deployment.id, built);
+        assert_eq!(deployment.service_id, service.id.to_string());
+    }
+
+    #[test]
+    fn invalid_logs_filters_are_bad_requests() {
+        let error = logs_error(
+            tonic::Status::invalid_argument("invalid level `loud`"),
+            "failed to retrieve logs for service",
+        );
+        assert!(
+            matches!(error, Error::BadRequest(ref message) if message == "invalid level `loud`")
+        );
+
+        let error = logs_error(
+            tonic::Status::unavailable("logger is down"),
+            "failed to retrieve logs for service",
+        );
+        assert!(matches!(error, Error::Internal(_)));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use std::{collections::HashSet, convert::Infallible, net::SocketAddr, sync::Arc};
 
 use fqdn::FQDN;
 use hyper::{
@@ -66,12 +66,17 @@ pub async fn start(
     persistence.cleanup_invalid_states().await.unwrap();
 
     let runnable_deployments = persistence.get_all_runnable_deployments().await.unwrap();
-    info!(count = %runnable_deployments.len(), "stopping all but last running deploy");
+    info!(count = %runnable_deployments.len(), "stopping all but last running deploy of each service");
 
-    // Make sure we don't stop the last running deploy. This works because they are returned in descending order.
+    // Make sure we don't stop the last running deploy of a service. This works because they are returned in descending order.
     let project_id = Ulid::from_string(args.project_id.as_str())
         .expect("to have a valid ULID as project_id arg");
-    for existing_deployment in runnable_deployments.into_iter().skip(1) {
+    let mut seen_services = HashSet::new();
+    for existing_deployment in runnable_deployments {
+        if seen_services.insert(existing_deployment.service_id) {
+            continue;
+        }
+
         persistence
             .stop_running_deployment(existing_deployment)
             .await
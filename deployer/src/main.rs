@@ -1,4 +1,5 @@
 use std::process::exit;
+use std::time::Duration;
 
 use clap::Parser;
 use shuttle_common::{
@@ -79,6 +80,7 @@ async fn main() {
         args.provisioner_address.to_string(),
         logger_batcher.clone(),
         Some(args.auth_uri.to_string()),
+        Some(Duration::from_secs(args.drain_timeout)),
     );
 
     select! {
@@ -2,6 +2,7 @@ use std::{net::SocketAddr, str::FromStr};
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use shuttle_common::models::deployment::RunConfig;
 use sqlx::{sqlite::SqliteRow, FromRow, Row};
 use tracing::error;
 use ulid::Ulid;
@@ -22,6 +23,9 @@ pub struct Deployment {
     pub git_commit_msg: Option<String>,
     pub git_branch: Option<String>,
     pub git_dirty: Option<bool>,
+    /// Set on the deployments of the other services that were built from the same
+    /// workspace, pointing at the deployment of the workspace's primary service
+    pub parent_id: Option<Uuid>,
 }
 
 impl FromRow<'_, SqliteRow> for Deployment {
@@ -50,6 +54,7 @@ impl FromRow<'_, SqliteRow> for Deployment {
             git_commit_msg: row.try_get("git_commit_msg")?,
             git_branch: row.try_get("git_branch")?,
             git_dirty: row.try_get("git_dirty")?,
+            parent_id: row.try_get("parent_id")?,
         })
     }
 }
@@ -77,8 +82,22 @@ pub trait DeploymentUpdater: Clone + Send + Sync + 'static {
     /// Set the address for a deployment
     async fn set_address(&self, id: &Uuid, address: &SocketAddr) -> Result<(), Self::Err>;
 
+    /// Stop routing traffic to a deployment until it sets a new address
+    async fn clear_address(&self, id: &Uuid) -> Result<(), Self::Err>;
+
     /// Set if a deployment is build on shuttle-next
     async fn set_is_next(&self, id: &Uuid, is_next: bool) -> Result<(), Self::Err>;
+
+    /// Set the health check and restart policy a deployment is run with
+    async fn set_run_config(&self, id: &Uuid, run_config: &RunConfig) -> Result<(), Self::Err>;
+
+    /// Record a deployment for another service that was built in the same workspace as
+    /// the `parent_id` deployment. Returns the ids of the new deployment and of its service
+    async fn add_child_deployment(
+        &self,
+        parent_id: &Uuid,
+        service_name: &str,
+    ) -> Result<(Uuid, Ulid), Self::Err>;
 }
 
 #[derive(Debug, PartialEq, Eq)]
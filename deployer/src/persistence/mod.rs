@@ -7,10 +7,14 @@ use error::{Error, Result};
 use hyper::Uri;
 use shuttle_common::{
     claims::{Claim, ClaimLayer, InjectPropagationLayer},
+    models::deployment::RunConfig,
     resource::Type,
 };
 use shuttle_proto::{
-    provisioner::{provisioner_client::ProvisionerClient, DatabaseRequest},
+    provisioner::{
+        provisioner_client::ProvisionerClient, Backup, DatabaseRequest, ListBackupsResponse,
+        ObjectStoreRequest, RestoreRequest, RotateCredentialsResponse,
+    },
     resource_recorder::{
         self, record_request, RecordRequest, ResourceIds, ResourceResponse, ResourcesResponse,
         ResultResponse, ServiceResourcesRequest,
@@ -113,7 +117,7 @@ impl Persistence {
     }
 
     #[cfg(test)]
-    async fn new_in_memory() -> (Self, JoinHandle<()>) {
+    pub(crate) async fn new_in_memory() -> (Self, JoinHandle<()>) {
         let pool = SqlitePool::connect_with(
             SqliteConnectOptions::from_str("sqlite::memory:")
                 .unwrap()
@@ -169,6 +173,15 @@ impl Persistence {
         (persistence, handle)
     }
 
+    /// Keep resources in the resource recorder at this uri, for tests that need one
+    #[cfg(test)]
+    pub(crate) async fn with_resource_recorder(mut self, resource_recorder_uri: Uri) -> Self {
+        self.resource_recorder_client =
+            Some(resource_recorder::get_client(resource_recorder_uri).await);
+
+        self
+    }
+
     async fn from_pool(
         pool: SqlitePool,
     ) -> (
@@ -205,7 +218,7 @@ impl Persistence {
     pub async fn insert_deployment(&self, deployment: impl Into<&Deployment>) -> Result<()> {
         let deployment: &Deployment = deployment.into();
 
-        sqlx::query("INSERT INTO deployments VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+        sqlx::query("INSERT INTO deployments VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
             .bind(deployment.id)
             .bind(deployment.service_id.to_string())
             .bind(deployment.state)
@@ -216,6 +229,7 @@ impl Persistence {
             .bind(deployment.git_commit_msg.as_ref())
             .bind(deployment.git_branch.as_ref())
             .bind(deployment.git_dirty)
+            .bind(deployment.parent_id)
             .execute(&self.pool)
             .await
             .map(|_| ())
@@ -250,10 +264,20 @@ impl Persistence {
             .map_err(Error::from)
     }
 
+    /// Gets the deployments of the other services built together with the `parent_id` deployment
+    pub async fn get_child_deployments(&self, parent_id: &Uuid) -> Result<Vec<Deployment>> {
+        sqlx::query_as("SELECT * FROM deployments WHERE parent_id = ?")
+            .bind(parent_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
     pub async fn get_active_deployment(&self, service_id: &Ulid) -> Result<Option<Deployment>> {
-        sqlx::query_as("SELECT * FROM deployments WHERE service_id = ? AND state = ?")
+        sqlx::query_as("SELECT * FROM deployments WHERE service_id = ? AND state IN (?, ?)")
             .bind(service_id.to_string())
             .bind(State::Running)
+            .bind(State::Unhealthy)
             .fetch_optional(&self.pool)
             .await
             .map_err(Error::from)
@@ -300,13 +324,42 @@ impl Persistence {
             .map_err(Error::from)
     }
 
+    /// Deletes a service along with the other services that were deployed from its workspace
     pub async fn delete_service(&self, id: &Ulid) -> Result<()> {
-        sqlx::query("DELETE FROM services WHERE id = ?")
-            .bind(id.to_string())
-            .execute(&self.pool)
-            .await
-            .map(|_| ())
-            .map_err(Error::from)
+        let mut transaction = self.pool.begin().await?;
+
+        let mut service_ids: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT child.service_id FROM deployments AS child
+            JOIN deployments AS parent ON child.parent_id = parent.id
+            WHERE parent.service_id = ? AND child.service_id != parent.service_id",
+        )
+        .bind(id.to_string())
+        .fetch_all(&mut *transaction)
+        .await?;
+        service_ids.push(id.to_string());
+
+        for service_id in service_ids {
+            sqlx::query(
+                "DELETE FROM logs WHERE id IN (SELECT id FROM deployments WHERE service_id = ?)",
+            )
+            .bind(&service_id)
+            .execute(&mut *transaction)
+            .await?;
+            sqlx::query("DELETE FROM deployments WHERE service_id = ?")
+                .bind(&service_id)
+                .execute(&mut *transaction)
+                .await?;
+            sqlx::query("DELETE FROM resources WHERE service_id = ?")
+                .bind(&service_id)
+                .execute(&mut *transaction)
+                .await?;
+            sqlx::query("DELETE FROM services WHERE id = ?")
+                .bind(&service_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await.map_err(Error::from)
     }
 
     pub async fn get_all_services(&self) -> Result<Vec<Service>> {
@@ -316,30 +369,54 @@ impl Persistence {
             .map_err(Error::from)
     }
 
+    /// Ids of all the deployments of all the services of this project
+    pub async fn get_all_deployment_ids(&self) -> Result<Vec<Uuid>> {
+        sqlx::query_scalar("SELECT id FROM deployments")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
     pub async fn get_all_runnable_deployments(&self) -> Result<Vec<DeploymentRunnable>> {
         sqlx::query_as(
             r#"SELECT d.id, service_id, s.name AS service_name, d.is_next
                 FROM deployments AS d
                 JOIN services AS s ON s.id = d.service_id
-                WHERE state = ?
+                WHERE state IN (?, ?)
                 ORDER BY last_update DESC"#,
         )
         .bind(State::Running)
+        .bind(State::Unhealthy)
         .fetch_all(&self.pool)
         .await
         .map_err(Error::from)
     }
 
+    /// Gets the health check and restart policy a deployment was built with
+    pub async fn get_run_config(&self, id: &Uuid) -> Result<RunConfig> {
+        let run_config: Option<sqlx::types::Json<RunConfig>> =
+            sqlx::query_scalar("SELECT run_config FROM deployments WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?
+                .flatten();
+
+        Ok(run_config
+            .map(|run_config| run_config.0)
+            .unwrap_or_default())
+    }
+
     /// Gets a deployment if it is runnable
     pub async fn get_runnable_deployment(&self, id: &Uuid) -> Result<Option<DeploymentRunnable>> {
         sqlx::query_as(
             r#"SELECT d.id, service_id, s.name AS service_name, d.is_next
                 FROM deployments AS d
                 JOIN services AS s ON s.id = d.service_id
-                WHERE state IN (?, ?, ?)
+                WHERE state IN (?, ?, ?, ?)
                 AND d.id = ?"#,
         )
         .bind(State::Running)
+        .bind(State::Unhealthy)
         .bind(State::Stopped)
         .bind(State::Completed)
         .bind(id)
@@ -358,6 +435,130 @@ impl Persistence {
         )
         .await
     }
+
+    pub async fn backup_database(
+        &mut self,
+        project_name: String,
+        db_type: shuttle_common::database::Type,
+        name: Option<String>,
+        claim: Claim,
+    ) -> Result<Backup> {
+        let mut backup_request = Request::new(DatabaseRequest {
+            project_name,
+            name: name.unwrap_or_default(),
+            db_type: Some(db_type.into()),
+        });
+        backup_request.extensions_mut().insert(claim);
+
+        self.provisioner_client
+            .as_mut()
+            .expect("to have the provisioner set up")
+            .backup_database(backup_request)
+            .await
+            .map(|res| res.into_inner())
+            .map_err(Error::Provisioner)
+    }
+
+    pub async fn list_backups(
+        &mut self,
+        project_name: String,
+        db_type: shuttle_common::database::Type,
+        name: Option<String>,
+        claim: Claim,
+    ) -> Result<ListBackupsResponse> {
+        let mut list_request = Request::new(DatabaseRequest {
+            project_name,
+            name: name.unwrap_or_default(),
+            db_type: Some(db_type.into()),
+        });
+        list_request.extensions_mut().insert(claim);
+
+        self.provisioner_client
+            .as_mut()
+            .expect("to have the provisioner set up")
+            .list_backups(list_request)
+            .await
+            .map(|res| res.into_inner())
+            .map_err(Error::Provisioner)
+    }
+
+    pub async fn restore_database(
+        &mut self,
+        project_name: String,
+        db_type: shuttle_common::database::Type,
+        name: Option<String>,
+        backup_id: String,
+        target_name: Option<String>,
+        claim: Claim,
+    ) -> Result<()> {
+        let mut restore_request = Request::new(RestoreRequest {
+            database: Some(DatabaseRequest {
+                project_name,
+                name: name.unwrap_or_default(),
+                db_type: Some(db_type.into()),
+            }),
+            backup_id,
+            target_name: target_name.unwrap_or_default(),
+        });
+        restore_request.extensions_mut().insert(claim);
+
+        self.provisioner_client
+            .as_mut()
+            .expect("to have the provisioner set up")
+            .restore_database(restore_request)
+            .await
+            .map(|_| ())
+            .map_err(Error::Provisioner)
+    }
+
+    pub async fn rotate_database_credentials(
+        &mut self,
+        project_name: String,
+        db_type: shuttle_common::database::Type,
+        name: Option<String>,
+        claim: Claim,
+    ) -> Result<RotateCredentialsResponse> {
+        let mut rotate_request = Request::new(DatabaseRequest {
+            project_name,
+            name: name.unwrap_or_default(),
+            db_type: Some(db_type.into()),
+        });
+        rotate_request.extensions_mut().insert(claim);
+
+        self.provisioner_client
+            .as_mut()
+            .expect("to have the provisioner set up")
+            .rotate_database_credentials(rotate_request)
+            .await
+            .map(|res| res.into_inner())
+            .map_err(Error::Provisioner)
+    }
+
+    /// Stop the recorded output of a resource from being reused, so the next load provisions it again
+    pub async fn invalidate_resource(
+        &mut self,
+        service_id: &Ulid,
+        r#type: shuttle_common::resource::Type,
+        name: Option<String>,
+        claim: Claim,
+    ) -> Result<ResultResponse> {
+        let mut invalidate_resource_req = tonic::Request::new(ResourceIds {
+            project_id: self.project_id.to_string(),
+            service_id: service_id.to_string(),
+            r#type: r#type.to_string(),
+            name: name.unwrap_or_default(),
+        });
+
+        invalidate_resource_req.extensions_mut().insert(claim);
+
+        self.resource_recorder_client
+            .as_mut()
+            .expect("to have the resource recorder set up")
+            .invalidate_resource(invalidate_resource_req)
+            .await
+            .map(|res| res.into_inner())
+            .map_err(PersistenceError::ResourceRecorder)
+    }
 }
 
 async fn update_deployment(pool: &SqlitePool, state: DeploymentState) -> Result<()> {
@@ -453,6 +654,7 @@ impl ResourceManager for Persistence {
                         r#type: res.r#type.to_string(),
                         config: res.config.to_string().into_bytes(),
                         data: res.data.to_string().into_bytes(),
+                        name: String::new(),
                     })
                     .collect();
 
@@ -499,12 +701,14 @@ impl ResourceManager for Persistence {
         &mut self,
         service_id: &Ulid,
         r#type: shuttle_common::resource::Type,
+        name: Option<String>,
         claim: Claim,
     ) -> Result<ResourceResponse> {
         let mut get_resource_req = tonic::Request::new(ResourceIds {
             project_id: self.project_id.to_string(),
             service_id: service_id.to_string(),
             r#type: r#type.to_string(),
+            name: name.unwrap_or_default(),
         });
 
         get_resource_req.extensions_mut().insert(claim);
@@ -524,14 +728,18 @@ impl ResourceManager for Persistence {
         project_name: String,
         service_id: &Ulid,
         resource_type: shuttle_common::resource::Type,
+        name: Option<String>,
         claim: Claim,
     ) -> Result<ResultResponse> {
+        let name = name.unwrap_or_default();
+
         if let Type::Database(db_type) = resource_type {
             let proto_db_type: shuttle_proto::provisioner::database_request::DbType =
                 db_type.into();
             if let Some(inner) = &mut self.provisioner_client {
                 let mut db_request = Request::new(DatabaseRequest {
                     project_name,
+                    name: name.clone(),
                     db_type: Some(proto_db_type),
                 });
                 db_request.extensions_mut().insert(claim.clone());
@@ -540,12 +748,25 @@ impl ResourceManager for Persistence {
                     .await
                     .map_err(error::Error::Provisioner)?;
             };
+        } else if let Type::ObjectStore = resource_type {
+            if let Some(inner) = &mut self.provisioner_client {
+                let mut object_store_request = Request::new(ObjectStoreRequest {
+                    project_name,
+                    name: name.clone(),
+                });
+                object_store_request.extensions_mut().insert(claim.clone());
+                inner
+                    .delete_object_store(object_store_request)
+                    .await
+                    .map_err(error::Error::Provisioner)?;
+            };
         }
 
         let mut delete_resource_req = tonic::Request::new(ResourceIds {
             project_id: self.project_id.to_string(),
             service_id: service_id.to_string(),
             r#type: resource_type.to_string(),
+            name,
         });
 
         delete_resource_req.extensions_mut().insert(claim);
@@ -572,12 +793,13 @@ impl AddressGetter for Persistence {
             r#"SELECT d.address
                 FROM deployments AS d
                 JOIN services AS s ON d.service_id = s.id
-                WHERE s.name = ? AND d.state = ?
+                WHERE s.name = ? AND d.state IN (?, ?) AND d.address IS NOT NULL
                 ORDER BY d.last_update
                 DESC"#,
         )
         .bind(service_name)
         .bind(State::Running)
+        .bind(State::Unhealthy)
         .fetch_optional(&self.pool)
         .await
         .map_err(Error::from)
@@ -611,6 +833,15 @@ impl DeploymentUpdater for Persistence {
             .map_err(Error::from)
     }
 
+    async fn clear_address(&self, id: &Uuid) -> Result<()> {
+        sqlx::query("UPDATE deployments SET address = NULL WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(Error::from)
+    }
+
     async fn set_is_next(&self, id: &Uuid, is_next: bool) -> Result<()> {
         sqlx::query("UPDATE deployments SET is_next = ? WHERE id = ?")
             .bind(is_next)
@@ -620,6 +851,44 @@ impl DeploymentUpdater for Persistence {
             .map(|_| ())
             .map_err(Error::from)
     }
+
+    async fn set_run_config(&self, id: &Uuid, run_config: &RunConfig) -> Result<()> {
+        sqlx::query("UPDATE deployments SET run_config = ? WHERE id = ?")
+            .bind(sqlx::types::Json(run_config))
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(Error::from)
+    }
+
+    async fn add_child_deployment(
+        &self,
+        parent_id: &Uuid,
+        service_name: &str,
+    ) -> Result<(Uuid, Ulid)> {
+        let parent = get_deployment(&self.pool, parent_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let service = self.get_or_create_service(service_name).await?;
+
+        let deployment = Deployment {
+            id: Uuid::new_v4(),
+            service_id: service.id,
+            state: State::Building,
+            last_update: Utc::now(),
+            address: None,
+            is_next: false,
+            git_commit_id: parent.git_commit_id,
+            git_commit_msg: parent.git_commit_msg,
+            git_branch: parent.git_branch,
+            git_dirty: parent.git_dirty,
+            parent_id: Some(parent.id),
+        };
+        self.insert_deployment(&deployment).await?;
+
+        Ok((deployment.id, service.id))
+    }
 }
 
 #[async_trait::async_trait]
@@ -631,10 +900,11 @@ impl ActiveDeploymentsGetter for Persistence {
         service_id: &Ulid,
     ) -> std::result::Result<Vec<Uuid>, Self::Err> {
         let ids: Vec<_> = sqlx::query_as::<_, Deployment>(
-            "SELECT * FROM deployments WHERE service_id = ? AND state = ?",
+            "SELECT * FROM deployments WHERE service_id = ? AND state IN (?, ?)",
         )
         .bind(service_id.to_string())
         .bind(State::Running)
+        .bind(State::Unhealthy)
         .fetch_all(&self.pool)
         .await
         .map_err(Error::from)?
@@ -727,6 +997,7 @@ mod tests {
                 git_commit_msg: None,
                 git_branch: None,
                 git_dirty: None,
+                parent_id: None,
             })
             .collect();
 
@@ -1081,6 +1352,78 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn child_deployments() {
+        let (p, _) = Persistence::new_in_memory().await;
+        let service_id = add_service_named(&p.pool, "api").await.unwrap();
+
+        let parent = Deployment {
+            id: Uuid::new_v4(),
+            service_id,
+            state: State::Building,
+            last_update: Utc::now(),
+            git_commit_id: Some("abc123".to_string()),
+            git_branch: Some("main".to_string()),
+            ..Default::default()
+        };
+        p.insert_deployment(&parent).await.unwrap();
+
+        let (child_id, worker_id) = p.add_child_deployment(&parent.id, "worker").await.unwrap();
+
+        let worker = p.get_service_by_name("worker").await.unwrap().unwrap();
+        assert_eq!(worker.id, worker_id);
+
+        let children = p.get_child_deployments(&parent.id).await.unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].id, child_id);
+        assert_eq!(children[0].service_id, worker_id);
+        assert_eq!(children[0].parent_id, Some(parent.id));
+        assert_eq!(children[0].git_commit_id, parent.git_commit_id);
+        assert_eq!(children[0].git_branch, parent.git_branch);
+
+        assert!(p.get_child_deployments(&child_id).await.unwrap().is_empty());
+        assert!(p
+            .add_child_deployment(&Uuid::new_v4(), "worker")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn delete_service_with_children() {
+        let (p, _) = Persistence::new_in_memory().await;
+        let service_id = add_service_named(&p.pool, "api").await.unwrap();
+        let other_id = add_service_named(&p.pool, "other").await.unwrap();
+
+        let parent = Deployment {
+            id: Uuid::new_v4(),
+            service_id,
+            state: State::Running,
+            last_update: Utc::now(),
+            ..Default::default()
+        };
+        let other = Deployment {
+            id: Uuid::new_v4(),
+            service_id: other_id,
+            state: State::Running,
+            last_update: Utc::now(),
+            ..Default::default()
+        };
+        p.insert_deployment(&parent).await.unwrap();
+        p.insert_deployment(&other).await.unwrap();
+
+        let (child_id, _) = p.add_child_deployment(&parent.id, "worker").await.unwrap();
+
+        p.delete_service(&service_id).await.unwrap();
+
+        assert!(p.get_service_by_name("api").await.unwrap().is_none());
+        assert!(p.get_service_by_name("worker").await.unwrap().is_none());
+        assert!(p.get_deployment(&parent.id).await.unwrap().is_none());
+        assert!(p.get_deployment(&child_id).await.unwrap().is_none());
+
+        assert!(p.get_service_by_name("other").await.unwrap().is_some());
+        assert!(p.get_deployment(&other.id).await.unwrap().is_some());
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn service() {
         let (p, _) = Persistence::new_in_memory().await;
@@ -1112,14 +1455,20 @@ mod tests {
         let service_other_id = add_service_named(&p.pool, "other-name").await.unwrap();
 
         sqlx::query(
-            "INSERT INTO deployments (id, service_id, state, last_update, address) VALUES (?, ?, ?, ?, ?), (?, ?, ?, ?, ?), (?, ?, ?, ?, ?)",
+            "INSERT INTO deployments (id, service_id, state, last_update, address) VALUES (?, ?, ?, ?, ?), (?, ?, ?, ?, ?), (?, ?, ?, ?, ?), (?, ?, ?, ?, ?)",
         )
         // This running item should match
         .bind(Uuid::new_v4())
         .bind(service_id.to_string())
         .bind(State::Running)
-        .bind(Utc::now())
+        .bind(Utc::now() - Duration::seconds(5))
         .bind("10.0.0.5:12356")
+        // A newer item that is not ready for traffic yet should not match
+        .bind(Uuid::new_v4())
+        .bind(service_id.to_string())
+        .bind(State::Running)
+        .bind(Utc::now())
+        .bind(None::<String>)
         // A stopped item should not match
         .bind(Uuid::new_v4())
         .bind(service_id.to_string())
@@ -32,6 +32,7 @@ pub trait ResourceManager: Clone + Send + Sync + 'static {
         &mut self,
         service_id: &ulid::Ulid,
         r#type: CommonResourceType,
+        name: Option<String>,
         claim: Claim,
     ) -> Result<ResourceResponse, Self::Err>;
     async fn delete_resource(
@@ -39,6 +40,7 @@ pub trait ResourceManager: Clone + Send + Sync + 'static {
         project_name: String,
         service_id: &ulid::Ulid,
         r#type: CommonResourceType,
+        name: Option<String>,
         claim: Claim,
     ) -> Result<ResultResponse, Self::Err>;
 }
@@ -67,6 +69,7 @@ impl From<Resource> for shuttle_common::resource::Response {
     fn from(resource: Resource) -> Self {
         shuttle_common::resource::Response {
             r#type: resource.r#type.into(),
+            name: None,
             config: resource.config,
             data: resource.data,
         }
@@ -80,6 +83,7 @@ pub enum Type {
     StaticFolder,
     Persist,
     Turso,
+    ObjectStore,
     Metadata,
     Custom,
 }
@@ -92,6 +96,7 @@ impl From<Type> for CommonResourceType {
             Type::StaticFolder => Self::StaticFolder,
             Type::Persist => Self::Persist,
             Type::Turso => Self::Turso,
+            Type::ObjectStore => Self::ObjectStore,
             Type::Metadata => Self::Metadata,
             Type::Custom => Self::Custom,
         }
@@ -106,6 +111,7 @@ impl From<CommonResourceType> for Type {
             CommonResourceType::StaticFolder => Self::StaticFolder,
             CommonResourceType::Persist => Self::Persist,
             CommonResourceType::Turso => Self::Turso,
+            CommonResourceType::ObjectStore => Self::ObjectStore,
             CommonResourceType::Metadata => Self::Metadata,
             CommonResourceType::Custom => Self::Custom,
         }
@@ -120,6 +126,7 @@ impl Display for Type {
             Type::StaticFolder => write!(f, "static_folder"),
             Type::Persist => write!(f, "persist"),
             Type::Turso => write!(f, "turso"),
+            Type::ObjectStore => write!(f, "object_store"),
             Type::Metadata => write!(f, "metadata"),
             Type::Custom => write!(f, "custom"),
         }
@@ -141,6 +148,7 @@ impl FromStr for Type {
                 "static_folder" => Ok(Self::StaticFolder),
                 "persist" => Ok(Self::Persist),
                 "turso" => Ok(Self::Turso),
+                "object_store" => Ok(Self::ObjectStore),
                 "metadata" => Ok(Self::Metadata),
                 "custom" => Ok(Self::Custom),
                 _ => Err(format!("'{s}' is an unknown resource type")),
@@ -188,10 +196,12 @@ mod tests {
             Type::Database(database::Type::AwsRds(database::AwsRdsType::MariaDB)),
             Type::Database(database::Type::Shared(database::SharedType::Postgres)),
             Type::Database(database::Type::Shared(database::SharedType::MongoDb)),
+            Type::Database(database::Type::Shared(database::SharedType::Redis)),
             Type::Secrets,
             Type::StaticFolder,
             Type::Persist,
             Type::Turso,
+            Type::ObjectStore,
             Type::Metadata,
             Type::Custom,
         ];
@@ -20,6 +20,9 @@ pub enum State {
     /// Deployment is running - ie. its thread is active
     Running,
 
+    /// Deployment is running, but its health check has been failing
+    Unhealthy,
+
     /// Deployment was running, but stopped running all by itself. This is expected for things like background workers
     Completed,
 
@@ -53,6 +56,7 @@ impl From<State> for shuttle_common::deployment::State {
             State::Built => Self::Built,
             State::Loading => Self::Loading,
             State::Running => Self::Running,
+            State::Unhealthy => Self::Unhealthy,
             State::Completed => Self::Completed,
             State::Stopped => Self::Stopped,
             State::Crashed => Self::Crashed,
@@ -69,6 +73,7 @@ impl From<shuttle_common::deployment::State> for State {
             shuttle_common::deployment::State::Built => Self::Built,
             shuttle_common::deployment::State::Loading => Self::Loading,
             shuttle_common::deployment::State::Running => Self::Running,
+            shuttle_common::deployment::State::Unhealthy => Self::Unhealthy,
             shuttle_common::deployment::State::Completed => Self::Completed,
             shuttle_common::deployment::State::Stopped => Self::Stopped,
             shuttle_common::deployment::State::Crashed => Self::Crashed,
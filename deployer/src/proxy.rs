@@ -54,18 +54,7 @@ pub async fn handle(
     };
     span.record("http.host", host.to_string());
 
-    if host != fqdn {
-        trace!(?host, "proxy won't serve foreign domain");
-        Span::current().record("proxy.status_code", StatusCode::BAD_REQUEST.as_u16());
-        return Ok(Response::builder()
-            .status(StatusCode::BAD_REQUEST)
-            .body(Body::from("this domain is not served by proxy"))
-            .unwrap());
-    }
-
-    // We only have one service per project, and its name coincides
-    // with that of the project
-    let service = match req.headers().typed_get::<XShuttleProject>() {
+    let project = match req.headers().typed_get::<XShuttleProject>() {
         Some(project) => project.0,
         None => {
             trace!("proxy request has no X-Shuttle-Project header");
@@ -77,8 +66,29 @@ pub async fn handle(
         }
     };
 
+    // The project domain serves the project's service, while the other services deployed from
+    // its workspace are served on `<service>--<project>` next to it
+    let service = if host == fqdn {
+        Some(project.clone())
+    } else {
+        host.labels()
+            .next()
+            .and_then(|label| label.strip_suffix(&format!("--{project}")))
+            .filter(|service| !service.is_empty())
+            .map(ToString::to_string)
+    };
+
+    let Some(service) = service else {
+        trace!(?host, "proxy won't serve foreign domain");
+        Span::current().record("proxy.status_code", StatusCode::BAD_REQUEST.as_u16());
+        return Ok(Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from("this domain is not served by proxy"))
+            .unwrap());
+    };
+
     // Record current service for tracing purposes
-    span.record("shuttle.project.name", &service);
+    span.record("shuttle.project.name", &project);
     span.record("shuttle.service.name", &service);
 
     let proxy_address = match address_getter.get_address_for_service(&service).await {
@@ -2,6 +2,7 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::Context;
@@ -9,7 +10,7 @@ use chrono::Utc;
 use prost_types::Timestamp;
 use shuttle_common::{
     claims::{ClaimService, InjectPropagation},
-    log::Backend,
+    log::{Backend, LogLevel},
 };
 use shuttle_proto::{
     logger::{logger_client::LoggerClient, Batcher, LogItem, LogLine},
@@ -49,6 +50,7 @@ pub struct RuntimeManager {
         >,
     >,
     auth_uri: Option<String>,
+    drain_timeout: Option<Duration>,
 }
 
 impl RuntimeManager {
@@ -62,12 +64,14 @@ impl RuntimeManager {
             >,
         >,
         auth_uri: Option<String>,
+        drain_timeout: Option<Duration>,
     ) -> Arc<Mutex<Self>> {
         Arc::new(Mutex::new(Self {
             runtimes: Default::default(),
             provisioner_address,
             logger_client,
             auth_uri,
+            drain_timeout,
         }))
     }
 
@@ -130,6 +134,7 @@ impl RuntimeManager {
             Environment::Deployment,
             &self.provisioner_address,
             self.auth_uri.as_ref(),
+            self.drain_timeout,
             port,
             runtime_executable,
             project_path,
@@ -161,6 +166,10 @@ impl RuntimeManager {
                             nanos: utc.timestamp_subsec_nanos().try_into().unwrap_or_default(),
                         }),
                         data: line.as_bytes().to_vec(),
+                        level: LogLevel::detect(&line)
+                            .map(|level| level.to_string())
+                            .unwrap_or_default(),
+                        ..Default::default()
                     }),
                 };
                 logger_client.send(log);
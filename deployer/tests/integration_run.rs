@@ -7,14 +7,16 @@ use std::{
 
 use async_trait::async_trait;
 use portpicker::pick_unused_port;
-use shuttle_common::{claims::Claim, constants::EXECUTABLE_DIRNAME};
+use shuttle_common::{claims::Claim, constants::EXECUTABLE_DIRNAME, models::deployment::RunConfig};
 use shuttle_common_tests::logger::{get_mocked_logger_client, MockedLogger};
 use shuttle_proto::{
     logger::Batcher,
     provisioner::{
         provisioner_server::{Provisioner, ProvisionerServer},
-        ContainerRequest, ContainerResponse, DatabaseDeletionResponse, DatabaseRequest,
-        DatabaseResponse, Ping, Pong,
+        Backup, ContainerRequest, ContainerResponse, DatabaseDeletionResponse, DatabaseRequest,
+        DatabaseResponse, ListBackupsResponse, ObjectStoreDeletionResponse, ObjectStoreRequest,
+        ObjectStoreResponse, Ping, Pong, RestoreRequest, RestoreResponse,
+        RotateCredentialsResponse,
     },
     resource_recorder::{ResourceResponse, ResourcesResponse, ResultResponse},
     runtime::{StopReason, SubscribeStopResponse},
@@ -66,6 +68,48 @@ impl Provisioner for ProvisionerMock {
         panic!("no run tests should request container")
     }
 
+    async fn provision_object_store(
+        &self,
+        _request: tonic::Request<ObjectStoreRequest>,
+    ) -> Result<tonic::Response<ObjectStoreResponse>, tonic::Status> {
+        panic!("no run tests should request an object store");
+    }
+
+    async fn delete_object_store(
+        &self,
+        _request: tonic::Request<ObjectStoreRequest>,
+    ) -> Result<tonic::Response<ObjectStoreDeletionResponse>, tonic::Status> {
+        panic!("no run tests should delete an object store");
+    }
+
+    async fn backup_database(
+        &self,
+        _request: tonic::Request<DatabaseRequest>,
+    ) -> Result<tonic::Response<Backup>, tonic::Status> {
+        panic!("no run tests should back up a db");
+    }
+
+    async fn list_backups(
+        &self,
+        _request: tonic::Request<DatabaseRequest>,
+    ) -> Result<tonic::Response<ListBackupsResponse>, tonic::Status> {
+        panic!("no run tests should list backups");
+    }
+
+    async fn restore_database(
+        &self,
+        _request: tonic::Request<RestoreRequest>,
+    ) -> Result<tonic::Response<RestoreResponse>, tonic::Status> {
+        panic!("no run tests should restore a db");
+    }
+
+    async fn rotate_database_credentials(
+        &self,
+        _request: tonic::Request<DatabaseRequest>,
+    ) -> Result<tonic::Response<RotateCredentialsResponse>, tonic::Status> {
+        panic!("no run tests should rotate db credentials");
+    }
+
     async fn health_check(
         &self,
         _request: tonic::Request<Ping>,
@@ -87,7 +131,12 @@ async fn get_runtime_manager() -> Arc<Mutex<RuntimeManager>> {
 
     let logger_client = Batcher::wrap(get_mocked_logger_client(MockedLogger).await);
 
-    RuntimeManager::new(format!("http://{}", provisioner_addr), logger_client, None)
+    RuntimeManager::new(
+        format!("http://{}", provisioner_addr),
+        logger_client,
+        None,
+        None,
+    )
 }
 
 #[derive(Clone)]
@@ -125,6 +174,7 @@ impl ResourceManager for StubResourceManager {
         _project_name: String,
         _service_id: &Ulid,
         _resource_type: shuttle_common::resource::Type,
+        _name: Option<String>,
         _claim: Claim,
     ) -> Result<ResultResponse, Self::Err> {
         Ok(ResultResponse {
@@ -137,6 +187,7 @@ impl ResourceManager for StubResourceManager {
         &mut self,
         _service_id: &ulid::Ulid,
         _resource_type: shuttle_common::resource::Type,
+        _name: Option<String>,
         _claim: Claim,
     ) -> Result<ResourceResponse, Self::Err> {
         Ok(ResourceResponse {
@@ -158,9 +209,25 @@ impl DeploymentUpdater for StubDeploymentUpdater {
         Ok(())
     }
 
+    async fn clear_address(&self, _id: &Uuid) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
     async fn set_is_next(&self, _id: &Uuid, _is_next: bool) -> Result<(), Self::Err> {
         Ok(())
     }
+
+    async fn set_run_config(&self, _id: &Uuid, _run_config: &RunConfig) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    async fn add_child_deployment(
+        &self,
+        _parent_id: &Uuid,
+        _service_name: &str,
+    ) -> Result<(Uuid, Ulid), Self::Err> {
+        Ok((Uuid::new_v4(), Ulid::new()))
+    }
 }
 
 // This test uses the kill signal to make sure a service does stop when asked to
@@ -339,13 +406,16 @@ async fn make_and_built(crate_name: &str) -> (Built, PathBuf) {
         Built {
             id,
             service_name: crate_name.to_string(),
+            project_name: crate_name.to_string(),
             service_id: Ulid::new(),
             project_id: Ulid::new(),
             tracing_context: Default::default(),
             is_next: false,
             claim: Default::default(),
             secrets: Default::default(),
+            run_config: Default::default(),
+            restarts: 0,
         },
-        RESOURCES_PATH.into(), // is later joined with `service_name` to arrive at `crate_name`
+        RESOURCES_PATH.into(), // is later joined with `project_name` to arrive at `crate_name`
     )
 }
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS certificate_renewal_failures (
+  fqdn TEXT PRIMARY KEY,
+  failures INTEGER NOT NULL,
+  last_error TEXT NOT NULL,
+  last_attempt_at INTEGER NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE projects ADD COLUMN container_limits TEXT;
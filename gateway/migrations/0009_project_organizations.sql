@@ -0,0 +1 @@
+ALTER TABLE projects ADD COLUMN org_name TEXT;
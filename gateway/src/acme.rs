@@ -5,6 +5,7 @@ use std::time::Duration;
 
 use axum::body::boxed;
 use axum::response::Response;
+use chrono::{DateTime, TimeZone, Utc};
 use fqdn::FQDN;
 use futures::future::BoxFuture;
 use hyper::server::conn::AddrStream;
@@ -19,6 +20,8 @@ use tokio::sync::Mutex;
 use tokio::time::sleep;
 use tower::{Layer, Service};
 use tracing::{error, trace, warn};
+use x509_parser::parse_x509_certificate;
+use x509_parser::pem::parse_x509_pem;
 
 use crate::proxy::AsResponderTo;
 use crate::Error;
@@ -34,23 +37,53 @@ pub struct CustomDomain {
     pub private_key: String,
 }
 
+impl CustomDomain {
+    /// When the certificate of this domain stops being valid, if it can be parsed
+    pub fn certificate_expires_at(&self) -> Option<DateTime<Utc>> {
+        pem_certificate_expires_at(&self.certificate)
+    }
+}
+
+/// When the first certificate of a PEM encoded chain stops being valid, if it can be parsed
+pub fn pem_certificate_expires_at(chain: &str) -> Option<DateTime<Utc>> {
+    let (_, pem) = parse_x509_pem(chain.as_bytes()).ok()?;
+    let (_, certificate) = parse_x509_certificate(&pem.contents).ok()?;
+
+    Utc.timestamp_opt(certificate.validity().not_after.timestamp(), 0)
+        .single()
+}
+
 /// An ACME client implementation that completes Http01 challenges
 /// It is safe to clone this type as it functions as a singleton
 #[derive(Clone, Default)]
-pub struct AcmeClient(Arc<Mutex<HashMap<String, KeyAuthorization>>>);
+pub struct AcmeClient {
+    challenges: Arc<Mutex<HashMap<String, KeyAuthorization>>>,
+    /// Sign certificates itself instead of ordering them from the ACME server
+    #[cfg(test)]
+    self_signed: bool,
+}
 
 impl AcmeClient {
     pub fn new() -> Self {
-        Self(Arc::new(Mutex::new(HashMap::default())))
+        Self::default()
+    }
+
+    /// A client which hands out self-signed certificates, for tests which have no ACME server
+    #[cfg(test)]
+    pub fn self_signed() -> Self {
+        Self {
+            self_signed: true,
+            ..Default::default()
+        }
     }
 
     async fn add_http01_challenge_authorization(&self, token: String, key: KeyAuthorization) {
         trace!(token, "saving acme http01 challenge");
-        self.0.lock().await.insert(token, key);
+        self.challenges.lock().await.insert(token, key);
     }
 
     async fn get_http01_challenge_authorization(&self, token: &str) -> Option<String> {
-        self.0
+        self.challenges
             .lock()
             .await
             .get(token)
@@ -59,7 +92,7 @@ impl AcmeClient {
 
     async fn remove_http01_challenge_authorization(&self, token: &str) {
         trace!(token, "removing acme http01 challenge");
-        self.0.lock().await.remove(token);
+        self.challenges.lock().await.remove(token);
     }
 
     /// Create a new ACME account that can be restored by using the deserialization
@@ -104,6 +137,17 @@ impl AcmeClient {
     ) -> Result<(String, String), AcmeClientError> {
         trace!(identifier, "requesting acme certificate");
 
+        #[cfg(test)]
+        if self.self_signed {
+            let certificate = rcgen::generate_simple_self_signed(vec![identifier.to_owned()])
+                .map_err(|_| AcmeClientError::CertificateCreation)?;
+            let chain = certificate
+                .serialize_pem()
+                .map_err(|_| AcmeClientError::CertificateSigning)?;
+
+            return Ok((chain, certificate.serialize_private_key_pem()));
+        }
+
         let mut order = AccountWrapper::from(credentials)
             .0
             .new_order(&NewOrder {
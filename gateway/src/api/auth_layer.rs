@@ -16,7 +16,11 @@ use once_cell::sync::Lazy;
 use opentelemetry::global;
 use opentelemetry_http::HeaderInjector;
 use shuttle_common::{
-    backends::{auth::ConvertResponse, cache::CacheManagement, headers::XShuttleAdminSecret},
+    backends::{
+        auth::ConvertResponse,
+        cache::CacheManagement,
+        headers::{XShuttleAdminSecret, XShuttleOrganization},
+    },
     ApiKey,
 };
 use tower::{Layer, Service};
@@ -32,7 +36,8 @@ static PROXY_CLIENT: Lazy<ReverseProxy<HttpConnector<GaiResolver>>> =
 const CACHE_MINUTES: u64 = 5;
 
 /// The idea of this layer is to do two things:
-/// 1. Forward all user related routes (`/users/*`) to our auth service
+/// 1. Forward all user and organization related routes (`/users/*` and `/organizations/*`), the Stripe webhook
+/// (`/webhooks/stripe`) and the token routes (`/auth/key` and `/auth/refresh`), to our auth service
 /// 2. Upgrade all Authorization Bearer keys to JWT tokens for internal communication inside and below gateway, fetching
 /// the JWT token from a ttl-cache if it isn't expired, and inserting it in the cache if it isn't there.
 #[derive(Clone)]
@@ -123,44 +128,52 @@ where
         if req_path == "/users/reset-api-key" {
             if let Some((cache_key, _)) = cache_key_and_token_req(
                 req.headers().typed_get::<Authorization<Bearer>>(),
+                req.headers().typed_get::<XShuttleOrganization>(),
                 self.gateway_admin_key.as_str(),
             ) {
                 self.cache_manager.invalidate(&cache_key);
             };
         }
 
-        if req_path.starts_with("/users") {
-            let target_url = self.auth_uri.to_string();
+        // Clients which keep their token instead of sending their key on every request get it
+        // here, together with a refresh token to get the next one from `/auth/refresh`
+        if req_path == "/auth/key" {
+            let bearer = req
+                .headers()
+                .typed_get::<Authorization<Bearer>>()
+                .filter(|bearer| ApiKey::parse(bearer.token()).is_ok());
+            let Some(bearer) = bearer else {
+                return Box::pin(async move {
+                    Ok(Response::builder()
+                        .status(StatusCode::UNAUTHORIZED)
+                        .body(boxed(Body::empty()))
+                        .unwrap())
+                });
+            };
 
+            // Only the key of the caller is converted, so the admin key gives it no extra access
+            let token_request = make_token_request(
+                "/auth/key?refresh_token=true",
+                bearer,
+                req.headers().typed_get::<XShuttleOrganization>(),
+                Some(self.gateway_admin_key.as_str()),
+            );
+
+            return Box::pin(forward_to_auth(self.auth_uri.to_string(), token_request));
+        }
+
+        if req_path.starts_with("/users")
+            || req_path.starts_with("/organizations")
+            || req_path == "/webhooks/stripe"
+            || req_path == "/auth/refresh"
+        {
             let cx = Span::current().context();
 
             global::get_text_map_propagator(|propagator| {
                 propagator.inject_context(&cx, &mut HeaderInjector(req.headers_mut()))
             });
 
-            Box::pin(async move {
-                let response = PROXY_CLIENT
-                    .call(Ipv4Addr::LOCALHOST.into(), &target_url, req)
-                    .await;
-
-                match response {
-                    Ok(res) => {
-                        let (parts, body) = res.into_parts();
-                        let body =
-                            <Body as HttpBody>::map_err(body, axum::Error::new).boxed_unsync();
-
-                        Ok(Response::from_parts(parts, body))
-                    }
-                    Err(error) => {
-                        error!(?error, "failed to call authentication service");
-
-                        Ok(Response::builder()
-                            .status(StatusCode::SERVICE_UNAVAILABLE)
-                            .body(boxed(Body::empty()))
-                            .unwrap())
-                    }
-                }
-            })
+            Box::pin(forward_to_auth(self.auth_uri.to_string(), req))
         } else {
             // Enrich the current key | session
 
@@ -169,6 +182,7 @@ where
             let mut this = self.clone();
 
             let bearer = req.headers().typed_get::<Authorization<Bearer>>();
+            let org = req.headers().typed_get::<XShuttleOrganization>();
 
             // If this is not a valid api key, then assume it must be a JWT token key. Therefore use it as is
             if !bearer
@@ -192,90 +206,86 @@ where
             }
 
             Box::pin(async move {
+                // The cache key of the token set on the request, if it came from the cache
+                let mut cached_token_key = None;
+                // Requests without a body can be sent again if the cached token was rejected
+                let mut retry = None;
+
                 // Only if there is something to upgrade
                 if let Some((cache_key, token_request)) =
-                    cache_key_and_token_req(bearer, this.gateway_admin_key.as_str())
+                    cache_key_and_token_req(bearer, org, this.gateway_admin_key.as_str())
                 {
-                    let target_url = this.auth_uri.to_string();
-
                     // Check if the token is cached.
                     if let Some(token) = this.cache_manager.get(&cache_key) {
                         trace!("JWT cache hit, setting token from cache on request");
 
+                        // Copied while it still has the key, to convert it again
+                        if req.body().size_hint().exact() == Some(0) {
+                            retry = Some(bodiless_copy(&req));
+                        }
+
                         // Token is cached and not expired, return it in the response.
                         req.headers_mut()
                             .typed_insert(Authorization::bearer(&token).unwrap());
+                        cached_token_key = Some(cache_key);
                     } else {
                         trace!("JWT cache missed, sending convert token request");
 
-                        // Token is not in the cache, send a convert request.
-                        let token_response = match PROXY_CLIENT
-                            .call(Ipv4Addr::LOCALHOST.into(), &target_url, token_request)
-                            .await
-                        {
-                            Ok(res) => res,
-                            Err(error) => {
-                                error!(?error, "failed to call authentication service");
-
-                                return Ok(Response::builder()
-                                    .status(StatusCode::SERVICE_UNAVAILABLE)
-                                    .body(boxed(Body::empty()))
-                                    .unwrap());
-                            }
-                        };
-
-                        // Bubble up auth errors
-                        if token_response.status() != StatusCode::OK {
-                            let (parts, body) = token_response.into_parts();
-                            let body = body.map_err(axum::Error::new).boxed_unsync();
-
-                            return Ok(Response::from_parts(parts, body));
-                        }
-
-                        let body = match hyper::body::to_bytes(token_response.into_body()).await {
-                            Ok(body) => body,
-                            Err(error) => {
-                                error!(
-                                    error = &error as &dyn std::error::Error,
-                                    "failed to get response body"
-                                );
-
-                                return Ok(Response::builder()
-                                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                                    .body(boxed(Body::empty()))
-                                    .unwrap());
-                            }
-                        };
-
-                        let response = match serde_json::from_slice::<ConvertResponse>(&body) {
-                            Ok(response) => response,
-                            Err(error) => {
-                                error!(
-                                    error = &error as &dyn std::error::Error,
-                                    "failed to convert body to ConvertResponse"
-                                );
-
-                                return Ok(Response::builder()
-                                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                                    .body(boxed(Body::empty()))
-                                    .unwrap());
-                            }
+                        let token = match this.convert_token(&cache_key, token_request).await {
+                            Ok(token) => token,
+                            Err(response) => return Ok(response),
                         };
 
-                        let bearer = Authorization::bearer(&response.token).expect("bearer token");
-
-                        this.cache_manager.insert(
-                            cache_key.as_str(),
-                            response.token,
-                            Duration::from_secs(CACHE_MINUTES * 60),
-                        );
-
                         trace!("token inserted in cache, request proceeding");
-                        req.headers_mut().typed_insert(bearer);
+                        req.headers_mut()
+                            .typed_insert(Authorization::bearer(&token).expect("bearer token"));
                     };
                 }
 
-                match this.inner.call(req).await {
+                let response = match this.inner.call(req).await {
+                    Ok(response) => response,
+                    Err(error) => {
+                        error!(?error, "unexpected internal error from gateway");
+
+                        return Ok(Response::builder()
+                            .status(StatusCode::SERVICE_UNAVAILABLE)
+                            .body(boxed(Body::empty()))
+                            .unwrap());
+                    }
+                };
+
+                // The cached token might have been revoked while the key is still valid, so a new
+                // one is converted. Only once, since a new token being rejected is a real failure.
+                let Some(cache_key) = cached_token_key else {
+                    return Ok(response);
+                };
+                if response.status() != StatusCode::UNAUTHORIZED {
+                    return Ok(response);
+                }
+
+                trace!("cached JWT was rejected, invalidating it");
+                this.cache_manager.invalidate(&cache_key);
+
+                let Some(mut retry) = retry else {
+                    return Ok(response);
+                };
+                let Some((_, token_request)) = cache_key_and_token_req(
+                    retry.headers().typed_get(),
+                    retry.headers().typed_get(),
+                    this.gateway_admin_key.as_str(),
+                ) else {
+                    return Ok(response);
+                };
+
+                let token = match this.convert_token(&cache_key, token_request).await {
+                    Ok(token) => token,
+                    Err(response) => return Ok(response),
+                };
+                retry
+                    .headers_mut()
+                    .typed_insert(Authorization::bearer(&token).expect("bearer token"));
+
+                match this.inner.call(retry).await {
                     Ok(response) => Ok(response),
                     Err(error) => {
                         error!(?error, "unexpected internal error from gateway");
@@ -291,13 +301,129 @@ where
     }
 }
 
+impl<S> ShuttleAuthService<S> {
+    /// Convert a key to a JWT token with the auth service and cache it. Failures are returned as
+    /// the response to send back.
+    async fn convert_token(
+        &self,
+        cache_key: &str,
+        token_request: Request<Body>,
+    ) -> Result<String, Response> {
+        let target_url = self.auth_uri.to_string();
+
+        let token_response = match PROXY_CLIENT
+            .call(Ipv4Addr::LOCALHOST.into(), &target_url, token_request)
+            .await
+        {
+            Ok(res) => res,
+            Err(error) => {
+                error!(?error, "failed to call authentication service");
+
+                return Err(Response::builder()
+                    .status(StatusCode::SERVICE_UNAVAILABLE)
+                    .body(boxed(Body::empty()))
+                    .unwrap());
+            }
+        };
+
+        // Bubble up auth errors
+        if token_response.status() != StatusCode::OK {
+            let (parts, body) = token_response.into_parts();
+            let body = body.map_err(axum::Error::new).boxed_unsync();
+
+            return Err(Response::from_parts(parts, body));
+        }
+
+        let body = match hyper::body::to_bytes(token_response.into_body()).await {
+            Ok(body) => body,
+            Err(error) => {
+                error!(
+                    error = &error as &dyn std::error::Error,
+                    "failed to get response body"
+                );
+
+                return Err(Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(boxed(Body::empty()))
+                    .unwrap());
+            }
+        };
+
+        let response = match serde_json::from_slice::<ConvertResponse>(&body) {
+            Ok(response) => response,
+            Err(error) => {
+                error!(
+                    error = &error as &dyn std::error::Error,
+                    "failed to convert body to ConvertResponse"
+                );
+
+                return Err(Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(boxed(Body::empty()))
+                    .unwrap());
+            }
+        };
+
+        self.cache_manager.insert(
+            cache_key,
+            response.token.clone(),
+            Duration::from_secs(CACHE_MINUTES * 60),
+        );
+
+        Ok(response.token)
+    }
+}
+
+/// Send a request on to the auth service as is
+async fn forward_to_auth(target_url: String, req: Request<Body>) -> Result<Response, Infallible> {
+    let response = PROXY_CLIENT
+        .call(Ipv4Addr::LOCALHOST.into(), &target_url, req)
+        .await;
+
+    match response {
+        Ok(res) => {
+            let (parts, body) = res.into_parts();
+            let body = <Body as HttpBody>::map_err(body, axum::Error::new).boxed_unsync();
+
+            Ok(Response::from_parts(parts, body))
+        }
+        Err(error) => {
+            error!(?error, "failed to call authentication service");
+
+            Ok(Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .body(boxed(Body::empty()))
+                .unwrap())
+        }
+    }
+}
+
+/// Copy of a request without a body, to send it again
+fn bodiless_copy(req: &Request<Body>) -> Request<Body> {
+    let mut copy = Request::builder()
+        .method(req.method().clone())
+        .uri(req.uri().clone())
+        .version(req.version())
+        .body(Body::empty())
+        .expect("copy of a valid request to be valid");
+    *copy.headers_mut() = req.headers().clone();
+
+    copy
+}
+
+/// Tokens are cached per key and organization, since acting for an organization gives a token with
+/// other scopes
 fn cache_key_and_token_req(
     bearer: Option<Authorization<Bearer>>,
+    org: Option<XShuttleOrganization>,
     gateway_admin_key: &str,
 ) -> Option<(String, Request<Body>)> {
     bearer.map(|bearer| {
-        let cache_key = bearer.token().trim().to_string();
-        let token_request = make_token_request("/auth/key", bearer, Some(gateway_admin_key));
+        let mut cache_key = bearer.token().trim().to_string();
+        if let Some(XShuttleOrganization(ref org_name)) = org {
+            cache_key = format!("{cache_key}:{org_name}");
+        }
+        let token_request = make_token_request("/auth/key", bearer, org, Some(gateway_admin_key));
         (cache_key, token_request)
     })
 }
@@ -305,6 +431,7 @@ fn cache_key_and_token_req(
 fn make_token_request(
     uri: &str,
     header: impl Header,
+    org: Option<XShuttleOrganization>,
     gateway_admin_key: Option<&str>,
 ) -> Request<Body> {
     let mut token_request = Request::builder().uri(uri);
@@ -313,6 +440,13 @@ fn make_token_request(
         .expect("manual request to be valid")
         .typed_insert(header);
 
+    if let Some(org) = org {
+        token_request
+            .headers_mut()
+            .expect("manual request to be valid")
+            .typed_insert(org);
+    }
+
     if let Some(key) = gateway_admin_key {
         trace!("XShuttleAdminSecret header inserted for token request");
         token_request
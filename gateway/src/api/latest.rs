@@ -10,8 +10,9 @@ use axum::handler::Handler;
 use axum::http::Request;
 use axum::middleware::{self, from_extractor};
 use axum::response::Response;
-use axum::routing::{any, delete, get, post};
+use axum::routing::{any, delete, get, post, put};
 use axum::{Json as AxumJson, Router};
+use bollard::models::ContainerInspectResponse;
 use fqdn::FQDN;
 use futures::Future;
 use http::{Method, StatusCode, Uri};
@@ -26,7 +27,8 @@ use shuttle_common::models::error::axum::CustomErrorPath;
 use shuttle_common::models::error::ErrorKind;
 use shuttle_common::models::{
     admin::ProjectResponse,
-    project::{self, ProjectName},
+    domain,
+    project::{self, ContainerLimits, ProjectName},
     stats,
 };
 use shuttle_common::{deployment, request_span, VersionInfo};
@@ -35,7 +37,7 @@ use shuttle_proto::provisioner::Ping;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::{Mutex, MutexGuard};
 use tower::ServiceBuilder;
-use tracing::{error, field, instrument, trace};
+use tracing::{error, field, instrument, trace, warn};
 use ttl_cache::TtlCache;
 use ulid::Ulid;
 use uuid::Uuid;
@@ -48,7 +50,7 @@ use crate::acme::{AccountWrapper, AcmeClient, CustomDomain};
 use crate::api::tracing::project_name_tracing_layer;
 use crate::auth::{ScopedUser, User};
 use crate::project::{ContainerInspectResponseExt, Project, ProjectCreating};
-use crate::service::GatewayService;
+use crate::service::{GatewayService, NewProject};
 use crate::task::{self, BoxedTask, TaskResult};
 use crate::tls::{GatewayCertResolver, RENEWAL_VALIDITY_THRESHOLD_IN_DAYS};
 use crate::worker::WORKER_QUEUE_SIZE;
@@ -108,12 +110,14 @@ async fn get_project(
 ) -> Result<AxumJson<project::Response>, Error> {
     let project = service.find_project(&scope).await?;
     let idle_minutes = project.state.idle_minutes();
+    let limits = project.state.container_limits();
 
     let response = project::Response {
         id: project.project_id.to_uppercase(),
         name: scope.to_string(),
         state: project.state.into(),
         idle_minutes,
+        limits,
     };
 
     Ok(AxumJson(response))
@@ -132,19 +136,25 @@ async fn check_project_name(
 
 async fn get_projects_list(
     State(RouterState { service, .. }): State<RouterState>,
-    User { name, .. }: User,
+    user: User,
     Query(PaginationDetails { page, limit }): Query<PaginationDetails>,
 ) -> Result<AxumJson<Vec<project::Response>>, Error> {
     let limit = limit.unwrap_or(u32::MAX);
     let page = page.unwrap_or(0);
     let projects = service
         // The `offset` is page size * amount of pages
-        .iter_user_projects_detailed(&name, limit * page, limit)
+        .iter_user_projects_detailed(
+            &user.owner(),
+            user.claim.projects.as_deref(),
+            limit * page,
+            limit,
+        )
         .await?
         .map(|project| project::Response {
             id: project.0.to_uppercase(),
             name: project.1.to_string(),
             idle_minutes: project.2.idle_minutes(),
+            limits: project.2.container_limits(),
             state: project.2.into(),
         })
         .collect();
@@ -157,16 +167,22 @@ async fn create_project(
     State(RouterState {
         service, sender, ..
     }): State<RouterState>,
-    User { name, claim, .. }: User,
+    user: User,
     CustomErrorPath(project_name): CustomErrorPath<ProjectName>,
     AxumJson(config): AxumJson<project::Config>,
 ) -> Result<AxumJson<project::Response>, Error> {
     let is_cch_project = project_name.is_cch_project();
+    let owner = user.owner();
+    let User { name, claim, .. } = user;
+
+    if !claim.allows_project(&project_name) {
+        return Err(Error::from_kind(ErrorKind::Forbidden));
+    }
 
     // Check that the user is within their project limits.
     let can_create_project = claim.can_create_project(
         service
-            .get_project_count(&name)
+            .get_project_count(&owner)
             .await?
             .saturating_sub(is_cch_project as u32),
     );
@@ -175,20 +191,30 @@ async fn create_project(
         service.has_capacity(is_cch_project, &claim.tier).await?;
     }
 
+    let container_limits = service
+        .container_limits_override(&project_name)
+        .await?
+        .unwrap_or_else(|| claim.tier.into());
+
     let project = service
         .create_project(
             project_name.clone(),
-            name.clone(),
+            NewProject {
+                account_name: name.clone(),
+                org_name: claim.org.clone(),
+                idle_minutes: if is_cch_project {
+                    5
+                } else {
+                    config.idle_minutes
+                },
+                container_limits,
+            },
             claim.is_admin(),
             can_create_project,
-            if is_cch_project {
-                5
-            } else {
-                config.idle_minutes
-            },
         )
         .await?;
     let idle_minutes = project.state.idle_minutes();
+    let limits = project.state.container_limits();
 
     service
         .new_task()
@@ -203,6 +229,7 @@ async fn create_project(
         name: project_name.to_string(),
         state: project.state.into(),
         idle_minutes,
+        limits,
     };
 
     Ok(AxumJson(response))
@@ -220,12 +247,14 @@ async fn destroy_project(
 ) -> Result<AxumJson<project::Response>, Error> {
     let project = service.find_project(&project_name).await?;
     let idle_minutes = project.state.idle_minutes();
+    let limits = project.state.container_limits();
 
     let mut response = project::Response {
         id: project.project_id.to_uppercase(),
         name: project_name.to_string(),
         state: project.state.into(),
         idle_minutes,
+        limits,
     };
 
     if response.state == shuttle_common::models::project::State::Destroyed {
@@ -306,6 +335,7 @@ async fn delete_project(
         !matches!(
             d.state,
             deployment::State::Running
+                | deployment::State::Unhealthy
                 | deployment::State::Completed
                 | deployment::State::Crashed
                 | deployment::State::Stopped
@@ -316,30 +346,54 @@ async fn delete_project(
         return Err(Error::from_kind(ErrorKind::ProjectHasBuildingDeployment));
     }
 
-    let running_deployments = deployments
-        .into_iter()
-        .filter(|d| d.state == deployment::State::Running);
+    let running_deployments = deployments.into_iter().filter(|d| {
+        matches!(
+            d.state,
+            deployment::State::Running | deployment::State::Unhealthy
+        )
+    });
+
+    let mut running_deployments: Vec<_> = running_deployments.map(|d| d.id).collect();
+
+    // The other services deployed from the project's workspace have deployments of their own
+    let services = project_caller.get_services().await?;
+    for service in services.iter().filter(|s| s.name != *project_name) {
+        let deployment = project_caller
+            .get_service_summary(&service.name)
+            .await?
+            .and_then(|summary| summary.deployment);
+
+        if let Some(deployment) = deployment {
+            running_deployments.push(deployment.id);
+        }
+    }
 
     for running_deployment in running_deployments {
-        let res = project_caller
-            .stop_deployment(&running_deployment.id)
-            .await?;
+        let res = project_caller.stop_deployment(&running_deployment).await?;
 
         if res.status() != StatusCode::OK {
             return Err(Error::from_kind(ErrorKind::ProjectHasRunningDeployment));
         }
     }
 
-    // check if any resources exist
-    let resources = project_caller.get_resources().await?;
+    // check if any resources exist, for every service of the project
     let mut delete_fails = Vec::new();
 
-    for resource in resources {
-        let resource_type = resource.r#type.to_string();
-        let res = project_caller.delete_resource(&resource_type).await?;
+    for service in &services {
+        let resources = project_caller.get_resources(&service.name).await?;
 
-        if res.status() != StatusCode::OK {
-            delete_fails.push(resource_type)
+        for resource in resources {
+            let resource_type = resource.r#type.to_string();
+            let res = project_caller
+                .delete_resource(&service.name, &resource_type, resource.name.as_deref())
+                .await?;
+
+            if res.status() != StatusCode::OK {
+                delete_fails.push(match resource.name {
+                    Some(name) => format!("{resource_type} ({name})"),
+                    None => resource_type,
+                })
+            }
         }
     }
 
@@ -349,6 +403,13 @@ async fn delete_project(
         )));
     }
 
+    // Logs that fail to be deleted here are still removed by the logger once past their retention
+    match project_caller.delete_logs().await {
+        Ok(res) if res.status() == StatusCode::OK => {}
+        Ok(res) => warn!(status = %res.status(), "failed to delete project logs"),
+        Err(error) => warn!(error = %error, "failed to delete project logs"),
+    }
+
     let task = service
         .new_task()
         .project(project_name.clone())
@@ -501,6 +562,38 @@ async fn get_load_admin(
     Ok(AxumJson(load))
 }
 
+#[instrument(skip_all)]
+async fn get_certificate_renewal_failures_admin(
+    State(RouterState { service, .. }): State<RouterState>,
+) -> Result<AxumJson<Vec<stats::CertificateRenewalFailure>>, Error> {
+    let failures = service.iter_certificate_renewal_failures().await?.collect();
+
+    Ok(AxumJson(failures))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name))]
+async fn get_container_limits_admin(
+    State(RouterState { service, .. }): State<RouterState>,
+    CustomErrorPath(project_name): CustomErrorPath<ProjectName>,
+) -> Result<AxumJson<Option<ContainerLimits>>, Error> {
+    let container_limits = service.container_limits_override(&project_name).await?;
+
+    Ok(AxumJson(container_limits))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name))]
+async fn set_container_limits_admin(
+    State(RouterState { service, .. }): State<RouterState>,
+    CustomErrorPath(project_name): CustomErrorPath<ProjectName>,
+    AxumJson(container_limits): AxumJson<Option<ContainerLimits>>,
+) -> Result<AxumJson<Option<ContainerLimits>>, Error> {
+    service
+        .set_container_limits_override(&project_name, container_limits)
+        .await?;
+
+    Ok(AxumJson(container_limits))
+}
+
 #[instrument(skip_all)]
 async fn delete_load_admin(
     State(RouterState { running_builds, .. }): State<RouterState>,
@@ -586,41 +679,147 @@ async fn request_custom_domain_acme_certificate(
         .create_custom_domain_certificate(&fqdn, &acme_client, &project_name, credentials)
         .await?;
 
-    let project = service.find_project(&project_name).await?;
-    let project_id = project
-        .state
-        .container()
-        .unwrap()
-        .project_id()
-        .map_err(|_| Error::custom(ErrorKind::Internal, "Missing project_id from the container"))?;
+    // Destroy and recreate the project with the new domain.
+    recreate_project_with_fqdn(
+        &service,
+        &sender,
+        project_name.clone(),
+        Some(fqdn.to_string()),
+    )
+    .await?;
 
-    let container = project.state.container().unwrap();
-    let idle_minutes = container.idle_minutes();
+    let mut buf = Vec::new();
+    buf.extend(certs.as_bytes());
+    buf.extend(private_key.as_bytes());
+    resolver
+        .serve_pem(&fqdn.to_string(), Cursor::new(buf))
+        .await?;
+    Ok(format!(
+        r#""New certificate created for {} project.""#,
+        project_name
+    ))
+}
+
+/// Destroy and recreate the container of a project so that it serves under `fqdn`, or
+/// under its default domain when `fqdn` is `None`. Projects without a container pick
+/// up their domain the next time they are started.
+async fn recreate_project_with_fqdn(
+    service: &GatewayService,
+    sender: &Sender<BoxedTask>,
+    project_name: ProjectName,
+    fqdn: Option<String>,
+) -> Result<(), Error> {
+    let project = service.find_project(&project_name).await?;
+    let Some(container) = project.state.container() else {
+        return Ok(());
+    };
+    let creating = recreated_project(&container, project_name.clone(), fqdn)?;
 
-    // Destroy and recreate the project with the new domain.
     service
         .new_task()
-        .project(project_name.clone())
+        .project(project_name)
         .and_then(task::destroy())
         .and_then(task::run_until_done())
-        .and_then(task::run({
-            let fqdn = fqdn.to_string();
-            move |ctx| {
-                let fqdn = fqdn.clone();
-                async move {
-                    let creating = ProjectCreating::new_with_random_initial_key(
-                        ctx.project_name,
-                        project_id,
-                        idle_minutes,
-                    )
-                    .with_fqdn(fqdn);
-                    TaskResult::Done(Project::Creating(creating))
-                }
-            }
+        .and_then(task::run(move |_ctx| {
+            let creating = creating.clone();
+            async move { TaskResult::Done(Project::Creating(creating)) }
         }))
         .and_then(task::run_until_done())
         .and_then(task::start_idle_deploys())
-        .send(&sender)
+        .send(sender)
+        .await?;
+
+    Ok(())
+}
+
+/// The project to create in place of `container`, which keeps the id, idle minutes and limits
+/// of the container
+fn recreated_project(
+    container: &ContainerInspectResponse,
+    project_name: ProjectName,
+    fqdn: Option<String>,
+) -> Result<ProjectCreating, Error> {
+    let project_id = container
+        .project_id()
+        .map_err(|_| Error::custom(ErrorKind::Internal, "Missing project_id from the container"))?;
+
+    let mut creating = ProjectCreating::new_with_random_initial_key(
+        project_name,
+        project_id,
+        container.idle_minutes(),
+    )
+    .with_limits(container.container_limits().unwrap_or_default());
+    if let Some(fqdn) = fqdn {
+        creating = creating.with_fqdn(fqdn);
+    }
+
+    Ok(creating)
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name))]
+async fn get_custom_domains(
+    State(RouterState { service, .. }): State<RouterState>,
+    ScopedUser {
+        scope: project_name,
+        ..
+    }: ScopedUser,
+) -> Result<AxumJson<Vec<domain::Response>>, Error> {
+    let domains = service
+        .iter_custom_domains_for_project(&project_name)
+        .await?
+        .map(|custom_domain| domain::Response {
+            certificate_expires_at: custom_domain.certificate_expires_at(),
+            fqdn: custom_domain.fqdn.to_string(),
+        })
+        .collect();
+
+    Ok(AxumJson(domains))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, fqdn = %request.fqdn))]
+async fn add_custom_domain(
+    State(RouterState {
+        service, sender, ..
+    }): State<RouterState>,
+    Extension(acme_client): Extension<AcmeClient>,
+    Extension(resolver): Extension<Arc<GatewayCertResolver>>,
+    ScopedUser {
+        scope: project_name,
+        ..
+    }: ScopedUser,
+    AxumJson(request): AxumJson<domain::AddRequest>,
+) -> Result<AxumJson<domain::Response>, Error> {
+    let fqdn: FQDN = request
+        .fqdn
+        .parse()
+        .map_err(|_err| Error::from(ErrorKind::InvalidCustomDomain))?;
+
+    // The container of a project is started with a single domain to serve
+    if service
+        .iter_custom_domains_for_project(&project_name)
+        .await?
+        .next()
+        .is_some()
+    {
+        return Err(Error::from_kind(ErrorKind::ProjectHasCustomDomain));
+    }
+    match service.project_details_for_custom_domain(&fqdn).await {
+        Ok(_) => return Err(Error::from_kind(ErrorKind::CustomDomainAlreadyExists)),
+        Err(err) if err.kind() == ErrorKind::CustomDomainNotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    service.verify_custom_domain(&project_name, &fqdn).await?;
+
+    let (certs, private_key) = acme_client
+        .create_certificate(
+            &fqdn.to_string(),
+            ChallengeType::Http01,
+            service.credentials(),
+        )
+        .await?;
+    service
+        .create_custom_domain(&project_name, &fqdn, &certs, &private_key)
         .await?;
 
     let mut buf = Vec::new();
@@ -629,10 +828,53 @@ async fn request_custom_domain_acme_certificate(
     resolver
         .serve_pem(&fqdn.to_string(), Cursor::new(buf))
         .await?;
-    Ok(format!(
-        r#""New certificate created for {} project.""#,
-        project_name
-    ))
+
+    recreate_project_with_fqdn(
+        &service,
+        &sender,
+        project_name.clone(),
+        Some(fqdn.to_string()),
+    )
+    .await?;
+
+    let custom_domain = service.project_details_for_custom_domain(&fqdn).await?;
+
+    Ok(AxumJson(domain::Response {
+        certificate_expires_at: custom_domain.certificate_expires_at(),
+        fqdn: custom_domain.fqdn.to_string(),
+    }))
+}
+
+#[instrument(skip_all, fields(shuttle.project.name = %project_name, %fqdn))]
+async fn remove_custom_domain(
+    State(RouterState {
+        service, sender, ..
+    }): State<RouterState>,
+    Extension(resolver): Extension<Arc<GatewayCertResolver>>,
+    ScopedUser {
+        scope: project_name,
+        ..
+    }: ScopedUser,
+    CustomErrorPath((_, fqdn)): CustomErrorPath<(ProjectName, String)>,
+) -> Result<AxumJson<domain::Response>, Error> {
+    let fqdn: FQDN = fqdn
+        .parse()
+        .map_err(|_err| Error::from(ErrorKind::InvalidCustomDomain))?;
+
+    let custom_domain = service.project_details_for_custom_domain(&fqdn).await?;
+    if custom_domain.project_name != project_name {
+        return Err(Error::from_kind(ErrorKind::CustomDomainNotFound));
+    }
+
+    service.delete_custom_domain(&project_name, &fqdn).await?;
+    resolver.remove(&fqdn.to_string()).await;
+
+    recreate_project_with_fqdn(&service, &sender, project_name, None).await?;
+
+    Ok(AxumJson(domain::Response {
+        certificate_expires_at: custom_domain.certificate_expires_at(),
+        fqdn: custom_domain.fqdn.to_string(),
+    }))
 }
 
 #[instrument(skip_all, fields(shuttle.project.name = %project_name, %fqdn))]
@@ -749,7 +991,7 @@ async fn renew_gateway_acme_certificate(
         let tls_path = service.state_location.join("ssl.pem");
         let certs = service
             .create_certificate(&acme_client, account.credentials())
-            .await;
+            .await?;
         resolver
             .serve_default_der(certs.clone())
             .await
@@ -840,6 +1082,15 @@ impl ApiBuilder {
                         .layer(ScopedLayer::new(vec![Scope::GatewayCertificateRenew])),
                 ),
             )
+            .route(
+                "/projects/:project_name/domains",
+                get(get_custom_domains.layer(ScopedLayer::new(vec![Scope::Project])))
+                    .post(add_custom_domain.layer(ScopedLayer::new(vec![Scope::ProjectWrite]))),
+            )
+            .route(
+                "/projects/:project_name/domains/:fqdn",
+                delete(remove_custom_domain.layer(ScopedLayer::new(vec![Scope::ProjectWrite]))),
+            )
             .layer(Extension(acme))
             .layer(Extension(resolver));
         self
@@ -888,6 +1139,14 @@ impl ApiBuilder {
             .route("/destroy", post(destroy_projects))
             .route("/idle-cch", post(idle_cch_projects))
             .route("/stats/load", get(get_load_admin).delete(delete_load_admin))
+            .route(
+                "/stats/certificates",
+                get(get_certificate_renewal_failures_admin),
+            )
+            .route(
+                "/projects/:project_name/limits",
+                get(get_container_limits_admin).put(set_container_limits_admin),
+            )
             .layer(ScopedLayer::new(vec![Scope::Admin]));
 
         const CARGO_SHUTTLE_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -983,12 +1242,21 @@ impl ApiBuilder {
 
 #[cfg(test)]
 pub mod tests {
+    use std::collections::HashMap;
     use std::sync::Arc;
 
+    use std::str::FromStr;
+
     use axum::body::Body;
     use axum::headers::Authorization;
     use axum::http::Request;
+    use bollard::models::ContainerConfig;
     use futures::TryFutureExt;
+    use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+    use hickory_resolver::proto::op::{Message, MessageType};
+    use hickory_resolver::proto::rr::rdata::CNAME;
+    use hickory_resolver::proto::rr::{Name, RData, Record};
+    use hickory_resolver::TokioAsyncResolver;
     use http::Method;
     use hyper::body::to_bytes;
     use hyper::StatusCode;
@@ -996,6 +1264,7 @@ pub mod tests {
     use shuttle_common::claims::AccountTier;
     use shuttle_common::constants::limits::{MAX_PROJECTS_DEFAULT, MAX_PROJECTS_EXTRA};
     use test_context::test_context;
+    use tokio::net::UdpSocket;
     use tokio::sync::mpsc::channel;
     use tokio::sync::oneshot;
     use tokio::time::sleep;
@@ -1260,6 +1529,206 @@ pub mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn api_add_custom_domain_that_is_not_verified() -> anyhow::Result<()> {
+        let world = World::new().await;
+        let service = Arc::new(GatewayService::init(world.args(), world.pool(), "".into()).await?);
+
+        let (sender, mut receiver) = channel::<BoxedTask>(256);
+        tokio::spawn(async move {
+            while receiver.recv().await.is_some() {
+                // do not do any work with inbound requests
+            }
+        });
+
+        let mut router = ApiBuilder::new()
+            .with_service(Arc::clone(&service))
+            .with_sender(sender)
+            .with_default_routes()
+            .with_acme(AcmeClient::new(), Arc::new(GatewayCertResolver::new()))
+            .with_auth_service(world.context().auth_uri, "dummykey".to_string())
+            .into_router();
+
+        let neo_key = world.create_user("neo", AccountTier::Basic);
+        let authorization = Authorization::bearer(&neo_key).unwrap();
+
+        router
+            .call(
+                Request::builder()
+                    .method("POST")
+                    .uri("/projects/matrix")
+                    .header("Content-Type", "application/json")
+                    .body("{\"idle_minutes\": 3}".into())
+                    .unwrap()
+                    .with_header(&authorization),
+            )
+            .map_ok(|resp| assert_eq!(resp.status(), StatusCode::OK))
+            .await
+            .unwrap();
+
+        // The reserved `.invalid` TLD never has records, so it cannot point to the project
+        router
+            .call(
+                Request::builder()
+                    .method("POST")
+                    .uri("/projects/matrix/domains")
+                    .header("Content-Type", "application/json")
+                    .body("{\"fqdn\": \"not-pointed.invalid\"}".into())
+                    .unwrap()
+                    .with_header(&authorization),
+            )
+            .map_ok(|resp| assert_eq!(resp.status(), StatusCode::BAD_REQUEST))
+            .await
+            .unwrap();
+
+        assert!(
+            service
+                .iter_custom_domains_for_project(&"matrix".parse().unwrap())
+                .await?
+                .next()
+                .is_none(),
+            "an unverified domain should not be added"
+        );
+
+        Ok(())
+    }
+
+    /// A resolver asking a local DNS server which answers every query with a CNAME to `target`
+    async fn cname_resolver(target: &str) -> TokioAsyncResolver {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        let target = Name::from_str(target).unwrap();
+
+        tokio::spawn(async move {
+            let mut buf = [0; 512];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                let query = Message::from_vec(&buf[..len]).unwrap();
+                let mut response = Message::new();
+                response
+                    .set_id(query.id())
+                    .set_message_type(MessageType::Response)
+                    .set_op_code(query.op_code())
+                    .set_recursion_desired(query.recursion_desired())
+                    .set_recursion_available(true)
+                    .add_queries(query.queries().to_vec());
+
+                for question in query.queries() {
+                    response.add_answer(Record::from_rdata(
+                        question.name().clone(),
+                        60,
+                        RData::CNAME(CNAME(target.clone())),
+                    ));
+                }
+
+                socket
+                    .send_to(&response.to_vec().unwrap(), peer)
+                    .await
+                    .unwrap();
+            }
+        });
+
+        TokioAsyncResolver::tokio(
+            ResolverConfig::from_parts(
+                None,
+                Vec::new(),
+                NameServerConfigGroup::from_ips_clear(&[addr.ip()], addr.port(), true),
+            ),
+            ResolverOpts::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn api_add_custom_domain_that_is_verified() -> anyhow::Result<()> {
+        let world = World::new().await;
+        let state_location = tempfile::tempdir()?;
+        // Never used by the self-signing client, but the handler still loads them
+        std::fs::write(
+            state_location.path().join("acme.json"),
+            serde_json::json!({
+                "id": "https://acme.invalid/acct/1",
+                "key_pkcs8": "",
+                "directory": "https://acme.invalid/directory",
+                "urls": {
+                    "newNonce": "https://acme.invalid/nonce",
+                    "newAccount": "https://acme.invalid/acct",
+                    "newOrder": "https://acme.invalid/order"
+                }
+            })
+            .to_string(),
+        )?;
+        let service = Arc::new(
+            GatewayService::init(world.args(), world.pool(), state_location.path().into())
+                .await?
+                .with_dns_resolver(cname_resolver(&format!("matrix.{}.", world.fqdn())).await),
+        );
+
+        let (sender, mut receiver) = channel::<BoxedTask>(256);
+        tokio::spawn(async move {
+            while receiver.recv().await.is_some() {
+                // do not do any work with inbound requests
+            }
+        });
+
+        let resolver = Arc::new(GatewayCertResolver::new());
+        let mut router = ApiBuilder::new()
+            .with_service(Arc::clone(&service))
+            .with_sender(sender)
+            .with_default_routes()
+            .with_acme(AcmeClient::self_signed(), Arc::clone(&resolver))
+            .with_auth_service(world.context().auth_uri, "dummykey".to_string())
+            .into_router();
+
+        let neo_key = world.create_user("neo", AccountTier::Basic);
+        let authorization = Authorization::bearer(&neo_key).unwrap();
+
+        router
+            .call(
+                Request::builder()
+                    .method("POST")
+                    .uri("/projects/matrix")
+                    .header("Content-Type", "application/json")
+                    .body("{\"idle_minutes\": 3}".into())
+                    .unwrap()
+                    .with_header(&authorization),
+            )
+            .map_ok(|resp| assert_eq!(resp.status(), StatusCode::OK))
+            .await
+            .unwrap();
+
+        let resp = router
+            .call(
+                Request::builder()
+                    .method("POST")
+                    .uri("/projects/matrix/domains")
+                    .header("Content-Type", "application/json")
+                    .body("{\"fqdn\": \"www.matrix.com\"}".into())
+                    .unwrap()
+                    .with_header(&authorization),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let domain: domain::Response = serde_json::from_slice(&body).unwrap();
+        assert_eq!(domain.fqdn, "www.matrix.com");
+        assert!(
+            domain.certificate_expires_at.is_some(),
+            "the response should tell when the certificate expires"
+        );
+
+        let custom_domain = service
+            .project_details_for_custom_domain(&"www.matrix.com".parse().unwrap())
+            .await?;
+        assert_eq!(custom_domain.project_name, "matrix".parse().unwrap());
+        assert!(
+            resolver.get("www.matrix.com").await.is_some(),
+            "the certificate should be served for the domain"
+        );
+
+        Ok(())
+    }
+
     #[test_context(TestGateway)]
     #[tokio::test]
     async fn api_create_project_above_container_limit(gateway: &mut TestGateway) {
@@ -1511,6 +1980,43 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn recreated_project_keeps_container_limits() {
+        let limits = ContainerLimits {
+            cpu_millis: 500,
+            memory_mib: 512,
+            pids: 64,
+            disk_gib: 1,
+        };
+        let project_id = Ulid::new();
+        let container = ContainerInspectResponse {
+            config: Some(ContainerConfig {
+                labels: Some(HashMap::from([
+                    ("shuttle.project_id".to_string(), project_id.to_string()),
+                    ("shuttle.idle_minutes".to_string(), "5".to_string()),
+                    (
+                        "shuttle.limits".to_string(),
+                        serde_json::to_string(&limits).unwrap(),
+                    ),
+                ])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let creating = recreated_project(
+            &container,
+            "matrix".parse().unwrap(),
+            Some("neo.the-matrix.com".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(creating.fqdn(), &Some("neo.the-matrix.com".to_string()));
+
+        let project = Project::Creating(creating);
+        assert_eq!(project.container_limits(), Some(limits));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn status() {
         let world = World::new().await;
@@ -5,7 +5,7 @@ use http::{HeaderMap, Method, Request, StatusCode, Uri};
 use hyper::Body;
 use serde::de::DeserializeOwned;
 use shuttle_common::{
-    models::{deployment, error::ErrorKind, project::ProjectName},
+    models::{deployment, error::ErrorKind, project::ProjectName, service},
     resource,
 };
 use uuid::Uuid;
@@ -109,13 +109,42 @@ impl ProjectCaller {
         .await
     }
 
-    /// Get all the resources the project is using
-    pub async fn get_resources(&self) -> Result<Vec<resource::Response>, Error> {
+    /// Get the services of the project, which are more than the one named after it when a
+    /// workspace with several services was deployed
+    pub async fn get_services(&self) -> Result<Vec<service::Response>, Error> {
+        let project_name = &self.project_name;
+
+        let services = self
+            .call_deserialize(&format!("/projects/{project_name}/services"), Method::GET)
+            .await?;
+
+        Ok(services.unwrap_or_default())
+    }
+
+    /// Get a service of the project along with its running deployment
+    pub async fn get_service_summary(
+        &self,
+        service_name: &str,
+    ) -> Result<Option<service::Summary>, Error> {
+        let project_name = &self.project_name;
+
+        self.call_deserialize(
+            &format!("/projects/{project_name}/services/{service_name}"),
+            Method::GET,
+        )
+        .await
+    }
+
+    /// Get all the resources a service of the project is using
+    pub async fn get_resources(
+        &self,
+        service_name: &str,
+    ) -> Result<Vec<resource::Response>, Error> {
         let project_name = &self.project_name;
 
         let resources = self
             .call_deserialize(
-                &format!("/projects/{project_name}/services/{project_name}/resources"),
+                &format!("/projects/{project_name}/services/{service_name}/resources"),
                 Method::GET,
             )
             .await?;
@@ -123,14 +152,29 @@ impl ProjectCaller {
         Ok(resources.unwrap_or_default())
     }
 
-    /// Delete a resource used by the project
-    pub async fn delete_resource(&self, r#type: &str) -> Result<Response<Body>, Error> {
+    /// Delete the logs of all the deployments of the project
+    pub async fn delete_logs(&self) -> Result<Response<Body>, Error> {
         let project_name = &self.project_name;
 
-        self.call(
-            &format!("/projects/{project_name}/services/{project_name}/resources/{type}"),
-            Method::DELETE,
-        )
-        .await
+        self.call(&format!("/projects/{project_name}/logs"), Method::DELETE)
+            .await
+    }
+
+    /// Delete a resource used by a service of the project, with `name` picking out a named resource
+    /// of the type
+    pub async fn delete_resource(
+        &self,
+        service_name: &str,
+        r#type: &str,
+        name: Option<&str>,
+    ) -> Result<Response<Body>, Error> {
+        let project_name = &self.project_name;
+        let mut path = format!("/projects/{project_name}/services/{service_name}/resources/{type}");
+
+        if let Some(name) = name {
+            path.push_str(&format!("?name={name}"));
+        }
+
+        self.call(&path, Method::DELETE).await
     }
 }
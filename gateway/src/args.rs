@@ -4,6 +4,8 @@ use clap::{Parser, Subcommand, ValueEnum};
 use fqdn::FQDN;
 use http::Uri;
 
+use crate::tls::RENEWAL_VALIDITY_THRESHOLD_IN_DAYS;
+
 #[derive(Parser, Debug)]
 pub struct Args {
     /// Where to store gateway state (such as sqlite state, and certs)
@@ -39,6 +41,12 @@ pub struct StartArgs {
     /// Allows to disable the use of TLS in the user proxy service (DANGEROUS)
     #[arg(long, default_value = "enable")]
     pub use_tls: UseTls,
+    /// How often to look for certificates which need to be renewed, in minutes
+    #[arg(long, default_value = "60")]
+    pub certificate_renewal_interval: u64,
+    /// Renew certificates which expire within this many days
+    #[arg(long, default_value_t = RENEWAL_VALIDITY_THRESHOLD_IN_DAYS)]
+    pub certificate_renewal_window: i64,
     #[command(flatten)]
     pub context: ContextArgs,
 }
@@ -89,6 +97,10 @@ pub struct ContextArgs {
     /// Maximum number of containers to start on this node before blocking any project
     #[arg(long, default_value = "990")]
     pub hard_container_limit: u32,
+    /// Limit the disk space used by the container and volume of each project. Needs storage which
+    /// supports quotas, such as overlay2 and local volumes on xfs mounted with `pquota`
+    #[arg(long)]
+    pub enforce_disk_quota: bool,
 
     /// Allow tests to set some extra /etc/hosts
     pub extra_hosts: Vec<String>,
@@ -10,7 +10,7 @@ use shuttle_common::models::project::ProjectName;
 use tracing::{trace, Span};
 
 use crate::api::latest::RouterState;
-use crate::{AccountName, Error, ErrorKind};
+use crate::{AccountName, Error, ErrorKind, ProjectOwner};
 
 /// A wrapper to enrich a token with user details
 ///
@@ -42,9 +42,15 @@ where
 
         let RouterState { service, .. } = RouterState::from_ref(state);
 
+        let owner = ProjectOwner::new(name.clone(), claim.org.clone());
         let user = User {
             claim: claim.clone(),
-            projects: service.iter_user_projects(&name).await?.collect(),
+            projects: service
+                .iter_user_projects(&owner)
+                .await?
+                // Keys can be restricted to only some of the projects of the owner
+                .filter(|project| claim.allows_project(project))
+                .collect(),
             name,
         };
 
@@ -54,6 +60,13 @@ where
     }
 }
 
+impl User {
+    /// The owner of the projects this user is working on
+    pub fn owner(&self) -> ProjectOwner {
+        ProjectOwner::new(self.name.clone(), self.claim.org.clone())
+    }
+}
+
 /// A wrapper for a guard that validates a user's API token *and*
 /// scopes the request to a project they own.
 ///
@@ -84,7 +97,10 @@ where
                 .map_err(|_| Error::from(ErrorKind::InvalidProjectName(InvalidProjectName)))?,
         };
 
-        if user.projects.contains(&scope) || user.claim.scopes.contains(&Scope::Admin) {
+        let is_allowed =
+            user.projects.contains(&scope) || user.claim.scopes.contains(&Scope::Admin);
+
+        if is_allowed && user.claim.allows_project(&scope) {
             Ok(Self { user, scope })
         } else {
             Err(Error::from(ErrorKind::ProjectNotFound(scope.to_string())))
@@ -167,6 +167,26 @@ impl<'de> Deserialize<'de> for AccountName {
     }
 }
 
+/// Who a project belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectOwner {
+    /// The personal projects of an account
+    Account(AccountName),
+    /// The projects shared by the members of an organization
+    Organization(String),
+}
+
+impl ProjectOwner {
+    /// The owner of the projects an account works on, which is the organization it is acting
+    /// for, if any
+    pub fn new(account_name: AccountName, org_name: Option<String>) -> Self {
+        match org_name {
+            Some(org_name) => Self::Organization(org_name),
+            None => Self::Account(account_name),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ProjectDetails {
     pub project_name: ProjectName,
@@ -294,7 +314,7 @@ pub mod tests {
     use jsonwebtoken::EncodingKey;
     use rand::distributions::{Alphanumeric, DistString, Distribution, Uniform};
     use ring::signature::{self, Ed25519KeyPair, KeyPair};
-    use shuttle_common::backends::auth::ConvertResponse;
+    use shuttle_common::backends::auth::{ConvertResponse, Jwk, Jwks, Revocations};
     use shuttle_common::claims::{AccountTier, Claim};
     use shuttle_common::models::deployment::DeploymentRequest;
     use shuttle_common::models::{project, service};
@@ -313,6 +333,7 @@ pub mod tests {
     use crate::proxy::UserServiceBuilder;
     use crate::service::{ContainerSettings, GatewayService, MIGRATIONS};
     use crate::task::BoxedTask;
+    use crate::tls::RENEWAL_VALIDITY_THRESHOLD_IN_DAYS;
     use crate::worker::Worker;
     use crate::{DockerContext, Error};
 
@@ -567,6 +588,8 @@ pub mod tests {
                 user,
                 bouncer,
                 use_tls: UseTls::Disable,
+                certificate_renewal_interval: 60,
+                certificate_renewal_window: RENEWAL_VALIDITY_THRESHOLD_IN_DAYS,
                 context: ContextArgs {
                     docker_host,
                     image,
@@ -599,6 +622,7 @@ pub mod tests {
                     cch_container_limit: 1,
                     soft_container_limit: 2,
                     hard_container_limit: 3,
+                    enforce_disk_quota: false,
 
                     // Allow access to the auth on the host
                     extra_hosts: vec!["host.docker.internal:host-gateway".to_string()],
@@ -770,11 +794,21 @@ pub mod tests {
 
             let router = Router::new()
                 .route(
-                    "/public-key",
+                    "/.well-known/jwks.json",
                     get(|extract::State(state): extract::State<Arc<Mutex<Self>>>| async move {
-                        state.lock().unwrap().public_key.clone()
+                        let jwks = Jwks {
+                            keys: vec![Jwk::ed25519(
+                                "test".to_string(),
+                                &state.lock().unwrap().public_key,
+                            )],
+                        };
+                        serde_json::to_vec(&jwks).unwrap()
                     }),
                 )
+                .route(
+                    "/auth/revocations",
+                    get(|| async { serde_json::to_vec(&Revocations::default()).unwrap() }),
+                )
                 .route(
                     "/auth/key",
                     get(|extract::State(state): extract::State<Arc<Mutex<Self>>>, TypedHeader(bearer): TypedHeader<Authorization<Bearer>> | async move {
@@ -783,7 +817,7 @@ pub mod tests {
                         if let Some(tier) = state.users.get(bearer.token()) {
                             let claim = Claim::new(bearer.token().to_string(), (*tier).into(), *tier, *tier);
                             let token = claim.into_token(&state.encoding_key)?;
-                            Ok(serde_json::to_vec(&ConvertResponse { token }).unwrap())
+                            Ok(serde_json::to_vec(&ConvertResponse { token, refresh_token: None }).unwrap())
                         } else {
                             Err(StatusCode::NOT_FOUND)
                         }
@@ -21,6 +21,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::time::Instant;
 use tracing::{debug, error, info, info_span, trace, warn, Instrument};
 
 #[tokio::main(flavor = "multi_thread")]
@@ -178,6 +179,35 @@ async fn start(
                 .unwrap();
         }
 
+        // Every so often renew the certificates which are about to expire
+        tokio::spawn({
+            let gateway = gateway.clone();
+            let acme_client = acme_client.clone();
+            let resolver = resolver.clone();
+            let period = Duration::from_secs(args.certificate_renewal_interval * 60);
+            let renewal_window = chrono::Duration::days(args.certificate_renewal_window);
+            async move {
+                // The first check waits a period so it does not race the startup certificate fetch
+                let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+                loop {
+                    interval.tick().await;
+
+                    if let Err(error) = gateway
+                        .renew_expiring_certificates(&acme_client, &resolver, renewal_window)
+                        .instrument(info_span!("renewing certificates"))
+                        .await
+                    {
+                        error!(
+                            error = &error as &dyn std::error::Error,
+                            "failed to renew certificates"
+                        );
+                    }
+                }
+            }
+        });
+
         tokio::spawn(async move {
             // Make sure we have a certificate for ourselves.
             let certs = gateway
@@ -8,7 +8,7 @@ use bollard::container::{
     StopContainerOptions,
 };
 use bollard::errors::Error as DockerError;
-use bollard::models::{ContainerInspectResponse, ContainerStateStatusEnum};
+use bollard::models::{ContainerInspectResponse, ContainerStateStatusEnum, HostConfig};
 use bollard::network::{ConnectNetworkOptions, DisconnectNetworkOptions};
 use bollard::service::MountTypeEnum;
 use bollard::system::EventsOptions;
@@ -26,7 +26,7 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Deserializer, Serialize};
 use shuttle_common::backends::headers::{X_SHUTTLE_ACCOUNT_NAME, X_SHUTTLE_ADMIN_SECRET};
 use shuttle_common::constants::{default_idle_minutes, DEFAULT_IDLE_MINUTES};
-use shuttle_common::models::project::ProjectName;
+use shuttle_common::models::project::{ContainerLimits, ProjectName};
 use shuttle_common::models::service;
 use tokio::time::{sleep, timeout};
 use tracing::{debug, error, info, instrument, trace, warn};
@@ -129,6 +129,13 @@ pub trait ContainerInspectResponseExt {
         DEFAULT_IDLE_MINUTES
     }
 
+    /// The limits the container was created with, if it has them
+    fn container_limits(&self) -> Option<ContainerLimits> {
+        let labels = self.container().config.as_ref()?.labels.as_ref()?;
+
+        serde_json::from_str(labels.get("shuttle.limits")?).ok()
+    }
+
     fn find_arg_and_then<'s, F, O>(&'s self, find: &str, and_then: F) -> Result<O, ProjectError>
     where
         F: FnOnce(&'s str) -> O,
@@ -413,6 +420,15 @@ impl Project {
     pub fn idle_minutes(&self) -> Option<u64> {
         self.container().map(|container| container.idle_minutes())
     }
+
+    pub fn container_limits(&self) -> Option<ContainerLimits> {
+        match self {
+            Self::Creating(ProjectCreating { limits, .. }) => Some(*limits),
+            _ => self
+                .container()
+                .and_then(|container| container.container_limits()),
+        }
+    }
 }
 
 impl From<Project> for shuttle_common::models::project::State {
@@ -745,6 +761,9 @@ pub struct ProjectCreating {
     /// Label set on container as to how many minutes to wait before a project is considered idle
     #[serde(default = "default_idle_minutes")]
     idle_minutes: u64,
+    /// Resources the container can use, also set as a label on it so they survive a recreate
+    #[serde(default)]
+    limits: ContainerLimits,
 }
 
 impl ProjectCreating {
@@ -763,6 +782,7 @@ impl ProjectCreating {
             from: None,
             recreate_count: 0,
             idle_minutes,
+            limits: Default::default(),
         }
     }
 
@@ -774,6 +794,7 @@ impl ProjectCreating {
         let project_id = container.project_id()?;
         let idle_minutes = container.idle_minutes();
         let initial_key = container.initial_key()?;
+        let limits = container.container_limits().unwrap_or_default();
 
         Ok(Self {
             project_name,
@@ -784,6 +805,7 @@ impl ProjectCreating {
             from: Some(container),
             recreate_count,
             idle_minutes,
+            limits,
         })
     }
 
@@ -806,6 +828,11 @@ impl ProjectCreating {
         Self::new(project_name, project_id, initial_key, idle_minutes)
     }
 
+    pub fn with_limits(mut self, limits: ContainerLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     pub fn with_image(mut self, image: String) -> Self {
         self.image = Some(image);
         self
@@ -852,6 +879,7 @@ impl ProjectCreating {
             resource_recorder_uri,
             fqdn: public,
             extra_hosts,
+            enforce_disk_quota,
             ..
         } = ctx.container_settings();
 
@@ -861,6 +889,7 @@ impl ProjectCreating {
             fqdn,
             image,
             idle_minutes,
+            limits,
             ..
         } = &self;
 
@@ -914,20 +943,43 @@ impl ProjectCreating {
 
         let mut config = Config::<String>::from(container_config);
 
-        config.host_config = deserialize_json!({
+        // Containers recreated from an older one keep its labels, so always set the current limits
+        config.labels.get_or_insert_with(Default::default).insert(
+            "shuttle.limits".to_string(),
+            serde_json::to_string(limits).expect("container limits to serialize"),
+        );
+
+        // Only some storage drivers support quotas, and creating the container fails on the others.
+        // Builds and the state of the deployer are kept on the volume, so it is limited as well as
+        // the writable layer of the container. The volume only gets its size when it is created.
+        let disk_quota = enforce_disk_quota.then(|| format!("{}G", limits.disk_gib));
+
+        let memory = (limits.memory_mib * 1024 * 1024) as i64;
+        let mut host_config: HostConfig = deserialize_json!({
             "Mounts": [{
                 "Target": "/opt/shuttle",
                 "Source": self.volume_name(ctx),
-                "Type": "volume"
+                "Type": "volume",
+                "VolumeOptions": disk_quota.as_ref().map(|size| serde_json::json!({
+                    "DriverConfig": {
+                        "Name": "local",
+                        "Options": { "size": size }
+                    }
+                }))
             }],
             // https://docs.docker.com/config/containers/resource_constraints/#memory
-            "Memory": 6442450000i64, // 6 GiB hard limit
-            "MemoryReservation": 4295000000i64, // 4 GiB soft limit, applied if host is low on memory
+            "Memory": memory, // hard limit
+            "MemoryReservation": memory / 3 * 2, // soft limit, applied if host is low on memory
             // https://docs.docker.com/config/containers/resource_constraints/#cpu
             "CpuPeriod": 100000i64,
-            "CpuQuota": 400000i64,
+            "CpuQuota": (limits.cpu_millis * 100) as i64,
+            "PidsLimit": limits.pids as i64,
             "ExtraHosts": extra_hosts,
         });
+        if let Some(size) = disk_quota {
+            host_config.storage_opt = Some(HashMap::from([("size".to_string(), size)]));
+        }
+        config.host_config = Some(host_config);
 
         // TODO: remove the config from the log message, add that into the span attributes as a
         // serialized JSON.
@@ -2026,6 +2078,7 @@ pub mod tests {
                 from: None,
                 recreate_count: 0,
                 idle_minutes: 0,
+                limits: Default::default(),
             }),
             #[assertion = "Container created, attach network"]
             Ok(Project::Attaching(ProjectAttaching {
@@ -24,6 +24,7 @@ use opentelemetry::global;
 use opentelemetry_http::HeaderInjector;
 use shuttle_common::backends::headers::XShuttleProject;
 use shuttle_common::models::error::InvalidProjectName;
+use shuttle_common::models::project::ProjectName;
 use tokio::sync::mpsc::Sender;
 use tower::{Service, ServiceBuilder};
 use tower_sanitize_path::SanitizePath;
@@ -99,6 +100,26 @@ where
 }
 
 impl UserProxy {
+    /// Services of a project other than its main one are served on `<service>--<project>.<public>`,
+    /// which the certificate of `*.<public>` covers as well. A project whose own name has a `--`
+    /// in it keeps being served on its label.
+    async fn project_of_label<'l>(&self, label: &'l str) -> &'l str {
+        let Some((_, project_name)) = label.rsplit_once("--") else {
+            return label;
+        };
+
+        let is_project = match label.parse::<ProjectName>() {
+            Ok(name) => self.gateway.find_project(&name).await.is_ok(),
+            Err(_) => false,
+        };
+
+        if is_project {
+            label
+        } else {
+            project_name
+        }
+    }
+
     async fn proxy(
         self,
         task_sender: Sender<BoxedTask>,
@@ -118,10 +139,8 @@ impl UserProxy {
         let project_name = if fqdn.is_subdomain_of(&self.public)
             && fqdn.depth() - self.public.depth() == 1
         {
-            fqdn.labels()
-                .next()
-                .unwrap()
-                .to_owned()
+            self.project_of_label(fqdn.labels().next().unwrap())
+                .await
                 .parse()
                 .map_err(|_| Error::from_kind(ErrorKind::InvalidProjectName(InvalidProjectName)))?
         } else if let Ok(CustomDomain { project_name, .. }) =
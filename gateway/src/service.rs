@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::io;
 use std::io::Cursor;
 use std::net::Ipv4Addr;
@@ -11,7 +12,11 @@ use axum::http::Request;
 use axum::response::Response;
 use bollard::container::StatsOptions;
 use bollard::{Docker, API_DEFAULT_VERSION};
+use chrono::{DateTime, TimeZone, Utc};
 use fqdn::{Fqdn, FQDN};
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use hickory_resolver::proto::rr::{RData, RecordType};
+use hickory_resolver::TokioAsyncResolver;
 use http::Uri;
 use hyper::client::connect::dns::GaiResolver;
 use hyper::client::HttpConnector;
@@ -23,10 +28,11 @@ use opentelemetry::global;
 use opentelemetry_http::HeaderInjector;
 use shuttle_common::backends::headers::{XShuttleAccountName, XShuttleAdminSecret};
 use shuttle_common::claims::AccountTier;
-use shuttle_common::models::project::{ProjectName, State};
+use shuttle_common::models::project::{ContainerLimits, ProjectName, State};
+use shuttle_common::models::stats;
 use sqlx::error::DatabaseError;
 use sqlx::migrate::Migrator;
-use sqlx::sqlite::SqlitePool;
+use sqlx::sqlite::{Sqlite, SqlitePool};
 use sqlx::types::Json as SqlxJson;
 use sqlx::{query, Error as SqlxError, QueryBuilder, Row};
 use tokio::sync::mpsc::Sender;
@@ -37,15 +43,15 @@ use tracing::{debug, error, info, instrument, trace, warn, Span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use ulid::Ulid;
 
-use crate::acme::{AcmeClient, CustomDomain};
+use crate::acme::{pem_certificate_expires_at, AcmeClient, CustomDomain};
 use crate::args::ContextArgs;
 use crate::project::{Project, ProjectCreating, ProjectError, IS_HEALTHY_TIMEOUT};
 use crate::task::{self, BoxedTask, TaskBuilder};
-use crate::tls::ChainAndPrivateKey;
+use crate::tls::{ChainAndPrivateKey, GatewayCertResolver};
 use crate::worker::TaskRouter;
 use crate::{
-    AccountName, DockerContext, DockerStatsSource, Error, ErrorKind, ProjectDetails, AUTH_CLIENT,
-    DOCKER_STATS_PATH_CGROUP_V1, DOCKER_STATS_PATH_CGROUP_V2,
+    AccountName, DockerContext, DockerStatsSource, Error, ErrorKind, ProjectDetails, ProjectOwner,
+    AUTH_CLIENT, DOCKER_STATS_PATH_CGROUP_V1, DOCKER_STATS_PATH_CGROUP_V2,
 };
 
 pub static MIGRATIONS: Migrator = sqlx::migrate!("./migrations");
@@ -69,6 +75,7 @@ pub struct ContainerSettingsBuilder {
     network_name: Option<String>,
     fqdn: Option<String>,
     extra_hosts: Option<Vec<String>>,
+    enforce_disk_quota: Option<bool>,
 }
 
 impl Default for ContainerSettingsBuilder {
@@ -89,6 +96,7 @@ impl ContainerSettingsBuilder {
             network_name: None,
             fqdn: None,
             extra_hosts: None,
+            enforce_disk_quota: None,
         }
     }
 
@@ -103,6 +111,7 @@ impl ContainerSettingsBuilder {
             image,
             proxy_fqdn,
             extra_hosts,
+            enforce_disk_quota,
             ..
         } = args;
         self.prefix(prefix)
@@ -114,6 +123,7 @@ impl ContainerSettingsBuilder {
             .network_name(network_name)
             .fqdn(proxy_fqdn)
             .extra_hosts(extra_hosts)
+            .enforce_disk_quota(*enforce_disk_quota)
             .build()
             .await
     }
@@ -163,6 +173,11 @@ impl ContainerSettingsBuilder {
         self
     }
 
+    pub fn enforce_disk_quota(mut self, enforce_disk_quota: bool) -> Self {
+        self.enforce_disk_quota = Some(enforce_disk_quota);
+        self
+    }
+
     pub async fn build(mut self) -> ContainerSettings {
         let prefix = self.prefix.take().unwrap();
         let image = self.image.take().unwrap();
@@ -171,6 +186,7 @@ impl ContainerSettingsBuilder {
         let auth_uri = self.auth_uri.take().unwrap();
         let resource_recorder_uri = self.resource_recorder_uri.take().unwrap();
         let extra_hosts = self.extra_hosts.take().unwrap();
+        let enforce_disk_quota = self.enforce_disk_quota.take().unwrap_or_default();
 
         let network_name = self.network_name.take().unwrap();
         let fqdn = self.fqdn.take().unwrap();
@@ -185,6 +201,7 @@ impl ContainerSettingsBuilder {
             network_name,
             fqdn,
             extra_hosts,
+            enforce_disk_quota,
         }
     }
 }
@@ -200,6 +217,8 @@ pub struct ContainerSettings {
     pub network_name: String,
     pub fqdn: String,
     pub extra_hosts: Vec<String>,
+    /// Whether to limit the size of the writable layer of containers
+    pub enforce_disk_quota: bool,
 }
 
 impl ContainerSettings {
@@ -264,6 +283,9 @@ pub struct GatewayService {
     // We store these because we'll need them for the health checks
     provisioner_host: Endpoint,
     auth_host: Uri,
+
+    /// Looks up the records of custom domains to verify them
+    dns_resolver: TokioAsyncResolver,
 }
 
 impl GatewayService {
@@ -318,6 +340,12 @@ impl GatewayService {
         );
 
         let task_router = TaskRouter::default();
+
+        let dns_resolver = TokioAsyncResolver::tokio_from_system_conf().unwrap_or_else(|error| {
+            warn!(%error, "failed to read the system DNS config, using the default resolver");
+            TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default())
+        });
+
         Ok(Self {
             provider,
             db,
@@ -329,9 +357,17 @@ impl GatewayService {
             cch_container_limit: args.cch_container_limit,
             soft_container_limit: args.soft_container_limit,
             hard_container_limit: args.hard_container_limit,
+            dns_resolver,
         })
     }
 
+    /// Look up the records of custom domains with `dns_resolver` instead of the system resolver
+    #[cfg(test)]
+    pub fn with_dns_resolver(mut self, dns_resolver: TokioAsyncResolver) -> Self {
+        self.dns_resolver = dns_resolver;
+        self
+    }
+
     pub async fn route(
         &self,
         project: &Project,
@@ -455,18 +491,30 @@ impl GatewayService {
         )
     }
 
+    /// The projects of an owner, only keeping those in `projects` when it is set. The restriction
+    /// is applied before the pagination so that every page is full.
     pub async fn iter_user_projects_detailed(
         &self,
-        account_name: &AccountName,
+        owner: &ProjectOwner,
+        projects: Option<&[String]>,
         offset: u32,
         limit: u32,
     ) -> Result<impl Iterator<Item = (String, ProjectName, Project)>, Error> {
         let mut query = QueryBuilder::new(
-            "SELECT project_id, project_name, project_state FROM projects WHERE account_name = ",
+            "SELECT project_id, project_name, project_state FROM projects WHERE ",
         );
 
+        push_owner_condition(&mut query, owner);
+        if let Some(projects) = projects {
+            query.push(" AND project_name IN (");
+            let mut names = query.separated(", ");
+            for project in projects {
+                names.push_bind(project.clone());
+            }
+            // `IN ()` is valid in SQLite and matches nothing
+            names.push_unseparated(")");
+        }
         query
-            .push_bind(account_name)
             .push(" ORDER BY project_id DESC, project_name LIMIT ")
             .push_bind(limit);
 
@@ -544,10 +592,13 @@ impl GatewayService {
 
     pub async fn iter_user_projects(
         &self,
-        AccountName(account_name): &AccountName,
+        owner: &ProjectOwner,
     ) -> Result<impl Iterator<Item = ProjectName>, Error> {
-        let iter = query("SELECT project_name FROM projects WHERE account_name = ?1")
-            .bind(account_name)
+        let mut query = QueryBuilder::new("SELECT project_name FROM projects WHERE ");
+
+        push_owner_condition(&mut query, owner);
+        let iter = query
+            .build()
             .fetch_all(&self.db)
             .await?
             .into_iter()
@@ -558,22 +609,29 @@ impl GatewayService {
     pub async fn create_project(
         &self,
         project_name: ProjectName,
-        account_name: AccountName,
+        new_project: NewProject,
         is_admin: bool,
         can_create_project: bool,
-        idle_minutes: u64,
     ) -> Result<FindProjectPayload, Error> {
+        let NewProject {
+            account_name,
+            org_name,
+            idle_minutes,
+            container_limits,
+        } = &new_project;
+
         if let Some(row) = query(
             r#"
         SELECT project_name, project_id, account_name, initial_key, project_state
         FROM projects
         WHERE (project_name = ?1)
-        AND (account_name = ?2 OR ?3)
+        AND ((account_name = ?2 AND org_name IS NULL AND ?4 IS NULL) OR org_name = ?4 OR ?3)
         "#,
         )
         .bind(&project_name)
-        .bind(&account_name)
+        .bind(account_name)
         .bind(is_admin)
+        .bind(org_name)
         .fetch_optional(&self.db)
         .await?
         {
@@ -598,8 +656,9 @@ impl GatewayService {
                             format!("The project id of the destroyed project is not a valid ULID: {err}"),
                         )
                     })?,
-                    idle_minutes,
-                );
+                    *idle_minutes,
+                )
+                .with_limits(*container_limits);
                 // Restore previous custom domain, if any
                 match self.find_custom_domain_for_project(&project_id).await {
                     Ok(custom_domain) => {
@@ -658,20 +717,58 @@ impl GatewayService {
             // Attempt to create a new one. This will fail
             // outright if the project already exists (this happens if
             // it belongs to another account).
-            self.insert_project(project_name, Ulid::new(), account_name, idle_minutes)
+            self.insert_project(project_name, Ulid::new(), new_project)
                 .await
         } else {
             Err(Error::from_kind(ErrorKind::TooManyProjects))
         }
     }
 
-    pub async fn get_project_count(&self, account_name: &AccountName) -> Result<u32, Error> {
-        let proj_count: u32 =
-            query("SELECT COUNT(project_name) FROM projects WHERE account_name = ?1")
-                .bind(account_name)
-                .fetch_one(&self.db)
+    /// Get the limits an admin set for the containers of a project, instead of the limits of
+    /// its owner's tier
+    pub async fn container_limits_override(
+        &self,
+        project_name: &ProjectName,
+    ) -> Result<Option<ContainerLimits>, Error> {
+        let container_limits =
+            query("SELECT container_limits FROM projects WHERE project_name = ?1")
+                .bind(project_name)
+                .fetch_optional(&self.db)
                 .await?
-                .get::<_, usize>(0);
+                .and_then(|row| row.get::<Option<SqlxJson<ContainerLimits>>, _>("container_limits"))
+                .map(|limits| limits.0);
+
+        Ok(container_limits)
+    }
+
+    /// Set or clear the container limits override of a project. It applies the next time the
+    /// project container is created.
+    pub async fn set_container_limits_override(
+        &self,
+        project_name: &ProjectName,
+        container_limits: Option<ContainerLimits>,
+    ) -> Result<(), Error> {
+        let updated = query("UPDATE projects SET container_limits = ?1 WHERE project_name = ?2")
+            .bind(container_limits.map(SqlxJson))
+            .bind(project_name)
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+
+        if updated == 0 {
+            return Err(Error::from_kind(ErrorKind::ProjectNotFound(
+                project_name.to_string(),
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_project_count(&self, owner: &ProjectOwner) -> Result<u32, Error> {
+        let mut query = QueryBuilder::new("SELECT COUNT(project_name) FROM projects WHERE ");
+
+        push_owner_condition(&mut query, owner);
+        let proj_count: u32 = query.build().fetch_one(&self.db).await?.get::<_, usize>(0);
 
         Ok(proj_count)
     }
@@ -680,23 +777,31 @@ impl GatewayService {
         &self,
         project_name: ProjectName,
         project_id: Ulid,
-        account_name: AccountName,
-        idle_minutes: u64,
+        new_project: NewProject,
     ) -> Result<FindProjectPayload, Error> {
+        let NewProject {
+            account_name,
+            org_name,
+            idle_minutes,
+            container_limits,
+        } = new_project;
+
         let project = SqlxJson(Project::Creating(
             ProjectCreating::new_with_random_initial_key(
                 project_name.clone(),
                 project_id,
                 idle_minutes,
-            ),
+            )
+            .with_limits(container_limits),
         ));
 
-        query("INSERT INTO projects (project_id, project_name, account_name, initial_key, project_state) VALUES (?1, ?2, ?3, ?4, ?5)")
+        query("INSERT INTO projects (project_id, project_name, account_name, initial_key, project_state, org_name) VALUES (?1, ?2, ?3, ?4, ?5, ?6)")
             .bind(&project_id.to_string())
             .bind(&project_name)
             .bind(&account_name)
             .bind(project.initial_key().unwrap())
             .bind(&project)
+            .bind(&org_name)
             .execute(&self.db)
             .await
             .map_err(|err| {
@@ -728,6 +833,11 @@ impl GatewayService {
 
         let mut transaction = self.db.begin().await?;
 
+        query("DELETE FROM certificate_renewal_failures WHERE fqdn IN (SELECT fqdn FROM custom_domains WHERE project_id = ?1)")
+            .bind(&project_id)
+            .execute(&mut *transaction)
+            .await?;
+
         query("DELETE FROM custom_domains WHERE project_id = ?1")
             .bind(project_id)
             .execute(&mut *transaction)
@@ -822,6 +932,97 @@ impl GatewayService {
         Ok(custom_domain)
     }
 
+    pub async fn iter_custom_domains_for_project(
+        &self,
+        project_name: &ProjectName,
+    ) -> Result<impl Iterator<Item = CustomDomain>, Error> {
+        let iter = query(
+            "SELECT fqdn, project_name, certificate, private_key FROM custom_domains AS cd JOIN projects AS p ON cd.project_id = p.project_id WHERE p.project_name = ?1",
+        )
+        .bind(project_name)
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(|row| CustomDomain {
+            fqdn: row.get::<&str, _>("fqdn").parse().unwrap(),
+            project_name: row.try_get("project_name").unwrap(),
+            certificate: row.get("certificate"),
+            private_key: row.get("private_key"),
+        });
+        Ok(iter)
+    }
+
+    pub async fn delete_custom_domain(
+        &self,
+        project_name: &ProjectName,
+        fqdn: &Fqdn,
+    ) -> Result<(), Error> {
+        let deleted = query(
+            "DELETE FROM custom_domains WHERE fqdn = ?1 AND project_id = (SELECT project_id FROM projects WHERE project_name = ?2)",
+        )
+        .bind(fqdn.to_string())
+        .bind(project_name)
+        .execute(&self.db)
+        .await?
+        .rows_affected();
+
+        if deleted == 0 {
+            return Err(Error::from_kind(ErrorKind::CustomDomainNotFound));
+        }
+
+        query("DELETE FROM certificate_renewal_failures WHERE fqdn = ?1")
+            .bind(fqdn.to_string())
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Check that a custom domain has a CNAME record pointing to the default domain of the project.
+    /// Every project domain resolves to the addresses of this gateway, so only the exact target of
+    /// the record shows the domain is meant for this project.
+    pub async fn verify_custom_domain(
+        &self,
+        project_name: &ProjectName,
+        fqdn: &Fqdn,
+    ) -> Result<(), Error> {
+        let public: FQDN = self.context().settings.fqdn.parse().unwrap();
+        if fqdn.is_subdomain_of(&public) {
+            return Err(Error::from_kind(ErrorKind::InvalidCustomDomain));
+        }
+
+        let project_fqdn = format!("{project_name}.{public}");
+        let targets = self
+            .dns_resolver
+            .lookup(fqdn.to_string(), RecordType::CNAME)
+            .await
+            .map(|lookup| {
+                lookup
+                    .record_iter()
+                    .filter_map(|record| match record.data() {
+                        Some(RData::CNAME(target)) => Some(target.to_string()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_else(|error| {
+                debug!(%fqdn, %error, "failed to look up CNAME of custom domain");
+                Vec::new()
+            });
+
+        if !targets.iter().any(|target| {
+            target
+                .trim_end_matches('.')
+                .eq_ignore_ascii_case(&project_fqdn)
+        }) {
+            return Err(Error::from_kind(ErrorKind::CustomDomainNotVerified(format!(
+                "{fqdn} does not have a CNAME record pointing to {project_fqdn}. Add one, and try again once the record has propagated."
+            ))));
+        }
+
+        Ok(())
+    }
+
     pub async fn iter_projects_detailed(
         &self,
     ) -> Result<impl Iterator<Item = ProjectDetails>, Error> {
@@ -868,22 +1069,187 @@ impl GatewayService {
         &self,
         acme: &AcmeClient,
         creds: AccountCredentials<'a>,
-    ) -> ChainAndPrivateKey {
-        let public: FQDN = self.context().settings.fqdn.parse().unwrap();
-        let identifier = format!("*.{public}");
+    ) -> Result<ChainAndPrivateKey, Error> {
+        let identifier = self.gateway_certificate_identifier();
 
         // Use ::Dns01 challenge because that's the only supported
         // challenge type for wildcard domains.
         let (chain, private_key) = acme
             .create_certificate(&identifier, ChallengeType::Dns01, creds)
-            .await
-            .unwrap();
+            .await?;
 
         let mut buf = Vec::new();
         buf.extend(chain.as_bytes());
         buf.extend(private_key.as_bytes());
 
-        ChainAndPrivateKey::parse_pem(Cursor::new(buf)).expect("Malformed PEM buffer.")
+        ChainAndPrivateKey::parse_pem(Cursor::new(buf))
+    }
+
+    fn gateway_certificate_identifier(&self) -> String {
+        let public: FQDN = self.context().settings.fqdn.parse().unwrap();
+        format!("*.{public}")
+    }
+
+    /// Renew the gateway certificate and the custom domain certificates which expire within
+    /// `renewal_window`, and serve the new certificates straight away. Failed renewals are
+    /// recorded, and retried on later runs with an increasing delay. Certificates which cannot be
+    /// parsed are replaced, and a replacement which cannot be parsed either counts as a failure.
+    pub async fn renew_expiring_certificates(
+        &self,
+        acme: &AcmeClient,
+        resolver: &GatewayCertResolver,
+        renewal_window: chrono::Duration,
+    ) -> Result<(), Error> {
+        let now = Utc::now();
+        let failures: HashMap<String, stats::CertificateRenewalFailure> = self
+            .iter_certificate_renewal_failures()
+            .await?
+            .map(|failure| (failure.fqdn.clone(), failure))
+            .collect();
+        let is_expiring = |expires_at: Option<DateTime<Utc>>| {
+            expires_at.map_or(true, |expires_at| expires_at <= now + renewal_window)
+        };
+        let unparseable = || {
+            Error::custom(
+                ErrorKind::Internal,
+                "the renewed certificate could not be parsed",
+            )
+        };
+        let is_retry_due = |fqdn: &str| {
+            failures.get(fqdn).map_or(true, |failure| {
+                failure.last_attempt_at + renewal_retry_delay(failure.failures) <= now
+            })
+        };
+
+        for custom_domain in self.iter_custom_domains().await? {
+            let fqdn = custom_domain.fqdn.to_string();
+            if !is_expiring(custom_domain.certificate_expires_at()) {
+                // The certificate was renewed some other way since the last failure
+                if failures.contains_key(&fqdn) {
+                    self.try_record_certificate_renewal(&fqdn, Ok(())).await;
+                }
+                continue;
+            }
+            if !is_retry_due(&fqdn) {
+                continue;
+            }
+
+            info!(%fqdn, "renewing custom domain certificate");
+            let result: Result<(), Error> = async {
+                let (certs, private_key) = acme
+                    .create_certificate(&fqdn, ChallengeType::Http01, self.credentials())
+                    .await?;
+                if pem_certificate_expires_at(&certs).is_none() {
+                    return Err(unparseable());
+                }
+                self.create_custom_domain(
+                    &custom_domain.project_name,
+                    &custom_domain.fqdn,
+                    &certs,
+                    &private_key,
+                )
+                .await?;
+
+                let mut buf = Vec::new();
+                buf.extend(certs.as_bytes());
+                buf.extend(private_key.as_bytes());
+                resolver.serve_pem(&fqdn, Cursor::new(buf)).await
+            }
+            .await;
+
+            self.try_record_certificate_renewal(&fqdn, result).await;
+        }
+
+        // The gateway certificate is created on startup if it is missing
+        let tls_path = self.state_location.join("ssl.pem");
+        let Ok(gateway_certs) = ChainAndPrivateKey::load_pem(&tls_path) else {
+            return Ok(());
+        };
+        let identifier = self.gateway_certificate_identifier();
+        if is_expiring(gateway_certs.expires_at()) && is_retry_due(&identifier) {
+            info!(%identifier, "renewing gateway certificate");
+            let result: Result<(), Error> = async {
+                let certs = self.create_certificate(acme, self.credentials()).await?;
+                if certs.expires_at().is_none() {
+                    return Err(unparseable());
+                }
+                resolver.serve_default_der(certs.clone()).await?;
+                certs.save_pem(&tls_path)
+            }
+            .await;
+
+            self.try_record_certificate_renewal(&identifier, result)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Record the outcome of a renewal, only logging when that fails so the renewal of the other
+    /// certificates goes on
+    async fn try_record_certificate_renewal(&self, fqdn: &str, result: Result<(), Error>) {
+        if let Err(error) = self.record_certificate_renewal(fqdn, result).await {
+            error!(
+                fqdn,
+                error = &error as &dyn std::error::Error,
+                "failed to record certificate renewal"
+            );
+        }
+    }
+
+    async fn record_certificate_renewal(
+        &self,
+        fqdn: &str,
+        result: Result<(), Error>,
+    ) -> Result<(), Error> {
+        match result {
+            Ok(()) => {
+                query("DELETE FROM certificate_renewal_failures WHERE fqdn = ?1")
+                    .bind(fqdn)
+                    .execute(&self.db)
+                    .await?;
+            }
+            Err(error) => {
+                error!(
+                    fqdn,
+                    error = &error as &dyn std::error::Error,
+                    "failed to renew certificate"
+                );
+
+                query(
+                    "INSERT INTO certificate_renewal_failures (fqdn, failures, last_error, last_attempt_at) VALUES (?1, 1, ?2, ?3)
+                    ON CONFLICT (fqdn) DO UPDATE SET failures = failures + 1, last_error = excluded.last_error, last_attempt_at = excluded.last_attempt_at",
+                )
+                .bind(fqdn)
+                .bind(error.to_string())
+                .bind(Utc::now().timestamp())
+                .execute(&self.db)
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn iter_certificate_renewal_failures(
+        &self,
+    ) -> Result<impl Iterator<Item = stats::CertificateRenewalFailure>, Error> {
+        let iter = query(
+            "SELECT fqdn, failures, last_error, last_attempt_at FROM certificate_renewal_failures",
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(|row| stats::CertificateRenewalFailure {
+            fqdn: row.get("fqdn"),
+            failures: row.get("failures"),
+            last_error: row.get("last_error"),
+            last_attempt_at: Utc
+                .timestamp_opt(row.get("last_attempt_at"), 0)
+                .single()
+                .unwrap_or_default(),
+        });
+        Ok(iter)
     }
 
     /// Fetch the gateway certificate from the state location.
@@ -903,7 +1269,7 @@ impl GatewayService {
                     tls_path.display()
                 );
 
-                let certs = self.create_certificate(acme, creds).await;
+                let certs = self.create_certificate(acme, creds).await.unwrap();
                 certs.clone().save_pem(&tls_path).unwrap();
                 certs
             }
@@ -1152,11 +1518,44 @@ impl GatewayContext {
     }
 }
 
+/// Who creates a new project, and the settings of its container
+#[derive(Debug, Clone)]
+pub struct NewProject {
+    pub account_name: AccountName,
+    /// The organization the project is created for, if any
+    pub org_name: Option<String>,
+    pub idle_minutes: u64,
+    pub container_limits: ContainerLimits,
+}
+
 pub struct FindProjectPayload {
     pub project_id: String,
     pub state: Project,
 }
 
+/// How long to wait before retrying a certificate renewal which failed `failures` times in a
+/// row: an hour after the first failure, doubling up to a day.
+fn renewal_retry_delay(failures: u32) -> chrono::Duration {
+    chrono::Duration::hours(2_i64.pow(failures.saturating_sub(1).min(5)))
+        .min(chrono::Duration::days(1))
+}
+
+/// Restrict a query on the projects table to the projects of an owner. Organization projects
+/// are not part of the personal projects of the account which created them.
+fn push_owner_condition(query: &mut QueryBuilder<'_, Sqlite>, owner: &ProjectOwner) {
+    match owner {
+        ProjectOwner::Account(account_name) => {
+            query
+                .push("account_name = ")
+                .push_bind(account_name.clone())
+                .push(" AND org_name IS NULL");
+        }
+        ProjectOwner::Organization(org_name) => {
+            query.push("org_name = ").push_bind(org_name.clone());
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use fqdn::FQDN;
@@ -1186,7 +1585,17 @@ pub mod tests {
         };
 
         let project = svc
-            .create_project(matrix.clone(), neo.clone(), false, true, 0)
+            .create_project(
+                matrix.clone(),
+                NewProject {
+                    account_name: neo.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default(),
+                },
+                false,
+                true,
+            )
             .await
             .unwrap();
 
@@ -1208,7 +1617,7 @@ pub mod tests {
             }
         );
         assert_eq!(
-            svc.iter_user_projects_detailed(&neo, 0, u32::MAX)
+            svc.iter_user_projects_detailed(&ProjectOwner::Account(neo.clone()), None, 0, u32::MAX)
                 .await
                 .unwrap()
                 .map(|item| item.1)
@@ -1218,25 +1627,45 @@ pub mod tests {
 
         // Test project pagination, first create 20 projects.
         for p in (0..20).map(|p| format!("matrix-{p}")) {
-            svc.create_project(p.parse().unwrap(), admin.clone(), true, true, 0)
-                .await
-                .unwrap();
+            svc.create_project(
+                p.parse().unwrap(),
+                NewProject {
+                    account_name: admin.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default(),
+                },
+                true,
+                true,
+            )
+            .await
+            .unwrap();
         }
 
         // Creating a project with can_create_project set to false should fail.
         assert_eq!(
-            svc.create_project("final-one".parse().unwrap(), admin.clone(), false, false, 0)
-                .await
-                .err()
-                .unwrap()
-                .kind(),
+            svc.create_project(
+                "final-one".parse().unwrap(),
+                NewProject {
+                    account_name: admin.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default()
+                },
+                false,
+                false
+            )
+            .await
+            .err()
+            .unwrap()
+            .kind(),
             ErrorKind::TooManyProjects
         );
 
         // We need to fetch all of them from the DB since they are ordered by created_at (in the id) and project_name,
         // and created_at will be the same for some of them.
         let all_projects = svc
-            .iter_user_projects_detailed(&admin, 0, u32::MAX)
+            .iter_user_projects_detailed(&ProjectOwner::Account(admin.clone()), None, 0, u32::MAX)
             .await
             .unwrap()
             .map(|item| item.0)
@@ -1246,7 +1675,7 @@ pub mod tests {
 
         // Get first 5 projects.
         let paginated = svc
-            .iter_user_projects_detailed(&admin, 0, 5)
+            .iter_user_projects_detailed(&ProjectOwner::Account(admin.clone()), None, 0, 5)
             .await
             .unwrap()
             .map(|item| item.0)
@@ -1256,7 +1685,7 @@ pub mod tests {
 
         // Get 10 projects starting at an offset of 10.
         let paginated = svc
-            .iter_user_projects_detailed(&admin, 10, 10)
+            .iter_user_projects_detailed(&ProjectOwner::Account(admin.clone()), None, 10, 10)
             .await
             .unwrap()
             .map(|item| item.0)
@@ -1265,11 +1694,38 @@ pub mod tests {
 
         // Get 20 projects starting at an offset of 200.
         let paginated = svc
-            .iter_user_projects_detailed(&admin, 200, 20)
+            .iter_user_projects_detailed(&ProjectOwner::Account(admin.clone()), None, 200, 20)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>();
+
+        assert!(paginated.is_empty());
+
+        // Restricting the projects happens before the pagination
+        let restricted = [
+            "matrix-3".to_string(),
+            "matrix-7".to_string(),
+            "matrix-12".to_string(),
+        ];
+        let paginated = svc
+            .iter_user_projects_detailed(
+                &ProjectOwner::Account(admin.clone()),
+                Some(&restricted),
+                0,
+                2,
+            )
             .await
             .unwrap()
+            .map(|item| item.1.to_string())
             .collect::<Vec<_>>();
+        assert_eq!(paginated.len(), 2);
+        assert!(paginated.iter().all(|name| restricted.contains(name)));
 
+        let paginated = svc
+            .iter_user_projects_detailed(&ProjectOwner::Account(admin.clone()), Some(&[]), 0, 5)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>();
         assert!(paginated.is_empty());
 
         let mut work = svc
@@ -1292,8 +1748,18 @@ pub mod tests {
 
         // If recreated by a different user
         assert!(matches!(
-            svc.create_project(matrix.clone(), trinity.clone(), false, true, 0)
-                .await,
+            svc.create_project(
+                matrix.clone(),
+                NewProject {
+                    account_name: trinity.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default()
+                },
+                false,
+                true
+            )
+            .await,
             Err(Error {
                 kind: ErrorKind::ProjectAlreadyExists,
                 ..
@@ -1302,8 +1768,18 @@ pub mod tests {
 
         // If recreated by the same user
         assert!(matches!(
-            svc.create_project(matrix.clone(), neo.clone(), false, true, 0)
-                .await,
+            svc.create_project(
+                matrix.clone(),
+                NewProject {
+                    account_name: neo.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default()
+                },
+                false,
+                true
+            )
+            .await,
             Ok(FindProjectPayload {
                 project_id: _,
                 state: Project::Creating(_),
@@ -1312,8 +1788,18 @@ pub mod tests {
 
         // If recreated by the same user again while it's running
         assert!(matches!(
-            svc.create_project(matrix.clone(), neo.clone(), false, true, 0)
-                .await,
+            svc.create_project(
+                matrix.clone(),
+                NewProject {
+                    account_name: neo.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default()
+                },
+                false,
+                true
+            )
+            .await,
             Err(Error {
                 kind: ErrorKind::OwnProjectAlreadyExists(_),
                 ..
@@ -1340,8 +1826,18 @@ pub mod tests {
 
         // If recreated by an admin
         assert!(matches!(
-            svc.create_project(matrix.clone(), admin.clone(), true, true, 0)
-                .await,
+            svc.create_project(
+                matrix.clone(),
+                NewProject {
+                    account_name: admin.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default()
+                },
+                true,
+                true
+            )
+            .await,
             Ok(FindProjectPayload {
                 project_id: _,
                 state: Project::Creating(_),
@@ -1350,8 +1846,18 @@ pub mod tests {
 
         // If recreated by an admin again while it's running
         assert!(matches!(
-            svc.create_project(matrix.clone(), admin.clone(), true, true, 0)
-                .await,
+            svc.create_project(
+                matrix.clone(),
+                NewProject {
+                    account_name: admin.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default()
+                },
+                true,
+                true
+            )
+            .await,
             Err(Error {
                 kind: ErrorKind::OwnProjectAlreadyExists(_),
                 ..
@@ -1372,8 +1878,18 @@ pub mod tests {
 
         // It can be re-created by anyone, with the same project name
         assert!(matches!(
-            svc.create_project(matrix, trinity.clone(), false, true, 0)
-                .await,
+            svc.create_project(
+                matrix,
+                NewProject {
+                    account_name: trinity.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default()
+                },
+                false,
+                true
+            )
+            .await,
             Ok(FindProjectPayload {
                 project_id: _,
                 state: Project::Creating(_),
@@ -1390,9 +1906,19 @@ pub mod tests {
         let neo: AccountName = "neo".parse().unwrap();
         let matrix: ProjectName = "matrix".parse().unwrap();
 
-        svc.create_project(matrix.clone(), neo.clone(), false, true, 0)
-            .await
-            .unwrap();
+        svc.create_project(
+            matrix.clone(),
+            NewProject {
+                account_name: neo.clone(),
+                org_name: None,
+                idle_minutes: 0,
+                container_limits: Default::default(),
+            },
+            false,
+            true,
+        )
+        .await
+        .unwrap();
 
         let mut task = svc.new_task().project(matrix.clone()).build();
 
@@ -1451,7 +1977,17 @@ pub mod tests {
         );
 
         let _ = svc
-            .create_project(project_name.clone(), account.clone(), false, true, 0)
+            .create_project(
+                project_name.clone(),
+                NewProject {
+                    account_name: account.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default(),
+                },
+                false,
+                true,
+            )
             .await
             .unwrap();
 
@@ -1488,6 +2024,261 @@ pub mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn service_list_delete_custom_domain() -> anyhow::Result<()> {
+        let world = World::new().await;
+        let svc = Arc::new(GatewayService::init(world.args(), world.pool(), "".into()).await?);
+
+        let account: AccountName = "neo".parse().unwrap();
+        let project_name: ProjectName = "matrix".parse().unwrap();
+        let other_project_name: ProjectName = "zion".parse().unwrap();
+        let domain: FQDN = "neo.the.matrix".parse().unwrap();
+
+        for project_name in [&project_name, &other_project_name] {
+            let _ = svc
+                .create_project(
+                    project_name.clone(),
+                    NewProject {
+                        account_name: account.clone(),
+                        org_name: None,
+                        idle_minutes: 0,
+                        container_limits: Default::default(),
+                    },
+                    false,
+                    true,
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            svc.iter_custom_domains_for_project(&project_name)
+                .await
+                .unwrap()
+                .count(),
+            0
+        );
+
+        svc.create_custom_domain(&project_name, &domain, "dummy certificate", "dummy key")
+            .await
+            .unwrap();
+
+        let domains: Vec<_> = svc
+            .iter_custom_domains_for_project(&project_name)
+            .await
+            .unwrap()
+            .collect();
+        assert_eq!(domains.len(), 1);
+        assert_eq!(domains[0].fqdn, domain);
+        assert_eq!(
+            svc.iter_custom_domains_for_project(&other_project_name)
+                .await
+                .unwrap()
+                .count(),
+            0
+        );
+
+        // Only the project owning the domain can remove it
+        assert_err_kind!(
+            svc.delete_custom_domain(&other_project_name, &domain).await,
+            ErrorKind::CustomDomainNotFound
+        );
+
+        svc.delete_custom_domain(&project_name, &domain)
+            .await
+            .unwrap();
+
+        assert_err_kind!(
+            svc.project_details_for_custom_domain(&domain).await,
+            ErrorKind::CustomDomainNotFound
+        );
+        assert_err_kind!(
+            svc.delete_custom_domain(&project_name, &domain).await,
+            ErrorKind::CustomDomainNotFound
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn service_record_certificate_renewal() -> anyhow::Result<()> {
+        let world = World::new().await;
+        let svc = Arc::new(GatewayService::init(world.args(), world.pool(), "".into()).await?);
+
+        let fqdn = "neo.the.matrix";
+
+        assert_eq!(svc.iter_certificate_renewal_failures().await?.count(), 0);
+
+        svc.record_certificate_renewal(fqdn, Err(Error::from_kind(ErrorKind::Internal)))
+            .await?;
+        svc.record_certificate_renewal(
+            fqdn,
+            Err(Error::custom(ErrorKind::Internal, "challenge timed out")),
+        )
+        .await?;
+
+        let failures: Vec<_> = svc.iter_certificate_renewal_failures().await?.collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].fqdn, fqdn);
+        assert_eq!(failures[0].failures, 2);
+        assert!(failures[0].last_error.contains("challenge timed out"));
+
+        // A successful renewal clears the failures
+        svc.record_certificate_renewal(fqdn, Ok(())).await?;
+        assert_eq!(svc.iter_certificate_renewal_failures().await?.count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn certificate_renewal_retry_delay() {
+        assert_eq!(renewal_retry_delay(1), chrono::Duration::hours(1));
+        assert_eq!(renewal_retry_delay(2), chrono::Duration::hours(2));
+        assert_eq!(renewal_retry_delay(5), chrono::Duration::hours(16));
+        assert_eq!(renewal_retry_delay(100), chrono::Duration::days(1));
+    }
+
+    #[tokio::test]
+    async fn service_organization_projects() -> anyhow::Result<()> {
+        let world = World::new().await;
+        let svc = Arc::new(GatewayService::init(world.args(), world.pool(), "".into()).await?);
+
+        let neo: AccountName = "neo".parse().unwrap();
+        let trinity: AccountName = "trinity".parse().unwrap();
+        let zion = ProjectOwner::Organization("zion".to_string());
+        let matrix: ProjectName = "matrix".parse().unwrap();
+        let nebuchadnezzar: ProjectName = "nebuchadnezzar".parse().unwrap();
+
+        svc.create_project(
+            matrix.clone(),
+            NewProject {
+                account_name: neo.clone(),
+                org_name: None,
+                idle_minutes: 0,
+                container_limits: Default::default(),
+            },
+            false,
+            true,
+        )
+        .await?;
+        svc.create_project(
+            nebuchadnezzar.clone(),
+            NewProject {
+                account_name: neo.clone(),
+                org_name: Some("zion".to_string()),
+                idle_minutes: 0,
+                container_limits: Default::default(),
+            },
+            false,
+            true,
+        )
+        .await?;
+
+        // Organization projects are not personal projects of their creator
+        assert_eq!(
+            svc.iter_user_projects(&ProjectOwner::Account(neo.clone()))
+                .await?
+                .collect::<Vec<_>>(),
+            vec![matrix.clone()]
+        );
+        assert_eq!(
+            svc.iter_user_projects(&zion).await?.collect::<Vec<_>>(),
+            vec![nebuchadnezzar.clone()]
+        );
+        assert_eq!(svc.get_project_count(&zion).await?, 1);
+
+        // Other members of the organization own its projects too
+        assert!(matches!(
+            svc.create_project(
+                nebuchadnezzar.clone(),
+                NewProject {
+                    account_name: trinity.clone(),
+                    org_name: Some("zion".to_string()),
+                    idle_minutes: 0,
+                    container_limits: Default::default()
+                },
+                false,
+                true
+            )
+            .await,
+            Err(Error {
+                kind: ErrorKind::OwnProjectAlreadyExists(_),
+                ..
+            })
+        ));
+
+        // But they don't own the personal projects of members
+        assert!(matches!(
+            svc.create_project(
+                matrix,
+                NewProject {
+                    account_name: trinity,
+                    org_name: Some("zion".to_string()),
+                    idle_minutes: 0,
+                    container_limits: Default::default()
+                },
+                false,
+                true
+            )
+            .await,
+            Err(Error {
+                kind: ErrorKind::ProjectAlreadyExists,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn service_container_limits_override() -> anyhow::Result<()> {
+        let world = World::new().await;
+        let svc = Arc::new(GatewayService::init(world.args(), world.pool(), "".into()).await?);
+
+        let neo: AccountName = "neo".parse().unwrap();
+        let matrix: ProjectName = "matrix".parse().unwrap();
+        let limits = ContainerLimits {
+            cpu_millis: 500,
+            memory_mib: 512,
+            pids: 64,
+            disk_gib: 1,
+        };
+
+        assert!(matches!(
+            svc.set_container_limits_override(&matrix, Some(limits))
+                .await,
+            Err(Error {
+                kind: ErrorKind::ProjectNotFound(_),
+                ..
+            })
+        ));
+
+        let project = svc
+            .create_project(
+                matrix.clone(),
+                NewProject {
+                    account_name: neo,
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: limits,
+                },
+                false,
+                true,
+            )
+            .await?;
+        assert_eq!(project.state.container_limits(), Some(limits));
+        assert_eq!(svc.container_limits_override(&matrix).await?, None);
+
+        svc.set_container_limits_override(&matrix, Some(limits))
+            .await?;
+        assert_eq!(svc.container_limits_override(&matrix).await?, Some(limits));
+
+        svc.set_container_limits_override(&matrix, None).await?;
+        assert_eq!(svc.container_limits_override(&matrix).await?, None);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn service_create_custom_domain_destroy_recreate_project() -> anyhow::Result<()> {
         let world = World::new().await;
@@ -1505,7 +2296,17 @@ pub mod tests {
         );
 
         let _ = svc
-            .create_project(project_name.clone(), account.clone(), false, true, 0)
+            .create_project(
+                project_name.clone(),
+                NewProject {
+                    account_name: account.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default(),
+                },
+                false,
+                true,
+            )
             .await
             .unwrap();
 
@@ -1523,7 +2324,17 @@ pub mod tests {
         assert!(matches!(work.poll(()).await, TaskResult::Done(())));
 
         let recreated_project = svc
-            .create_project(project_name.clone(), account.clone(), false, true, 0)
+            .create_project(
+                project_name.clone(),
+                NewProject {
+                    account_name: account.clone(),
+                    org_name: None,
+                    idle_minutes: 0,
+                    container_limits: Default::default(),
+                },
+                false,
+                true,
+            )
             .await
             .unwrap();
 
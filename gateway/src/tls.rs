@@ -6,6 +6,7 @@ use std::sync::Arc;
 
 use axum_server::accept::DefaultAcceptor;
 use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
+use chrono::{DateTime, TimeZone, Utc};
 use futures::executor::block_on;
 use pem::Pem;
 use rustls::server::{ClientHello, ResolvesServerCert};
@@ -15,6 +16,7 @@ use rustls_pemfile::Item;
 use shuttle_common::models::error::ErrorKind;
 use tokio::runtime::Handle;
 use tokio::sync::RwLock;
+use x509_parser::parse_x509_certificate;
 
 use crate::Error;
 
@@ -78,6 +80,14 @@ impl ChainAndPrivateKey {
         Ok(CertifiedKey::new(self.chain, signing_key))
     }
 
+    /// When the leaf certificate of the chain stops being valid, if it can be parsed
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        let (_, certificate) = parse_x509_certificate(&self.chain.first()?.0).ok()?;
+
+        Utc.timestamp_opt(certificate.validity().not_after.timestamp(), 0)
+            .single()
+    }
+
     pub fn save_pem<P: AsRef<Path>>(self, path: P) -> Result<(), Error> {
         let as_pem = self.into_pem()?;
         let mut f = File::create(path)?;
@@ -136,6 +146,12 @@ impl GatewayCertResolver {
         let certs = ChainAndPrivateKey::parse_pem(rd)?;
         self.serve_der(sni, certs).await
     }
+
+    /// Stop serving the certificate of the given domain. Incoming TLS connections
+    /// for it get the default certificate again.
+    pub async fn remove(&self, sni: &str) {
+        self.keys.write().await.remove(sni);
+    }
 }
 
 impl ResolvesServerCert for GatewayCertResolver {
@@ -0,0 +1,6 @@
+ALTER TABLE logs
+    ADD COLUMN level TEXT,   -- Level of the event (trace, debug, info, warn, error), if known.
+    ADD COLUMN target TEXT,  -- Target (module path) of the event, if known.
+    ADD COLUMN fields JSONB; -- Fields of the event and of its deployment span.
+
+CREATE INDEX deployment_timestamp_idx ON logs (deployment_id, tx_timestamp);
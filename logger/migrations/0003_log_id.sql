@@ -0,0 +1,2 @@
+-- Gives logs with the same timestamp a stable order, so listings can be continued from a cursor
+ALTER TABLE logs ADD COLUMN id BIGSERIAL;
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS deployment_tiers (
+    deployment_id TEXT PRIMARY KEY, -- The deployment whose logs are kept.
+    tier TEXT NOT NULL              -- Account tier of the owner, which decides for how long.
+);
+
+CREATE INDEX tx_timestamp_idx ON logs (tx_timestamp);
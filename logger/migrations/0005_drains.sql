@@ -0,0 +1,13 @@
+-- Drains are configured per project, so the logs of a deployment are routed through its project
+ALTER TABLE deployment_tiers ADD COLUMN project_name TEXT;
+
+CREATE INDEX deployment_tiers_project_idx ON deployment_tiers (project_name);
+
+CREATE TABLE IF NOT EXISTS drains (
+    id BIGSERIAL PRIMARY KEY,
+    project_name TEXT NOT NULL, -- Project whose logs are forwarded.
+    kind TEXT NOT NULL,         -- Either http or syslog.
+    url TEXT NOT NULL           -- Where the logs are forwarded to.
+);
+
+CREATE INDEX drains_project_idx ON drains (project_name);
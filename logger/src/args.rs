@@ -1,8 +1,11 @@
 use std::net::SocketAddr;
 
 use clap::Parser;
+use shuttle_common::claims::AccountTier;
 use tonic::transport::Uri;
 
+use crate::retention::{Retention, RetentionPolicy};
+
 #[derive(Parser, Debug)]
 pub struct Args {
     /// Address to bind to
@@ -16,4 +19,55 @@ pub struct Args {
     /// Address to reach the authentication service at
     #[arg(long, default_value = "http://127.0.0.1:8008")]
     pub auth_uri: Uri,
+
+    /// Number of days logs are kept for, unless their account tier has its own retention
+    #[arg(long, default_value_t = 30)]
+    pub retention_days: u32,
+
+    /// Number of bytes of logs kept per deployment, unless their account tier has its own retention
+    #[arg(long, default_value_t = 100 * 1024 * 1024)]
+    pub retention_bytes: u64,
+
+    /// Retention of an account tier, as TIER=DAYS or TIER=DAYS:BYTES (e.g. pro=90:1073741824)
+    #[arg(long = "tier-retention", value_parser = parse_tier_retention)]
+    pub tier_retentions: Vec<(AccountTier, Retention)>,
+
+    /// Number of seconds between each pruning of the logs past their retention
+    #[arg(long, default_value_t = 60 * 60)]
+    pub prune_interval: u64,
+
+    /// Let drains send logs to loopback, private and link-local addresses, for local setups
+    #[arg(long)]
+    pub allow_private_drains: bool,
+}
+
+impl Args {
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            default: Retention {
+                days: self.retention_days,
+                max_bytes: Some(self.retention_bytes),
+            },
+            tiers: self.tier_retentions.clone(),
+        }
+    }
+}
+
+fn parse_tier_retention(s: &str) -> Result<(AccountTier, Retention), String> {
+    let invalid = || format!("invalid tier retention `{s}`, expected TIER=DAYS or TIER=DAYS:BYTES");
+
+    let (tier, retention) = s.split_once('=').ok_or_else(invalid)?;
+    let tier = tier.parse().map_err(|_| invalid())?;
+    let (days, max_bytes) = match retention.split_once(':') {
+        Some((days, bytes)) => (days, Some(bytes.parse().map_err(|_| invalid())?)),
+        None => (retention, None),
+    };
+
+    Ok((
+        tier,
+        Retention {
+            days: days.parse().map_err(|_| invalid())?,
+            max_bytes,
+        },
+    ))
 }
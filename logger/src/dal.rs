@@ -1,9 +1,15 @@
+use std::fmt::Display;
+use std::str::FromStr;
 use std::time::{Duration, SystemTime};
 
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
 use prost_types::Timestamp;
-use shuttle_proto::logger::{LogItem, LogLine};
+use shuttle_common::{
+    claims::AccountTier,
+    log::{DrainKind, LogLevel},
+};
+use shuttle_proto::logger::{self, LogItem, LogLine, LogsRequest, ServiceLogsRequest};
 use sqlx::{
     migrate::Migrator,
     postgres::PgConnectOptions,
@@ -16,6 +22,8 @@ use tracing::{error, info, warn, Instrument, Span};
 
 use tonic::transport::Uri;
 
+use crate::retention::{Retention, RetentionScope};
+
 pub static MIGRATIONS: Migrator = sqlx::migrate!("./migrations");
 
 #[derive(Error, Debug)]
@@ -24,10 +32,302 @@ pub enum DalError {
     Sqlx(#[from] sqlx::Error),
 }
 
+/// Filters to select the logs of some deployments with
+#[derive(Clone, Debug, Default)]
+pub struct LogFilter {
+    pub deployment_ids: Vec<String>,
+    /// Only logs at this level or above
+    pub level: Option<LogLevel>,
+    /// Only logs whose target starts with this
+    pub target: Option<String>,
+    /// Only logs from this time onwards
+    pub since: Option<DateTime<Utc>>,
+    /// Only logs from before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only logs containing this text, ignoring case. Always lowercase
+    pub search: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: u32,
+}
+
+impl TryFrom<LogsRequest> for LogFilter {
+    type Error = String;
+
+    fn try_from(request: LogsRequest) -> Result<Self, Self::Error> {
+        let LogsRequest {
+            deployment_id,
+            level,
+            target,
+            since,
+            until,
+            search,
+            limit,
+            offset,
+        } = request;
+
+        Ok(Self {
+            deployment_ids: vec![deployment_id],
+            level: parse_level(level)?,
+            target: (!target.is_empty()).then_some(target),
+            since: since.map(to_datetime),
+            until: until.map(to_datetime),
+            search: (!search.is_empty()).then(|| search.to_lowercase()),
+            limit: (limit != 0).then_some(limit),
+            offset,
+        })
+    }
+}
+
+impl TryFrom<ServiceLogsRequest> for LogFilter {
+    type Error = String;
+
+    fn try_from(request: ServiceLogsRequest) -> Result<Self, Self::Error> {
+        let ServiceLogsRequest {
+            deployment_ids,
+            level,
+            target,
+            since,
+            until,
+            search,
+            limit,
+            cursor: _,
+        } = request;
+
+        Ok(Self {
+            deployment_ids,
+            level: parse_level(level)?,
+            target: (!target.is_empty()).then_some(target),
+            since: since.map(to_datetime),
+            until: until.map(to_datetime),
+            search: (!search.is_empty()).then(|| search.to_lowercase()),
+            limit: (limit != 0).then_some(limit),
+            offset: 0,
+        })
+    }
+}
+
+fn parse_level(level: String) -> Result<Option<LogLevel>, String> {
+    if level.is_empty() {
+        return Ok(None);
+    }
+
+    level
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("'{level}' is not a valid log level"))
+}
+
+impl LogFilter {
+    /// Check if a log passes this filter. Used for logs that are not read from the database,
+    /// so the limit and offset are not taken into account.
+    pub fn matches(&self, log: &Log) -> bool {
+        if !self.deployment_ids.contains(&log.deployment_id) {
+            return false;
+        }
+
+        if let Some(min_level) = self.level {
+            match log
+                .level
+                .as_deref()
+                .and_then(|l| l.parse::<LogLevel>().ok())
+            {
+                Some(level) if level >= min_level => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(target) = &self.target {
+            if !log
+                .target
+                .as_deref()
+                .is_some_and(|t| t.starts_with(target.as_str()))
+            {
+                return false;
+            }
+        }
+
+        if self.since.is_some_and(|since| log.tx_timestamp < since)
+            || self.until.is_some_and(|until| log.tx_timestamp >= until)
+        {
+            return false;
+        }
+
+        if let Some(search) = &self.search {
+            if !String::from_utf8_lossy(&log.data)
+                .to_lowercase()
+                .contains(search.as_str())
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Add the conditions of this filter to a query on the logs table
+    fn push_conditions(&self, builder: &mut QueryBuilder<'_, sqlx::Postgres>) {
+        builder
+            .push(" WHERE deployment_id = ANY(")
+            .push_bind(self.deployment_ids.clone())
+            .push(")");
+
+        if let Some(level) = self.level {
+            let levels: Vec<String> = level.and_above().map(|l| l.to_string()).collect();
+            builder
+                .push(" AND level = ANY(")
+                .push_bind(levels)
+                .push(")");
+        }
+        if let Some(target) = &self.target {
+            builder
+                .push(" AND strpos(target, ")
+                .push_bind(target.clone())
+                .push(") = 1");
+        }
+        if let Some(since) = self.since {
+            builder.push(" AND tx_timestamp >= ").push_bind(since);
+        }
+        if let Some(until) = self.until {
+            builder.push(" AND tx_timestamp < ").push_bind(until);
+        }
+        if let Some(search) = &self.search {
+            // Data does not have to be valid UTF-8, which `convert_from` would fail on. The escape
+            // encoding keeps ASCII characters as they are, so they can be matched ignoring case,
+            // and other characters match when the search is encoded the same way.
+            builder
+                .push(" AND strpos(lower(encode(data, 'escape')), encode(")
+                .push_bind(search.as_bytes().to_vec())
+                .push(", 'escape')) > 0");
+        }
+    }
+}
+
+/// Position of a log in a newest first listing, to continue the listing after it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogCursor {
+    tx_timestamp: DateTime<Utc>,
+    id: i64,
+}
+
+impl LogCursor {
+    /// Cursor pointing after this log. Only logs read from the database have one.
+    pub fn after(log: &Log) -> Option<Self> {
+        Some(Self {
+            tx_timestamp: log.tx_timestamp,
+            id: log.id?,
+        })
+    }
+}
+
+impl Display for LogCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}_{}",
+            self.tx_timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::Nanos, true),
+            self.id
+        )
+    }
+}
+
+impl FromStr for LogCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{s}' is not a valid cursor");
+        let (tx_timestamp, id) = s.rsplit_once('_').ok_or_else(invalid)?;
+
+        Ok(Self {
+            tx_timestamp: DateTime::parse_from_rfc3339(tx_timestamp)
+                .map_err(|_| invalid())?
+                .with_timezone(&Utc),
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
 #[async_trait]
 pub trait Dal {
-    /// Get logs for a deployment
-    async fn get_logs(&self, deployment_id: String) -> Result<Vec<Log>, DalError>;
+    /// Get the logs that pass a filter, oldest first
+    async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<Log>, DalError>;
+
+    /// Get the logs that pass a filter, newest first, starting after the cursor if one is given
+    async fn get_logs_newest_first(
+        &self,
+        filter: &LogFilter,
+        cursor: Option<&LogCursor>,
+    ) -> Result<Vec<Log>, DalError>;
+
+    /// Record the account tier that decides the retention of the logs of a deployment, and the
+    /// project whose drains they are forwarded to
+    async fn set_deployment_tier(
+        &self,
+        deployment_id: &str,
+        project_name: Option<&str>,
+        tier: AccountTier,
+    ) -> Result<(), DalError>;
+
+    /// Delete the logs of a scope that are past a retention, returning the number of lines deleted
+    async fn prune_logs(
+        &self,
+        scope: &RetentionScope,
+        retention: &Retention,
+    ) -> Result<u64, DalError>;
+
+    /// Record the project of deployments, for those whose logs were stored before their project
+    /// was. Deployments without a tier yet get the given one.
+    async fn set_deployments_project(
+        &self,
+        project_name: &str,
+        deployment_ids: &[String],
+        tier: AccountTier,
+    ) -> Result<(), DalError>;
+
+    /// Delete all the logs of some deployments, returning the number of lines deleted. The drains
+    /// of their projects go with them.
+    async fn delete_logs(&self, deployment_ids: &[String]) -> Result<u64, DalError>;
+
+    async fn add_drain(
+        &self,
+        project_name: &str,
+        kind: DrainKind,
+        url: &str,
+    ) -> Result<Drain, DalError>;
+
+    async fn get_drains(&self, project_name: &str) -> Result<Vec<Drain>, DalError>;
+
+    /// Remove a drain of a project, returning whether it existed
+    async fn remove_drain(&self, project_name: &str, id: i64) -> Result<bool, DalError>;
+
+    /// Ids of all the drains there are
+    async fn get_drain_ids(&self) -> Result<Vec<i64>, DalError>;
+
+    /// Get the drains the logs of some deployments are forwarded to, through their projects
+    async fn get_deployment_drains(
+        &self,
+        deployment_ids: &[String],
+    ) -> Result<Vec<DeploymentDrain>, DalError>;
+}
+
+/// Add the condition selecting the deployments of a retention scope to a query on the logs table
+fn push_scope(builder: &mut QueryBuilder<'_, sqlx::Postgres>, scope: &RetentionScope) {
+    let tiers = match scope {
+        RetentionScope::Tier(tier) => {
+            builder
+                .push("deployment_id IN (SELECT deployment_id FROM deployment_tiers WHERE tier = ");
+            builder.push_bind(tier.to_string());
+            builder.push(")");
+
+            return;
+        }
+        RetentionScope::Other(tiers) => tiers,
+    };
+
+    builder
+        .push("deployment_id NOT IN (SELECT deployment_id FROM deployment_tiers WHERE tier = ANY(")
+        .push_bind(tiers.iter().map(ToString::to_string).collect::<Vec<_>>())
+        .push("))");
 }
 
 #[derive(Clone)]
@@ -76,7 +376,7 @@ impl Postgres {
                 match rx.recv().await {
                     Ok((logs, parent_span)) => {
                         let mut builder = QueryBuilder::new(
-                            "INSERT INTO logs (deployment_id, shuttle_service_name, data, tx_timestamp)",
+                            "INSERT INTO logs (deployment_id, shuttle_service_name, data, tx_timestamp, level, target, fields)",
                         );
 
                         parent_span.in_scope(|| {
@@ -96,7 +396,10 @@ impl Postgres {
                             b.push_bind(log.deployment_id)
                                 .push_bind(log.shuttle_service_name)
                                 .push_bind(log.data)
-                                .push_bind(log.tx_timestamp);
+                                .push_bind(log.tx_timestamp)
+                                .push_bind(log.level)
+                                .push_bind(log.target)
+                                .push_bind(log.fields);
                         });
                         let query = builder.build();
 
@@ -123,15 +426,244 @@ impl Postgres {
 
 #[async_trait]
 impl Dal for Postgres {
-    async fn get_logs(&self, deployment_id: String) -> Result<Vec<Log>, DalError> {
-        let result =
-            sqlx::query_as("SELECT * FROM logs WHERE deployment_id = $1 ORDER BY tx_timestamp")
-                .bind(deployment_id)
-                .fetch_all(&self.pool)
-                .await?;
+    async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<Log>, DalError> {
+        let mut builder = QueryBuilder::new("SELECT * FROM logs");
+        filter.push_conditions(&mut builder);
+
+        builder.push(" ORDER BY tx_timestamp, id");
+
+        if let Some(limit) = filter.limit {
+            builder.push(" LIMIT ").push_bind(i64::from(limit));
+        }
+        if filter.offset != 0 {
+            builder.push(" OFFSET ").push_bind(i64::from(filter.offset));
+        }
+
+        let result = builder.build_query_as().fetch_all(&self.pool).await?;
 
         Ok(result)
     }
+
+    async fn get_logs_newest_first(
+        &self,
+        filter: &LogFilter,
+        cursor: Option<&LogCursor>,
+    ) -> Result<Vec<Log>, DalError> {
+        let mut builder = QueryBuilder::new("SELECT * FROM logs");
+        filter.push_conditions(&mut builder);
+
+        if let Some(cursor) = cursor {
+            builder
+                .push(" AND (tx_timestamp, id) < (")
+                .push_bind(cursor.tx_timestamp)
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+
+        builder.push(" ORDER BY tx_timestamp DESC, id DESC");
+
+        if let Some(limit) = filter.limit {
+            builder.push(" LIMIT ").push_bind(i64::from(limit));
+        }
+
+        let result = builder.build_query_as().fetch_all(&self.pool).await?;
+
+        Ok(result)
+    }
+
+    async fn set_deployment_tier(
+        &self,
+        deployment_id: &str,
+        project_name: Option<&str>,
+        tier: AccountTier,
+    ) -> Result<(), DalError> {
+        sqlx::query(
+            "INSERT INTO deployment_tiers (deployment_id, tier, project_name) VALUES ($1, $2, $3)
+            ON CONFLICT (deployment_id) DO UPDATE SET tier = EXCLUDED.tier,
+                project_name = COALESCE(EXCLUDED.project_name, deployment_tiers.project_name)",
+        )
+        .bind(deployment_id)
+        .bind(tier.to_string())
+        .bind(project_name)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn prune_logs(
+        &self,
+        scope: &RetentionScope,
+        retention: &Retention,
+    ) -> Result<u64, DalError> {
+        let cutoff = Utc::now()
+            .checked_sub_signed(chrono::Duration::days(retention.days.into()))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+
+        let mut builder = QueryBuilder::new("DELETE FROM logs WHERE tx_timestamp < ");
+        builder.push_bind(cutoff).push(" AND ");
+        push_scope(&mut builder, scope);
+
+        let mut deleted = builder.build().execute(&self.pool).await?.rows_affected();
+
+        if let Some(max_bytes) = retention.max_bytes {
+            // Keep the newest lines of each deployment that fit in the byte budget
+            let mut builder = QueryBuilder::new(
+                "DELETE FROM logs WHERE id IN (
+                    SELECT id FROM (
+                        SELECT id, SUM(octet_length(data)) OVER (
+                            PARTITION BY deployment_id ORDER BY tx_timestamp DESC, id DESC
+                        ) AS kept_bytes
+                        FROM logs WHERE ",
+            );
+            push_scope(&mut builder, scope);
+            builder
+                .push(") AS sized WHERE kept_bytes > ")
+                .push_bind(i64::try_from(max_bytes).unwrap_or(i64::MAX))
+                .push(")");
+
+            deleted += builder.build().execute(&self.pool).await?.rows_affected();
+        }
+
+        Ok(deleted)
+    }
+
+    async fn delete_logs(&self, deployment_ids: &[String]) -> Result<u64, DalError> {
+        let mut transaction = self.pool.begin().await?;
+
+        let deleted = sqlx::query("DELETE FROM logs WHERE deployment_id = ANY($1)")
+            .bind(deployment_ids)
+            .execute(&mut *transaction)
+            .await?
+            .rows_affected();
+        sqlx::query(
+            "DELETE FROM drains WHERE project_name IN (
+                SELECT project_name FROM deployment_tiers WHERE deployment_id = ANY($1)
+            )",
+        )
+        .bind(deployment_ids)
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query("DELETE FROM deployment_tiers WHERE deployment_id = ANY($1)")
+            .bind(deployment_ids)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(deleted)
+    }
+
+    async fn set_deployments_project(
+        &self,
+        project_name: &str,
+        deployment_ids: &[String],
+        tier: AccountTier,
+    ) -> Result<(), DalError> {
+        sqlx::query(
+            "INSERT INTO deployment_tiers (deployment_id, tier, project_name)
+            SELECT deployment_id, $2, $3 FROM UNNEST($1::TEXT[]) AS deployment_id
+            ON CONFLICT (deployment_id) DO UPDATE SET project_name = EXCLUDED.project_name",
+        )
+        .bind(deployment_ids)
+        .bind(tier.to_string())
+        .bind(project_name)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn add_drain(
+        &self,
+        project_name: &str,
+        kind: DrainKind,
+        url: &str,
+    ) -> Result<Drain, DalError> {
+        let drain = sqlx::query_as(
+            "INSERT INTO drains (project_name, kind, url) VALUES ($1, $2, $3) RETURNING *",
+        )
+        .bind(project_name)
+        .bind(kind.as_str())
+        .bind(url)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(drain)
+    }
+
+    async fn get_drains(&self, project_name: &str) -> Result<Vec<Drain>, DalError> {
+        let drains = sqlx::query_as("SELECT * FROM drains WHERE project_name = $1 ORDER BY id")
+            .bind(project_name)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(drains)
+    }
+
+    async fn remove_drain(&self, project_name: &str, id: i64) -> Result<bool, DalError> {
+        let removed = sqlx::query("DELETE FROM drains WHERE project_name = $1 AND id = $2")
+            .bind(project_name)
+            .bind(id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        Ok(removed > 0)
+    }
+
+    async fn get_drain_ids(&self) -> Result<Vec<i64>, DalError> {
+        let ids = sqlx::query_scalar("SELECT id FROM drains")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(ids)
+    }
+
+    async fn get_deployment_drains(
+        &self,
+        deployment_ids: &[String],
+    ) -> Result<Vec<DeploymentDrain>, DalError> {
+        let drains = sqlx::query_as(
+            "SELECT t.deployment_id, d.* FROM deployment_tiers t
+            JOIN drains d ON d.project_name = t.project_name
+            WHERE t.deployment_id = ANY($1)",
+        )
+        .bind(deployment_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(drains)
+    }
+}
+
+/// An external sink the logs of a project are forwarded to
+#[derive(Clone, Debug, FromRow)]
+pub struct Drain {
+    pub id: i64,
+    pub project_name: String,
+    pub kind: String,
+    pub url: String,
+}
+
+/// A drain the logs of a deployment go to
+#[derive(Clone, Debug, FromRow)]
+pub struct DeploymentDrain {
+    pub deployment_id: String,
+    #[sqlx(flatten)]
+    pub drain: Drain,
+}
+
+impl From<Drain> for logger::Drain {
+    fn from(drain: Drain) -> Self {
+        logger::Drain {
+            id: drain.id,
+            project_name: drain.project_name,
+            kind: drain.kind,
+            url: drain.url,
+        }
+    }
 }
 
 #[derive(Clone, Debug, FromRow)]
@@ -140,6 +672,22 @@ pub struct Log {
     pub(crate) shuttle_service_name: String,
     pub(crate) tx_timestamp: DateTime<Utc>,
     pub(crate) data: Vec<u8>,
+    pub(crate) level: Option<String>,
+    pub(crate) target: Option<String>,
+    pub(crate) fields: Option<serde_json::Value>,
+    /// Set for the logs read from the database
+    pub(crate) id: Option<i64>,
+}
+
+fn to_datetime(timestamp: Timestamp) -> DateTime<Utc> {
+    DateTime::from_naive_utc_and_offset(
+        NaiveDateTime::from_timestamp_opt(
+            timestamp.seconds,
+            timestamp.nanos.try_into().unwrap_or_default(),
+        )
+        .unwrap_or_default(),
+        Utc,
+    )
 }
 
 impl Log {
@@ -149,15 +697,12 @@ impl Log {
         Some(Log {
             deployment_id: log.deployment_id,
             shuttle_service_name: log_line.service_name,
-            tx_timestamp: DateTime::from_naive_utc_and_offset(
-                NaiveDateTime::from_timestamp_opt(
-                    timestamp.seconds,
-                    timestamp.nanos.try_into().unwrap_or_default(),
-                )
-                .unwrap_or_default(),
-                Utc,
-            ),
+            tx_timestamp: to_datetime(timestamp),
             data: log_line.data,
+            level: (!log_line.level.is_empty()).then_some(log_line.level),
+            target: (!log_line.target.is_empty()).then_some(log_line.target),
+            fields: serde_json::from_str(&log_line.fields).ok(),
+            id: None,
         })
     }
 }
@@ -165,12 +710,8 @@ impl Log {
 impl From<Log> for LogItem {
     fn from(log: Log) -> Self {
         LogItem {
-            deployment_id: log.deployment_id,
-            log_line: Some(LogLine {
-                service_name: log.shuttle_service_name,
-                tx_timestamp: Some(Timestamp::from(SystemTime::from(log.tx_timestamp))),
-                data: log.data,
-            }),
+            deployment_id: log.deployment_id.clone(),
+            log_line: Some(log.into()),
         }
     }
 }
@@ -181,6 +722,12 @@ impl From<Log> for LogLine {
             service_name: log.shuttle_service_name,
             tx_timestamp: Some(Timestamp::from(SystemTime::from(log.tx_timestamp))),
             data: log.data,
+            level: log.level.unwrap_or_default(),
+            target: log.target.unwrap_or_default(),
+            fields: log
+                .fields
+                .map(|fields| fields.to_string())
+                .unwrap_or_default(),
         }
     }
 }
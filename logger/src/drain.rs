@@ -0,0 +1,561 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::CONTENT_TYPE,
+    redirect, StatusCode, Url,
+};
+use shuttle_common::log::{strip_ansi_escapes, Backend, DrainKind, LogItem, LogLevel};
+use thiserror::Error;
+use tokio::{
+    io::AsyncWriteExt,
+    net::TcpStream,
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc::{self, error::TrySendError},
+    },
+};
+use tracing::{error, info_span, warn, Instrument, Span};
+
+use crate::dal::{Dal, Drain, Log};
+
+/// Port syslog drains are sent to when their url does not have one
+const SYSLOG_PORT: u16 = 514;
+
+/// Facility of the syslog messages, which are all user-level messages
+const SYSLOG_FACILITY: u8 = 1;
+
+#[derive(Error, Debug)]
+pub enum SinkError {
+    #[error("failed to send logs over http: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("drain responded with status {0}")]
+    Status(StatusCode),
+    #[error("failed to send logs over tcp: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to serialize logs: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("refusing to send logs: {0}")]
+    Address(String),
+}
+
+/// Somewhere outside of shuttle logs can be sent to
+#[async_trait]
+pub trait Sink: Send {
+    async fn send(&mut self, batch: &[LogItem]) -> Result<(), SinkError>;
+}
+
+/// Posts each batch as JSON lines
+pub struct HttpSink {
+    client: reqwest::Client,
+    url: Url,
+    allow_private: bool,
+}
+
+impl HttpSink {
+    pub fn new(url: Url, allow_private: bool) -> Result<Self, reqwest::Error> {
+        // A redirect could point anywhere, so only the checked url is ever posted to
+        let client = reqwest::Client::builder()
+            .dns_resolver(Arc::new(DrainResolver { allow_private }))
+            .redirect(redirect::Policy::none())
+            .build()?;
+
+        Ok(Self {
+            client,
+            url,
+            allow_private,
+        })
+    }
+}
+
+/// Resolves the hosts of HTTP drains, failing for those with addresses that are not public
+struct DrainResolver {
+    allow_private: bool,
+}
+
+impl Resolve for DrainResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let allow_private = self.allow_private;
+
+        Box::pin(async move {
+            let addrs = resolve(name.as_str(), 0, allow_private).await?;
+            let addrs: Addrs = Box::new(addrs.into_iter());
+
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(addrs)
+        })
+    }
+}
+
+#[async_trait]
+impl Sink for HttpSink {
+    async fn send(&mut self, batch: &[LogItem]) -> Result<(), SinkError> {
+        let mut body = Vec::new();
+        for item in batch {
+            serde_json::to_writer(&mut body, item)?;
+            body.push(b'\n');
+        }
+
+        // Addresses in the url are connected to without going through the resolver
+        if let Ok(ip) = url_host(&self.url).parse() {
+            check_address(ip, self.allow_private).map_err(SinkError::Address)?;
+        }
+
+        let response = self
+            .client
+            .post(self.url.clone())
+            .header(CONTENT_TYPE, "application/x-ndjson")
+            .timeout(Duration::from_secs(10))
+            .body(body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(SinkError::Status(response.status()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes RFC 5424 messages to a TCP connection, framed by octet counting (RFC 6587)
+pub struct SyslogSink {
+    host: String,
+    port: u16,
+    allow_private: bool,
+    /// Sent as the HOSTNAME of the messages
+    project_name: String,
+    stream: Option<TcpStream>,
+}
+
+impl SyslogSink {
+    pub fn new(host: String, port: u16, allow_private: bool, project_name: String) -> Self {
+        Self {
+            host,
+            port,
+            allow_private,
+            project_name,
+            stream: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for SyslogSink {
+    async fn send(&mut self, batch: &[LogItem]) -> Result<(), SinkError> {
+        let mut frames = String::new();
+        for item in batch {
+            let message = syslog_message(&self.project_name, item);
+            _ = write!(frames, "{} {message}", message.len());
+        }
+
+        let mut stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => {
+                let addrs = resolve(&self.host, self.port, self.allow_private)
+                    .await
+                    .map_err(SinkError::Address)?;
+
+                TcpStream::connect(&addrs[..]).await?
+            }
+        };
+        stream.write_all(frames.as_bytes()).await?;
+
+        // A connection that failed is dropped, to be opened again on the next attempt
+        self.stream = Some(stream);
+
+        Ok(())
+    }
+}
+
+fn syslog_message(hostname: &str, item: &LogItem) -> String {
+    let severity = match item.level {
+        Some(LogLevel::Error) => 3,
+        Some(LogLevel::Warn) => 4,
+        Some(LogLevel::Info) | None => 6,
+        Some(LogLevel::Debug) | Some(LogLevel::Trace) => 7,
+    };
+    let app_name = match item.internal_origin {
+        Backend::Unknown => "-",
+        Backend::Auth => "auth",
+        Backend::Builder => "builder",
+        Backend::Deployer => "deployer",
+        Backend::Gateway => "gateway",
+        Backend::Logger => "logger",
+        Backend::Provisioner => "provisioner",
+        Backend::ResourceRecorder => "resource-recorder",
+        Backend::Runtime(_) => "runtime",
+    };
+
+    format!(
+        "<{}>1 {} {hostname} {app_name} {} - - {}",
+        SYSLOG_FACILITY * 8 + severity,
+        item.timestamp
+            .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+        item.id,
+        strip_ansi_escapes(&item.line).trim_end()
+    )
+}
+
+/// Check that logs can be drained to a url with a kind of sink
+pub(crate) fn parse_url(kind: DrainKind, url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url).map_err(|error| format!("invalid drain url '{url}': {error}"))?;
+
+    let schemes: &[&str] = match kind {
+        DrainKind::Http => &["http", "https"],
+        DrainKind::Syslog => &["tcp", "syslog"],
+    };
+    if !schemes.contains(&parsed.scheme()) {
+        return Err(format!(
+            "a {kind} drain needs a {} url",
+            schemes.join(" or ")
+        ));
+    }
+    if parsed.host_str().is_none() {
+        return Err(format!("drain url '{url}' has no host"));
+    }
+
+    Ok(parsed)
+}
+
+/// Check that logs can be drained to a url, and that its host is one they may be sent to
+pub(crate) async fn check_url(
+    kind: DrainKind,
+    url: &str,
+    allow_private: bool,
+) -> Result<Url, String> {
+    let parsed = parse_url(kind, url)?;
+    resolve(url_host(&parsed), port(kind, &parsed), allow_private).await?;
+
+    Ok(parsed)
+}
+
+/// Host of a url, without the brackets around an IPv6 address
+fn url_host(url: &Url) -> &str {
+    url.host_str()
+        .unwrap_or_default()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+}
+
+fn port(kind: DrainKind, url: &Url) -> u16 {
+    match kind {
+        DrainKind::Http => url.port_or_known_default().unwrap_or_default(),
+        DrainKind::Syslog => url.port().unwrap_or(SYSLOG_PORT),
+    }
+}
+
+/// Look up the addresses of a drain host, failing if any of them is one logs may not be sent to
+async fn resolve(host: &str, port: u16, allow_private: bool) -> Result<Vec<SocketAddr>, String> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|error| format!("failed to resolve drain host '{host}': {error}"))?
+        .collect();
+
+    if addrs.is_empty() {
+        return Err(format!("drain host '{host}' has no addresses"));
+    }
+    for addr in &addrs {
+        check_address(addr.ip(), allow_private)?;
+    }
+
+    Ok(addrs)
+}
+
+/// Drains are set by users, so unless told otherwise they may not reach the loopback, private or
+/// link-local networks the logger itself runs in
+fn check_address(ip: IpAddr, allow_private: bool) -> Result<(), String> {
+    if allow_private || is_public(ip) {
+        Ok(())
+    } else {
+        Err(format!("{ip} is not a public address"))
+    }
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => {
+                let first_segment = ip.segments()[0];
+                let unique_local = first_segment & 0xfe00 == 0xfc00;
+                let link_local = first_segment & 0xffc0 == 0xfe80;
+
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || unique_local
+                    || link_local)
+            }
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    // 100.64.0.0/10, used by carrier-grade NAT
+    let shared = first == 100 && second & 0xc0 == 64;
+
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || shared)
+}
+
+fn sink(drain: &Drain, allow_private: bool) -> Result<Box<dyn Sink>, String> {
+    let kind = drain
+        .kind
+        .parse()
+        .map_err(|_| format!("unknown drain kind '{}'", drain.kind))?;
+    let url = parse_url(kind, &drain.url)?;
+
+    Ok(match kind {
+        DrainKind::Http => Box::new(
+            HttpSink::new(url, allow_private)
+                .map_err(|error| format!("failed to create http client: {error}"))?,
+        ),
+        DrainKind::Syslog => Box::new(SyslogSink::new(
+            url_host(&url).to_string(),
+            port(kind, &url),
+            allow_private,
+            drain.project_name.clone(),
+        )),
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct DrainOptions {
+    /// Most logs sent to a sink at once
+    pub batch_size: usize,
+    /// Longest time a log waits for its batch to fill up
+    pub batch_timeout: Duration,
+    /// Number of times a batch is sent before it is dropped
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled on every retry after it
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Logs waiting to be sent to a drain, past which new ones are dropped
+    pub queue_size: usize,
+    /// How often drain changes are picked up
+    pub refresh_interval: Duration,
+    /// Let drains send to loopback, private and link-local addresses, like when running locally
+    pub allow_private_addresses: bool,
+}
+
+impl Default for DrainOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 100,
+            batch_timeout: Duration::from_secs(1),
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            queue_size: 10_000,
+            refresh_interval: Duration::from_secs(30),
+            allow_private_addresses: false,
+        }
+    }
+}
+
+/// Forwards the logs being stored to the drains of their projects
+pub struct DrainManager<D> {
+    dal: D,
+    options: DrainOptions,
+    /// Drains the logs of each deployment go to, looked up the first time a deployment is seen
+    routes: HashMap<String, Vec<i64>>,
+    /// Queue of the task sending to each drain
+    forwarders: HashMap<i64, mpsc::Sender<LogItem>>,
+}
+
+impl<D> DrainManager<D>
+where
+    D: Dal + Send + Sync + 'static,
+{
+    pub fn new(dal: D, options: DrainOptions) -> Self {
+        Self {
+            dal,
+            options,
+            routes: Default::default(),
+            forwarders: Default::default(),
+        }
+    }
+
+    pub async fn run(mut self, mut logs_rx: broadcast::Receiver<(Vec<Log>, Span)>) {
+        let mut refresh = tokio::time::interval(self.options.refresh_interval);
+
+        loop {
+            tokio::select! {
+                _ = refresh.tick() => self.refresh().await,
+                received = logs_rx.recv() => match received {
+                    Ok((logs, parent_span)) => self.forward(logs).instrument(parent_span).await,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "drains fell behind, some logs were not forwarded");
+                    }
+                    Err(RecvError::Closed) => return,
+                },
+            }
+        }
+    }
+
+    /// Forget the routes so that new drains are picked up, and stop the removed ones
+    async fn refresh(&mut self) {
+        self.routes.clear();
+
+        match self.dal.get_drain_ids().await {
+            Ok(ids) => self.forwarders.retain(|id, _| ids.contains(id)),
+            Err(error) => error!(error = %error, "failed to get the drain ids"),
+        }
+    }
+
+    async fn forward(&mut self, logs: Vec<Log>) {
+        let mut unknown: Vec<String> = logs
+            .iter()
+            .filter(|log| !self.routes.contains_key(&log.deployment_id))
+            .map(|log| log.deployment_id.clone())
+            .collect();
+        unknown.sort();
+        unknown.dedup();
+
+        if !unknown.is_empty() {
+            match self.dal.get_deployment_drains(&unknown).await {
+                Ok(drains) => {
+                    for deployment_id in unknown {
+                        self.routes.entry(deployment_id).or_default();
+                    }
+
+                    for deployment_drain in drains {
+                        let drain = deployment_drain.drain;
+                        self.routes
+                            .entry(deployment_drain.deployment_id)
+                            .or_default()
+                            .push(drain.id);
+
+                        if self.forwarders.contains_key(&drain.id) {
+                            continue;
+                        }
+                        match sink(&drain, self.options.allow_private_addresses) {
+                            Ok(sink) => {
+                                let forwarder = self.spawn_forwarder(drain.id, sink);
+                                self.forwarders.insert(drain.id, forwarder);
+                            }
+                            Err(error) => {
+                                warn!(drain_id = drain.id, error = %error, "skipping drain")
+                            }
+                        }
+                    }
+                }
+                // The routes are looked up again with the next logs
+                Err(error) => error!(error = %error, "failed to get the drains of deployments"),
+            }
+        }
+
+        let mut dropped = 0;
+        for log in logs {
+            let Some(drain_ids) = self.routes.get(&log.deployment_id) else {
+                continue;
+            };
+            if drain_ids.is_empty() {
+                continue;
+            }
+            let Some(item) = to_log_item(&log) else {
+                continue;
+            };
+
+            for drain_id in drain_ids {
+                if let Some(forwarder) = self.forwarders.get(drain_id) {
+                    if let Err(TrySendError::Full(_)) = forwarder.try_send(item.clone()) {
+                        dropped += 1;
+                    }
+                }
+            }
+        }
+
+        if dropped > 0 {
+            warn!(dropped, "drain queues are full, dropping logs");
+        }
+    }
+
+    fn spawn_forwarder(&self, drain_id: i64, sink: Box<dyn Sink>) -> mpsc::Sender<LogItem> {
+        let (tx, rx) = mpsc::channel(self.options.queue_size);
+
+        tokio::spawn(
+            forward_batches(sink, rx, self.options.clone())
+                .instrument(info_span!("forward logs", drain_id)),
+        );
+
+        tx
+    }
+}
+
+/// Send the queued logs to a sink in batches, until the queue is closed
+async fn forward_batches(
+    mut sink: Box<dyn Sink>,
+    mut rx: mpsc::Receiver<LogItem>,
+    options: DrainOptions,
+) {
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        let deadline = tokio::time::sleep(options.batch_timeout);
+        tokio::pin!(deadline);
+
+        while batch.len() < options.batch_size {
+            tokio::select! {
+                item = rx.recv() => match item {
+                    Some(item) => batch.push(item),
+                    None => break,
+                },
+                _ = &mut deadline => break,
+            }
+        }
+
+        send_with_retries(sink.as_mut(), &batch, &options).await;
+    }
+}
+
+async fn send_with_retries(sink: &mut dyn Sink, batch: &[LogItem], options: &DrainOptions) {
+    let mut backoff = options.initial_backoff;
+
+    for attempt in 1..=options.max_attempts {
+        match sink.send(batch).await {
+            Ok(()) => return,
+            Err(error) if attempt < options.max_attempts => {
+                warn!(error = %error, attempt, "failed to send logs to drain, retrying");
+                tokio::time::sleep(backoff).await;
+                backoff = backoff.saturating_mul(2).min(options.max_backoff);
+            }
+            Err(error) => {
+                error!(
+                    error = %error,
+                    batch_size = batch.len(),
+                    "failed to send logs to drain, dropping them"
+                );
+            }
+        }
+    }
+}
+
+/// Only the logs of real deployments, which have a uuid, are forwarded
+fn to_log_item(log: &Log) -> Option<LogItem> {
+    Some(LogItem {
+        id: log.deployment_id.parse().ok()?,
+        internal_origin: log.shuttle_service_name.parse().unwrap_or_default(),
+        timestamp: log.tx_timestamp,
+        line: String::from_utf8_lossy(&log.data).into_owned(),
+        level: log.level.as_deref().and_then(|level| level.parse().ok()),
+        target: log.target.clone(),
+        fields: log
+            .fields
+            .clone()
+            .and_then(|fields| serde_json::from_value(fields).ok())
+            .unwrap_or_default(),
+    })
+}
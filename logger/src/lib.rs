@@ -1,10 +1,13 @@
 use async_trait::async_trait;
-use dal::Log;
-use dal::{Dal, DalError};
-use shuttle_common::{backends::auth::VerifyClaim, claims::Scope};
+use dal::DalError;
+use dal::{Log, LogCursor, LogFilter};
+use shuttle_common::{backends::auth::VerifyClaim, claims::Scope, log::DrainKind};
 use shuttle_proto::logger::LogLine;
 use shuttle_proto::logger::{
-    logger_server::Logger, LogsRequest, LogsResponse, StoreLogsRequest, StoreLogsResponse,
+    logger_server::Logger, AddDrainRequest, DeleteLogsRequest, DeleteLogsResponse,
+    DeploymentTierRequest, DeploymentTierResponse, Drain, DrainsRequest, DrainsResponse, LogItem,
+    LogsRequest, LogsResponse, RemoveDrainRequest, RemoveDrainResponse, ServiceLogsRequest,
+    ServiceLogsResponse, StoreLogsRequest, StoreLogsResponse,
 };
 use thiserror::Error;
 use tokio::sync::broadcast::Sender;
@@ -15,25 +18,39 @@ use tracing::{debug, error, field, Span};
 
 pub mod args;
 mod dal;
+pub mod drain;
+pub mod retention;
 
-pub use dal::Postgres;
+pub use dal::{Dal, Postgres};
+
+/// Number of logs returned for the logs of a service when no limit is asked for
+const SERVICE_LOGS_PAGE_SIZE: u32 = 1000;
 
 /// A wrapper to capture any error possible with this service
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("failed to interact with database: {0}")]
     Dal(#[from] DalError),
+    #[error("invalid logs request: {0}")]
+    InvalidRequest(String),
+    #[error("{0} not found")]
+    NotFound(&'static str),
 }
 
 impl From<Error> for Status {
     fn from(error: Error) -> Self {
-        Self::internal(error.to_string())
+        match error {
+            Error::InvalidRequest(_) => Self::invalid_argument(error.to_string()),
+            Error::NotFound(_) => Self::not_found(error.to_string()),
+            Error::Dal(_) => Self::internal(error.to_string()),
+        }
     }
 }
 
 pub struct Service<D> {
     dal: D,
     logs_tx: Sender<(Vec<Log>, Span)>,
+    allow_private_drains: bool,
 }
 
 impl<D> Service<D>
@@ -41,11 +58,21 @@ where
     D: Dal + Send + Sync + 'static,
 {
     pub fn new(logs_tx: Sender<(Vec<Log>, Span)>, dal: D) -> Self {
-        Self { dal, logs_tx }
+        Self {
+            dal,
+            logs_tx,
+            allow_private_drains: false,
+        }
     }
 
-    async fn get_logs(&self, deployment_id: String) -> Result<Vec<LogLine>, Error> {
-        let logs = self.dal.get_logs(deployment_id).await?;
+    /// Accept drains on loopback, private and link-local addresses
+    pub fn allow_private_drains(mut self) -> Self {
+        self.allow_private_drains = true;
+        self
+    }
+
+    async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<LogLine>, Error> {
+        let logs = self.dal.get_logs(filter).await?;
 
         Ok(logs.into_iter().map(Into::into).collect())
     }
@@ -92,13 +119,182 @@ where
     ) -> Result<Response<LogsResponse>, Status> {
         request.verify(Scope::Logs)?;
 
-        let request = request.into_inner();
-        let log_items = self.get_logs(request.deployment_id).await?;
+        let filter = LogFilter::try_from(request.into_inner()).map_err(Error::InvalidRequest)?;
+        let log_items = self.get_logs(&filter).await?;
         let result = LogsResponse { log_items };
 
         Ok(Response::new(result))
     }
 
+    #[tracing::instrument(skip(self, request))]
+    async fn get_service_logs(
+        &self,
+        request: Request<ServiceLogsRequest>,
+    ) -> Result<Response<ServiceLogsResponse>, Status> {
+        request.verify(Scope::Logs)?;
+
+        let request = request.into_inner();
+        let cursor = if request.cursor.is_empty() {
+            None
+        } else {
+            Some(
+                request
+                    .cursor
+                    .parse::<LogCursor>()
+                    .map_err(Error::InvalidRequest)?,
+            )
+        };
+        let mut filter = LogFilter::try_from(request).map_err(Error::InvalidRequest)?;
+        let limit = *filter.limit.get_or_insert(SERVICE_LOGS_PAGE_SIZE);
+
+        let logs = self
+            .dal
+            .get_logs_newest_first(&filter, cursor.as_ref())
+            .await
+            .map_err(Error::from)?;
+
+        // A full page means there might be more logs to get
+        let next_cursor = if logs.len() == limit as usize {
+            logs.last()
+                .and_then(LogCursor::after)
+                .map(|cursor| cursor.to_string())
+                .unwrap_or_default()
+        } else {
+            Default::default()
+        };
+
+        Ok(Response::new(ServiceLogsResponse {
+            log_items: logs.into_iter().map(LogItem::from).collect(),
+            next_cursor,
+        }))
+    }
+
+    #[tracing::instrument(skip(self, request))]
+    async fn set_deployment_tier(
+        &self,
+        request: Request<DeploymentTierRequest>,
+    ) -> Result<Response<DeploymentTierResponse>, Status> {
+        request.verify(Scope::DeploymentPush)?;
+
+        let tier = request.get_claim()?.tier;
+        let DeploymentTierRequest {
+            deployment_id,
+            project_name,
+        } = request.into_inner();
+
+        if deployment_id.is_empty() {
+            return Err(Error::InvalidRequest("a deployment id is required".to_string()).into());
+        }
+
+        self.dal
+            .set_deployment_tier(
+                &deployment_id,
+                (!project_name.is_empty()).then_some(project_name.as_str()),
+                tier,
+            )
+            .await
+            .map_err(Error::from)?;
+
+        Ok(Response::new(DeploymentTierResponse {}))
+    }
+
+    #[tracing::instrument(skip(self, request), fields(rows_deleted = field::Empty))]
+    async fn delete_logs(
+        &self,
+        request: Request<DeleteLogsRequest>,
+    ) -> Result<Response<DeleteLogsResponse>, Status> {
+        request.verify(Scope::ProjectWrite)?;
+
+        let DeleteLogsRequest { deployment_ids } = request.into_inner();
+        let deleted = self
+            .dal
+            .delete_logs(&deployment_ids)
+            .await
+            .map_err(Error::from)?;
+
+        Span::current().record("rows_deleted", deleted);
+
+        Ok(Response::new(DeleteLogsResponse { deleted }))
+    }
+
+    #[tracing::instrument(skip(self, request))]
+    async fn add_drain(
+        &self,
+        request: Request<AddDrainRequest>,
+    ) -> Result<Response<Drain>, Status> {
+        request.verify(Scope::ProjectWrite)?;
+
+        let tier = request.get_claim()?.tier;
+        let AddDrainRequest {
+            project_name,
+            kind,
+            url,
+            deployment_ids,
+        } = request.into_inner();
+
+        if project_name.is_empty() {
+            return Err(Error::InvalidRequest("a project name is required".to_string()).into());
+        }
+        let kind: DrainKind = kind
+            .parse()
+            .map_err(|_| Error::InvalidRequest(format!("'{kind}' is not a valid drain kind")))?;
+        drain::check_url(kind, &url, self.allow_private_drains)
+            .await
+            .map_err(Error::InvalidRequest)?;
+
+        self.dal
+            .set_deployments_project(&project_name, &deployment_ids, tier)
+            .await
+            .map_err(Error::from)?;
+        let drain = self
+            .dal
+            .add_drain(&project_name, kind, &url)
+            .await
+            .map_err(Error::from)?;
+
+        Ok(Response::new(drain.into()))
+    }
+
+    #[tracing::instrument(skip(self, request))]
+    async fn get_drains(
+        &self,
+        request: Request<DrainsRequest>,
+    ) -> Result<Response<DrainsResponse>, Status> {
+        request.verify(Scope::Logs)?;
+
+        let DrainsRequest { project_name } = request.into_inner();
+        let drains = self
+            .dal
+            .get_drains(&project_name)
+            .await
+            .map_err(Error::from)?;
+
+        Ok(Response::new(DrainsResponse {
+            drains: drains.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    #[tracing::instrument(skip(self, request))]
+    async fn remove_drain(
+        &self,
+        request: Request<RemoveDrainRequest>,
+    ) -> Result<Response<RemoveDrainResponse>, Status> {
+        request.verify(Scope::ProjectWrite)?;
+
+        let RemoveDrainRequest { project_name, id } = request.into_inner();
+        let removed = self
+            .dal
+            .remove_drain(&project_name, id)
+            .await
+            .map_err(Error::from)?;
+
+        if !removed {
+            return Err(Error::NotFound("drain").into());
+        }
+
+        Ok(Response::new(RemoveDrainResponse {}))
+    }
+
     type GetLogsStreamStream = ReceiverStream<Result<LogLine, Status>>;
 
     #[tracing::instrument(skip(self))]
@@ -110,11 +306,11 @@ where
 
         // Subscribe as soon as possible
         let mut logs_rx = self.logs_tx.subscribe();
-        let LogsRequest { deployment_id } = request.into_inner();
+        let filter = LogFilter::try_from(request.into_inner()).map_err(Error::InvalidRequest)?;
         let (tx, rx) = mpsc::channel(1);
 
         // Get logs before stream was started
-        let logs = self.get_logs(deployment_id.clone()).await?;
+        let logs = self.get_logs(&filter).await?;
 
         tokio::spawn(async move {
             let mut last = Default::default();
@@ -140,7 +336,7 @@ where
                         }
 
                         for log in logs {
-                            if log.deployment_id == deployment_id
+                            if filter.matches(&log)
                                 && log.tx_timestamp.timestamp() >= last.seconds
                                 && log.tx_timestamp.timestamp_nanos_opt().unwrap_or_default()
                                     > last.nanos.into()
@@ -8,7 +8,12 @@ use shuttle_common::{
     },
     log::Backend,
 };
-use shuttle_logger::{args::Args, Postgres, Service};
+use shuttle_logger::{
+    args::Args,
+    drain::{DrainManager, DrainOptions},
+    retention::prune_task,
+    Postgres, Service,
+};
 use shuttle_proto::logger::logger_server::LoggerServer;
 use tonic::transport::Server;
 use tracing::trace;
@@ -24,16 +29,33 @@ async fn main() {
     let mut server_builder = Server::builder()
         .http2_keepalive_interval(Some(Duration::from_secs(60)))
         .layer(JwtAuthenticationLayer::new(AuthPublicKey::new(
-            args.auth_uri,
+            args.auth_uri.clone(),
         )))
         .layer(ExtractPropagationLayer);
 
     let postgres = Postgres::new(&args.db_connection_uri).await;
 
-    let router = server_builder.add_service(LoggerServer::new(Service::new(
-        postgres.get_sender(),
-        postgres,
-    )));
+    tokio::spawn(prune_task(
+        postgres.clone(),
+        args.retention_policy(),
+        Duration::from_secs(args.prune_interval),
+    ));
+    tokio::spawn(
+        DrainManager::new(
+            postgres.clone(),
+            DrainOptions {
+                allow_private_addresses: args.allow_private_drains,
+                ..Default::default()
+            },
+        )
+        .run(postgres.get_sender().subscribe()),
+    );
+
+    let mut service = Service::new(postgres.get_sender(), postgres);
+    if args.allow_private_drains {
+        service = service.allow_private_drains();
+    }
+    let router = server_builder.add_service(LoggerServer::new(service));
 
     router.serve(args.address).await.unwrap();
 }
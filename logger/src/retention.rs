@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use shuttle_common::claims::AccountTier;
+use tracing::{error, field, info, info_span, Instrument, Span};
+
+use crate::dal::Dal;
+
+/// How much of the logs of a deployment are kept
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Retention {
+    /// Logs older than this many days are removed
+    pub days: u32,
+    /// Once the logs of a deployment go over this many bytes, its oldest lines are removed
+    pub max_bytes: Option<u64>,
+}
+
+/// The deployments a [`Retention`] applies to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RetentionScope {
+    /// Deployments of accounts on this tier
+    Tier(AccountTier),
+    /// Deployments that are on none of these tiers, including those with an unknown tier
+    Other(Vec<AccountTier>),
+}
+
+#[derive(Clone, Debug)]
+pub struct RetentionPolicy {
+    /// Retention of the deployments that have no tier specific one
+    pub default: Retention,
+    pub tiers: Vec<(AccountTier, Retention)>,
+}
+
+impl RetentionPolicy {
+    /// Each group of deployments with the retention that applies to it
+    pub fn scopes(&self) -> Vec<(RetentionScope, Retention)> {
+        let mut scopes: Vec<_> = self
+            .tiers
+            .iter()
+            .map(|(tier, retention)| (RetentionScope::Tier(*tier), *retention))
+            .collect();
+
+        scopes.push((
+            RetentionScope::Other(self.tiers.iter().map(|(tier, _)| *tier).collect()),
+            self.default,
+        ));
+
+        scopes
+    }
+}
+
+/// Remove all the logs that are past their retention and return how many lines were removed
+pub async fn prune_logs<D>(dal: &D, policy: &RetentionPolicy) -> u64
+where
+    D: Dal + Send + Sync,
+{
+    let mut total = 0;
+
+    for (scope, retention) in policy.scopes() {
+        match dal.prune_logs(&scope, &retention).await {
+            Ok(deleted) => {
+                info!(
+                    ?scope,
+                    rows_deleted = deleted,
+                    "pruned logs past their retention"
+                );
+                total += deleted;
+            }
+            Err(error) => {
+                error!(
+                    error = &error as &dyn std::error::Error,
+                    ?scope,
+                    "failed to prune logs"
+                );
+            }
+        }
+    }
+
+    total
+}
+
+/// Prune the logs on every tick of the interval
+pub async fn prune_task<D>(dal: D, policy: RetentionPolicy, interval: Duration)
+where
+    D: Dal + Send + Sync,
+{
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        interval.tick().await;
+
+        let span = info_span!("prune logs", rows_deleted = field::Empty);
+        async {
+            let deleted = prune_logs(&dal, &policy).await;
+            Span::current().record("rows_deleted", deleted);
+        }
+        .instrument(span)
+        .await;
+    }
+}
@@ -1,27 +1,46 @@
 use std::{
+    convert::Infallible,
     net::{Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::{Duration, SystemTime},
 };
 
 use ctor::dtor;
 use once_cell::sync::Lazy;
 use portpicker::pick_unused_port;
-use shuttle_common::claims::Scope;
+use shuttle_common::claims::{AccountTier, Scope};
 use shuttle_common_tests::JwtScopesLayer;
-use shuttle_logger::{Postgres, Service};
+use shuttle_logger::{
+    drain::{DrainManager, DrainOptions},
+    retention::{prune_logs, Retention, RetentionPolicy},
+    Postgres, Service,
+};
 use shuttle_proto::logger::{
-    logger_client::LoggerClient, logger_server::LoggerServer, LogItem, LogLine, LogsRequest,
-    StoreLogsRequest,
+    logger_client::LoggerClient, logger_server::LoggerServer, AddDrainRequest, DeleteLogsRequest,
+    DeploymentTierRequest, DrainsRequest, LogItem, LogLine, LogsRequest, RemoveDrainRequest,
+    ServiceLogsRequest, StoreLogsRequest,
 };
 use sqlx::__rt::timeout;
-use tokio::task::JoinHandle;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, BufReader},
+    net::TcpListener,
+    sync::mpsc,
+    task::JoinHandle,
+};
 use tonic::{
     transport::{Server, Uri},
-    Request,
+    Code, Request,
 };
 
 use shuttle_common_tests::postgres::DockerInstance;
 
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body,
+};
 use prost_types::Timestamp;
 
 const SHUTTLE_SERVICE: &str = "test";
@@ -58,6 +77,7 @@ mod needs_docker {
                         service_name: SHUTTLE_SERVICE.to_string(),
                         tx_timestamp: Some(Timestamp::from(SystemTime::UNIX_EPOCH)),
                         data: "log 1 example".as_bytes().to_vec(),
+                        ..Default::default()
                     }),
                 },
                 LogItem {
@@ -70,6 +90,7 @@ mod needs_docker {
                                 .unwrap(),
                         )),
                         data: "log 2 example".as_bytes().to_vec(),
+                        ..Default::default()
                     }),
                 },
             ];
@@ -86,6 +107,7 @@ mod needs_docker {
             let logs = client
                 .get_logs(Request::new(LogsRequest {
                     deployment_id: deployment_id.into(),
+                    ..Default::default()
                 }))
                 .await
                 .unwrap()
@@ -107,6 +129,600 @@ mod needs_docker {
         }
     }
 
+    #[tokio::test]
+    async fn filter_logs() {
+        let logger_port = pick_unused_port().unwrap();
+        let deployment_id = "runtime-filter-logs-deployment-id";
+
+        let server = spawn_server(logger_port);
+
+        let test_future = tokio::spawn(async move {
+            // Ensure the DB has been created and server has started.
+            tokio::time::sleep(Duration::from_millis(300)).await;
+
+            let dst = format!("http://localhost:{logger_port}");
+            let mut client = LoggerClient::connect(dst).await.unwrap();
+
+            let log_line = |secs: u64, level: &str, target: &str, data: &str| LogItem {
+                deployment_id: deployment_id.to_string(),
+                log_line: Some(LogLine {
+                    service_name: SHUTTLE_SERVICE.to_string(),
+                    tx_timestamp: Some(Timestamp::from(
+                        SystemTime::UNIX_EPOCH
+                            .checked_add(Duration::from_secs(secs))
+                            .unwrap(),
+                    )),
+                    data: data.as_bytes().to_vec(),
+                    level: level.to_string(),
+                    target: target.to_string(),
+                    fields: r#"{"state":"running"}"#.to_string(),
+                }),
+            };
+            // Lines do not have to be valid UTF-8
+            let mut binary_line = log_line(50, "", "", "");
+            binary_line.log_line.as_mut().unwrap().data =
+                b"\xff\xfe bytes before a TIMEOUT".to_vec();
+
+            let stored_logs = vec![
+                log_line(0, "info", "shuttle_deployer::run", "starting up"),
+                log_line(10, "warn", "my_app::db", "query Timeout, retrying"),
+                log_line(20, "error", "my_app::db", "gave up after timeout"),
+                log_line(30, "warn", "my_app::http", "slow request"),
+                log_line(40, "", "", "plain println with a timeout"),
+                binary_line,
+            ];
+            let response = client
+                .store_logs(Request::new(StoreLogsRequest {
+                    logs: stored_logs.clone(),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert!(response.success);
+
+            // Wait for the logs to be inserted
+            tokio::time::sleep(Duration::from_millis(300)).await;
+
+            let get_logs = |request: LogsRequest| {
+                let mut client = client.clone();
+                async move {
+                    client
+                        .get_logs(Request::new(LogsRequest {
+                            deployment_id: deployment_id.into(),
+                            ..request
+                        }))
+                        .await
+                        .unwrap()
+                        .into_inner()
+                        .log_items
+                }
+            };
+            let expected = |indices: &[usize]| {
+                indices
+                    .iter()
+                    .map(|i| stored_logs[*i].clone().log_line.unwrap())
+                    .collect::<Vec<LogLine>>()
+            };
+
+            let logs = get_logs(LogsRequest {
+                level: "warn".to_string(),
+                ..Default::default()
+            })
+            .await;
+            assert_eq!(logs, expected(&[1, 2, 3]));
+
+            let logs = get_logs(LogsRequest {
+                level: "warn".to_string(),
+                search: "timeout".to_string(),
+                ..Default::default()
+            })
+            .await;
+            assert_eq!(logs, expected(&[1, 2]));
+
+            let logs = get_logs(LogsRequest {
+                search: "timeout".to_string(),
+                ..Default::default()
+            })
+            .await;
+            assert_eq!(logs, expected(&[1, 2, 4, 5]));
+
+            let logs = get_logs(LogsRequest {
+                target: "my_app".to_string(),
+                since: Some(Timestamp::from(
+                    SystemTime::UNIX_EPOCH
+                        .checked_add(Duration::from_secs(20))
+                        .unwrap(),
+                )),
+                until: Some(Timestamp::from(
+                    SystemTime::UNIX_EPOCH
+                        .checked_add(Duration::from_secs(40))
+                        .unwrap(),
+                )),
+                ..Default::default()
+            })
+            .await;
+            assert_eq!(logs, expected(&[2, 3]));
+
+            let logs = get_logs(LogsRequest {
+                limit: 2,
+                offset: 1,
+                ..Default::default()
+            })
+            .await;
+            assert_eq!(logs, expected(&[1, 2]));
+
+            let status = client
+                .get_logs(Request::new(LogsRequest {
+                    deployment_id: deployment_id.into(),
+                    level: "loud".to_string(),
+                    ..Default::default()
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        });
+
+        tokio::select! {
+            _ = server => panic!("server stopped first"),
+            result = test_future => result.expect("test should succeed")
+        }
+    }
+
+    #[tokio::test]
+    async fn get_service_logs() {
+        let logger_port = pick_unused_port().unwrap();
+        let first_deployment = "service-logs-first-deployment-id";
+        let second_deployment = "service-logs-second-deployment-id";
+        let other_deployment = "service-logs-other-deployment-id";
+
+        let server = spawn_server(logger_port);
+
+        let test_future = tokio::spawn(async move {
+            // Ensure the DB has been created and server has started.
+            tokio::time::sleep(Duration::from_millis(300)).await;
+
+            let dst = format!("http://localhost:{logger_port}");
+            let mut client = LoggerClient::connect(dst).await.unwrap();
+
+            let log_item = |deployment_id: &str, secs: u64, data: &str| LogItem {
+                deployment_id: deployment_id.to_string(),
+                log_line: Some(LogLine {
+                    service_name: SHUTTLE_SERVICE.to_string(),
+                    tx_timestamp: Some(Timestamp::from(
+                        SystemTime::UNIX_EPOCH
+                            .checked_add(Duration::from_secs(secs))
+                            .unwrap(),
+                    )),
+                    data: data.as_bytes().to_vec(),
+                    ..Default::default()
+                }),
+            };
+            let stored_logs = vec![
+                log_item(first_deployment, 0, "starting"),
+                log_item(first_deployment, 10, "panicked"),
+                log_item(other_deployment, 15, "another service"),
+                log_item(second_deployment, 20, "starting again"),
+                log_item(second_deployment, 30, "running"),
+            ];
+            let response = client
+                .store_logs(Request::new(StoreLogsRequest {
+                    logs: stored_logs.clone(),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert!(response.success);
+
+            // Wait for the logs to be inserted
+            tokio::time::sleep(Duration::from_millis(300)).await;
+
+            let request = ServiceLogsRequest {
+                deployment_ids: vec![first_deployment.into(), second_deployment.into()],
+                limit: 3,
+                ..Default::default()
+            };
+            let page = client
+                .get_service_logs(Request::new(request.clone()))
+                .await
+                .unwrap()
+                .into_inner();
+
+            assert_eq!(
+                page.log_items,
+                vec![
+                    stored_logs[4].clone(),
+                    stored_logs[3].clone(),
+                    stored_logs[1].clone()
+                ]
+            );
+            assert!(!page.next_cursor.is_empty());
+
+            let page = client
+                .get_service_logs(Request::new(ServiceLogsRequest {
+                    cursor: page.next_cursor,
+                    ..request
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+
+            assert_eq!(page.log_items, vec![stored_logs[0].clone()]);
+            assert!(page.next_cursor.is_empty());
+        });
+
+        tokio::select! {
+            _ = server => panic!("server stopped first"),
+            result = test_future => result.expect("test should succeed")
+        }
+    }
+
+    #[tokio::test]
+    async fn prune_and_delete_logs() {
+        let logger_port = pick_unused_port().unwrap();
+        let basic_deployment = "prune-basic-deployment-id";
+        let unknown_deployment = "prune-unknown-deployment-id";
+
+        let pg = Postgres::new(&Uri::try_from(PG.get_unique_uri()).unwrap()).await;
+        let server = spawn_server_with_postgres(logger_port, pg.clone());
+
+        let test_future = tokio::spawn(async move {
+            // Ensure the server has started.
+            tokio::time::sleep(Duration::from_millis(300)).await;
+
+            let dst = format!("http://localhost:{logger_port}");
+            let mut client = LoggerClient::connect(dst).await.unwrap();
+
+            let log_item = |deployment_id: &str, secs: u64, data: &str| LogItem {
+                deployment_id: deployment_id.to_string(),
+                log_line: Some(LogLine {
+                    service_name: SHUTTLE_SERVICE.to_string(),
+                    tx_timestamp: Some(Timestamp::from(
+                        SystemTime::UNIX_EPOCH
+                            .checked_add(Duration::from_secs(secs))
+                            .unwrap(),
+                    )),
+                    data: data.as_bytes().to_vec(),
+                    ..Default::default()
+                }),
+            };
+            let stored_logs = vec![
+                log_item(basic_deployment, 0, "basic log"),
+                log_item(unknown_deployment, 0, "old line"),
+                log_item(unknown_deployment, 10, "newer line"),
+                log_item(unknown_deployment, 20, "newest"),
+            ];
+            client
+                .store_logs(Request::new(StoreLogsRequest {
+                    logs: stored_logs.clone(),
+                }))
+                .await
+                .unwrap();
+
+            // The claim of the test server is on the basic tier
+            client
+                .set_deployment_tier(Request::new(DeploymentTierRequest {
+                    deployment_id: basic_deployment.to_string(),
+                    ..Default::default()
+                }))
+                .await
+                .unwrap();
+
+            // Wait for the logs to be inserted
+            tokio::time::sleep(Duration::from_millis(300)).await;
+
+            // Everything is older than a day, but the default retention is far older than these logs
+            let policy = RetentionPolicy {
+                default: Retention {
+                    days: 100_000,
+                    max_bytes: Some(16),
+                },
+                tiers: vec![(
+                    AccountTier::Basic,
+                    Retention {
+                        days: 1,
+                        max_bytes: None,
+                    },
+                )],
+            };
+            assert_eq!(prune_logs(&pg, &policy).await, 2);
+
+            let logs_client = client.clone();
+            let get_logs = move |deployment_id: &str| {
+                let mut client = logs_client.clone();
+                let deployment_id = deployment_id.to_string();
+                async move {
+                    client
+                        .get_logs(Request::new(LogsRequest {
+                            deployment_id,
+                            ..Default::default()
+                        }))
+                        .await
+                        .unwrap()
+                        .into_inner()
+                        .log_items
+                }
+            };
+
+            assert!(get_logs(basic_deployment).await.is_empty());
+            assert_eq!(
+                get_logs(unknown_deployment).await,
+                vec![
+                    stored_logs[2].clone().log_line.unwrap(),
+                    stored_logs[3].clone().log_line.unwrap()
+                ]
+            );
+
+            let deleted = client
+                .delete_logs(Request::new(DeleteLogsRequest {
+                    deployment_ids: vec![unknown_deployment.to_string()],
+                }))
+                .await
+                .unwrap()
+                .into_inner()
+                .deleted;
+            assert_eq!(deleted, 2);
+            assert!(get_logs(unknown_deployment).await.is_empty());
+        });
+
+        tokio::select! {
+            _ = server => panic!("server stopped first"),
+            result = test_future => result.expect("test should succeed")
+        }
+    }
+
+    #[tokio::test]
+    async fn drain_logs() {
+        let logger_port = pick_unused_port().unwrap();
+        let http_port = pick_unused_port().unwrap();
+        let project_name = "drained-project";
+        let deployment_id = "6c1bd5d8-b4f1-4f5b-8d5d-3c1b8f0e6a21";
+        // Stored before the project of deployments was recorded
+        let legacy_deployment_id = "0e5c2f4a-3d8b-4a57-9c1e-7b2f6d9a4e10";
+
+        let pg = Postgres::new(&Uri::try_from(PG.get_unique_uri()).unwrap()).await;
+        let server = spawn_server_with_service(
+            logger_port,
+            Service::new(pg.get_sender(), pg.clone()).allow_private_drains(),
+        );
+        tokio::spawn(
+            DrainManager::new(
+                pg.clone(),
+                DrainOptions {
+                    batch_timeout: Duration::from_millis(100),
+                    initial_backoff: Duration::from_millis(50),
+                    refresh_interval: Duration::from_millis(100),
+                    allow_private_addresses: true,
+                    ..Default::default()
+                },
+            )
+            .run(pg.get_sender().subscribe()),
+        );
+
+        let (http_tx, mut http_rx) = mpsc::unbounded_channel();
+        tokio::spawn(http_receiver(http_port, http_tx));
+
+        let syslog_listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let syslog_port = syslog_listener.local_addr().unwrap().port();
+        let (syslog_tx, mut syslog_rx) = mpsc::unbounded_channel();
+        tokio::spawn(syslog_receiver(syslog_listener, syslog_tx));
+
+        let test_future = tokio::spawn(async move {
+            // Ensure the server has started.
+            tokio::time::sleep(Duration::from_millis(300)).await;
+
+            let dst = format!("http://localhost:{logger_port}");
+            let mut client = LoggerClient::connect(dst).await.unwrap();
+
+            client
+                .set_deployment_tier(Request::new(DeploymentTierRequest {
+                    deployment_id: legacy_deployment_id.to_string(),
+                    project_name: String::new(),
+                }))
+                .await
+                .unwrap();
+
+            let add_drain = |kind: &str, url: String| {
+                let mut client = client.clone();
+                let kind = kind.to_string();
+                async move {
+                    client
+                        .add_drain(Request::new(AddDrainRequest {
+                            project_name: project_name.to_string(),
+                            kind,
+                            url,
+                            deployment_ids: vec![
+                                deployment_id.to_string(),
+                                legacy_deployment_id.to_string(),
+                            ],
+                        }))
+                        .await
+                }
+            };
+
+            let http_drain = add_drain("http", format!("http://localhost:{http_port}/logs"))
+                .await
+                .unwrap()
+                .into_inner();
+            add_drain("syslog", format!("tcp://127.0.0.1:{syslog_port}"))
+                .await
+                .unwrap();
+
+            let error = add_drain("syslog", format!("http://localhost:{http_port}"))
+                .await
+                .unwrap_err();
+            assert_eq!(error.code(), Code::InvalidArgument);
+            let error = add_drain("ftp", "ftp://localhost".to_string())
+                .await
+                .unwrap_err();
+            assert_eq!(error.code(), Code::InvalidArgument);
+
+            client
+                .set_deployment_tier(Request::new(DeploymentTierRequest {
+                    deployment_id: deployment_id.to_string(),
+                    project_name: project_name.to_string(),
+                }))
+                .await
+                .unwrap();
+
+            client
+                .store_logs(Request::new(StoreLogsRequest {
+                    logs: vec![
+                        LogItem {
+                            deployment_id: deployment_id.to_string(),
+                            log_line: Some(LogLine {
+                                service_name: "Runtime".to_string(),
+                                tx_timestamp: Some(Timestamp::from(SystemTime::UNIX_EPOCH)),
+                                data: "listening on port 8000".as_bytes().to_vec(),
+                                level: "info".to_string(),
+                                ..Default::default()
+                            }),
+                        },
+                        LogItem {
+                            deployment_id: deployment_id.to_string(),
+                            log_line: Some(LogLine {
+                                service_name: "Runtime".to_string(),
+                                tx_timestamp: Some(Timestamp::from(SystemTime::UNIX_EPOCH)),
+                                data: "connection refused".as_bytes().to_vec(),
+                                level: "error".to_string(),
+                                ..Default::default()
+                            }),
+                        },
+                        LogItem {
+                            deployment_id: legacy_deployment_id.to_string(),
+                            log_line: Some(LogLine {
+                                service_name: "Runtime".to_string(),
+                                tx_timestamp: Some(Timestamp::from(SystemTime::UNIX_EPOCH)),
+                                data: "still running".as_bytes().to_vec(),
+                                level: "info".to_string(),
+                                ..Default::default()
+                            }),
+                        },
+                    ],
+                }))
+                .await
+                .unwrap();
+
+            // The first batch is refused by the receiver, so these only arrive after a retry
+            let mut lines = Vec::new();
+            while lines.len() < 3 {
+                let body = timeout(Duration::from_secs(5), http_rx.recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                lines.extend(body.lines().map(|line| {
+                    serde_json::from_str::<shuttle_common::log::LogItem>(line).unwrap()
+                }));
+            }
+            assert_eq!(lines[0].id.to_string(), deployment_id);
+            assert_eq!(lines[0].line, "listening on port 8000");
+            assert_eq!(lines[1].line, "connection refused");
+            assert_eq!(lines[2].id.to_string(), legacy_deployment_id);
+            assert_eq!(lines[2].line, "still running");
+
+            let mut messages = Vec::new();
+            while messages.len() < 3 {
+                messages.push(
+                    timeout(Duration::from_secs(5), syslog_rx.recv())
+                        .await
+                        .unwrap()
+                        .unwrap(),
+                );
+            }
+            assert_eq!(
+                messages,
+                vec![
+                    format!(
+                        "<14>1 1970-01-01T00:00:00.000000Z {project_name} runtime {deployment_id} - - listening on port 8000"
+                    ),
+                    format!(
+                        "<11>1 1970-01-01T00:00:00.000000Z {project_name} runtime {deployment_id} - - connection refused"
+                    ),
+                    format!(
+                        "<14>1 1970-01-01T00:00:00.000000Z {project_name} runtime {legacy_deployment_id} - - still running"
+                    ),
+                ]
+            );
+
+            let drains_client = client.clone();
+            let get_drains = move || {
+                let mut client = drains_client.clone();
+                async move {
+                    client
+                        .get_drains(Request::new(DrainsRequest {
+                            project_name: project_name.to_string(),
+                        }))
+                        .await
+                        .unwrap()
+                        .into_inner()
+                        .drains
+                }
+            };
+            assert_eq!(get_drains().await.len(), 2);
+
+            let remove_request = || {
+                Request::new(RemoveDrainRequest {
+                    project_name: project_name.to_string(),
+                    id: http_drain.id,
+                })
+            };
+            client.remove_drain(remove_request()).await.unwrap();
+            let error = client.remove_drain(remove_request()).await.unwrap_err();
+            assert_eq!(error.code(), Code::NotFound);
+
+            let drains = get_drains().await;
+            assert_eq!(drains.len(), 1);
+            assert_eq!(drains[0].kind, "syslog");
+        });
+
+        tokio::select! {
+            _ = server => panic!("server stopped first"),
+            result = test_future => result.expect("test should succeed")
+        }
+    }
+
+    #[tokio::test]
+    async fn drain_to_private_address() {
+        let logger_port = pick_unused_port().unwrap();
+        let pg = Postgres::new(&Uri::try_from(PG.get_unique_uri()).unwrap()).await;
+        let server = spawn_server_with_postgres(logger_port, pg);
+
+        let test_future = tokio::spawn(async move {
+            // Ensure the server has started.
+            tokio::time::sleep(Duration::from_millis(300)).await;
+
+            let dst = format!("http://localhost:{logger_port}");
+            let client = LoggerClient::connect(dst).await.unwrap();
+
+            for (kind, url) in [
+                ("http", "http://localhost:8000/logs"),
+                ("http", "http://127.0.0.1/logs"),
+                ("http", "http://169.254.169.254/latest/meta-data"),
+                ("http", "https://[::1]/logs"),
+                ("http", "http://[::ffff:10.0.0.1]/logs"),
+                ("syslog", "tcp://192.168.1.10:514"),
+                ("syslog", "syslog://10.1.2.3"),
+            ] {
+                let error = client
+                    .clone()
+                    .add_drain(Request::new(AddDrainRequest {
+                        project_name: "private-drains".to_string(),
+                        kind: kind.to_string(),
+                        url: url.to_string(),
+                        deployment_ids: Vec::new(),
+                    }))
+                    .await
+                    .unwrap_err();
+
+                assert_eq!(error.code(), Code::InvalidArgument, "{url}");
+            }
+        });
+
+        tokio::select! {
+            _ = server => panic!("server stopped first"),
+            result = test_future => result.expect("test should succeed")
+        }
+    }
+
     #[tokio::test]
     async fn get_stream_logs() {
         let logger_port = pick_unused_port().unwrap();
@@ -127,6 +743,7 @@ mod needs_docker {
                         service_name: SHUTTLE_SERVICE.to_string(),
                         tx_timestamp: Some(Timestamp::from(SystemTime::UNIX_EPOCH)),
                         data: "log 1 example".as_bytes().to_vec(),
+                        ..Default::default()
                     }),
                 },
                 LogItem {
@@ -139,6 +756,7 @@ mod needs_docker {
                                 .unwrap(),
                         )),
                         data: "log 2 example".as_bytes().to_vec(),
+                        ..Default::default()
                     }),
                 },
             ];
@@ -156,6 +774,7 @@ mod needs_docker {
             let mut response = client
                 .get_logs_stream(Request::new(LogsRequest {
                     deployment_id: deployment_id.into(),
+                    ..Default::default()
                 }))
                 .await
                 .unwrap()
@@ -196,4 +815,82 @@ mod needs_docker {
                 .unwrap()
         })
     }
+
+    fn spawn_server_with_postgres(port: u16, pg: Postgres) -> JoinHandle<()> {
+        spawn_server_with_service(port, Service::new(pg.get_sender(), pg))
+    }
+
+    fn spawn_server_with_service(port: u16, service: Service<Postgres>) -> JoinHandle<()> {
+        let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port);
+
+        tokio::task::spawn(async move {
+            Server::builder()
+                .layer(JwtScopesLayer::new(vec![
+                    Scope::Logs,
+                    Scope::DeploymentPush,
+                    Scope::ProjectWrite,
+                ]))
+                .add_service(LoggerServer::new(service))
+                .serve(addr)
+                .await
+                .unwrap()
+        })
+    }
+
+    /// Stand-in for an HTTP log aggregator that refuses the first batch it is sent
+    async fn http_receiver(port: u16, bodies: mpsc::UnboundedSender<String>) {
+        let refused = Arc::new(AtomicBool::new(false));
+        let make_service = make_service_fn(move |_| {
+            let bodies = bodies.clone();
+            let refused = refused.clone();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: hyper::Request<Body>| {
+                    let bodies = bodies.clone();
+                    let refused = refused.clone();
+
+                    async move {
+                        if !refused.swap(true, Ordering::SeqCst) {
+                            return Ok::<_, Infallible>(
+                                hyper::Response::builder()
+                                    .status(500)
+                                    .body(Body::empty())
+                                    .unwrap(),
+                            );
+                        }
+
+                        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                        bodies
+                            .send(String::from_utf8(body.to_vec()).unwrap())
+                            .unwrap();
+
+                        Ok(hyper::Response::new(Body::empty()))
+                    }
+                }))
+            }
+        });
+
+        hyper::Server::bind(&SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port))
+            .serve(make_service)
+            .await
+            .unwrap();
+    }
+
+    /// Stand-in for a syslog server, reading octet counted messages from a single connection
+    async fn syslog_receiver(listener: TcpListener, messages: mpsc::UnboundedSender<String>) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut reader = BufReader::new(stream);
+
+        loop {
+            let mut length = Vec::new();
+            if reader.read_until(b' ', &mut length).await.unwrap() == 0 {
+                return;
+            }
+            let length: usize = String::from_utf8(length).unwrap().trim().parse().unwrap();
+
+            let mut message = vec![0; length];
+            reader.read_exact(&mut message).await.unwrap();
+            messages.send(String::from_utf8(message).unwrap()).unwrap();
+        }
+    }
 }
@@ -15,6 +15,27 @@ pub struct StoreLogsResponse {
 pub struct LogsRequest {
     #[prost(string, tag = "1")]
     pub deployment_id: ::prost::alloc::string::String,
+    /// Only return logs at this level or above (trace, debug, info, warn, error). Empty for all levels
+    #[prost(string, tag = "2")]
+    pub level: ::prost::alloc::string::String,
+    /// Only return logs whose target starts with this prefix
+    #[prost(string, tag = "3")]
+    pub target: ::prost::alloc::string::String,
+    /// Only return logs from this time onwards
+    #[prost(message, optional, tag = "4")]
+    pub since: ::core::option::Option<::prost_types::Timestamp>,
+    /// Only return logs from before this time
+    #[prost(message, optional, tag = "5")]
+    pub until: ::core::option::Option<::prost_types::Timestamp>,
+    /// Only return logs containing this text, ignoring case
+    #[prost(string, tag = "6")]
+    pub search: ::prost::alloc::string::String,
+    /// Maximum number of logs to return. Zero for no limit
+    #[prost(uint32, tag = "7")]
+    pub limit: u32,
+    /// Number of matching logs to skip
+    #[prost(uint32, tag = "8")]
+    pub offset: u32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -24,6 +45,39 @@ pub struct LogsResponse {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServiceLogsRequest {
+    /// The deployments of the service to get the logs of
+    #[prost(string, repeated, tag = "1")]
+    pub deployment_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Same filters as in LogsRequest
+    #[prost(string, tag = "2")]
+    pub level: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub target: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub since: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "5")]
+    pub until: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(string, tag = "6")]
+    pub search: ::prost::alloc::string::String,
+    /// Maximum number of logs to return. Zero for the default page size
+    #[prost(uint32, tag = "7")]
+    pub limit: u32,
+    /// Continue after the last log of a previous response
+    #[prost(string, tag = "8")]
+    pub cursor: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServiceLogsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub log_items: ::prost::alloc::vec::Vec<LogItem>,
+    /// Cursor to get the next (older) logs with. Empty when there are no more logs
+    #[prost(string, tag = "2")]
+    pub next_cursor: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LogItem {
     #[prost(string, tag = "1")]
     pub deployment_id: ::prost::alloc::string::String,
@@ -39,7 +93,90 @@ pub struct LogLine {
     pub tx_timestamp: ::core::option::Option<::prost_types::Timestamp>,
     #[prost(bytes = "vec", tag = "3")]
     pub data: ::prost::alloc::vec::Vec<u8>,
+    /// Level of the event that produced this line. Empty when it is not known
+    #[prost(string, tag = "4")]
+    pub level: ::prost::alloc::string::String,
+    /// Target of the event that produced this line. Empty when it is not known
+    #[prost(string, tag = "5")]
+    pub target: ::prost::alloc::string::String,
+    /// Fields of the event and of its deployment span, as a JSON object
+    #[prost(string, tag = "6")]
+    pub fields: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeploymentTierRequest {
+    #[prost(string, tag = "1")]
+    pub deployment_id: ::prost::alloc::string::String,
+    /// Project of the deployment, whose drains its logs are forwarded to
+    #[prost(string, tag = "2")]
+    pub project_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeploymentTierResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteLogsRequest {
+    #[prost(string, repeated, tag = "1")]
+    pub deployment_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteLogsResponse {
+    /// Number of log lines that were deleted
+    #[prost(uint64, tag = "1")]
+    pub deleted: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Drain {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(string, tag = "2")]
+    pub project_name: ::prost::alloc::string::String,
+    /// Kind of sink: http (JSON lines) or syslog (RFC 5424 over TCP)
+    #[prost(string, tag = "3")]
+    pub kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub url: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AddDrainRequest {
+    #[prost(string, tag = "1")]
+    pub project_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub url: ::prost::alloc::string::String,
+    /// Deployments of the project, for those that were created before their project was recorded
+    #[prost(string, repeated, tag = "4")]
+    pub deployment_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DrainsRequest {
+    #[prost(string, tag = "1")]
+    pub project_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DrainsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub drains: ::prost::alloc::vec::Vec<Drain>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RemoveDrainRequest {
+    #[prost(string, tag = "1")]
+    pub project_name: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    pub id: i64,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RemoveDrainResponse {}
 /// Generated client implementations.
 pub mod logger_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -181,6 +318,114 @@ pub mod logger_client {
                 .insert(GrpcMethod::new("logger.Logger", "GetLogsStream"));
             self.inner.server_streaming(req, path, codec).await
         }
+        /// Get stored logs of all the deployments of a service, newest first
+        pub async fn get_service_logs(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ServiceLogsRequest>,
+        ) -> std::result::Result<tonic::Response<super::ServiceLogsResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/logger.Logger/GetServiceLogs");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("logger.Logger", "GetServiceLogs"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Keep the logs of a deployment for as long as the account tier of the request's claim allows
+        pub async fn set_deployment_tier(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeploymentTierRequest>,
+        ) -> std::result::Result<tonic::Response<super::DeploymentTierResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/logger.Logger/SetDeploymentTier");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("logger.Logger", "SetDeploymentTier"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Delete all the logs of some deployments
+        pub async fn delete_logs(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteLogsRequest>,
+        ) -> std::result::Result<tonic::Response<super::DeleteLogsResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/logger.Logger/DeleteLogs");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("logger.Logger", "DeleteLogs"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Forward the logs of a project to an external sink
+        pub async fn add_drain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AddDrainRequest>,
+        ) -> std::result::Result<tonic::Response<super::Drain>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/logger.Logger/AddDrain");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("logger.Logger", "AddDrain"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Get the drains the logs of a project are forwarded to
+        pub async fn get_drains(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DrainsRequest>,
+        ) -> std::result::Result<tonic::Response<super::DrainsResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/logger.Logger/GetDrains");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("logger.Logger", "GetDrains"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Stop forwarding logs to a drain
+        pub async fn remove_drain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RemoveDrainRequest>,
+        ) -> std::result::Result<tonic::Response<super::RemoveDrainResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/logger.Logger/RemoveDrain");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("logger.Logger", "RemoveDrain"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -210,6 +455,36 @@ pub mod logger_server {
             &self,
             request: tonic::Request<super::LogsRequest>,
         ) -> std::result::Result<tonic::Response<Self::GetLogsStreamStream>, tonic::Status>;
+        /// Get stored logs of all the deployments of a service, newest first
+        async fn get_service_logs(
+            &self,
+            request: tonic::Request<super::ServiceLogsRequest>,
+        ) -> std::result::Result<tonic::Response<super::ServiceLogsResponse>, tonic::Status>;
+        /// Keep the logs of a deployment for as long as the account tier of the request's claim allows
+        async fn set_deployment_tier(
+            &self,
+            request: tonic::Request<super::DeploymentTierRequest>,
+        ) -> std::result::Result<tonic::Response<super::DeploymentTierResponse>, tonic::Status>;
+        /// Delete all the logs of some deployments
+        async fn delete_logs(
+            &self,
+            request: tonic::Request<super::DeleteLogsRequest>,
+        ) -> std::result::Result<tonic::Response<super::DeleteLogsResponse>, tonic::Status>;
+        /// Forward the logs of a project to an external sink
+        async fn add_drain(
+            &self,
+            request: tonic::Request<super::AddDrainRequest>,
+        ) -> std::result::Result<tonic::Response<super::Drain>, tonic::Status>;
+        /// Get the drains the logs of a project are forwarded to
+        async fn get_drains(
+            &self,
+            request: tonic::Request<super::DrainsRequest>,
+        ) -> std::result::Result<tonic::Response<super::DrainsResponse>, tonic::Status>;
+        /// Stop forwarding logs to a drain
+        async fn remove_drain(
+            &self,
+            request: tonic::Request<super::RemoveDrainRequest>,
+        ) -> std::result::Result<tonic::Response<super::RemoveDrainResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct LoggerServer<T: Logger> {
@@ -406,6 +681,234 @@ pub mod logger_server {
                     };
                     Box::pin(fut)
                 }
+                "/logger.Logger/GetServiceLogs" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetServiceLogsSvc<T: Logger>(pub Arc<T>);
+                    impl<T: Logger> tonic::server::UnaryService<super::ServiceLogsRequest> for GetServiceLogsSvc<T> {
+                        type Response = super::ServiceLogsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ServiceLogsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Logger>::get_service_logs(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetServiceLogsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/logger.Logger/SetDeploymentTier" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetDeploymentTierSvc<T: Logger>(pub Arc<T>);
+                    impl<T: Logger> tonic::server::UnaryService<super::DeploymentTierRequest> for SetDeploymentTierSvc<T> {
+                        type Response = super::DeploymentTierResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeploymentTierRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Logger>::set_deployment_tier(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SetDeploymentTierSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/logger.Logger/DeleteLogs" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteLogsSvc<T: Logger>(pub Arc<T>);
+                    impl<T: Logger> tonic::server::UnaryService<super::DeleteLogsRequest> for DeleteLogsSvc<T> {
+                        type Response = super::DeleteLogsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteLogsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Logger>::delete_logs(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteLogsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/logger.Logger/AddDrain" => {
+                    #[allow(non_camel_case_types)]
+                    struct AddDrainSvc<T: Logger>(pub Arc<T>);
+                    impl<T: Logger> tonic::server::UnaryService<super::AddDrainRequest> for AddDrainSvc<T> {
+                        type Response = super::Drain;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AddDrainRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Logger>::add_drain(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = AddDrainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/logger.Logger/GetDrains" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetDrainsSvc<T: Logger>(pub Arc<T>);
+                    impl<T: Logger> tonic::server::UnaryService<super::DrainsRequest> for GetDrainsSvc<T> {
+                        type Response = super::DrainsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DrainsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Logger>::get_drains(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetDrainsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/logger.Logger/RemoveDrain" => {
+                    #[allow(non_camel_case_types)]
+                    struct RemoveDrainSvc<T: Logger>(pub Arc<T>);
+                    impl<T: Logger> tonic::server::UnaryService<super::RemoveDrainRequest> for RemoveDrainSvc<T> {
+                        type Response = super::RemoveDrainResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RemoveDrainRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Logger>::remove_drain(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RemoveDrainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
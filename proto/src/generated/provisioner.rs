@@ -3,6 +3,9 @@
 pub struct DatabaseRequest {
     #[prost(string, tag = "1")]
     pub project_name: ::prost::alloc::string::String,
+    /// Set to provision a database apart from the default database of the project
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
     #[prost(oneof = "database_request::DbType", tags = "10, 11")]
     pub db_type: ::core::option::Option<database_request::DbType>,
 }
@@ -20,7 +23,7 @@ pub mod database_request {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Shared {
-    #[prost(oneof = "shared::Engine", tags = "1, 50")]
+    #[prost(oneof = "shared::Engine", tags = "1, 50, 51")]
     pub engine: ::core::option::Option<shared::Engine>,
 }
 /// Nested message and enum types in `Shared`.
@@ -32,6 +35,8 @@ pub mod shared {
         Postgres(::prost::alloc::string::String),
         #[prost(string, tag = "50")]
         Mongodb(::prost::alloc::string::String),
+        #[prost(string, tag = "51")]
+        Redis(::prost::alloc::string::String),
     }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -105,6 +110,76 @@ pub struct ContainerResponse {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ObjectStoreRequest {
+    #[prost(string, tag = "1")]
+    pub project_name: ::prost::alloc::string::String,
+    /// Set to provision a bucket apart from the default bucket of the project
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ObjectStoreResponse {
+    #[prost(string, tag = "1")]
+    pub bucket: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub region: ::prost::alloc::string::String,
+    /// Empty when the bucket is on AWS S3 itself
+    #[prost(string, tag = "3")]
+    pub endpoint: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub access_key_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub secret_access_key: ::prost::alloc::string::String,
+    /// Key prefix the objects of this store are kept under inside the bucket
+    #[prost(string, tag = "6")]
+    pub prefix: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ObjectStoreDeletionResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Backup {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// Size of the dump in bytes
+    #[prost(uint64, tag = "3")]
+    pub size: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListBackupsResponse {
+    /// Newest first
+    #[prost(message, repeated, tag = "1")]
+    pub backups: ::prost::alloc::vec::Vec<Backup>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RestoreRequest {
+    /// The database the backup was taken from
+    #[prost(message, optional, tag = "1")]
+    pub database: ::core::option::Option<DatabaseRequest>,
+    #[prost(string, tag = "2")]
+    pub backup_id: ::prost::alloc::string::String,
+    /// Set to restore into a new database of this name instead of the original database
+    #[prost(string, tag = "3")]
+    pub target_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RestoreResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RotateCredentialsResponse {
+    /// When the credentials that were in use before the rotation stop working
+    #[prost(message, optional, tag = "1")]
+    pub old_expires_at: ::core::option::Option<::prost_types::Timestamp>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Ping {}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -253,6 +328,127 @@ pub mod provisioner_client {
                 .insert(GrpcMethod::new("provisioner.Provisioner", "DeleteDatabase"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn provision_object_store(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ObjectStoreRequest>,
+        ) -> std::result::Result<tonic::Response<super::ObjectStoreResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/provisioner.Provisioner/ProvisionObjectStore",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "provisioner.Provisioner",
+                "ProvisionObjectStore",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn delete_object_store(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ObjectStoreRequest>,
+        ) -> std::result::Result<tonic::Response<super::ObjectStoreDeletionResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/provisioner.Provisioner/DeleteObjectStore");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "provisioner.Provisioner",
+                "DeleteObjectStore",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn backup_database(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DatabaseRequest>,
+        ) -> std::result::Result<tonic::Response<super::Backup>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/provisioner.Provisioner/BackupDatabase");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("provisioner.Provisioner", "BackupDatabase"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_backups(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DatabaseRequest>,
+        ) -> std::result::Result<tonic::Response<super::ListBackupsResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/provisioner.Provisioner/ListBackups");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("provisioner.Provisioner", "ListBackups"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn restore_database(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RestoreRequest>,
+        ) -> std::result::Result<tonic::Response<super::RestoreResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/provisioner.Provisioner/RestoreDatabase");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "provisioner.Provisioner",
+                "RestoreDatabase",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn rotate_database_credentials(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DatabaseRequest>,
+        ) -> std::result::Result<tonic::Response<super::RotateCredentialsResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/provisioner.Provisioner/RotateDatabaseCredentials",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "provisioner.Provisioner",
+                "RotateDatabaseCredentials",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
         pub async fn health_check(
             &mut self,
             request: impl tonic::IntoRequest<super::Ping>,
@@ -291,6 +487,30 @@ pub mod provisioner_server {
             &self,
             request: tonic::Request<super::DatabaseRequest>,
         ) -> std::result::Result<tonic::Response<super::DatabaseDeletionResponse>, tonic::Status>;
+        async fn provision_object_store(
+            &self,
+            request: tonic::Request<super::ObjectStoreRequest>,
+        ) -> std::result::Result<tonic::Response<super::ObjectStoreResponse>, tonic::Status>;
+        async fn delete_object_store(
+            &self,
+            request: tonic::Request<super::ObjectStoreRequest>,
+        ) -> std::result::Result<tonic::Response<super::ObjectStoreDeletionResponse>, tonic::Status>;
+        async fn backup_database(
+            &self,
+            request: tonic::Request<super::DatabaseRequest>,
+        ) -> std::result::Result<tonic::Response<super::Backup>, tonic::Status>;
+        async fn list_backups(
+            &self,
+            request: tonic::Request<super::DatabaseRequest>,
+        ) -> std::result::Result<tonic::Response<super::ListBackupsResponse>, tonic::Status>;
+        async fn restore_database(
+            &self,
+            request: tonic::Request<super::RestoreRequest>,
+        ) -> std::result::Result<tonic::Response<super::RestoreResponse>, tonic::Status>;
+        async fn rotate_database_credentials(
+            &self,
+            request: tonic::Request<super::DatabaseRequest>,
+        ) -> std::result::Result<tonic::Response<super::RotateCredentialsResponse>, tonic::Status>;
         async fn health_check(
             &self,
             request: tonic::Request<super::Ping>,
@@ -497,6 +717,250 @@ pub mod provisioner_server {
                     };
                     Box::pin(fut)
                 }
+                "/provisioner.Provisioner/ProvisionObjectStore" => {
+                    #[allow(non_camel_case_types)]
+                    struct ProvisionObjectStoreSvc<T: Provisioner>(pub Arc<T>);
+                    impl<T: Provisioner> tonic::server::UnaryService<super::ObjectStoreRequest>
+                        for ProvisionObjectStoreSvc<T>
+                    {
+                        type Response = super::ObjectStoreResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ObjectStoreRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Provisioner>::provision_object_store(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ProvisionObjectStoreSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/provisioner.Provisioner/DeleteObjectStore" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteObjectStoreSvc<T: Provisioner>(pub Arc<T>);
+                    impl<T: Provisioner> tonic::server::UnaryService<super::ObjectStoreRequest>
+                        for DeleteObjectStoreSvc<T>
+                    {
+                        type Response = super::ObjectStoreDeletionResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ObjectStoreRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Provisioner>::delete_object_store(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteObjectStoreSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/provisioner.Provisioner/BackupDatabase" => {
+                    #[allow(non_camel_case_types)]
+                    struct BackupDatabaseSvc<T: Provisioner>(pub Arc<T>);
+                    impl<T: Provisioner> tonic::server::UnaryService<super::DatabaseRequest> for BackupDatabaseSvc<T> {
+                        type Response = super::Backup;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DatabaseRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Provisioner>::backup_database(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = BackupDatabaseSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/provisioner.Provisioner/ListBackups" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListBackupsSvc<T: Provisioner>(pub Arc<T>);
+                    impl<T: Provisioner> tonic::server::UnaryService<super::DatabaseRequest> for ListBackupsSvc<T> {
+                        type Response = super::ListBackupsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DatabaseRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Provisioner>::list_backups(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListBackupsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/provisioner.Provisioner/RestoreDatabase" => {
+                    #[allow(non_camel_case_types)]
+                    struct RestoreDatabaseSvc<T: Provisioner>(pub Arc<T>);
+                    impl<T: Provisioner> tonic::server::UnaryService<super::RestoreRequest> for RestoreDatabaseSvc<T> {
+                        type Response = super::RestoreResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RestoreRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Provisioner>::restore_database(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RestoreDatabaseSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/provisioner.Provisioner/RotateDatabaseCredentials" => {
+                    #[allow(non_camel_case_types)]
+                    struct RotateDatabaseCredentialsSvc<T: Provisioner>(pub Arc<T>);
+                    impl<T: Provisioner> tonic::server::UnaryService<super::DatabaseRequest> for RotateDatabaseCredentialsSvc<T> {
+                        type Response = super::RotateCredentialsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DatabaseRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Provisioner>::rotate_database_credentials(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RotateDatabaseCredentialsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/provisioner.Provisioner/HealthCheck" => {
                     #[allow(non_camel_case_types)]
                     struct HealthCheckSvc<T: Provisioner>(pub Arc<T>);
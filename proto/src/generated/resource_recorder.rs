@@ -19,6 +19,9 @@ pub mod record_request {
         pub config: ::prost::alloc::vec::Vec<u8>,
         #[prost(bytes = "vec", tag = "3")]
         pub data: ::prost::alloc::vec::Vec<u8>,
+        /// Empty for the default resource of a type
+        #[prost(string, tag = "4")]
+        pub name: ::prost::alloc::string::String,
     }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -70,6 +73,9 @@ pub struct ResourceIds {
     pub service_id: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
     pub r#type: ::prost::alloc::string::String,
+    /// Empty for the default resource of a type
+    #[prost(string, tag = "4")]
+    pub name: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -90,6 +96,9 @@ pub struct Resource {
     pub created_at: ::core::option::Option<::prost_types::Timestamp>,
     #[prost(message, optional, tag = "8")]
     pub last_updated: ::core::option::Option<::prost_types::Timestamp>,
+    /// Empty for the default resource of a type
+    #[prost(string, tag = "9")]
+    pub name: ::prost::alloc::string::String,
 }
 /// Generated client implementations.
 pub mod resource_recorder_client {
@@ -285,6 +294,28 @@ pub mod resource_recorder_client {
             ));
             self.inner.unary(req, path, codec).await
         }
+        /// Forget the config a resource was provisioned with, so its output is not reused on the next load
+        pub async fn invalidate_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResourceIds>,
+        ) -> std::result::Result<tonic::Response<super::ResultResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/resource_recorder.ResourceRecorder/InvalidateResource",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "resource_recorder.ResourceRecorder",
+                "InvalidateResource",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -319,6 +350,11 @@ pub mod resource_recorder_server {
             &self,
             request: tonic::Request<super::ResourceIds>,
         ) -> std::result::Result<tonic::Response<super::ResultResponse>, tonic::Status>;
+        /// Forget the config a resource was provisioned with, so its output is not reused on the next load
+        async fn invalidate_resource(
+            &self,
+            request: tonic::Request<super::ResourceIds>,
+        ) -> std::result::Result<tonic::Response<super::ResultResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct ResourceRecorderServer<T: ResourceRecorder> {
@@ -606,6 +642,46 @@ pub mod resource_recorder_server {
                     };
                     Box::pin(fut)
                 }
+                "/resource_recorder.ResourceRecorder/InvalidateResource" => {
+                    #[allow(non_camel_case_types)]
+                    struct InvalidateResourceSvc<T: ResourceRecorder>(pub Arc<T>);
+                    impl<T: ResourceRecorder> tonic::server::UnaryService<super::ResourceIds> for InvalidateResourceSvc<T> {
+                        type Response = super::ResultResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ResourceIds>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ResourceRecorder>::invalidate_resource(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = InvalidateResourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
@@ -7,6 +7,10 @@ pub struct LoadRequest {
     /// Path to compiled file to load for service
     #[prost(string, tag = "2")]
     pub path: ::prost::alloc::string::String,
+    /// Name of the project the service belongs to. Services deployed from the
+    /// same workspace share this name. Falls back to the service name when empty
+    #[prost(string, tag = "3")]
+    pub project_name: ::prost::alloc::string::String,
     /// A cache of resource details to use instead when asked
     #[prost(bytes = "vec", repeated, tag = "10")]
     pub resources: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
@@ -41,6 +45,11 @@ pub struct StartResponse {
     /// Was the start successful
     #[prost(bool, tag = "1")]
     pub success: bool,
+    /// The service does not accept connections on its address, so there is no
+    /// point waiting for it to. Unset by older runtimes, whose services are not
+    /// waited on either
+    #[prost(bool, optional, tag = "2")]
+    pub no_connections: ::core::option::Option<bool>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
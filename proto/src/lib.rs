@@ -14,7 +14,7 @@ pub mod provisioner {
 
     use shuttle_common::{
         database::{self, AwsRdsEngine, SharedEngine},
-        DatabaseInfo,
+        DatabaseInfo, ObjectStoreInfo,
     };
 
     pub use super::generated::provisioner::*;
@@ -33,6 +33,19 @@ pub mod provisioner {
         }
     }
 
+    impl From<ObjectStoreResponse> for ObjectStoreInfo {
+        fn from(response: ObjectStoreResponse) -> Self {
+            ObjectStoreInfo {
+                bucket: response.bucket,
+                region: response.region,
+                endpoint: Some(response.endpoint).filter(|endpoint| !endpoint.is_empty()),
+                access_key_id: response.access_key_id,
+                secret_access_key: response.secret_access_key.into(),
+                prefix: response.prefix,
+            }
+        }
+    }
+
     impl From<database::Type> for database_request::DbType {
         fn from(db_type: database::Type) -> Self {
             match db_type {
@@ -40,6 +53,7 @@ pub mod provisioner {
                     let engine = match engine {
                         SharedEngine::Postgres => shared::Engine::Postgres(String::new()),
                         SharedEngine::MongoDb => shared::Engine::Mongodb(String::new()),
+                        SharedEngine::Redis => shared::Engine::Redis(String::new()),
                     };
                     database_request::DbType::Shared(Shared {
                         engine: Some(engine),
@@ -72,6 +86,7 @@ pub mod provisioner {
                     shared::Engine::Mongodb(_) => {
                         Some(database::Type::Shared(SharedEngine::MongoDb))
                     }
+                    shared::Engine::Redis(_) => Some(database::Type::Shared(SharedEngine::Redis)),
                 },
                 database_request::DbType::AwsRds(AwsRds {
                     engine: Some(engine),
@@ -127,6 +142,7 @@ pub mod resource_recorder {
                 .context("resource type should have a valid resource string")?;
             let response = shuttle_common::resource::Response {
                 r#type,
+                name: Some(resource.name).filter(|name| !name.is_empty()),
                 config: serde_json::from_slice(&resource.config)
                     .context(format!("{} resource config should be valid JSON", r#type))?,
                 data: serde_json::from_slice(&resource.data)
@@ -147,6 +163,7 @@ pub mod resource_recorder {
 
             let response = shuttle_common::resource::Response {
                 r#type,
+                name: Some(resource.name).filter(|name| !name.is_empty()),
                 config: serde_json::from_slice(&resource.config)
                     .context(format!("{} resource config should be valid JSON", r#type))?,
                 data: serde_json::from_slice(&resource.data)
@@ -250,11 +267,19 @@ pub mod resource_recorder {
                             r#type: "database::shared::postgres".to_string(),
                             config: serde_json::to_vec(&json!({"public": true})).unwrap(),
                             data: serde_json::to_vec(&json!({"username": "test"})).unwrap(),
+                            name: String::new(),
+                        },
+                        record_request::Resource {
+                            r#type: "database::shared::postgres".to_string(),
+                            config: serde_json::to_vec(&json!({"public": true})).unwrap(),
+                            data: serde_json::to_vec(&json!({"username": "analytics"})).unwrap(),
+                            name: "analytics".to_string(),
                         },
                         record_request::Resource {
                             r#type: "database::aws_rds::mariadb".to_string(),
                             config: serde_json::to_vec(&json!({})).unwrap(),
                             data: serde_json::to_vec(&json!({"username": "maria"})).unwrap(),
+                            name: String::new(),
                         },
                     ],
                 }))
@@ -273,13 +298,23 @@ pub mod resource_recorder {
                         r#type: resource::Type::Database(database::Type::Shared(
                             database::SharedEngine::Postgres
                         )),
+                        name: None,
                         config: json!({"public": true}),
                         data: json!({"username": "test"}),
                     },
+                    resource::Response {
+                        r#type: resource::Type::Database(database::Type::Shared(
+                            database::SharedEngine::Postgres
+                        )),
+                        name: Some("analytics".to_string()),
+                        config: json!({"public": true}),
+                        data: json!({"username": "analytics"}),
+                    },
                     resource::Response {
                         r#type: resource::Type::Database(database::Type::AwsRds(
                             database::AwsRdsEngine::MariaDB
                         )),
+                        name: None,
                         config: json!({}),
                         data: json!({"username": "maria"}),
                     }
@@ -298,6 +333,7 @@ pub mod resource_recorder {
                     r#type: resource::Type::Database(database::Type::AwsRds(
                         database::AwsRdsEngine::MariaDB
                     )),
+                    name: None,
                     config: json!({}),
                     data: json!({"username": "maria"}),
                 }]
@@ -344,6 +380,16 @@ pub mod logger {
                     }),
                     service_name: format!("{:?}", value.internal_origin),
                     data: value.line.into_bytes(),
+                    level: value
+                        .level
+                        .map(|level| level.to_string())
+                        .unwrap_or_default(),
+                    target: value.target.unwrap_or_default(),
+                    fields: if value.fields.is_empty() {
+                        Default::default()
+                    } else {
+                        serde_json::to_string(&value.fields).unwrap_or_default()
+                    },
                 }),
             }
         }
@@ -364,6 +410,9 @@ pub mod logger {
                 service_name,
                 tx_timestamp,
                 data,
+                level,
+                target,
+                fields,
             } = self;
             let tx_timestamp = tx_timestamp.expect("log to have timestamp");
 
@@ -379,6 +428,9 @@ pub mod logger {
                     .unwrap_or_default(),
                 ),
                 line: String::from_utf8(data).expect("line to be utf-8"),
+                level: level.parse().ok(),
+                target: (!target.is_empty()).then_some(target),
+                fields: serde_json::from_str(&fields).unwrap_or_default(),
             }
         }
     }
@@ -30,9 +30,17 @@ impl ResourceRecorder for MockedResourceRecorder {
             resources,
         } = request.into_inner();
 
-        let mut resources = resources
-            .into_iter()
-            .map(|r| Resource {
+        let mut stored = self.resources.lock().unwrap();
+
+        for r in resources {
+            // Recording a resource again overwrites it, like the real recorder does
+            stored.retain(|s| {
+                !(s.project_id == project_id
+                    && s.service_id == service_id
+                    && s.r#type == r.r#type
+                    && s.name == r.name)
+            });
+            stored.push(Resource {
                 project_id: project_id.clone(),
                 service_id: service_id.clone(),
                 r#type: r.r#type,
@@ -41,10 +49,9 @@ impl ResourceRecorder for MockedResourceRecorder {
                 is_active: true,
                 created_at: None,
                 last_updated: None,
-            })
-            .collect();
-
-        self.resources.lock().unwrap().append(&mut resources);
+                name: r.name,
+            });
+        }
 
         Ok(Response::new(ResultResponse {
             success: true,
@@ -119,6 +126,7 @@ impl ResourceRecorder for MockedResourceRecorder {
             project_id,
             service_id,
             r#type,
+            name,
         } = request.into_inner();
         let resource = self
             .resources
@@ -126,7 +134,10 @@ impl ResourceRecorder for MockedResourceRecorder {
             .unwrap()
             .iter()
             .find(|r| {
-                r.project_id == project_id && r.service_id == service_id && r.r#type == r#type
+                r.project_id == project_id
+                    && r.service_id == service_id
+                    && r.r#type == r#type
+                    && r.name == name
             })
             .cloned();
 
@@ -147,6 +158,7 @@ impl ResourceRecorder for MockedResourceRecorder {
             project_id,
             service_id,
             r#type,
+            name,
         } = request.into_inner();
 
         // Fail to delete a metadata resource if requested
@@ -158,7 +170,10 @@ impl ResourceRecorder for MockedResourceRecorder {
         }
 
         self.resources.lock().unwrap().retain(|r| {
-            !(r.project_id == project_id && r.service_id == service_id && r.r#type == r#type)
+            !(r.project_id == project_id
+                && r.service_id == service_id
+                && r.r#type == r#type
+                && r.name == name)
         });
 
         Ok(Response::new(ResultResponse {
@@ -166,6 +181,35 @@ impl ResourceRecorder for MockedResourceRecorder {
             message: Default::default(),
         }))
     }
+
+    async fn invalidate_resource(
+        &self,
+        request: tonic::Request<ResourceIds>,
+    ) -> Result<Response<ResultResponse>, Status> {
+        println!("invalidate resource");
+
+        let ResourceIds {
+            project_id,
+            service_id,
+            r#type,
+            name,
+        } = request.into_inner();
+
+        for resource in self.resources.lock().unwrap().iter_mut() {
+            if resource.project_id == project_id
+                && resource.service_id == service_id
+                && resource.r#type == r#type
+                && resource.name == name
+            {
+                resource.config = b"null".to_vec();
+            }
+        }
+
+        Ok(Response::new(ResultResponse {
+            success: true,
+            message: Default::default(),
+        }))
+    }
 }
 
 /// Start a mocked resource recorder and return the port it started on
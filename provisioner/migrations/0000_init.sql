@@ -0,0 +1,11 @@
+-- Credentials the shared databases and IAM only keep hashes of or reveal once, so the same ones can
+-- be handed out on every provision. Secrets are encrypted, with their kind and name as associated
+-- data so a row cannot be swapped for another.
+CREATE TABLE IF NOT EXISTS credentials (
+  kind TEXT NOT NULL,
+  name TEXT NOT NULL,
+  nonce BYTEA NOT NULL,
+  secret BYTEA NOT NULL,
+  updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  PRIMARY KEY (kind, name)
+);
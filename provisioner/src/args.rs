@@ -26,6 +26,19 @@ pub struct Args {
     #[arg(long, env = "PROVISIONER_MONGODB_URI", hide_env_values = true)]
     pub shared_mongodb_uri: String,
 
+    /// URI to connect to Redis for managing shared key-value stores
+    #[arg(long, env = "PROVISIONER_REDIS_URI", hide_env_values = true)]
+    pub shared_redis_uri: String,
+
+    /// URI to connect to the Postgres of the provisioner itself, which the credentials handed out
+    /// to projects are stored in
+    #[arg(long, env = "PROVISIONER_DB_CONNECTION_URI", hide_env_values = true)]
+    pub db_connection_uri: String,
+
+    /// Base64 encoded 256-bit key the stored credentials are encrypted with
+    #[arg(long, env = "PROVISIONER_CREDENTIALS_KEY", hide_env_values = true)]
+    pub credentials_key: String,
+
     /// Fully qualified domain name this provisioner instance is reachable at
     #[arg(long, env = "PROVISIONER_FQDN", value_parser = parse_fqdn)]
     pub fqdn: FQDN,
@@ -38,6 +51,35 @@ pub struct Args {
     #[arg(long, env = "PROVISIONER_MONGODB_ADDRESS", default_value = "mongodb")]
     pub internal_mongodb_address: String,
 
+    /// Address the provisioned Redis can be reached at on the internal network
+    #[arg(long, env = "PROVISIONER_REDIS_ADDRESS", default_value = "redis")]
+    pub internal_redis_address: String,
+
+    /// Prefix of the buckets provisioned for object stores, since bucket names are shared by every
+    /// AWS account
+    #[arg(long, env = "PROVISIONER_BUCKET_PREFIX", default_value = "shuttle-")]
+    pub bucket_prefix: String,
+
+    /// Bucket the backups of shared databases are stored in
+    #[arg(
+        long,
+        env = "PROVISIONER_BACKUP_BUCKET",
+        default_value = "shuttle-backups"
+    )]
+    pub backup_bucket: String,
+
+    /// How many backups to keep for each shared database, the oldest are removed first
+    #[arg(long, env = "PROVISIONER_BACKUP_RETENTION", default_value_t = 7)]
+    pub backup_retention: usize,
+
+    /// How many minutes the old credentials of a database keep working after they are rotated
+    #[arg(
+        long,
+        env = "PROVISIONER_CREDENTIAL_GRACE_MINUTES",
+        default_value_t = 60
+    )]
+    pub credential_grace_minutes: u32,
+
     /// Address to reach the authentication service at
     #[arg(long, default_value = "http://127.0.0.1:8008")]
     pub auth_uri: Uri,
@@ -0,0 +1,138 @@
+use base64::{engine::general_purpose, Engine};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+use sqlx::{migrate::Migrator, postgres::PgPoolOptions, PgPool};
+
+use crate::Error;
+
+pub static MIGRATIONS: Migrator = sqlx::migrate!("./migrations");
+
+/// What a stored secret gives access to
+#[derive(Clone, Copy, Debug)]
+pub enum CredentialKind {
+    /// Password of a shared Postgres login role
+    Postgres,
+    /// Password of a shared Redis ACL user
+    Redis,
+    /// Access key of an object store user, as `{access_key_id}:{secret_access_key}`
+    ObjectStore,
+}
+
+impl CredentialKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Postgres => "postgres",
+            Self::Redis => "redis",
+            Self::ObjectStore => "object_store",
+        }
+    }
+}
+
+/// Credentials handed out to projects, kept encrypted in the provisioner's own database rather
+/// than on the shared infrastructure projects can reach
+pub struct CredentialStore {
+    pool: PgPool,
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl CredentialStore {
+    /// Connect to the provisioner's database and run its migrations. The key is a base64 encoded
+    /// 256-bit AES key, which can be generated using:
+    /// ```bash
+    /// openssl rand -base64 32
+    /// ```
+    pub async fn new(db_connection_uri: &str, key: &str) -> Result<Self, Error> {
+        let pool = PgPoolOptions::new()
+            .max_connections(4)
+            .connect(db_connection_uri)
+            .await?;
+
+        MIGRATIONS
+            .run(&pool)
+            .await
+            .map_err(|e| Error::Credentials(e.to_string()))?;
+
+        let key = general_purpose::STANDARD
+            .decode(key)
+            .map_err(|e| Error::Credentials(format!("invalid base64 key: {e}")))?;
+        let key = UnboundKey::new(&AES_256_GCM, &key)
+            .map_err(|_| Error::Credentials("the key should be 32 bytes long".to_string()))?;
+
+        Ok(Self {
+            pool,
+            key: LessSafeKey::new(key),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    pub async fn get(&self, kind: CredentialKind, name: &str) -> Result<Option<String>, Error> {
+        let row: Option<(Vec<u8>, Vec<u8>)> =
+            sqlx::query_as("SELECT nonce, secret FROM credentials WHERE kind = $1 AND name = $2")
+                .bind(kind.as_str())
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        let Some((nonce, mut secret)) = row else {
+            return Ok(None);
+        };
+
+        let nonce = Nonce::try_assume_unique_for_key(&nonce)
+            .map_err(|_| Error::Credentials(format!("invalid nonce for {name}")))?;
+        let secret = self
+            .key
+            .open_in_place(nonce, associated_data(kind, name), &mut secret)
+            .map_err(|_| Error::Credentials(format!("failed to decrypt secret of {name}")))?;
+
+        String::from_utf8(secret.to_vec())
+            .map(Some)
+            .map_err(|e| Error::Credentials(e.to_string()))
+    }
+
+    pub async fn set(&self, kind: CredentialKind, name: &str, secret: &str) -> Result<(), Error> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| Error::Credentials("failed to generate a nonce".to_string()))?;
+
+        let mut sealed = secret.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                associated_data(kind, name),
+                &mut sealed,
+            )
+            .map_err(|_| Error::Credentials(format!("failed to encrypt secret of {name}")))?;
+
+        sqlx::query(
+            "INSERT INTO credentials (kind, name, nonce, secret) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, name) DO UPDATE
+            SET nonce = EXCLUDED.nonce, secret = EXCLUDED.secret, updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(kind.as_str())
+        .bind(name)
+        .bind(nonce.as_slice())
+        .bind(sealed)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(&self, kind: CredentialKind, names: &[&str]) -> Result<(), Error> {
+        sqlx::query("DELETE FROM credentials WHERE kind = $1 AND name = ANY($2)")
+            .bind(kind.as_str())
+            .bind(names)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn associated_data(kind: CredentialKind, name: &str) -> Aad<String> {
+    Aad::from(format!("{}:{name}", kind.as_str()))
+}
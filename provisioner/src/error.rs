@@ -25,12 +25,26 @@ pub enum Error {
     UnexpectedSqlx(#[from] sqlx::Error),
     #[error("unexpected mongodb error: {0}")]
     UnexpectedMongodb(#[from] mongodb::error::Error),
+    #[error("unexpected redis error: {0}")]
+    UnexpectedRedis(#[from] redis::RedisError),
     #[error("failed to create RDS instance: {0}")]
     CreateRDSInstance(#[from] SdkError<CreateDBInstanceError>),
     #[error("failed to get description of RDS instance: {0}")]
     DescribeRDSInstance(#[from] SdkError<DescribeDBInstancesError>),
     #[error("failed to delete RDS instance: {0}")]
     DeleteRDSInstance(#[from] SdkError<DeleteDBInstanceError>),
+    #[error("failed to back up DB: {0}")]
+    BackupDB(String),
+    #[error("failed to restore DB: {0}")]
+    RestoreDB(String),
+    #[error("failed to rotate credentials: {0}")]
+    RotateCredentials(String),
+    #[error("failed to create object store: {0}")]
+    CreateObjectStore(String),
+    #[error("failed to delete object store: {0}")]
+    DeleteObjectStore(String),
+    #[error("failed to access stored credentials: {0}")]
+    Credentials(String),
     #[error["plain error: {0}"]]
     Plain(String),
 }
@@ -48,6 +62,11 @@ impl From<Error> for Status {
             Error::DeleteDB(_) | Error::DeleteRole(_) | Error::DeleteRDSInstance(_) => {
                 "failed to delete a database"
             }
+            Error::BackupDB(_) => "failed to back up a database",
+            Error::RestoreDB(_) => "failed to restore a database",
+            Error::RotateCredentials(_) => "failed to rotate the credentials of a database",
+            Error::CreateObjectStore(_) => "failed to provision an object store",
+            Error::DeleteObjectStore(_) => "failed to delete an object store",
             _ => "an unexpected error occurred",
         };
 
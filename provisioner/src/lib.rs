@@ -1,4 +1,5 @@
 use std::ops::Deref;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -8,42 +9,132 @@ use aws_sdk_rds::{
     error::SdkError, operation::modify_db_instance::ModifyDBInstanceError, types::DbInstance,
     Client,
 };
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{
+    BucketLocationConstraint, CreateBucketConfiguration, Delete, ObjectIdentifier,
+};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+pub use credentials::{CredentialKind, CredentialStore};
 pub use error::Error;
 use mongodb::{bson::doc, options::ClientOptions};
 use rand::Rng;
+use redis::AsyncCommands;
 use shuttle_common::backends::auth::VerifyClaim;
 use shuttle_common::backends::client::gateway;
 use shuttle_common::backends::ClaimExt;
 use shuttle_common::claims::Scope;
 use shuttle_common::models::project::ProjectName;
+use shuttle_common::resource;
+use shuttle_proto::prost_types::Timestamp;
 pub use shuttle_proto::provisioner::provisioner_server::ProvisionerServer;
 use shuttle_proto::provisioner::{
     aws_rds, database_request::DbType, shared, AwsRds, DatabaseRequest, DatabaseResponse, Shared,
 };
 use shuttle_proto::provisioner::{provisioner_server::Provisioner, DatabaseDeletionResponse};
+use shuttle_proto::provisioner::{
+    Backup, ListBackupsResponse, RestoreRequest, RestoreResponse, RotateCredentialsResponse,
+};
 use shuttle_proto::provisioner::{ContainerRequest, ContainerResponse, Ping, Pong};
+use shuttle_proto::provisioner::{
+    ObjectStoreDeletionResponse, ObjectStoreRequest, ObjectStoreResponse,
+};
 use shuttle_proto::resource_recorder;
 use sqlx::{postgres::PgPoolOptions, ConnectOptions, Executor, PgPool};
+use tempfile::TempPath;
+use tokio::process::Command;
 use tokio::sync::Mutex;
 use tokio::time::sleep;
 use tonic::transport::Uri;
 use tonic::{Request, Response, Status};
 use tracing::{debug, error, info, warn};
+use url::Url;
 
 mod args;
+mod credentials;
 mod error;
 
 const AWS_RDS_CLASS: &str = "db.t4g.micro";
 const MASTER_USERNAME: &str = "master";
 const RDS_SUBNET_GROUP: &str = "shuttle_rds";
+const OBJECT_STORE_POLICY: &str = "object-store";
+/// Backup ids start with the time they were taken, which makes them sort in the same order. A
+/// random suffix keeps backups taken in the same second apart.
+const BACKUP_ID_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+/// Hashes of the shared Redis credentials used to be stored in, before they were moved to the
+/// [CredentialStore]
+const LEGACY_CREDENTIALS_KEYS: &[(&str, CredentialKind)] = &[
+    ("shuttle_provisioner:credentials", CredentialKind::Redis),
+    (
+        "shuttle_provisioner:pg_credentials",
+        CredentialKind::Postgres,
+    ),
+    (
+        "shuttle_provisioner:object_store_keys",
+        CredentialKind::ObjectStore,
+    ),
+];
+/// Commands on the shared Redis that reach past a project's own keys and are not already in the
+/// `@dangerous` category
+const REDIS_DENIED_COMMANDS: &[&str] = &[
+    "-scan",
+    "-randomkey",
+    "-dbsize",
+    "-select",
+    "-swapdb",
+    "-move",
+    "-flushall",
+    "-flushdb",
+    "-pubsub",
+    "-memory",
+];
+
+/// Where provisioned resources are reached, and how their buckets, backups and credentials are
+/// managed
+#[derive(Clone, Debug)]
+pub struct ProvisionerConfig {
+    pub fqdn: String,
+    pub internal_pg_address: String,
+    pub internal_mongodb_address: String,
+    pub internal_redis_address: String,
+    pub bucket_prefix: String,
+    pub backup_bucket: String,
+    pub backup_retention: usize,
+    pub credential_grace_minutes: u32,
+}
+
+impl From<&Args> for ProvisionerConfig {
+    fn from(args: &Args) -> Self {
+        Self {
+            fqdn: args.fqdn.to_string(),
+            internal_pg_address: args.internal_pg_address.clone(),
+            internal_mongodb_address: args.internal_mongodb_address.clone(),
+            internal_redis_address: args.internal_redis_address.clone(),
+            bucket_prefix: args.bucket_prefix.clone(),
+            backup_bucket: args.backup_bucket.clone(),
+            backup_retention: args.backup_retention,
+            credential_grace_minutes: args.credential_grace_minutes,
+        }
+    }
+}
 
 pub struct ShuttleProvisioner {
     pool: PgPool,
+    shared_pg_uri: String,
     rds_client: aws_sdk_rds::Client,
+    s3_client: aws_sdk_s3::Client,
+    iam_client: aws_sdk_iam::Client,
+    object_store_region: String,
+    bucket_prefix: String,
+    backup_bucket: String,
+    backup_retention: usize,
+    credential_grace_period: chrono::Duration,
     mongodb_client: mongodb::Client,
+    redis_client: redis::Client,
+    credentials: CredentialStore,
     fqdn: String,
     internal_pg_address: String,
     internal_mongodb_address: String,
+    internal_redis_address: String,
     rr_client: Arc<Mutex<resource_recorder::Client>>,
     gateway_client: gateway::Client,
 }
@@ -52,9 +143,9 @@ impl ShuttleProvisioner {
     pub async fn new(
         shared_pg_uri: &str,
         shared_mongodb_uri: &str,
-        fqdn: String,
-        internal_pg_address: String,
-        internal_mongodb_address: String,
+        shared_redis_uri: &str,
+        credentials: CredentialStore,
+        config: ProvisionerConfig,
         resource_recorder_uri: Uri,
         gateway_uri: Uri,
     ) -> Result<Self, Error> {
@@ -67,6 +158,9 @@ impl ShuttleProvisioner {
         let mongodb_options = ClientOptions::parse(shared_mongodb_uri).await?;
         let mongodb_client = mongodb::Client::with_options(mongodb_options)?;
 
+        // Only parses the URI, connections are made when needed
+        let redis_client = redis::Client::open(shared_redis_uri)?;
+
         // Default timeout is too long so lowering it
         let timeout_config = timeout::TimeoutConfig::builder()
             .operation_timeout(Duration::from_secs(120))
@@ -79,23 +173,72 @@ impl ShuttleProvisioner {
             .await;
 
         let rds_client = aws_sdk_rds::Client::new(&aws_config);
+        let s3_client = aws_sdk_s3::Client::new(&aws_config);
+        let iam_client = aws_sdk_iam::Client::new(&aws_config);
+        let object_store_region = aws_config
+            .region()
+            .map(ToString::to_string)
+            .unwrap_or_else(|| "us-east-1".to_string());
 
         let rr_client = resource_recorder::get_client(resource_recorder_uri).await;
 
         let gateway_client = gateway::Client::new(gateway_uri.clone(), gateway_uri);
 
+        let ProvisionerConfig {
+            fqdn,
+            internal_pg_address,
+            internal_mongodb_address,
+            internal_redis_address,
+            bucket_prefix,
+            backup_bucket,
+            backup_retention,
+            credential_grace_minutes,
+        } = config;
+
         Ok(Self {
             pool,
+            shared_pg_uri: shared_pg_uri.to_string(),
             rds_client,
+            s3_client,
+            iam_client,
+            object_store_region,
+            bucket_prefix,
+            backup_bucket,
+            backup_retention,
+            credential_grace_period: chrono::Duration::minutes(credential_grace_minutes.into()),
             mongodb_client,
+            redis_client,
+            credentials,
             fqdn,
             internal_pg_address,
             internal_mongodb_address,
+            internal_redis_address,
             rr_client: Arc::new(Mutex::new(rr_client)),
             gateway_client,
         })
     }
 
+    /// Move the credentials earlier versions kept in the shared Redis to the [CredentialStore], so
+    /// existing projects keep their credentials
+    pub async fn move_legacy_credentials(&self) -> Result<(), Error> {
+        let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
+
+        for (key, kind) in LEGACY_CREDENTIALS_KEYS {
+            let secrets: Vec<(String, String)> = conn.hgetall(key).await?;
+
+            for (name, secret) in secrets {
+                // Credentials set since the upgrade are newer than the ones left in Redis
+                if self.credentials.get(*kind, &name).await?.is_none() {
+                    self.credentials.set(*kind, &name, &secret).await?;
+                }
+            }
+
+            conn.del::<_, ()>(key).await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn request_shared_db(
         &self,
         project_name: &str,
@@ -131,12 +274,44 @@ impl ShuttleProvisioner {
                     port: "27017".to_string(),
                 })
             }
+            shared::Engine::Redis(_) => {
+                let (username, password) = self.shared_redis_user(project_name).await?;
+
+                Ok(DatabaseResponse {
+                    engine: "redis".to_string(),
+                    username,
+                    password,
+                    // Projects are kept apart by key prefix, so all use the first logical DB
+                    database_name: "0".to_string(),
+                    address_private: self.internal_redis_address.clone(),
+                    address_public: self.fqdn.clone(),
+                    port: "6379".to_string(),
+                })
+            }
         }
     }
 
     async fn shared_pg_role(&self, project_name: &str) -> Result<(String, String), Error> {
-        let username = format!("user-{project_name}");
-        let password = generate_password();
+        let username = self.shared_pg_login_role(project_name).await?;
+
+        // Postgres only keeps hashes of passwords, so the plain ones are stored to hand out the
+        // same credentials to every service of the project. Rotating the credentials is how a
+        // password is changed.
+        let stored_password = self
+            .credentials
+            .get(CredentialKind::Postgres, &username)
+            .await?;
+        let password = match stored_password {
+            Some(password) => password,
+            None => {
+                let password = generate_password();
+                self.credentials
+                    .set(CredentialKind::Postgres, &username, &password)
+                    .await?;
+
+                password
+            }
+        };
 
         let matching_user = sqlx::query("SELECT rolname FROM pg_roles WHERE rolname = $1")
             .bind(&username)
@@ -155,12 +330,14 @@ impl ShuttleProvisioner {
                 .await
                 .map_err(|e| Error::CreateRole(e.to_string()))?;
         } else {
-            info!("cycling password of user");
+            info!("setting password of user");
 
+            // Also applied when the password is unchanged, in case the role was changed by hand
             // Binding does not work for identifiers
             // https://stackoverflow.com/questions/63723236/sql-statement-to-create-role-fails-on-postgres-12-using-dapper
-            let update_role_query =
-                format!("ALTER ROLE \"{username}\" WITH LOGIN PASSWORD '{password}'");
+            let update_role_query = format!(
+                "ALTER ROLE \"{username}\" WITH LOGIN PASSWORD '{password}' VALID UNTIL 'infinity'"
+            );
             sqlx::query(&update_role_query)
                 .execute(&self.pool)
                 .await
@@ -170,6 +347,116 @@ impl ShuttleProvisioner {
         Ok((username, password))
     }
 
+    /// The role a shared Postgres database is logged into with. Each database has an owner role and
+    /// an alternate role acting as the owner, and rotating credentials switches between the two so
+    /// that the old password can keep working for a while.
+    async fn shared_pg_login_role(&self, project_name: &str) -> Result<String, Error> {
+        let owner = format!("user-{project_name}");
+        let alternate = format!("{owner}-alt");
+
+        let active_roles: Vec<String> = sqlx::query_scalar(
+            "SELECT rolname FROM pg_roles WHERE rolname IN ($1, $2) AND (rolvaliduntil IS NULL OR rolvaliduntil = 'infinity')",
+        )
+        .bind(&owner)
+        .bind(&alternate)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if !active_roles.contains(&owner) && active_roles.contains(&alternate) {
+            Ok(alternate)
+        } else {
+            Ok(owner)
+        }
+    }
+
+    /// Give the other login role of a shared Postgres database a new password and let the one in
+    /// use expire after the grace period. Sessions of the expired role are ended by
+    /// [Self::terminate_expired_pg_sessions].
+    async fn rotate_shared_pg_credentials(
+        &self,
+        project_name: &str,
+    ) -> Result<DateTime<Utc>, Error> {
+        let owner = format!("user-{project_name}");
+        let alternate = format!("{owner}-alt");
+        let current = self.shared_pg_login_role(project_name).await?;
+        let next = if current == owner { &alternate } else { &owner };
+        let password = generate_password();
+        let expires_at = Utc::now() + self.credential_grace_period;
+
+        let next_exists = sqlx::query("SELECT rolname FROM pg_roles WHERE rolname = $1")
+            .bind(next)
+            .fetch_optional(&self.pool)
+            .await?
+            .is_some();
+
+        // Binding does not work for identifiers
+        // https://stackoverflow.com/questions/63723236/sql-statement-to-create-role-fails-on-postgres-12-using-dapper
+        let mut queries = if next_exists {
+            vec![format!(
+                "ALTER ROLE \"{next}\" WITH LOGIN PASSWORD '{password}' VALID UNTIL 'infinity'"
+            )]
+        } else {
+            // Anything the alternate role creates is owned by the owner role, so it stays
+            // reachable when the roles are switched again
+            vec![
+                format!(
+                    "CREATE ROLE \"{next}\" WITH LOGIN PASSWORD '{password}' IN ROLE \"{owner}\""
+                ),
+                format!("ALTER ROLE \"{next}\" SET role = '{owner}'"),
+            ]
+        };
+        queries.push(format!(
+            "ALTER ROLE \"{current}\" VALID UNTIL '{}'",
+            expires_at.to_rfc3339()
+        ));
+
+        let mut transaction = self.pool.begin().await?;
+
+        for query in queries {
+            sqlx::query(&query)
+                .execute(&mut *transaction)
+                .await
+                .map_err(|e| Error::RotateCredentials(e.to_string()))?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| Error::RotateCredentials(e.to_string()))?;
+
+        // Should this fail, the next provision sets the role back to the stored password, which
+        // then works for every service
+        self.credentials
+            .set(CredentialKind::Postgres, next, &password)
+            .await?;
+
+        info!("rotated credentials from {current} to {next}");
+
+        Ok(expires_at)
+    }
+
+    /// End the sessions of shared Postgres roles that are past their grace period. An expired
+    /// role can no longer log in, but sessions opened before it expired would go on forever.
+    pub async fn terminate_expired_pg_sessions(&self) -> Result<(), Error> {
+        // Terminating in the select list makes sure only the filtered sessions are ended
+        let sessions: Vec<(String, bool)> = sqlx::query_as(
+            r#"SELECT usename, pg_terminate_backend(pid) FROM (
+                SELECT activity.usename, activity.pid FROM pg_stat_activity activity
+                JOIN pg_roles roles ON roles.rolname = activity.usename
+                WHERE roles.rolname LIKE 'user-%'
+                AND roles.rolvaliduntil < CURRENT_TIMESTAMP
+            ) expired"#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        for (username, _) in sessions.iter().filter(|(_, terminated)| *terminated) {
+            info!("terminated session of expired role: {username}");
+        }
+
+        Ok(())
+    }
+
     async fn shared_pg(&self, project_name: &str, username: &str) -> Result<String, Error> {
         let database_name = format!("db-{project_name}");
 
@@ -262,6 +549,55 @@ impl ShuttleProvisioner {
         }
     }
 
+    /// Create or update the ACL user of a project, which can only use the keys and channels
+    /// starting with `{project_name}:`
+    async fn shared_redis_user(&self, project_name: &str) -> Result<(String, String), Error> {
+        let username = format!("user-{project_name}");
+
+        let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
+
+        // Redis only keeps hashes of ACL passwords, so the plain ones are stored to hand out the
+        // same credentials on every provision
+        let password = match self
+            .credentials
+            .get(CredentialKind::Redis, &username)
+            .await?
+        {
+            Some(password) => password,
+            None => {
+                info!("creating new shared redis user: {username}");
+
+                let password = generate_password();
+                self.credentials
+                    .set(CredentialKind::Redis, &username, &password)
+                    .await?;
+
+                password
+            }
+        };
+
+        // `reset` drops any rules set by hand so the user always ends up with exactly these
+        redis::cmd("ACL")
+            .arg("SETUSER")
+            .arg(&username)
+            .arg("reset")
+            .arg("on")
+            .arg(format!(">{password}"))
+            .arg(format!("~{project_name}:*"))
+            .arg(format!("&{project_name}:*"))
+            .arg("+@all")
+            .arg("-@dangerous")
+            .arg("-@scripting")
+            .arg(REDIS_DENIED_COMMANDS)
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .map_err(|e| Error::CreateRole(e.to_string()))?;
+
+        info!("set up shared redis user: {username}");
+
+        Ok((username, password))
+    }
+
     async fn request_aws_rds(
         &self,
         project_name: &str,
@@ -362,6 +698,7 @@ impl ShuttleProvisioner {
         match engine {
             shared::Engine::Postgres(_) => self.delete_shared_postgres(project_name).await?,
             shared::Engine::Mongodb(_) => self.delete_shared_mongodb(project_name).await?,
+            shared::Engine::Redis(_) => self.delete_shared_redis(project_name).await?,
         }
         Ok(DatabaseDeletionResponse {})
     }
@@ -393,6 +730,20 @@ impl ShuttleProvisioner {
             warn!("did not drop shared postgres database: {database_name}. Does not exist.");
         }
 
+        self.credentials
+            .delete(
+                CredentialKind::Postgres,
+                &[&role_name, &format!("{role_name}-alt")],
+            )
+            .await?;
+
+        // The alternate login role is only there once the credentials have been rotated
+        let drop_alternate_query = format!("DROP ROLE IF EXISTS \"{role_name}-alt\"");
+        sqlx::query(&drop_alternate_query)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::DeleteRole(e.to_string()))?;
+
         if sqlx::query("SELECT 1 FROM pg_roles WHERE rolname = $1")
             .bind(&role_name)
             .fetch_optional(&self.pool)
@@ -441,6 +792,58 @@ impl ShuttleProvisioner {
         Ok(())
     }
 
+    async fn delete_shared_redis(&self, project_name: &str) -> Result<(), Error> {
+        let username = format!("user-{project_name}");
+        let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
+
+        // Remove the user first so nothing new gets written while the keys are removed
+        let deleted: u64 = redis::cmd("ACL")
+            .arg("DELUSER")
+            .arg(&username)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| Error::DeleteRole(e.to_string()))?;
+
+        self.credentials
+            .delete(CredentialKind::Redis, &[&username])
+            .await?;
+
+        if deleted == 0 {
+            warn!("did not delete shared redis user: {username}. Does not exist.");
+        } else {
+            info!("deleted shared redis user: {username}");
+        }
+
+        let keys: Vec<String> = {
+            let mut iter = conn
+                .scan_match::<_, String>(format!("{project_name}:*"))
+                .await
+                .map_err(|e| Error::DeleteDB(e.to_string()))?;
+            let mut keys = Vec::new();
+
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+
+            keys
+        };
+
+        for keys in keys.chunks(1000) {
+            redis::cmd("UNLINK")
+                .arg(keys)
+                .query_async::<_, ()>(&mut conn)
+                .await
+                .map_err(|e| Error::DeleteDB(e.to_string()))?;
+        }
+
+        info!(
+            "deleted {} keys of shared redis user: {username}",
+            keys.len()
+        );
+
+        Ok(())
+    }
+
     async fn delete_aws_rds(
         &self,
         project_name: &str,
@@ -461,6 +864,626 @@ impl ShuttleProvisioner {
 
         Ok(DatabaseDeletionResponse {})
     }
+
+    async fn request_object_store(
+        &self,
+        bucket: &str,
+        prefix: &str,
+    ) -> Result<ObjectStoreResponse, Error> {
+        self.create_bucket(bucket).await?;
+        let (access_key_id, secret_access_key) = self.object_store_user(bucket).await?;
+
+        let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
+        conn.sadd::<_, _, ()>(object_store_members_key(bucket), prefix)
+            .await
+            .map_err(|e| Error::CreateObjectStore(e.to_string()))?;
+
+        Ok(ObjectStoreResponse {
+            bucket: bucket.to_string(),
+            region: self.object_store_region.clone(),
+            endpoint: String::new(),
+            access_key_id,
+            secret_access_key,
+            prefix: prefix.to_string(),
+        })
+    }
+
+    async fn create_bucket(&self, bucket: &str) -> Result<(), Error> {
+        let mut request = self.s3_client.create_bucket().bucket(bucket);
+
+        // S3 rejects a location constraint for its default region
+        if self.object_store_region != "us-east-1" {
+            request = request.create_bucket_configuration(
+                CreateBucketConfiguration::builder()
+                    .location_constraint(BucketLocationConstraint::from(
+                        self.object_store_region.as_str(),
+                    ))
+                    .build(),
+            );
+        }
+
+        match request.send().await {
+            Ok(_) => {
+                info!("created bucket: {bucket}");
+                Ok(())
+            }
+            Err(error)
+                if error
+                    .as_service_error()
+                    .is_some_and(|error| error.is_bucket_already_owned_by_you()) =>
+            {
+                debug!("bucket already exists: {bucket}");
+                Ok(())
+            }
+            Err(error) => Err(Error::CreateObjectStore(error.to_string())),
+        }
+    }
+
+    /// Create or update the IAM user of a project, which can only use the objects in the bucket of
+    /// that project. The user is named after the bucket and is shared by all the stores in it.
+    async fn object_store_user(&self, bucket: &str) -> Result<(String, String), Error> {
+        let client = &self.iam_client;
+
+        match client.get_user().user_name(bucket).send().await {
+            Ok(_) => {}
+            Err(error)
+                if error
+                    .as_service_error()
+                    .is_some_and(|error| error.is_no_such_entity_exception()) =>
+            {
+                client
+                    .create_user()
+                    .user_name(bucket)
+                    .send()
+                    .await
+                    .map_err(|e| Error::CreateRole(e.to_string()))?;
+
+                info!("created object store user: {bucket}");
+            }
+            Err(error) => return Err(Error::CreateRole(error.to_string())),
+        }
+
+        client
+            .put_user_policy()
+            .user_name(bucket)
+            .policy_name(OBJECT_STORE_POLICY)
+            .policy_document(object_store_policy(bucket))
+            .send()
+            .await
+            .map_err(|e| Error::CreateRole(e.to_string()))?;
+
+        let keys = client
+            .list_access_keys()
+            .user_name(bucket)
+            .send()
+            .await
+            .map_err(|e| Error::UpdateRole(e.to_string()))?;
+
+        // IAM only returns the secret of a key when it is created, so it is stored to hand the
+        // same key to every store of the project. Cycling it would break the stores already
+        // running with it.
+        let stored_key = self
+            .credentials
+            .get(CredentialKind::ObjectStore, bucket)
+            .await?
+            .and_then(|stored_key| {
+                stored_key
+                    .split_once(':')
+                    .map(|(id, secret)| (id.to_string(), secret.to_string()))
+            });
+
+        if let Some((access_key_id, secret_access_key)) = stored_key {
+            if keys
+                .access_key_metadata()
+                .unwrap_or_default()
+                .iter()
+                .any(|key| key.access_key_id() == Some(access_key_id.as_str()))
+            {
+                return Ok((access_key_id, secret_access_key));
+            }
+        }
+
+        // Any other keys were made by hand or lost their stored secret
+        self.delete_access_keys(bucket).await?;
+
+        let output = client
+            .create_access_key()
+            .user_name(bucket)
+            .send()
+            .await
+            .map_err(|e| Error::UpdateRole(e.to_string()))?;
+
+        let (Some(access_key_id), Some(secret_access_key)) = output
+            .access_key()
+            .map(|key| (key.access_key_id(), key.secret_access_key()))
+            .unwrap_or_default()
+        else {
+            return Err(Error::UpdateRole(format!(
+                "no access key was returned for object store user: {bucket}"
+            )));
+        };
+
+        self.credentials
+            .set(
+                CredentialKind::ObjectStore,
+                bucket,
+                &format!("{access_key_id}:{secret_access_key}"),
+            )
+            .await?;
+
+        info!("created access key of object store user: {bucket}");
+
+        Ok((access_key_id.to_string(), secret_access_key.to_string()))
+    }
+
+    async fn delete_access_keys(&self, user_name: &str) -> Result<(), Error> {
+        let client = &self.iam_client;
+
+        let keys = client
+            .list_access_keys()
+            .user_name(user_name)
+            .send()
+            .await
+            .map_err(|e| Error::DeleteRole(e.to_string()))?;
+
+        for access_key_id in keys
+            .access_key_metadata()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|key| key.access_key_id())
+        {
+            client
+                .delete_access_key()
+                .user_name(user_name)
+                .access_key_id(access_key_id)
+                .send()
+                .await
+                .map_err(|e| Error::DeleteRole(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete the objects of a store. The bucket and its user go with the last store of the
+    /// project.
+    async fn delete_object_store_prefix(&self, bucket: &str, prefix: &str) -> Result<(), Error> {
+        let exists = self.delete_objects(bucket, prefix).await?;
+
+        let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
+        let members_key = object_store_members_key(bucket);
+        conn.srem::<_, _, ()>(&members_key, prefix)
+            .await
+            .map_err(|e| Error::DeleteObjectStore(e.to_string()))?;
+
+        let remaining: u64 = conn
+            .scard(&members_key)
+            .await
+            .map_err(|e| Error::DeleteObjectStore(e.to_string()))?;
+        info!("deleted object store: {bucket}/{prefix}");
+        if remaining > 0 {
+            return Ok(());
+        }
+
+        // The user of the project can also have written outside of the prefixes of its stores
+        if exists && self.delete_objects(bucket, "").await? {
+            self.s3_client
+                .delete_bucket()
+                .bucket(bucket)
+                .send()
+                .await
+                .map_err(|e| Error::DeleteObjectStore(e.to_string()))?;
+
+            info!("deleted bucket: {bucket}");
+        }
+
+        self.delete_object_store_user(bucket).await?;
+
+        self.credentials
+            .delete(CredentialKind::ObjectStore, &[bucket])
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete all the objects under a prefix of a bucket. Returns whether the bucket exists.
+    async fn delete_objects(&self, bucket: &str, prefix: &str) -> Result<bool, Error> {
+        let client = &self.s3_client;
+        let mut continuation_token = None;
+
+        loop {
+            let page = match client
+                .list_objects_v2()
+                .bucket(bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+            {
+                Ok(page) => page,
+                Err(error)
+                    if error
+                        .as_service_error()
+                        .is_some_and(|error| error.is_no_such_bucket()) =>
+                {
+                    warn!("did not delete objects of bucket: {bucket}. Does not exist.");
+                    return Ok(false);
+                }
+                Err(error) => return Err(Error::DeleteObjectStore(error.to_string())),
+            };
+
+            let objects: Vec<_> = page
+                .contents()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|object| object.key())
+                .map(|key| ObjectIdentifier::builder().key(key).build())
+                .collect();
+
+            if !objects.is_empty() {
+                client
+                    .delete_objects()
+                    .bucket(bucket)
+                    .delete(Delete::builder().set_objects(Some(objects)).build())
+                    .send()
+                    .await
+                    .map_err(|e| Error::DeleteObjectStore(e.to_string()))?;
+            }
+
+            continuation_token = page.next_continuation_token().map(ToString::to_string);
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(true)
+    }
+
+    async fn delete_object_store_user(&self, bucket: &str) -> Result<(), Error> {
+        let client = &self.iam_client;
+
+        match client.get_user().user_name(bucket).send().await {
+            Ok(_) => {}
+            Err(error)
+                if error
+                    .as_service_error()
+                    .is_some_and(|error| error.is_no_such_entity_exception()) =>
+            {
+                warn!("did not delete object store user: {bucket}. Does not exist.");
+                return Ok(());
+            }
+            Err(error) => return Err(Error::DeleteRole(error.to_string())),
+        }
+
+        // A user has to be stripped of its keys and policies before it can be deleted
+        self.delete_access_keys(bucket).await?;
+
+        if let Err(error) = client
+            .delete_user_policy()
+            .user_name(bucket)
+            .policy_name(OBJECT_STORE_POLICY)
+            .send()
+            .await
+        {
+            if !error
+                .as_service_error()
+                .is_some_and(|error| error.is_no_such_entity_exception())
+            {
+                return Err(Error::DeleteRole(error.to_string()));
+            }
+        }
+
+        client
+            .delete_user()
+            .user_name(bucket)
+            .send()
+            .await
+            .map_err(|e| Error::DeleteRole(e.to_string()))?;
+
+        info!("deleted object store user: {bucket}");
+
+        Ok(())
+    }
+
+    async fn shared_pg_exists(&self, database_name: &str) -> Result<bool, Error> {
+        let matching_db = sqlx::query("SELECT datname FROM pg_database WHERE datname = $1")
+            .bind(database_name)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(matching_db.is_some())
+    }
+
+    /// A Postgres client tool connected to a shared database with the provisioner's own user
+    fn shared_pg_command(&self, program: &str, database_name: &str) -> Result<Command, Error> {
+        let mut uri = Url::parse(&self.shared_pg_uri).map_err(|e| Error::Plain(e.to_string()))?;
+        uri.set_path(&format!("/{database_name}"));
+
+        // Arguments can be seen by anyone listing the processes, so the password goes through the
+        // environment instead
+        let password = uri.password().map(ToString::to_string);
+        uri.set_password(None)
+            .map_err(|_| Error::Plain("invalid shared Postgres uri".to_string()))?;
+
+        let mut command = Command::new(program);
+        command.arg("--dbname").arg(uri.as_str());
+
+        if let Some(password) = password {
+            command.env("PGPASSWORD", password);
+        }
+
+        Ok(command)
+    }
+
+    /// Dump a shared Postgres database to the backup bucket and remove the backups that go over
+    /// the retention
+    async fn backup_shared_pg(&self, shared_db_id: &str) -> Result<Backup, Error> {
+        let database_name = format!("db-{shared_db_id}");
+        let created_at = Utc::now();
+        let id = format!(
+            "{}-{:08x}",
+            created_at.format(BACKUP_ID_FORMAT),
+            rand::thread_rng().gen::<u32>()
+        );
+        let path = dump_path(&database_name).map_err(|e| Error::BackupDB(e.to_string()))?;
+
+        let output = self
+            .shared_pg_command("pg_dump", &database_name)?
+            .arg("--format=custom")
+            .arg("--no-owner")
+            .arg("--no-acl")
+            .arg("--file")
+            .arg(&path)
+            .output()
+            .await
+            .map_err(|e| Error::BackupDB(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(Error::BackupDB(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let size = self
+            .upload_backup(&backup_key(shared_db_id, &id), &path)
+            .await?;
+
+        info!("backed up database: {database_name} as {id}");
+
+        // The backup is safe at this point, so failing to prune older ones should not fail it
+        if let Err(error) = self.prune_shared_pg_backups(shared_db_id, &id).await {
+            warn!(
+                error = &error as &dyn std::error::Error,
+                "failed to remove backups over the retention of database: {database_name}"
+            );
+        }
+
+        Ok(Backup {
+            id,
+            created_at: Some(Timestamp {
+                seconds: created_at.timestamp(),
+                nanos: 0,
+            }),
+            size,
+        })
+    }
+
+    /// Remove the backups of a shared Postgres database that go over the retention, never removing
+    /// the backup that was just taken
+    async fn prune_shared_pg_backups(
+        &self,
+        shared_db_id: &str,
+        latest_id: &str,
+    ) -> Result<(), Error> {
+        for backup in self
+            .list_shared_pg_backups(shared_db_id)
+            .await?
+            .iter()
+            .skip(self.backup_retention)
+            .filter(|backup| backup.id != latest_id)
+        {
+            self.s3_client
+                .delete_object()
+                .bucket(&self.backup_bucket)
+                .key(backup_key(shared_db_id, &backup.id))
+                .send()
+                .await
+                .map_err(|e| Error::BackupDB(e.to_string()))?;
+
+            info!(
+                "removed backup {} of database: db-{shared_db_id}",
+                backup.id
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn upload_backup(&self, key: &str, path: &Path) -> Result<u64, Error> {
+        let size = tokio::fs::metadata(path)
+            .await
+            .map_err(|e| Error::BackupDB(e.to_string()))?
+            .len();
+        let body = ByteStream::from_path(path)
+            .await
+            .map_err(|e| Error::BackupDB(e.to_string()))?;
+
+        self.s3_client
+            .put_object()
+            .bucket(&self.backup_bucket)
+            .key(key)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| Error::BackupDB(e.to_string()))?;
+
+        Ok(size)
+    }
+
+    /// The backups of a shared Postgres database, newest first
+    async fn list_shared_pg_backups(&self, shared_db_id: &str) -> Result<Vec<Backup>, Error> {
+        let prefix = backup_key(shared_db_id, "");
+        let mut backups = Vec::new();
+        let mut continuation_token = None;
+
+        loop {
+            let page = self
+                .s3_client
+                .list_objects_v2()
+                .bucket(&self.backup_bucket)
+                .prefix(&prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(|e| Error::BackupDB(e.to_string()))?;
+
+            for object in page.contents().unwrap_or_default() {
+                let Some((id, created_at)) = object
+                    .key()
+                    .and_then(|key| key.strip_prefix(&prefix))
+                    .and_then(|key| key.strip_suffix(".dump"))
+                    .and_then(|id| Some((id, parse_backup_id(id)?)))
+                else {
+                    continue;
+                };
+
+                backups.push(Backup {
+                    id: id.to_string(),
+                    created_at: Some(Timestamp {
+                        seconds: created_at.timestamp(),
+                        nanos: 0,
+                    }),
+                    size: u64::try_from(object.size()).unwrap_or_default(),
+                });
+            }
+
+            continuation_token = page.next_continuation_token().map(ToString::to_string);
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+
+        backups.sort_by(|a, b| b.id.cmp(&a.id));
+
+        Ok(backups)
+    }
+
+    /// Download a backup to a local file, which is removed when the returned path is dropped. Gives
+    /// `None` when the backup does not exist.
+    async fn download_shared_pg_backup(
+        &self,
+        shared_db_id: &str,
+        backup_id: &str,
+    ) -> Result<Option<TempPath>, Error> {
+        let object = match self
+            .s3_client
+            .get_object()
+            .bucket(&self.backup_bucket)
+            .key(backup_key(shared_db_id, backup_id))
+            .send()
+            .await
+        {
+            Ok(object) => object,
+            Err(error)
+                if error
+                    .as_service_error()
+                    .is_some_and(|error| error.is_no_such_key()) =>
+            {
+                return Ok(None);
+            }
+            Err(error) => return Err(Error::RestoreDB(error.to_string())),
+        };
+
+        let path = dump_path(&format!("db-{shared_db_id}"))
+            .map_err(|e| Error::RestoreDB(e.to_string()))?;
+
+        // Dumps can be large, so they are streamed to disk instead of being held in memory
+        let mut file = tokio::fs::File::create(&path)
+            .await
+            .map_err(|e| Error::RestoreDB(e.to_string()))?;
+        tokio::io::copy(&mut object.body.into_async_read(), &mut file)
+            .await
+            .map_err(|e| Error::RestoreDB(e.to_string()))?;
+        file.sync_all()
+            .await
+            .map_err(|e| Error::RestoreDB(e.to_string()))?;
+
+        Ok(Some(path))
+    }
+
+    /// Restore a dump into a shared Postgres database. The database is created when it is not
+    /// the one the dump was taken from, otherwise its current objects are replaced.
+    async fn restore_shared_pg(
+        &self,
+        shared_db_id: &str,
+        path: &Path,
+        into_existing: bool,
+    ) -> Result<(), Error> {
+        let username = if into_existing {
+            format!("user-{shared_db_id}")
+        } else {
+            let (username, _) = self.shared_pg_role(shared_db_id).await?;
+            self.shared_pg(shared_db_id, &username).await?;
+
+            username
+        };
+        let database_name = format!("db-{shared_db_id}");
+
+        let mut command = self.shared_pg_command("pg_restore", &database_name)?;
+        command
+            .arg("--no-owner")
+            .arg("--no-acl")
+            // Objects should belong to the user of the project, not the provisioner
+            .arg(format!("--role={username}"))
+            // Leave the database as it was if anything fails
+            .arg("--single-transaction")
+            .arg("--exit-on-error");
+
+        if into_existing {
+            command.arg("--clean").arg("--if-exists");
+        }
+
+        let output = command
+            .arg(path)
+            .output()
+            .await
+            .map_err(|e| Error::RestoreDB(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(Error::RestoreDB(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        info!("restored database: {database_name}");
+
+        Ok(())
+    }
+
+    /// The bucket and key prefix of an object store. All the stores of a project share one bucket,
+    /// which is also the name of its IAM user, and are kept apart by prefixes that cannot overlap
+    /// since resource names cannot contain a slash.
+    fn object_store_location(
+        &self,
+        project_name: &str,
+        name: &str,
+    ) -> Result<(String, String), Status> {
+        let bucket = format!("{}{project_name}", self.bucket_prefix);
+
+        // The limit on bucket names, which is also below the limit on IAM user names
+        if bucket.len() > 63 {
+            return Err(Status::invalid_argument(
+                "project name is too long for a bucket name",
+            ));
+        }
+
+        let prefix = if name.is_empty() {
+            "default/".to_string()
+        } else {
+            format!("named/{name}/")
+        };
+
+        Ok((bucket, prefix))
+    }
 }
 
 #[tonic::async_trait]
@@ -478,14 +1501,24 @@ impl Provisioner for ShuttleProvisioner {
         if !ProjectName::is_valid(&request.project_name) {
             return Err(Status::invalid_argument("invalid project name"));
         }
+        if !request.name.is_empty() && !resource::is_valid_name(&request.name) {
+            return Err(Status::invalid_argument("invalid database name"));
+        }
         let db_type = request.db_type.unwrap();
+        let shared_db_id = shared_db_id(&request.project_name, &request.name);
 
         let reply = match db_type {
             DbType::Shared(Shared { engine }) => {
-                self.request_shared_db(&request.project_name, engine.expect("engine to be set"))
+                self.request_shared_db(&shared_db_id, engine.expect("engine to be set"))
                     .await?
             }
             DbType::AwsRds(AwsRds { engine }) => {
+                if !request.name.is_empty() {
+                    return Err(Status::invalid_argument(
+                        "named databases are only supported for shared databases",
+                    ));
+                }
+
                 {
                     let mut rr_client = self.rr_client.lock().await;
 
@@ -532,14 +1565,24 @@ impl Provisioner for ShuttleProvisioner {
         if !ProjectName::is_valid(&request.project_name) {
             return Err(Status::invalid_argument("invalid project name"));
         }
+        if !request.name.is_empty() && !resource::is_valid_name(&request.name) {
+            return Err(Status::invalid_argument("invalid database name"));
+        }
         let db_type = request.db_type.unwrap();
+        let shared_db_id = shared_db_id(&request.project_name, &request.name);
 
         let reply = match db_type {
             DbType::Shared(Shared { engine }) => {
-                self.delete_shared_db(&request.project_name, engine.expect("engine to be set"))
+                self.delete_shared_db(&shared_db_id, engine.expect("engine to be set"))
                     .await?
             }
             DbType::AwsRds(AwsRds { engine }) => {
+                if !request.name.is_empty() {
+                    return Err(Status::invalid_argument(
+                        "named databases are only supported for shared databases",
+                    ));
+                }
+
                 self.delete_aws_rds(&request.project_name, engine.expect("engine to be set"))
                     .await?
             }
@@ -548,6 +1591,129 @@ impl Provisioner for ShuttleProvisioner {
         Ok(Response::new(reply))
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn provision_object_store(
+        &self,
+        request: Request<ObjectStoreRequest>,
+    ) -> Result<Response<ObjectStoreResponse>, Status> {
+        request.verify(Scope::ResourcesWrite)?;
+
+        let request = request.into_inner();
+        if !ProjectName::is_valid(&request.project_name) {
+            return Err(Status::invalid_argument("invalid project name"));
+        }
+        if !request.name.is_empty() && !resource::is_valid_name(&request.name) {
+            return Err(Status::invalid_argument("invalid object store name"));
+        }
+        let (bucket, prefix) = self.object_store_location(&request.project_name, &request.name)?;
+
+        let reply = self.request_object_store(&bucket, &prefix).await?;
+
+        Ok(Response::new(reply))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_object_store(
+        &self,
+        request: Request<ObjectStoreRequest>,
+    ) -> Result<Response<ObjectStoreDeletionResponse>, Status> {
+        request.verify(Scope::ResourcesWrite)?;
+
+        let request = request.into_inner();
+        if !ProjectName::is_valid(&request.project_name) {
+            return Err(Status::invalid_argument("invalid project name"));
+        }
+        if !request.name.is_empty() && !resource::is_valid_name(&request.name) {
+            return Err(Status::invalid_argument("invalid object store name"));
+        }
+        let (bucket, prefix) = self.object_store_location(&request.project_name, &request.name)?;
+
+        self.delete_object_store_prefix(&bucket, &prefix).await?;
+
+        Ok(Response::new(ObjectStoreDeletionResponse {}))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn backup_database(
+        &self,
+        request: Request<DatabaseRequest>,
+    ) -> Result<Response<Backup>, Status> {
+        request.verify(Scope::ResourcesWrite)?;
+
+        let shared_db_id = check_backup_request(&request.into_inner())?;
+        if !self.shared_pg_exists(&format!("db-{shared_db_id}")).await? {
+            return Err(Status::not_found("database does not exist"));
+        }
+
+        let reply = self.backup_shared_pg(&shared_db_id).await?;
+
+        Ok(Response::new(reply))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn list_backups(
+        &self,
+        request: Request<DatabaseRequest>,
+    ) -> Result<Response<ListBackupsResponse>, Status> {
+        request.verify(Scope::Resources)?;
+
+        let shared_db_id = check_backup_request(&request.into_inner())?;
+        let backups = self.list_shared_pg_backups(&shared_db_id).await?;
+
+        Ok(Response::new(ListBackupsResponse { backups }))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn restore_database(
+        &self,
+        request: Request<RestoreRequest>,
+    ) -> Result<Response<RestoreResponse>, Status> {
+        request.verify(Scope::ResourcesWrite)?;
+
+        let RestoreRequest {
+            database,
+            backup_id,
+            target_name,
+        } = request.into_inner();
+        let database = database.ok_or_else(|| Status::invalid_argument("missing database"))?;
+        let source_id = check_backup_request(&database)?;
+        if parse_backup_id(&backup_id).is_none() {
+            return Err(Status::invalid_argument("invalid backup id"));
+        }
+        if !target_name.is_empty() && !resource::is_valid_name(&target_name) {
+            return Err(Status::invalid_argument("invalid database name"));
+        }
+
+        let target_id = if target_name.is_empty() {
+            if !self.shared_pg_exists(&format!("db-{source_id}")).await? {
+                return Err(Status::not_found("database does not exist"));
+            }
+
+            source_id.clone()
+        } else {
+            let target_id = shared_db_id(&database.project_name, &target_name);
+            if self.shared_pg_exists(&format!("db-{target_id}")).await? {
+                return Err(Status::already_exists(
+                    "the database to restore into already exists",
+                ));
+            }
+
+            target_id
+        };
+
+        let Some(path) = self
+            .download_shared_pg_backup(&source_id, &backup_id)
+            .await?
+        else {
+            return Err(Status::not_found("backup does not exist"));
+        };
+
+        self.restore_shared_pg(&target_id, &path, target_name.is_empty())
+            .await?;
+
+        Ok(Response::new(RestoreResponse {}))
+    }
+
     #[tracing::instrument(skip(self))]
     async fn provision_arbitrary_container(
         &self,
@@ -559,12 +1725,145 @@ impl Provisioner for ShuttleProvisioner {
         ))
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn rotate_database_credentials(
+        &self,
+        request: Request<DatabaseRequest>,
+    ) -> Result<Response<RotateCredentialsResponse>, Status> {
+        request.verify(Scope::ResourcesWrite)?;
+
+        let request = request.into_inner();
+        if !ProjectName::is_valid(&request.project_name) {
+            return Err(Status::invalid_argument("invalid project name"));
+        }
+        if !request.name.is_empty() && !resource::is_valid_name(&request.name) {
+            return Err(Status::invalid_argument("invalid database name"));
+        }
+        if !matches!(
+            request.db_type,
+            Some(DbType::Shared(Shared {
+                engine: Some(shared::Engine::Postgres(_))
+            }))
+        ) {
+            return Err(Status::invalid_argument(
+                "credential rotation is only supported for shared Postgres databases",
+            ));
+        }
+        let shared_db_id = shared_db_id(&request.project_name, &request.name);
+        if !self.shared_pg_exists(&format!("db-{shared_db_id}")).await? {
+            return Err(Status::not_found("database does not exist"));
+        }
+
+        let old_expires_at = self.rotate_shared_pg_credentials(&shared_db_id).await?;
+
+        Ok(Response::new(RotateCredentialsResponse {
+            old_expires_at: Some(Timestamp {
+                seconds: old_expires_at.timestamp(),
+                nanos: old_expires_at.timestamp_subsec_nanos() as i32,
+            }),
+        }))
+    }
+
     #[tracing::instrument(skip(self))]
     async fn health_check(&self, _request: Request<Ping>) -> Result<Response<Pong>, Status> {
         Ok(Response::new(Pong {}))
     }
 }
 
+/// The identifier the roles and databases of a shared database are derived from. Named databases
+/// get their own, which cannot clash with the default database of another project since project
+/// names never contain an underscore.
+pub fn shared_db_id(project_name: &str, name: &str) -> String {
+    if name.is_empty() {
+        project_name.to_string()
+    } else {
+        format!("{project_name}_{name}")
+    }
+}
+
+/// Validate a request for the backups of a database and get the id of the database
+fn check_backup_request(request: &DatabaseRequest) -> Result<String, Status> {
+    if !ProjectName::is_valid(&request.project_name) {
+        return Err(Status::invalid_argument("invalid project name"));
+    }
+    if !request.name.is_empty() && !resource::is_valid_name(&request.name) {
+        return Err(Status::invalid_argument("invalid database name"));
+    }
+    if !matches!(
+        request.db_type,
+        Some(DbType::Shared(Shared {
+            engine: Some(shared::Engine::Postgres(_))
+        }))
+    ) {
+        return Err(Status::invalid_argument(
+            "backups are only supported for shared Postgres databases",
+        ));
+    }
+
+    Ok(shared_db_id(&request.project_name, &request.name))
+}
+
+/// Where a backup of a shared Postgres database is kept in the backup bucket
+fn backup_key(shared_db_id: &str, backup_id: &str) -> String {
+    if backup_id.is_empty() {
+        format!("postgres/{shared_db_id}/")
+    } else {
+        format!("postgres/{shared_db_id}/{backup_id}.dump")
+    }
+}
+
+fn parse_backup_id(backup_id: &str) -> Option<DateTime<Utc>> {
+    let (created_at, suffix) = backup_id.split_once('-')?;
+    if suffix.len() != 8 || !suffix.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    NaiveDateTime::parse_from_str(created_at, BACKUP_ID_FORMAT)
+        .ok()
+        .map(|created_at| Utc.from_utc_datetime(&created_at))
+}
+
+/// A temporary file for a dump of a database, which is removed when the path is dropped
+fn dump_path(database_name: &str) -> std::io::Result<TempPath> {
+    Ok(tempfile::Builder::new()
+        .prefix(&format!("{database_name}-"))
+        .suffix(".dump")
+        .tempfile()?
+        .into_temp_path())
+}
+
+/// The Redis set of the store prefixes provisioned in a bucket, to know when the last one is deleted
+fn object_store_members_key(bucket: &str) -> String {
+    format!("shuttle_provisioner:object_stores:{bucket}")
+}
+
+/// Only allows the user of a bucket to list it and to manage its objects
+fn object_store_policy(bucket: &str) -> String {
+    format!(
+        r#"{{
+    "Version": "2012-10-17",
+    "Statement": [
+        {{
+            "Effect": "Allow",
+            "Action": ["s3:ListBucket", "s3:ListBucketMultipartUploads", "s3:GetBucketLocation"],
+            "Resource": "arn:aws:s3:::{bucket}"
+        }},
+        {{
+            "Effect": "Allow",
+            "Action": [
+                "s3:GetObject",
+                "s3:PutObject",
+                "s3:DeleteObject",
+                "s3:AbortMultipartUpload",
+                "s3:ListMultipartUploadParts"
+            ],
+            "Resource": "arn:aws:s3:::{bucket}/*"
+        }}
+    ]
+}}"#
+    )
+}
+
 fn generate_password() -> String {
     rand::thread_rng()
         .sample_iter(&rand::distributions::Alphanumeric)
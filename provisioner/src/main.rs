@@ -1,4 +1,4 @@
-use std::{net::SocketAddr, time::Duration};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use clap::Parser;
 use shuttle_common::{
@@ -8,45 +8,72 @@ use shuttle_common::{
     },
     log::Backend,
 };
-use shuttle_provisioner::{Args, ProvisionerServer, ShuttleProvisioner};
+use shuttle_provisioner::{
+    Args, CredentialStore, ProvisionerConfig, ProvisionerServer, ShuttleProvisioner,
+};
 use tonic::transport::Server;
+use tracing::{error, warn};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     setup_tracing(tracing_subscriber::registry(), Backend::Provisioner, None);
 
-    let Args {
-        ip,
-        port,
-        shared_pg_uri,
-        shared_mongodb_uri,
-        fqdn,
-        internal_pg_address,
-        internal_mongodb_address,
-        auth_uri,
-        gateway_uri,
-        resource_recorder_uri,
-    } = Args::parse();
-    let addr = SocketAddr::new(ip, port);
-
-    let provisioner = ShuttleProvisioner::new(
-        &shared_pg_uri,
-        &shared_mongodb_uri,
-        fqdn.to_string(),
-        internal_pg_address,
-        internal_mongodb_address,
-        resource_recorder_uri,
-        gateway_uri,
-    )
-    .await
-    .unwrap();
+    let args = Args::parse();
+    let addr = SocketAddr::new(args.ip, args.port);
+
+    let credentials = CredentialStore::new(&args.db_connection_uri, &args.credentials_key)
+        .await
+        .unwrap();
+    let provisioner = Arc::new(
+        ShuttleProvisioner::new(
+            &args.shared_pg_uri,
+            &args.shared_mongodb_uri,
+            &args.shared_redis_uri,
+            credentials,
+            ProvisionerConfig::from(&args),
+            args.resource_recorder_uri,
+            args.gateway_uri,
+        )
+        .await
+        .unwrap(),
+    );
+
+    // Whatever is left in the shared Redis is moved on the next start
+    if let Err(error) = provisioner.move_legacy_credentials().await {
+        warn!(
+            error = &error as &dyn std::error::Error,
+            "failed to move credentials out of the shared redis"
+        );
+    }
+
+    tokio::spawn({
+        let provisioner = provisioner.clone();
+
+        async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+
+                if let Err(error) = provisioner.terminate_expired_pg_sessions().await {
+                    error!(
+                        error = &error as &dyn std::error::Error,
+                        "failed to terminate sessions of expired roles"
+                    );
+                }
+            }
+        }
+    });
 
     println!("starting provisioner on {}", addr);
     Server::builder()
         .http2_keepalive_interval(Some(Duration::from_secs(30))) // Prevent deployer clients from loosing connection #ENG-219
-        .layer(JwtAuthenticationLayer::new(AuthPublicKey::new(auth_uri)))
+        .layer(JwtAuthenticationLayer::new(AuthPublicKey::new(
+            args.auth_uri,
+        )))
         .layer(ExtractPropagationLayer)
-        .add_service(ProvisionerServer::new(provisioner))
+        .add_service(ProvisionerServer::from_arc(provisioner))
         .serve(addr)
         .await?;
 
@@ -7,6 +7,7 @@ use std::{
 
 const PG_CONTAINER_NAME: &str = "shuttle_provisioner_test_pg";
 const MONGODB_CONTAINER_NAME: &str = "shuttle_provisioner_test_mongodb";
+const REDIS_CONTAINER_NAME: &str = "shuttle_provisioner_test_redis";
 
 pub struct DockerInstance {
     pub container_name: &'static str,
@@ -19,12 +20,14 @@ struct Config<'a> {
     engine: &'a str,
     port: &'a str,
     env: Vec<&'a str>,
+    args: Vec<&'a str>,
     is_ready_cmd: Vec<&'a str>,
 }
 
 pub enum DbType {
     Postgres,
     MongoDb,
+    Redis,
 }
 
 impl DockerInstance {
@@ -32,6 +35,7 @@ impl DockerInstance {
         let Config {
             engine,
             env,
+            args: image_args,
             image,
             is_ready_cmd,
             port,
@@ -46,6 +50,7 @@ impl DockerInstance {
         args.extend(env.iter().flat_map(|e| ["-e", e]));
 
         args.push(image);
+        args.extend(image_args);
 
         Command::new("docker").args(args).spawn().unwrap();
 
@@ -107,6 +112,7 @@ impl From<DbType> for Config<'_> {
                 engine: "postgres",
                 port: "5432",
                 env: vec!["POSTGRES_PASSWORD=password"],
+                args: vec![],
                 is_ready_cmd: vec!["exec", PG_CONTAINER_NAME, "pg_isready"],
             },
             DbType::MongoDb => Config {
@@ -118,6 +124,7 @@ impl From<DbType> for Config<'_> {
                     "MONGO_INITDB_ROOT_USERNAME=mongodb",
                     "MONGO_INITDB_ROOT_PASSWORD=password",
                 ],
+                args: vec![],
                 is_ready_cmd: vec![
                     "exec",
                     MONGODB_CONTAINER_NAME,
@@ -127,6 +134,25 @@ impl From<DbType> for Config<'_> {
                     "db",
                 ],
             },
+            DbType::Redis => Config {
+                container_name: REDIS_CONTAINER_NAME,
+                image: "docker.io/library/redis:7.2",
+                engine: "redis",
+                port: "6379",
+                env: vec![],
+                // Add a user matching the engine like the other databases have
+                args: vec![
+                    "redis-server",
+                    "--user",
+                    "redis",
+                    "on",
+                    ">password",
+                    "~*",
+                    "&*",
+                    "+@all",
+                ],
+                is_ready_cmd: vec!["exec", REDIS_CONTAINER_NAME, "redis-cli", "ping"],
+            },
         }
     }
 }
@@ -179,3 +205,15 @@ pub fn exec_mongosh(command: &str, database_name: Option<&str>) -> String {
 
     String::from_utf8(output).unwrap().trim().to_string()
 }
+
+/// Execute a command with `redis-cli` via `docker exec`
+pub fn exec_redis(command: &[&str]) -> String {
+    let output = Command::new("docker")
+        .args(["exec", REDIS_CONTAINER_NAME, "redis-cli"])
+        .args(command)
+        .output()
+        .unwrap()
+        .stdout;
+
+    String::from_utf8(output).unwrap().trim().to_string()
+}
@@ -1,18 +1,19 @@
 mod helpers;
 
 use ctor::dtor;
-use helpers::{exec_mongosh, exec_psql, DbType, DockerInstance};
+use helpers::{exec_mongosh, exec_psql, exec_redis, DbType, DockerInstance};
 use once_cell::sync::Lazy;
 use serde_json::Value;
 use shuttle_common::test_utils::get_mocked_gateway_server;
 use shuttle_proto::{
     provisioner::shared, test_utils::resource_recorder::get_mocked_resource_recorder,
 };
-use shuttle_provisioner::ShuttleProvisioner;
+use shuttle_provisioner::{shared_db_id, CredentialStore, ProvisionerConfig, ShuttleProvisioner};
 use tonic::transport::Uri;
 
 static PG: Lazy<DockerInstance> = Lazy::new(|| DockerInstance::new(DbType::Postgres));
 static MONGODB: Lazy<DockerInstance> = Lazy::new(|| DockerInstance::new(DbType::MongoDb));
+static REDIS: Lazy<DockerInstance> = Lazy::new(|| DockerInstance::new(DbType::Redis));
 
 async fn get_rr_uri() -> Uri {
     let port = get_mocked_resource_recorder().await;
@@ -20,6 +21,26 @@ async fn get_rr_uri() -> Uri {
     format!("http://localhost:{port}").parse().unwrap()
 }
 
+/// The stored credentials go in the shared Postgres, which only the tests have access to
+async fn credentials() -> CredentialStore {
+    CredentialStore::new(&PG.uri, "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=")
+        .await
+        .unwrap()
+}
+
+fn config() -> ProvisionerConfig {
+    ProvisionerConfig {
+        fqdn: "fqdn".to_string(),
+        internal_pg_address: "pg".to_string(),
+        internal_mongodb_address: "mongodb".to_string(),
+        internal_redis_address: "redis".to_string(),
+        bucket_prefix: "shuttle-".to_string(),
+        backup_bucket: "shuttle-backups".to_string(),
+        backup_retention: 7,
+        credential_grace_minutes: 60,
+    }
+}
+
 async fn get_gateway_uri() -> Uri {
     let server = get_mocked_gateway_server().await;
 
@@ -30,9 +51,12 @@ async fn get_gateway_uri() -> Uri {
 fn cleanup() {
     PG.cleanup();
     MONGODB.cleanup();
+    REDIS.cleanup();
 }
 
 mod needs_docker {
+    use std::str::FromStr;
+
     use serde_json::json;
     use shuttle_common::{
         claims::{AccountTier, Claim},
@@ -42,10 +66,11 @@ mod needs_docker {
     use shuttle_proto::{
         provisioner::{
             aws_rds::Engine, database_request::DbType, provisioner_server::Provisioner, AwsRds,
-            DatabaseRequest,
+            DatabaseRequest, DatabaseResponse, ObjectStoreRequest, Shared,
         },
         resource_recorder::{self, record_request, RecordRequest},
     };
+    use sqlx::{postgres::PgConnectOptions, ConnectOptions};
     use tonic::{Code, Request};
 
     use super::*;
@@ -56,9 +81,9 @@ mod needs_docker {
         let provisioner = ShuttleProvisioner::new(
             &PG.uri,
             &MONGODB.uri,
-            "fqdn".to_string(),
-            "pg".to_string(),
-            "mongodb".to_string(),
+            &REDIS.uri,
+            credentials().await,
+            config(),
             rr_uri.clone(),
             get_gateway_uri().await,
         )
@@ -76,12 +101,14 @@ mod needs_docker {
                         r#type: "database::shared::postgres".to_string(),
                         config: serde_json::to_vec(&json!({"public": true})).unwrap(),
                         data: serde_json::to_vec(&json!({"username": "test"})).unwrap(),
+                        name: String::new(),
                     },
                     // Make one RDS record that already exists
                     record_request::Resource {
                         r#type: "database::aws_rds::mariadb".to_string(),
                         config: serde_json::to_vec(&json!({})).unwrap(),
                         data: serde_json::to_vec(&json!({"username": "maria"})).unwrap(),
+                        name: String::new(),
                     },
                 ],
             }))
@@ -90,6 +117,7 @@ mod needs_docker {
 
         let mut req = Request::new(DatabaseRequest {
             project_name: "user-1-project-1".to_string(),
+            name: String::new(),
             db_type: Some(DbType::AwsRds(AwsRds {
                 engine: Some(Engine::Postgres(Default::default())),
             })),
@@ -120,9 +148,9 @@ mod needs_docker {
         let provisioner = ShuttleProvisioner::new(
             &PG.uri,
             &MONGODB.uri,
-            "fqdn".to_string(),
-            "pg".to_string(),
-            "mongodb".to_string(),
+            &REDIS.uri,
+            credentials().await,
+            config(),
             get_rr_uri().await,
             get_gateway_uri().await,
         )
@@ -150,9 +178,9 @@ mod needs_docker {
         let provisioner = ShuttleProvisioner::new(
             &PG.uri,
             &MONGODB.uri,
-            "fqdn".to_string(),
-            "pg".to_string(),
-            "mongodb".to_string(),
+            &REDIS.uri,
+            credentials().await,
+            config(),
             get_rr_uri().await,
             get_gateway_uri().await,
         )
@@ -182,9 +210,9 @@ mod needs_docker {
         let provisioner = ShuttleProvisioner::new(
             &PG.uri,
             &MONGODB.uri,
-            "fqdn".to_string(),
-            "pg".to_string(),
-            "mongodb".to_string(),
+            &REDIS.uri,
+            credentials().await,
+            config(),
             get_rr_uri().await,
             get_gateway_uri().await,
         )
@@ -205,9 +233,9 @@ mod needs_docker {
         let provisioner = ShuttleProvisioner::new(
             &PG.uri,
             &MONGODB.uri,
-            "fqdn".to_string(),
-            "pg".to_string(),
-            "mongodb".to_string(),
+            &REDIS.uri,
+            credentials().await,
+            config(),
             get_rr_uri().await,
             get_gateway_uri().await,
         )
@@ -235,9 +263,9 @@ mod needs_docker {
         let provisioner = ShuttleProvisioner::new(
             &PG.uri,
             &MONGODB.uri,
-            "fqdn".to_string(),
-            "pg".to_string(),
-            "mongodb".to_string(),
+            &REDIS.uri,
+            credentials().await,
+            config(),
             get_rr_uri().await,
             get_gateway_uri().await,
         )
@@ -262,14 +290,50 @@ mod needs_docker {
         );
     }
 
+    #[tokio::test]
+    async fn shared_db_named() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        provisioner
+            .request_shared_db("named", shared::Engine::Postgres(String::new()))
+            .await
+            .unwrap();
+        let response = provisioner
+            .request_shared_db(
+                &shared_db_id("named", "analytics"),
+                shared::Engine::Postgres(String::new()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.username, "user-named_analytics");
+        assert_eq!(response.database_name, "db-named_analytics");
+        assert_eq!(
+            exec_psql(
+                "SELECT datname FROM pg_database WHERE datname LIKE 'db-named%' ORDER BY datname",
+            ),
+            "db-named\ndb-named_analytics"
+        );
+    }
+
     #[tokio::test]
     async fn shared_mongodb_role_does_not_exist() {
         let provisioner = ShuttleProvisioner::new(
             &PG.uri,
             &MONGODB.uri,
-            "fqdn".to_string(),
-            "pg".to_string(),
-            "mongodb".to_string(),
+            &REDIS.uri,
+            credentials().await,
+            config(),
             get_rr_uri().await,
             get_gateway_uri().await,
         )
@@ -293,9 +357,9 @@ mod needs_docker {
         let provisioner = ShuttleProvisioner::new(
             &PG.uri,
             &MONGODB.uri,
-            "fqdn".to_string(),
-            "pg".to_string(),
-            "mongodb".to_string(),
+            &REDIS.uri,
+            credentials().await,
+            config(),
             get_rr_uri().await,
             get_gateway_uri().await,
         )
@@ -345,4 +409,443 @@ mod needs_docker {
         let user_cycled_key = &user["credentials"]["SCRAM-SHA-256"]["storedKey"];
         assert_ne!(user_stored_key, user_cycled_key);
     }
+
+    #[tokio::test]
+    async fn shared_redis_user_does_not_exist() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(exec_redis(&["ACL", "GETUSER", "user-redis-new"]), "");
+
+        let response = provisioner
+            .request_shared_db("redis-new", shared::Engine::Redis(String::new()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.username, "user-redis-new");
+        assert_eq!(response.database_name, "0");
+
+        let user = exec_redis(&["ACL", "LIST"]);
+        assert!(user.contains("user user-redis-new on"));
+        assert!(user.contains("~redis-new:*"));
+    }
+
+    #[tokio::test]
+    async fn shared_redis_keeps_password() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        let first = provisioner
+            .request_shared_db("redis-kept-password", shared::Engine::Redis(String::new()))
+            .await
+            .unwrap();
+        let second = provisioner
+            .request_shared_db("redis-kept-password", shared::Engine::Redis(String::new()))
+            .await
+            .unwrap();
+
+        assert_eq!(first.password, second.password);
+    }
+
+    #[tokio::test]
+    async fn shared_redis_tenants_are_isolated() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        let first = provisioner
+            .request_shared_db("redis-first", shared::Engine::Redis(String::new()))
+            .await
+            .unwrap();
+        let second = provisioner
+            .request_shared_db("redis-second", shared::Engine::Redis(String::new()))
+            .await
+            .unwrap();
+
+        let as_user = |user: &DatabaseResponse, command: &[&str]| {
+            let mut args = vec![
+                "--user",
+                user.username.as_str(),
+                "--pass",
+                user.password.as_str(),
+            ];
+            args.extend_from_slice(command);
+            exec_redis(&args)
+        };
+
+        assert_eq!(as_user(&first, &["SET", "redis-first:key", "value"]), "OK");
+        assert_eq!(as_user(&first, &["GET", "redis-first:key"]), "value");
+
+        assert!(as_user(&second, &["GET", "redis-first:key"]).contains("NOPERM"));
+        assert!(as_user(&second, &["SCAN", "0"]).contains("NOPERM"));
+        assert!(as_user(&second, &["KEYS", "*"]).contains("NOPERM"));
+        assert!(as_user(&second, &["RANDOMKEY"]).contains("NOPERM"));
+        assert!(as_user(&second, &["DBSIZE"]).contains("NOPERM"));
+        assert!(as_user(&second, &["FLUSHALL"]).contains("NOPERM"));
+        assert!(as_user(&second, &["EVAL", "return 1", "0"]).contains("NOPERM"));
+        assert!(
+            as_user(&second, &["HGETALL", "shuttle_provisioner:credentials"]).contains("NOPERM")
+        );
+    }
+
+    #[tokio::test]
+    async fn shared_redis_deleted() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        provisioner
+            .request_shared_db("redis-deleted", shared::Engine::Redis(String::new()))
+            .await
+            .unwrap();
+
+        exec_redis(&["SET", "redis-deleted:key", "value"]);
+        exec_redis(&["SET", "redis-kept:key", "value"]);
+
+        let mut req = Request::new(DatabaseRequest {
+            project_name: "redis-deleted".to_string(),
+            name: String::new(),
+            db_type: Some(DbType::Shared(Shared {
+                engine: Some(shared::Engine::Redis(String::new())),
+            })),
+        });
+        req.extensions_mut().insert(
+            Claim::new(
+                "user-1".to_string(),
+                AccountTier::Basic.into(),
+                AccountTier::Basic,
+                Limits::default(),
+            )
+            .fill_token(),
+        );
+
+        provisioner.delete_database(req).await.unwrap();
+
+        assert!(!exec_redis(&["ACL", "LIST"]).contains("user-redis-deleted"));
+        assert_eq!(exec_redis(&["EXISTS", "redis-deleted:key"]), "0");
+        assert_eq!(exec_redis(&["EXISTS", "redis-kept:key"]), "1");
+    }
+
+    #[tokio::test]
+    async fn object_store_project_name_too_long() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        let mut req = Request::new(ObjectStoreRequest {
+            project_name: "a".repeat(60),
+            name: String::new(),
+        });
+        req.extensions_mut().insert(
+            Claim::new(
+                "user-1".to_string(),
+                AccountTier::Basic.into(),
+                AccountTier::Basic,
+                Limits::default(),
+            )
+            .fill_token(),
+        );
+
+        let err = provisioner.provision_object_store(req).await.unwrap_err();
+
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn backup_only_shared_postgres() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        let mut req = Request::new(DatabaseRequest {
+            project_name: "backup-mongodb".to_string(),
+            name: String::new(),
+            db_type: Some(DbType::Shared(Shared {
+                engine: Some(shared::Engine::Mongodb(String::new())),
+            })),
+        });
+        req.extensions_mut().insert(
+            Claim::new(
+                "user-1".to_string(),
+                AccountTier::Basic.into(),
+                AccountTier::Basic,
+                Limits::default(),
+            )
+            .fill_token(),
+        );
+
+        let err = provisioner.backup_database(req).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        let mut req = Request::new(DatabaseRequest {
+            project_name: "backup-missing".to_string(),
+            name: String::new(),
+            db_type: Some(DbType::Shared(Shared {
+                engine: Some(shared::Engine::Postgres(String::new())),
+            })),
+        });
+        req.extensions_mut().insert(
+            Claim::new(
+                "user-1".to_string(),
+                AccountTier::Basic.into(),
+                AccountTier::Basic,
+                Limits::default(),
+            )
+            .fill_token(),
+        );
+
+        let err = provisioner.backup_database(req).await.unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn shared_db_credentials_rotate() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        let rotate_request = || {
+            let mut req = Request::new(DatabaseRequest {
+                project_name: "rotate".to_string(),
+                name: String::new(),
+                db_type: Some(DbType::Shared(Shared {
+                    engine: Some(shared::Engine::Postgres(String::new())),
+                })),
+            });
+            req.extensions_mut().insert(
+                Claim::new(
+                    "user-1".to_string(),
+                    AccountTier::Basic.into(),
+                    AccountTier::Basic,
+                    Limits::default(),
+                )
+                .fill_token(),
+            );
+
+            req
+        };
+
+        let err = provisioner
+            .rotate_database_credentials(rotate_request())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+
+        let response = provisioner
+            .request_shared_db("rotate", shared::Engine::Postgres(String::new()))
+            .await
+            .unwrap();
+        assert_eq!(response.username, "user-rotate");
+
+        // Every service of the project gets the same credentials until they are rotated
+        let other_service = provisioner
+            .request_shared_db("rotate", shared::Engine::Postgres(String::new()))
+            .await
+            .unwrap();
+        assert_eq!(other_service.password, response.password);
+
+        provisioner
+            .rotate_database_credentials(rotate_request())
+            .await
+            .unwrap();
+
+        // The old role keeps working until the grace period is over
+        assert_eq!(
+            exec_psql("SELECT rolvaliduntil > now() FROM pg_roles WHERE rolname = 'user-rotate'"),
+            "t"
+        );
+
+        let rotated = provisioner
+            .request_shared_db("rotate", shared::Engine::Postgres(String::new()))
+            .await
+            .unwrap();
+        assert_eq!(rotated.username, "user-rotate-alt");
+        assert_ne!(rotated.password, response.password);
+
+        let other_service = provisioner
+            .request_shared_db("rotate", shared::Engine::Postgres(String::new()))
+            .await
+            .unwrap();
+        assert_eq!(other_service.password, rotated.password);
+
+        // Rotating again switches back to the owner role
+        provisioner
+            .rotate_database_credentials(rotate_request())
+            .await
+            .unwrap();
+
+        let response = provisioner
+            .request_shared_db("rotate", shared::Engine::Postgres(String::new()))
+            .await
+            .unwrap();
+        assert_eq!(response.username, "user-rotate");
+        assert_eq!(
+            exec_psql(
+                "SELECT rolvaliduntil = 'infinity' FROM pg_roles WHERE rolname = 'user-rotate'"
+            ),
+            "t"
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_shared_db_sessions_are_terminated() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        let response = provisioner
+            .request_shared_db("expired", shared::Engine::Postgres(String::new()))
+            .await
+            .unwrap();
+
+        let options = PgConnectOptions::from_str(&PG.uri)
+            .unwrap()
+            .username(&response.username)
+            .password(&response.password)
+            .database(&response.database_name);
+        let mut conn = options.connect().await.unwrap();
+
+        // Still valid, so the session is kept
+        provisioner.terminate_expired_pg_sessions().await.unwrap();
+        sqlx::query("SELECT 1").execute(&mut conn).await.unwrap();
+
+        exec_psql("ALTER ROLE \"user-expired\" VALID UNTIL '2000-01-01'");
+        provisioner.terminate_expired_pg_sessions().await.unwrap();
+
+        assert!(sqlx::query("SELECT 1").execute(&mut conn).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn shared_db_credentials_are_stored_encrypted() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        let response = provisioner
+            .request_shared_db("encrypted", shared::Engine::Postgres(String::new()))
+            .await
+            .unwrap();
+
+        let secret = exec_psql(
+            "SELECT encode(secret, 'escape') FROM credentials WHERE name = 'user-encrypted'",
+        );
+        assert!(!secret.is_empty());
+        assert!(!secret.contains(&response.password));
+        assert_eq!(
+            exec_redis(&[
+                "HGET",
+                "shuttle_provisioner:pg_credentials",
+                "user-encrypted"
+            ]),
+            ""
+        );
+    }
+
+    #[tokio::test]
+    async fn legacy_credentials_are_moved() {
+        let provisioner = ShuttleProvisioner::new(
+            &PG.uri,
+            &MONGODB.uri,
+            &REDIS.uri,
+            credentials().await,
+            config(),
+            get_rr_uri().await,
+            get_gateway_uri().await,
+        )
+        .await
+        .unwrap();
+
+        exec_redis(&[
+            "HSET",
+            "shuttle_provisioner:pg_credentials",
+            "user-legacy",
+            "legacy-password",
+        ]);
+        provisioner.move_legacy_credentials().await.unwrap();
+
+        let response = provisioner
+            .request_shared_db("legacy", shared::Engine::Postgres(String::new()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.password, "legacy-password");
+        assert_eq!(
+            exec_redis(&["EXISTS", "shuttle_provisioner:pg_credentials"]),
+            "0"
+        );
+    }
 }
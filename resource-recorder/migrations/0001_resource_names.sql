@@ -0,0 +1,23 @@
+-- Allow a service to have more than one resource of the same type by giving them names.
+-- SQLite cannot change a primary key in place, so the table has to be rebuilt.
+CREATE TABLE resources_new (
+    project_id TEXT,                                  -- Identifier of the project this resource belongs to.
+    service_id TEXT,                                  -- Identifier of the service this resource belongs to.
+    type TEXT,                                        -- Type of resource this is.
+    name TEXT NOT NULL DEFAULT '',                    -- Name of this resource, empty for the default resource of its type.
+    data TEXT,                                        -- Data about this resource.
+    config TEXT,                                      -- The config to create the object for this resource.
+    is_active boolean,                                -- Flag telling whether the resource is being actively used.
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,   -- Time this resource was created.
+    last_updated TIMESTAMP DEFAULT CURRENT_TIMESTAMP, -- Time this resource was last updated.
+    PRIMARY KEY (project_id, service_id, type, name)
+);
+
+INSERT INTO resources_new (project_id, service_id, type, data, config, is_active, created_at, last_updated)
+SELECT project_id, service_id, type, data, config, is_active, created_at, last_updated FROM resources;
+
+DROP TABLE resources;
+ALTER TABLE resources_new RENAME TO resources;
+
+CREATE INDEX IF NOT EXISTS project_id_idx ON resources(project_id);
+CREATE INDEX IF NOT EXISTS service_id_idx ON resources(service_id);
@@ -69,6 +69,12 @@ pub trait Dal {
         &self,
         resource: resource_recorder::ResourceIds,
     ) -> Result<(), DalError>;
+
+    /// Clear the config of a resource so that its output is provisioned again
+    async fn invalidate_resource(
+        &self,
+        resource: resource_recorder::ResourceIds,
+    ) -> Result<(), DalError>;
 }
 
 pub struct Sqlite {
@@ -151,10 +157,11 @@ impl Dal for Sqlite {
                 return Err(DalError::Inactive);
             }
 
-            sqlx::query("INSERT OR REPLACE INTO resources (project_id, service_id, type, config, data, is_active, last_updated) VALUES(?, ?, ?, ?, ?, ?, ?)")
+            sqlx::query("INSERT OR REPLACE INTO resources (project_id, service_id, type, name, config, data, is_active, last_updated) VALUES(?, ?, ?, ?, ?, ?, ?, ?)")
             .bind(project_id.to_string())
             .bind(service_id.to_string())
             .bind(resource.r#type)
+            .bind(resource.name)
             .bind(resource.config)
             .bind(resource.data)
             .bind(resource.is_active)
@@ -191,11 +198,12 @@ impl Dal for Sqlite {
         resource: resource_recorder::ResourceIds,
     ) -> Result<Option<Resource>, DalError> {
         let result = sqlx::query_as(
-            "SELECT * FROM resources WHERE project_id = ? AND service_id = ? AND type = ?",
+            "SELECT * FROM resources WHERE project_id = ? AND service_id = ? AND type = ? AND name = ?",
         )
         .bind(resource.project_id)
         .bind(resource.service_id)
         .bind(resource.r#type)
+        .bind(resource.name)
         .fetch_optional(&self.pool)
         .await?;
 
@@ -206,13 +214,38 @@ impl Dal for Sqlite {
         &self,
         resource: resource_recorder::ResourceIds,
     ) -> Result<(), DalError> {
-        sqlx::query("DELETE FROM resources WHERE project_id = ? AND service_id = ? AND type = ?")
-            .bind(resource.project_id)
-            .bind(resource.service_id)
-            .bind(resource.r#type)
-            .execute(&self.pool)
-            .await
-            .map(|_| ())?;
+        sqlx::query(
+            "DELETE FROM resources WHERE project_id = ? AND service_id = ? AND type = ? AND name = ?",
+        )
+        .bind(resource.project_id)
+        .bind(resource.service_id)
+        .bind(resource.r#type)
+        .bind(resource.name)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())?;
+
+        Ok(())
+    }
+
+    async fn invalidate_resource(
+        &self,
+        resource: resource_recorder::ResourceIds,
+    ) -> Result<(), DalError> {
+        // A runtime only reuses an output when the config it was recorded with matches the
+        // builder's, which a null config never does for a resource that gets provisioned
+        sqlx::query(
+            "UPDATE resources SET config = ?, last_updated = ? WHERE project_id = ? AND service_id = ? AND type = ? AND name = ?",
+        )
+        .bind(b"null".to_vec())
+        .bind(Utc::now())
+        .bind(resource.project_id)
+        .bind(resource.service_id)
+        .bind(resource.r#type)
+        .bind(resource.name)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())?;
 
         Ok(())
     }
@@ -223,6 +256,7 @@ pub struct Resource {
     project_id: Option<Ulid>,
     service_id: Option<Ulid>,
     r#type: Type,
+    name: String,
     data: Vec<u8>,
     config: Vec<u8>,
     is_active: bool,
@@ -242,6 +276,7 @@ impl FromRow<'_, SqliteRow> for Resource {
                     .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
             ),
             r#type: row.try_get("type")?,
+            name: row.try_get("name")?,
             data: row.try_get("data")?,
             config: row.try_get("config")?,
             is_active: row.try_get("is_active")?,
@@ -255,7 +290,12 @@ impl TryFrom<record_request::Resource> for Resource {
     type Error = String;
 
     fn try_from(value: record_request::Resource) -> Result<Self, Self::Error> {
-        Ok(Self::new(value.r#type.parse()?, value.data, value.config))
+        Ok(Self::new(
+            value.r#type.parse()?,
+            value.name,
+            value.data,
+            value.config,
+        ))
     }
 }
 
@@ -271,6 +311,7 @@ impl From<Resource> for resource_recorder::Resource {
                 .expect("row to have a service id")
                 .to_string(),
             r#type: value.r#type.to_string(),
+            name: value.name,
             config: value.config,
             data: value.data,
             is_active: value.is_active,
@@ -288,6 +329,7 @@ impl TryFrom<resource_recorder::Resource> for Resource {
             project_id: Some(value.project_id.parse()?),
             service_id: Some(value.service_id.parse()?),
             r#type: value.r#type.parse()?,
+            name: value.name,
             data: value.data,
             config: value.config,
             is_active: value.is_active,
@@ -301,11 +343,12 @@ impl TryFrom<resource_recorder::Resource> for Resource {
 
 impl Resource {
     /// Create a new type of resource
-    fn new(r#type: Type, data: Vec<u8>, config: Vec<u8>) -> Self {
+    fn new(r#type: Type, name: String, data: Vec<u8>, config: Vec<u8>) -> Self {
         Self {
             project_id: None,
             service_id: None,
             r#type,
+            name,
             data,
             config,
             is_active: true,
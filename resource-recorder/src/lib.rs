@@ -104,7 +104,11 @@ where
         &self,
         resource: ResourceIds,
     ) -> Result<resource_recorder::Resource, Error> {
-        tracing::info!(resource_type = %resource.r#type, "fetching resource for service");
+        tracing::info!(
+            resource_type = %resource.r#type,
+            resource_name = %resource.name,
+            "fetching resource for service"
+        );
         let resource_option = self.dal.get_resource(resource).await?;
 
         match resource_option {
@@ -115,11 +119,27 @@ where
 
     /// Delete a resource
     async fn delete_resource(&self, resource: ResourceIds) -> Result<(), Error> {
-        tracing::info!(resource_type = %resource.r#type, "deleting resource for service");
+        tracing::info!(
+            resource_type = %resource.r#type,
+            resource_name = %resource.name,
+            "deleting resource for service"
+        );
         self.dal.delete_resource(resource).await?;
 
         Ok(())
     }
+
+    /// Make the next load of a service provision a resource again
+    async fn invalidate_resource(&self, resource: ResourceIds) -> Result<(), Error> {
+        tracing::info!(
+            resource_type = %resource.r#type,
+            resource_name = %resource.name,
+            "invalidating resource for service"
+        );
+        self.dal.invalidate_resource(resource).await?;
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -243,4 +263,26 @@ where
 
         Ok(Response::new(result))
     }
+
+    #[tracing::instrument(skip(self))]
+    async fn invalidate_resource(
+        &self,
+        request: tonic::Request<ResourceIds>,
+    ) -> Result<Response<ResultResponse>, Status> {
+        request.verify(Scope::ResourcesWrite)?;
+
+        let request = request.into_inner();
+        let result = match self.invalidate_resource(request).await {
+            Ok(()) => ResultResponse {
+                success: true,
+                message: Default::default(),
+            },
+            Err(e) => ResultResponse {
+                success: false,
+                message: e.to_string(),
+            },
+        };
+
+        Ok(Response::new(result))
+    }
 }
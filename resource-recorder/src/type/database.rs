@@ -21,6 +21,7 @@ pub enum AwsRdsType {
 pub enum SharedType {
     Postgres,
     MongoDb,
+    Redis,
 }
 
 impl Display for Type {
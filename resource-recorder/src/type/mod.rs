@@ -11,6 +11,7 @@ pub enum Type {
     StaticFolder,
     Persist,
     Turso,
+    ObjectStore,
     Custom,
 }
 
@@ -22,6 +23,7 @@ impl Display for Type {
             Type::StaticFolder => write!(f, "static_folder"),
             Type::Persist => write!(f, "persist"),
             Type::Turso => write!(f, "turso"),
+            Type::ObjectStore => write!(f, "object_store"),
             Type::Custom => write!(f, "custom"),
         }
     }
@@ -42,6 +44,7 @@ impl FromStr for Type {
                 "static_folder" => Ok(Self::StaticFolder),
                 "persist" => Ok(Self::Persist),
                 "turso" => Ok(Self::Turso),
+                "object_store" => Ok(Self::ObjectStore),
                 "custom" => Ok(Self::Custom),
                 _ => Err(format!("'{s}' is an unknown resource type")),
             }
@@ -93,10 +96,12 @@ mod tests {
             Type::Database(database::Type::AwsRds(database::AwsRdsType::MariaDB)),
             Type::Database(database::Type::Shared(database::SharedType::Postgres)),
             Type::Database(database::Type::Shared(database::SharedType::MongoDb)),
+            Type::Database(database::Type::Shared(database::SharedType::Redis)),
             Type::Secrets,
             Type::StaticFolder,
             Type::Persist,
             Type::Turso,
+            Type::ObjectStore,
         ];
 
         for input in inputs {
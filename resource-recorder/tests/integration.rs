@@ -55,11 +55,13 @@ async fn manage_resources() {
                         r#type: "database::shared::postgres".to_string(),
                         config: serde_json::to_vec(&json!({"public": true})).unwrap(),
                         data: serde_json::to_vec(&json!({"username": "test"})).unwrap(),
+                        name: String::new(),
                     },
                     record_request::Resource {
                         r#type: "secrets".to_string(),
                         config: serde_json::to_vec(&json!({})).unwrap(),
                         data: serde_json::to_vec(&json!({"password": "brrrr"})).unwrap(),
+                        name: String::new(),
                     },
                 ],
             }))
@@ -85,6 +87,7 @@ async fn manage_resources() {
                     r#type: "static_folder".to_string(),
                     config: serde_json::to_vec(&json!({"folder": "static"})).unwrap(),
                     data: serde_json::to_vec(&json!({"path": "/tmp/static"})).unwrap(),
+                    name: String::new(),
                 }],
             }))
             .await
@@ -105,6 +108,7 @@ async fn manage_resources() {
                     r#type: "static_folder".to_string(),
                     config: serde_json::to_vec(&json!({"folder": "publi"})).unwrap(),
                     data: serde_json::to_vec(&json!({"path": "/tmp/publi"})).unwrap(),
+                    name: String::new(),
                 }],
             }))
             .await
@@ -129,6 +133,7 @@ async fn manage_resources() {
             config: serde_json::to_vec(&json!({"public": true})).unwrap(),
             data: serde_json::to_vec(&json!({"username": "test"})).unwrap(),
             is_active: true,
+            name: String::new(),
             created_at: response.resources[0].created_at.clone(),
             last_updated: response.resources[0].last_updated.clone(),
         };
@@ -139,6 +144,7 @@ async fn manage_resources() {
             config: serde_json::to_vec(&json!({})).unwrap(),
             data: serde_json::to_vec(&json!({"password": "brrrr"})).unwrap(),
             is_active: true,
+            name: String::new(),
             created_at: response.resources[1].created_at.clone(),
             last_updated: response.resources[1].last_updated.clone(),
         };
@@ -164,6 +170,7 @@ async fn manage_resources() {
             project_id: project_id.clone(),
             service_id: service_id2.clone(),
             r#type: "static_folder".to_string(),
+            name: String::new(),
         };
 
         let expected = ResourcesResponse {
@@ -181,6 +188,7 @@ async fn manage_resources() {
                     project_id: service2_static_folder.project_id.clone(),
                     service_id: service2_static_folder.service_id.clone(),
                     r#type: service2_static_folder.r#type.clone(),
+                    name: service2_static_folder.name.clone(),
                 },
             ],
         };
@@ -215,6 +223,7 @@ async fn manage_resources() {
                     r#type: "database::shared::postgres".to_string(),
                     config: serde_json::to_vec(&json!({"public": false})).unwrap(),
                     data: serde_json::to_vec(&json!({"username": "inner"})).unwrap(),
+                    name: String::new(),
                 }],
             }))
             .await
@@ -250,6 +259,98 @@ async fn manage_resources() {
         };
 
         assert_eq!(response, expected);
+
+        // Resources of the same type are told apart by their names
+        let service_id4 = Ulid::new().to_string();
+
+        let response = client
+            .record_resources(Request::new(RecordRequest {
+                project_id: project_id.clone(),
+                service_id: service_id4.clone(),
+                resources: vec![
+                    record_request::Resource {
+                        r#type: "database::shared::postgres".to_string(),
+                        config: serde_json::to_vec(&json!({"public": true})).unwrap(),
+                        data: serde_json::to_vec(&json!({"username": "default"})).unwrap(),
+                        name: String::new(),
+                    },
+                    record_request::Resource {
+                        r#type: "database::shared::postgres".to_string(),
+                        config: serde_json::to_vec(&json!({"public": true})).unwrap(),
+                        data: serde_json::to_vec(&json!({"username": "analytics"})).unwrap(),
+                        name: "analytics".to_string(),
+                    },
+                ],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(response.success, "{}", response.message);
+
+        let analytics_db = ResourceIds {
+            project_id: project_id.clone(),
+            service_id: service_id4.clone(),
+            r#type: "database::shared::postgres".to_string(),
+            name: "analytics".to_string(),
+        };
+
+        let response = client
+            .get_resource(Request::new(analytics_db.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(
+            response.resource.unwrap().data,
+            serde_json::to_vec(&json!({"username": "analytics"})).unwrap()
+        );
+
+        // Invalidating a resource keeps its output, but not the config it was made with
+        let response = client
+            .invalidate_resource(Request::new(analytics_db.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(response.success, "{}", response.message);
+
+        let resource = client
+            .get_resource(Request::new(analytics_db.clone()))
+            .await
+            .unwrap()
+            .into_inner()
+            .resource
+            .unwrap();
+
+        assert_eq!(resource.config, b"null".to_vec());
+        assert_eq!(
+            resource.data,
+            serde_json::to_vec(&json!({"username": "analytics"})).unwrap()
+        );
+
+        let response = client
+            .delete_resource(Request::new(analytics_db))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(response.success, "{}", response.message);
+
+        let response = client
+            .get_service_resources(Request::new(ServiceResourcesRequest {
+                service_id: service_id4,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(response.resources.len(), 1);
+        assert_eq!(response.resources[0].name, "");
+        assert_eq!(
+            response.resources[0].data,
+            serde_json::to_vec(&json!({"username": "default"})).unwrap()
+        );
     };
 
     select! {
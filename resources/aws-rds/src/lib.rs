@@ -42,7 +42,7 @@ macro_rules! aws_engine {
                     let info = match factory.get_metadata().env {
                         shuttle_service::Environment::Deployment => shuttle_service::DatabaseResource::Info(
                             factory
-                                .get_db_connection(shuttle_service::database::Type::AwsRds(shuttle_service::database::AwsRdsEngine::$struct_ident))
+                                .get_db_connection(shuttle_service::database::Type::AwsRds(shuttle_service::database::AwsRdsEngine::$struct_ident), None)
                                 .await?
                         ),
                         shuttle_service::Environment::Local => {
@@ -51,7 +51,7 @@ macro_rules! aws_engine {
                             } else {
                                 shuttle_service::DatabaseResource::Info(
                                     factory
-                                        .get_db_connection(shuttle_service::database::Type::AwsRds(shuttle_service::database::AwsRdsEngine::$struct_ident))
+                                        .get_db_connection(shuttle_service::database::Type::AwsRds(shuttle_service::database::AwsRdsEngine::$struct_ident), None)
                                         .await?
                                 )
                             }
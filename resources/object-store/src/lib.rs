@@ -0,0 +1,121 @@
+use async_trait::async_trait;
+use object_store::{
+    aws::{AmazonS3, AmazonS3Builder},
+    prefix::PrefixStore,
+};
+use serde::{Deserialize, Serialize};
+use shuttle_service::{
+    error::CustomError, resource::Type, ContainerRequest, Environment, Error, Factory,
+    IntoResource, ObjectStoreInfo, ResourceBuilder, Secret,
+};
+
+const LOCAL_BUCKET: &str = "shuttle";
+const LOCAL_ACCESS_KEY_ID: &str = "shuttle";
+const LOCAL_SECRET_ACCESS_KEY: &str = "shuttle-object-store";
+
+/// Shuttle managed S3-compatible object store, with credentials that can only use the bucket of
+/// the project.
+///
+/// A MinIO container is started instead when running locally.
+#[derive(Default, Serialize)]
+pub struct ObjectStore {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+impl ObjectStore {
+    /// Give this store a name to use it next to other object stores in a service.
+    /// Each named store keeps its objects under a prefix of its own in the bucket of the project.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+
+        self
+    }
+}
+
+#[async_trait]
+impl ResourceBuilder for ObjectStore {
+    const TYPE: Type = Type::ObjectStore;
+
+    type Config = Self;
+
+    type Output = Wrapper;
+
+    fn config(&self) -> &Self::Config {
+        self
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    async fn output(self, factory: &mut dyn Factory) -> Result<Self::Output, Error> {
+        let md = factory.get_metadata();
+        let info = match md.env {
+            Environment::Deployment => factory.get_object_store(self.name.as_deref()).await?,
+            Environment::Local => {
+                let container_type = match self.name {
+                    Some(name) => format!("object_store_{name}"),
+                    None => "object_store".to_string(),
+                };
+                let port = factory
+                    .get_container(ContainerRequest {
+                        project_name: md.project_name,
+                        container_type,
+                        image: "docker.io/bitnami/minio:2024".to_string(),
+                        port: "9000/tcp".to_string(),
+                        env: vec![
+                            format!("MINIO_ROOT_USER={LOCAL_ACCESS_KEY_ID}"),
+                            format!("MINIO_ROOT_PASSWORD={LOCAL_SECRET_ACCESS_KEY}"),
+                            format!("MINIO_DEFAULT_BUCKETS={LOCAL_BUCKET}"),
+                        ],
+                    })
+                    .await?
+                    .host_port;
+
+                ObjectStoreInfo {
+                    bucket: LOCAL_BUCKET.to_string(),
+                    region: "us-east-1".to_string(),
+                    endpoint: Some(format!("http://localhost:{port}")),
+                    access_key_id: LOCAL_ACCESS_KEY_ID.to_string(),
+                    secret_access_key: Secret::new(LOCAL_SECRET_ACCESS_KEY.to_string()),
+                    prefix: String::new(),
+                }
+            }
+        };
+
+        Ok(Wrapper(info))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Wrapper(ObjectStoreInfo);
+
+#[async_trait]
+impl IntoResource<ObjectStoreInfo> for Wrapper {
+    async fn into_resource(self) -> Result<ObjectStoreInfo, Error> {
+        Ok(self.0)
+    }
+}
+
+#[async_trait]
+impl IntoResource<PrefixStore<AmazonS3>> for Wrapper {
+    async fn into_resource(self) -> Result<PrefixStore<AmazonS3>, Error> {
+        let info = self.0;
+        let mut builder = AmazonS3Builder::new()
+            .with_bucket_name(info.bucket)
+            .with_region(info.region)
+            .with_access_key_id(info.access_key_id)
+            .with_secret_access_key(info.secret_access_key.expose());
+
+        if let Some(endpoint) = info.endpoint {
+            // Local MinIO containers are only reachable over plain HTTP
+            builder = builder.with_endpoint(endpoint).with_allow_http(true);
+        }
+
+        let store = builder.build().map_err(CustomError::new)?;
+
+        Ok(PrefixStore::new(store, info.prefix))
+    }
+}
@@ -10,3 +10,8 @@ mod postgres;
 
 #[cfg(any(feature = "postgres", feature = "postgres-rustls"))]
 pub use postgres::Postgres;
+
+#[cfg(feature = "redis")]
+mod redis_store;
+#[cfg(feature = "redis")]
+pub use redis_store::Redis;
@@ -16,6 +16,14 @@ impl MongoDb {
 
         self
     }
+
+    /// Give this database a name to use it next to other databases of the same type in a service.
+    /// Each named database is provisioned with its own role.
+    pub fn name(mut self, name: &str) -> Self {
+        self.0.name = Some(name.to_string());
+
+        self
+    }
 }
 
 #[async_trait]
@@ -30,11 +38,18 @@ impl ResourceBuilder for MongoDb {
         &self.0
     }
 
+    fn name(&self) -> Option<&str> {
+        self.0.name.as_deref()
+    }
+
     async fn output(self, factory: &mut dyn Factory) -> Result<Self::Output, Error> {
         let info = match factory.get_metadata().env {
             shuttle_service::Environment::Deployment => DatabaseResource::Info(
                 factory
-                    .get_db_connection(database::Type::Shared(database::SharedEngine::MongoDb))
+                    .get_db_connection(
+                        database::Type::Shared(database::SharedEngine::MongoDb),
+                        self.0.name.as_deref(),
+                    )
                     .await
                     .map_err(CustomError::new)?,
             ),
@@ -44,9 +59,10 @@ impl ResourceBuilder for MongoDb {
                 } else {
                     DatabaseResource::Info(
                         factory
-                            .get_db_connection(database::Type::Shared(
-                                database::SharedEngine::MongoDb,
-                            ))
+                            .get_db_connection(
+                                database::Type::Shared(database::SharedEngine::MongoDb),
+                                self.0.name.as_deref(),
+                            )
                             .await
                             .map_err(CustomError::new)?,
                     )
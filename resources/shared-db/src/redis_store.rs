@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use shuttle_service::{
+    database, error::CustomError, resource::Type, DatabaseResource, DbInput, Error, Factory,
+    IntoResource, ResourceBuilder,
+};
+
+/// Shuttle managed Redis in a shared instance.
+///
+/// The user of a project can only use the keys and channels starting with the name of the project
+/// followed by a colon, like `my-project:sessions`. A named store uses the project name and its
+/// own name joined by an underscore instead, like `my-project_cache:sessions`.
+#[derive(Default)]
+pub struct Redis(DbInput);
+
+impl Redis {
+    /// Use a custom connection string for local runs
+    pub fn local_uri(mut self, local_uri: &str) -> Self {
+        self.0.local_uri = Some(local_uri.to_string());
+
+        self
+    }
+
+    /// Give this store a name to use it next to other Redis stores in a service.
+    /// Each named store is provisioned with its own user and key prefix.
+    pub fn name(mut self, name: &str) -> Self {
+        self.0.name = Some(name.to_string());
+
+        self
+    }
+}
+
+#[async_trait]
+impl ResourceBuilder for Redis {
+    const TYPE: Type = Type::Database(database::Type::Shared(database::SharedEngine::Redis));
+
+    type Config = DbInput;
+
+    type Output = Wrapper;
+
+    fn config(&self) -> &Self::Config {
+        &self.0
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.0.name.as_deref()
+    }
+
+    async fn output(self, factory: &mut dyn Factory) -> Result<Self::Output, Error> {
+        let info = match factory.get_metadata().env {
+            shuttle_service::Environment::Deployment => DatabaseResource::Info(
+                factory
+                    .get_db_connection(
+                        database::Type::Shared(database::SharedEngine::Redis),
+                        self.0.name.as_deref(),
+                    )
+                    .await?,
+            ),
+            shuttle_service::Environment::Local => {
+                if let Some(local_uri) = self.0.local_uri {
+                    DatabaseResource::ConnectionString(local_uri)
+                } else {
+                    DatabaseResource::Info(
+                        factory
+                            .get_db_connection(
+                                database::Type::Shared(database::SharedEngine::Redis),
+                                self.0.name.as_deref(),
+                            )
+                            .await?,
+                    )
+                }
+            }
+        };
+
+        Ok(Wrapper(info))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Wrapper(DatabaseResource);
+
+#[async_trait]
+impl IntoResource<String> for Wrapper {
+    async fn into_resource(self) -> Result<String, Error> {
+        Ok(match self.0 {
+            DatabaseResource::ConnectionString(s) => s.clone(),
+            DatabaseResource::Info(info) => info.connection_string_shuttle(),
+        })
+    }
+}
+
+#[async_trait]
+impl IntoResource<redis::Client> for Wrapper {
+    async fn into_resource(self) -> Result<redis::Client, Error> {
+        let connection_string: String = self.into_resource().await.unwrap();
+
+        Ok(redis::Client::open(connection_string).map_err(CustomError::new)?)
+    }
+}
@@ -169,10 +169,18 @@ mod test {
         async fn get_db_connection(
             &mut self,
             _db_type: shuttle_service::database::Type,
+            _name: Option<&str>,
         ) -> Result<shuttle_service::DatabaseInfo, shuttle_service::Error> {
             panic!("no turso test should try to get a db connection string")
         }
 
+        async fn get_object_store(
+            &mut self,
+            _name: Option<&str>,
+        ) -> Result<shuttle_service::ObjectStoreInfo, shuttle_service::Error> {
+            panic!("no turso test should try to get an object store")
+        }
+
         async fn get_container(
             &mut self,
             _req: shuttle_service::ContainerRequest,
@@ -10,5 +10,7 @@ args! {
         "--provisioner-address" => #[arg(default_value = "http://localhost:3000")] pub provisioner_address: Endpoint,
         "--env" => pub env: Environment,
         "--auth-uri" => #[arg(default_value = "http://127.0.0.1:8008")] pub auth_uri: Uri,
+        // Seconds a service gets to shut down gracefully before it is aborted
+        "--drain-timeout" => #[arg(default_value = "10")] pub drain_timeout: u64,
     }
 }
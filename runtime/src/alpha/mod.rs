@@ -28,7 +28,7 @@ use shuttle_proto::{
         StopResponse, SubscribeStopRequest, SubscribeStopResponse,
     },
 };
-use shuttle_service::{Environment, Factory, Service};
+use shuttle_service::{Environment, Factory, Service, Shutdown};
 use tokio::sync::{
     broadcast::{self, Sender},
     mpsc, oneshot,
@@ -46,6 +46,9 @@ use self::args::Args;
 
 mod args;
 
+/// Time on top of the drain timeout a stop request waits for the service to be aborted in
+const STOP_MARGIN: Duration = Duration::from_secs(5);
+
 pub async fn start(loader: impl Loader<ProvisionerFactory> + Send + 'static) {
     // `--version` overrides any other arguments.
     if std::env::args().any(|arg| arg == "--version") {
@@ -117,7 +120,12 @@ pub async fn start(loader: impl Loader<ProvisionerFactory> + Send + 'static) {
         .layer(ExtractPropagationLayer);
 
     let router = {
-        let alpha = Alpha::new(provisioner_address, loader, args.env);
+        let alpha = Alpha::new(
+            provisioner_address,
+            loader,
+            args.env,
+            Duration::from_secs(args.drain_timeout),
+        );
 
         let svc = RuntimeServer::new(alpha);
         server_builder.add_service(svc)
@@ -137,10 +145,16 @@ pub struct Alpha<L, S> {
     loader: Mutex<Option<L>>,
     service: Mutex<Option<S>>,
     env: Environment,
+    drain_timeout: Duration,
 }
 
 impl<L, S> Alpha<L, S> {
-    pub fn new(provisioner_address: Endpoint, loader: L, env: Environment) -> Self {
+    pub fn new(
+        provisioner_address: Endpoint,
+        loader: L,
+        env: Environment,
+        drain_timeout: Duration,
+    ) -> Self {
         let (stopped_tx, _stopped_rx) = broadcast::channel(10);
 
         Self {
@@ -150,6 +164,7 @@ impl<L, S> Alpha<L, S> {
             loader: Mutex::new(Some(loader)),
             service: Mutex::new(None),
             env,
+            drain_timeout,
         }
     }
 }
@@ -201,6 +216,7 @@ where
             resources,
             secrets,
             service_name,
+            project_name,
         } = request.into_inner();
         println!("loading alpha service at {path}");
 
@@ -230,8 +246,21 @@ where
         // Sorts secrets by key
         let secrets = BTreeMap::from_iter(secrets.into_iter().map(|(k, v)| (k, Secret::new(v))));
 
-        let factory =
-            ProvisionerFactory::new(provisioner_client, service_name, secrets, self.env, claim);
+        // Older deployers don't send a project name, in which case the service is its own project
+        let project_name = if project_name.is_empty() {
+            service_name.clone()
+        } else {
+            project_name
+        };
+
+        let factory = ProvisionerFactory::new(
+            provisioner_client,
+            project_name,
+            service_name,
+            secrets,
+            self.env,
+            claim,
+        );
 
         let loader = self.loader.lock().unwrap().deref_mut().take().unwrap();
 
@@ -322,16 +351,21 @@ where
 
         println!("Starting on {service_address}");
 
+        let no_connections = !service.accepts_connections();
+
         let (kill_tx, kill_rx) = tokio::sync::oneshot::channel();
         *self.kill_tx.lock().unwrap() = Some(kill_tx);
 
         let stopped_tx = self.stopped_tx.clone();
+        let drain_timeout = self.drain_timeout;
 
         let handle = tokio::runtime::Handle::current();
 
         // start service as a background task with a kill receiver
         tokio::spawn(async move {
-            let mut background = handle.spawn(service.bind(service_address));
+            let (shutdown_tx, shutdown) = Shutdown::new();
+            let mut background =
+                handle.spawn(service.bind_with_shutdown(service_address, shutdown));
 
             tokio::select! {
                 res = &mut background => {
@@ -368,6 +402,19 @@ where
                     }
                 },
                 message = kill_rx => {
+                    shutdown_tx.send();
+
+                    println!(
+                        "waiting up to {}s for the service to shut down",
+                        drain_timeout.as_secs()
+                    );
+                    if tokio::time::timeout(drain_timeout, &mut background).await.is_err() {
+                        println!("service did not shut down in time, will now abort it");
+                        background.abort();
+                        let _ = background.await;
+                    }
+
+                    // Only report the stop once the service is gone so that a stop request can wait on it
                     match message {
                         Ok(_) => {
                             let _ = stopped_tx
@@ -376,15 +423,14 @@ where
                         }
                         Err(_) => println!("the kill sender dropped")
                     };
-
-                    println!("will now abort the service");
-                    background.abort();
-                    background.await.unwrap().expect("to stop service");
                 }
             }
         });
 
-        let message = StartResponse { success: true };
+        let message = StartResponse {
+            success: true,
+            no_connections: Some(no_connections),
+        };
 
         Ok(Response::new(message))
     }
@@ -393,11 +439,32 @@ where
         let kill_tx = self.kill_tx.lock().unwrap().deref_mut().take();
 
         if let Some(kill_tx) = kill_tx {
+            // Subscribe before signalling so the stop of this service cannot be missed
+            let mut stopped_rx = self.stopped_tx.subscribe();
+
             if kill_tx.send("stopping deployment".to_owned()).is_err() {
                 println!("the kill receiver dropped");
                 return Err(Status::internal("failed to stop deployment"));
             }
 
+            // Wait for the service to drain so callers can safely kill the process after this returns.
+            // The service can also end or crash just as it is asked to stop, in which case that is
+            // the stop reported, or was already reported before subscribing, hence the timeout.
+            let stopped = async {
+                loop {
+                    match stopped_rx.recv().await {
+                        Ok(_) | Err(broadcast::error::RecvError::Closed) => break,
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    }
+                }
+            };
+            if tokio::time::timeout(self.drain_timeout + STOP_MARGIN, stopped)
+                .await
+                .is_err()
+            {
+                println!("service did not report stopping, assuming it is gone");
+            }
+
             Ok(Response::new(StopResponse { success: true }))
         } else {
             println!("failed to stop deployment");
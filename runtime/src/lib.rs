@@ -205,7 +205,9 @@
 
 // Public API
 pub use shuttle_codegen::main;
-pub use shuttle_service::{CustomError, Error, Factory, IntoResource, ResourceBuilder, Service};
+pub use shuttle_service::{
+    CustomError, Error, Factory, IntoResource, ResourceBuilder, Service, Shutdown, ShutdownSender,
+};
 
 // Useful re-exports
 pub use async_trait::async_trait;
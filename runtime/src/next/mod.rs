@@ -113,7 +113,10 @@ impl Runtime for AxumWasm {
 
         tokio::spawn(run_until_stopped(router, address, kill_rx, stopped_tx));
 
-        let message = StartResponse { success: true };
+        let message = StartResponse {
+            success: true,
+            no_connections: Some(false),
+        };
 
         Ok(tonic::Response::new(message))
     }
@@ -6,16 +6,18 @@ use shuttle_common::{
     constants::STORAGE_DIRNAME,
     database,
     secrets::Secret,
-    DatabaseInfo,
+    DatabaseInfo, ObjectStoreInfo,
 };
 use shuttle_proto::provisioner::{
     provisioner_client::ProvisionerClient, ContainerRequest, ContainerResponse, DatabaseRequest,
+    ObjectStoreRequest,
 };
 use shuttle_service::{DeploymentMetadata, Environment, Factory};
 use tonic::{transport::Channel, Request};
 
 /// A factory (service locator) which goes through the provisioner crate
 pub struct ProvisionerFactory {
+    project_name: String,
     service_name: String,
     provisioner_client: ProvisionerClient<ClaimService<InjectPropagation<Channel>>>,
     secrets: BTreeMap<String, Secret<String>>,
@@ -26,6 +28,7 @@ pub struct ProvisionerFactory {
 impl ProvisionerFactory {
     pub(crate) fn new(
         provisioner_client: ProvisionerClient<ClaimService<InjectPropagation<Channel>>>,
+        project_name: String,
         service_name: String,
         secrets: BTreeMap<String, Secret<String>>,
         env: Environment,
@@ -33,6 +36,7 @@ impl ProvisionerFactory {
     ) -> Self {
         Self {
             provisioner_client,
+            project_name,
             service_name,
             secrets,
             env,
@@ -46,9 +50,11 @@ impl Factory for ProvisionerFactory {
     async fn get_db_connection(
         &mut self,
         db_type: database::Type,
+        name: Option<&str>,
     ) -> Result<DatabaseInfo, shuttle_service::Error> {
         let mut request = Request::new(DatabaseRequest {
-            project_name: self.service_name.to_string(),
+            project_name: self.project_name.to_string(),
+            name: name.unwrap_or_default().to_string(),
             db_type: Some(db_type.into()),
         });
 
@@ -68,6 +74,29 @@ impl Factory for ProvisionerFactory {
         Ok(info)
     }
 
+    async fn get_object_store(
+        &mut self,
+        name: Option<&str>,
+    ) -> Result<ObjectStoreInfo, shuttle_service::Error> {
+        let mut request = Request::new(ObjectStoreRequest {
+            project_name: self.project_name.to_string(),
+            name: name.unwrap_or_default().to_string(),
+        });
+
+        if let Some(claim) = &self.claim {
+            request.extensions_mut().insert(claim.clone());
+        }
+
+        let response = self
+            .provisioner_client
+            .provision_object_store(request)
+            .await
+            .map_err(shuttle_service::error::CustomError::new)?
+            .into_inner();
+
+        Ok(response.into())
+    }
+
     async fn get_container(
         &mut self,
         req: ContainerRequest,
@@ -97,7 +126,7 @@ impl Factory for ProvisionerFactory {
     fn get_metadata(&self) -> DeploymentMetadata {
         DeploymentMetadata {
             env: self.env,
-            project_name: self.service_name.to_string(),
+            project_name: self.project_name.to_string(),
             service_name: self.service_name.to_string(),
             storage_path: PathBuf::from(STORAGE_DIRNAME),
         }
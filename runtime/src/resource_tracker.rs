@@ -1,6 +1,6 @@
 use std::sync::{Arc, Mutex};
 
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
 use shuttle_common::resource::{self, Type};
@@ -26,7 +26,12 @@ impl ResourceTracker {
     }
 
     /// Get the output of a resource that has been constructed in the past if it exists
-    pub fn get_cached_output(&self, r#type: Type, config: &Value) -> Option<Value> {
+    pub fn get_cached_output(
+        &self,
+        r#type: Type,
+        name: Option<&str>,
+        config: &Value,
+    ) -> Option<Value> {
         // Secrets are returning unit configs, which deserialised come as a `serde_json::Value::Null`.
         // We always return the cached output for them, even if they change from a previous deployment.
         // We have to always call `output()` on them to get the latest secrets, since we don't track a
@@ -37,17 +42,28 @@ impl ResourceTracker {
 
         self.past_resources
             .iter()
-            .find(|resource| resource.r#type == r#type && resource.config == *config)
+            .find(|resource| {
+                resource.r#type == r#type
+                    && resource.name.as_deref() == name
+                    && resource.config == *config
+            })
             .map(|resource| resource.data.clone())
     }
 
     /// Record a resource that has been requested
-    pub fn record_resource(&mut self, r#type: Type, config: Value, output: Value) {
+    pub fn record_resource(
+        &mut self,
+        r#type: Type,
+        name: Option<String>,
+        config: Value,
+        output: Value,
+    ) {
         self.new_resources
             .lock()
             .expect("to get lock on new resources")
             .push(resource::Response {
                 r#type,
+                name,
                 config,
                 data: output,
             })
@@ -72,14 +88,25 @@ where
     B: ResourceBuilder<Output = O>,
     O: Serialize + DeserializeOwned + IntoResource<R>,
 {
-    log!("Getting resource");
+    let name = builder.name().map(str::to_string);
+
+    match &name {
+        Some(name) if !resource::is_valid_name(name) => {
+            return Err(anyhow!(
+                "invalid resource name '{name}': use up to 32 lowercase letters, digits and dashes"
+            )
+            .into());
+        }
+        Some(name) => log!(format!("Getting resource named '{name}'")),
+        None => log!("Getting resource"),
+    }
 
     let config = serde_json::to_value(builder.config())
         .context("failed to turn builder config into a value")?;
 
     log!(format!("Using config: {}", config)); // TODO: This can contain secrets
 
-    let output = resource_tracker.get_cached_output(B::TYPE, &config)
+    let output = resource_tracker.get_cached_output(B::TYPE, name.as_deref(), &config)
         .and_then(|output| {
             log!("Found past output for this config");
             match serde_json::from_value(output) {
@@ -110,7 +137,7 @@ where
 
     let output_value =
         serde_json::to_value(&output).context("failed to turn builder output into a JSON value")?;
-    resource_tracker.record_resource(B::TYPE, config, output_value);
+    resource_tracker.record_resource(B::TYPE, name, config, output_value);
 
     Ok(output)
 }
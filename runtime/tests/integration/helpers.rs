@@ -10,8 +10,10 @@ use shuttle_common::claims::{ClaimService, InjectPropagation};
 use shuttle_proto::{
     provisioner::{
         provisioner_server::{Provisioner, ProvisionerServer},
-        ContainerRequest, ContainerResponse, DatabaseDeletionResponse, DatabaseRequest,
-        DatabaseResponse, Ping, Pong,
+        Backup, ContainerRequest, ContainerResponse, DatabaseDeletionResponse, DatabaseRequest,
+        DatabaseResponse, ListBackupsResponse, ObjectStoreDeletionResponse, ObjectStoreRequest,
+        ObjectStoreResponse, Ping, Pong, RestoreRequest, RestoreResponse,
+        RotateCredentialsResponse,
     },
     runtime::runtime_client::RuntimeClient,
 };
@@ -55,6 +57,7 @@ pub async fn spawn_runtime(project_path: String, service_name: &str) -> Result<T
         Environment::Local,
         &format!("http://{}", provisioner_address),
         None,
+        None,
         runtime_port,
         runtime_executable,
         Path::new(&project_path),
@@ -111,6 +114,48 @@ impl Provisioner for DummyProvisioner {
         panic!("did not expect any runtime test to delete dbs")
     }
 
+    async fn provision_object_store(
+        &self,
+        _request: Request<ObjectStoreRequest>,
+    ) -> Result<Response<ObjectStoreResponse>, Status> {
+        panic!("did not expect any runtime test to use object stores")
+    }
+
+    async fn delete_object_store(
+        &self,
+        _request: Request<ObjectStoreRequest>,
+    ) -> Result<Response<ObjectStoreDeletionResponse>, Status> {
+        panic!("did not expect any runtime test to delete object stores")
+    }
+
+    async fn backup_database(
+        &self,
+        _request: Request<DatabaseRequest>,
+    ) -> Result<Response<Backup>, Status> {
+        panic!("did not expect any runtime test to back up dbs")
+    }
+
+    async fn list_backups(
+        &self,
+        _request: Request<DatabaseRequest>,
+    ) -> Result<Response<ListBackupsResponse>, Status> {
+        panic!("did not expect any runtime test to list backups")
+    }
+
+    async fn restore_database(
+        &self,
+        _request: Request<RestoreRequest>,
+    ) -> Result<Response<RestoreResponse>, Status> {
+        panic!("did not expect any runtime test to restore dbs")
+    }
+
+    async fn rotate_database_credentials(
+        &self,
+        _request: Request<DatabaseRequest>,
+    ) -> Result<Response<RotateCredentialsResponse>, Status> {
+        panic!("did not expect any runtime test to rotate db credentials")
+    }
+
     async fn health_check(&self, _request: Request<Ping>) -> Result<Response<Pong>, Status> {
         panic!("did not expect any runtime test to do a provisioner health check")
     }
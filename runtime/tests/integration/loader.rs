@@ -17,6 +17,7 @@ async fn bind_panic() {
 
     let load_request = tonic::Request::new(LoadRequest {
         path: bin_path,
+        project_name: Default::default(),
         service_name,
         resources: Default::default(),
         secrets,
@@ -66,6 +67,7 @@ async fn bind_panic_owned() {
 
     let load_request = tonic::Request::new(LoadRequest {
         path: bin_path,
+        project_name: Default::default(),
         service_name,
         resources: Default::default(),
         secrets,
@@ -113,6 +115,7 @@ async fn loader_panic() {
 
     let load_request = tonic::Request::new(LoadRequest {
         path: bin_path,
+        project_name: Default::default(),
         service_name,
         resources: Default::default(),
         secrets,
@@ -144,6 +147,7 @@ async fn loader_panic_owned() {
 
     let load_request = tonic::Request::new(LoadRequest {
         path: bin_path,
+        project_name: Default::default(),
         service_name,
         resources: Default::default(),
         secrets,
@@ -1,2 +1,3 @@
 pub mod helpers;
 pub mod loader;
+pub mod stop;
@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+use shuttle_proto::runtime::{
+    LoadRequest, StartRequest, StopReason, StopRequest, SubscribeStopRequest,
+};
+
+use crate::helpers::{spawn_runtime, TestRuntime};
+
+#[tokio::test]
+async fn graceful_shutdown() {
+    let project_path = format!(
+        "{}/tests/resources/graceful-shutdown",
+        env!("CARGO_MANIFEST_DIR")
+    );
+
+    let TestRuntime {
+        bin_path,
+        service_name,
+        secrets,
+        mut runtime_client,
+        runtime_address,
+        runtime: _runtime, // Keep it to not be dropped and have the process killed.
+    } = spawn_runtime(project_path, "graceful-shutdown")
+        .await
+        .unwrap();
+
+    let load_request = tonic::Request::new(LoadRequest {
+        path: bin_path,
+        project_name: Default::default(),
+        service_name,
+        resources: Default::default(),
+        secrets,
+    });
+
+    runtime_client.load(load_request).await.unwrap();
+
+    let mut stream = runtime_client
+        .subscribe_stop(tonic::Request::new(SubscribeStopRequest {}))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let start_request = StartRequest {
+        ip: runtime_address.to_string(),
+    };
+
+    runtime_client
+        .start(tonic::Request::new(start_request))
+        .await
+        .unwrap();
+
+    let stopping = Instant::now();
+    let response = runtime_client
+        .stop(tonic::Request::new(StopRequest {}))
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert!(response.success);
+    assert!(
+        stopping.elapsed() >= Duration::from_millis(500),
+        "stop should wait for the service to drain"
+    );
+
+    let reason = stream.message().await.unwrap().unwrap();
+
+    assert_eq!(reason.reason, StopReason::Request as i32);
+}
@@ -0,0 +1,28 @@
+struct MyService;
+
+#[shuttle_runtime::async_trait]
+impl shuttle_runtime::Service for MyService {
+    async fn bind(mut self, addr: std::net::SocketAddr) -> Result<(), shuttle_runtime::Error> {
+        self.bind_with_shutdown(addr, shuttle_runtime::Shutdown::never())
+            .await
+    }
+
+    async fn bind_with_shutdown(
+        self,
+        _: std::net::SocketAddr,
+        shutdown: shuttle_runtime::Shutdown,
+    ) -> Result<(), shuttle_runtime::Error> {
+        shutdown.recv().await;
+
+        // Pretend to finish some in-flight work
+        shuttle_runtime::tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        println!("finished draining");
+
+        Ok(())
+    }
+}
+
+#[shuttle_runtime::main]
+async fn graceful_shutdown() -> Result<MyService, shuttle_runtime::Error> {
+    Ok(MyService)
+}
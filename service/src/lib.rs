@@ -1,5 +1,8 @@
 use std::collections::BTreeMap;
+use std::future::{poll_fn, Future};
 use std::net::SocketAddr;
+use std::pin::pin;
+use std::task::Poll;
 
 use async_trait::async_trait;
 use serde::{de::DeserializeOwned, Serialize};
@@ -8,27 +11,38 @@ pub use shuttle_common::{
     deployment::{DeploymentMetadata, Environment},
     resource,
     secrets::Secret,
-    DatabaseInfo, DatabaseResource, DbInput, SecretStore,
+    DatabaseInfo, DatabaseResource, DbInput, ObjectStoreInfo, SecretStore,
 };
 pub use shuttle_proto::provisioner::{ContainerRequest, ContainerResponse};
 
 pub use crate::error::{CustomError, Error};
+pub use crate::shutdown::{Shutdown, ShutdownSender};
 
 #[cfg(feature = "builder")]
 pub mod builder;
 pub mod error;
 #[cfg(feature = "runner")]
 pub mod runner;
+mod shutdown;
 
 /// An interface for the provisioner used in [`ResourceBuilder::output`].
 #[async_trait]
 pub trait Factory: Send + Sync {
-    /// Provision a Shuttle database and get the connection information
+    /// Provision a Shuttle database and get the connection information.
+    /// A `name` provisions a database of its own instead of the default one for the type.
     async fn get_db_connection(
         &mut self,
         db_type: database::Type,
+        name: Option<&str>,
     ) -> Result<DatabaseInfo, crate::Error>;
 
+    /// Provision a Shuttle object store bucket and get credentials scoped to it.
+    /// A `name` provisions a bucket of its own instead of the default one.
+    async fn get_object_store(
+        &mut self,
+        name: Option<&str>,
+    ) -> Result<ObjectStoreInfo, crate::Error>;
+
     /// Start a Docker container. Only used in local runs.
     async fn get_container(
         &mut self,
@@ -77,6 +91,12 @@ pub trait ResourceBuilder: Default {
     /// will be passed to [`ResourceBuilder::build`].
     fn config(&self) -> &Self::Config;
 
+    /// The name of this resource, needed when a service uses more than one resource of the same
+    /// [`ResourceBuilder::TYPE`]. Resources without a name are the default resource of their type.
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
     /// Construct this resource with the help of metadata and by calling provisioner methods in the [`Factory`].
     ///
     /// This method is where the actual resource provisioning should take place and is expected to take the longest. It
@@ -116,4 +136,40 @@ pub trait Service: Send {
     /// The passed [`SocketAddr`] receives proxied HTTP traffic from you Shuttle subdomain (or custom domain).
     /// Binding to the address is only relevant if this service is an HTTP server.
     async fn bind(mut self, addr: SocketAddr) -> Result<(), error::Error>;
+
+    /// Same as [`Service::bind`], but also receives a [`Shutdown`] signal which fires when the runtime is asked
+    /// to stop this deployment.
+    ///
+    /// Implementors can override this to stop accepting new work and finish in-flight requests once the signal fires.
+    /// The runtime gives the service a drain period to return before it gets aborted.
+    ///
+    /// The default implementation calls [`Service::bind`] and drops it as soon as the signal fires.
+    async fn bind_with_shutdown(
+        self,
+        addr: SocketAddr,
+        shutdown: Shutdown,
+    ) -> Result<(), error::Error>
+    where
+        Self: Sized,
+    {
+        let mut bind = self.bind(addr);
+        let mut shutdown = pin!(shutdown.recv());
+
+        poll_fn(|cx| {
+            if let Poll::Ready(res) = bind.as_mut().poll(cx) {
+                return Poll::Ready(res);
+            }
+
+            shutdown.as_mut().poll(cx).map(Ok)
+        })
+        .await
+    }
+
+    /// Whether this service accepts connections on the address it is bound to.
+    ///
+    /// A new deployment of a service which does only gets traffic once it accepts connections, and fails if it
+    /// never does. Services which are not HTTP servers, like bots, should return `false`.
+    fn accepts_connections(&self) -> bool {
+        true
+    }
 }
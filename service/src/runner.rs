@@ -15,11 +15,13 @@ use tokio::process;
 use tower::ServiceBuilder;
 use tracing::{info, trace};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn start(
     wasm: bool,
     environment: Environment,
     provisioner_address: &str,
     auth_uri: Option<&String>,
+    drain_timeout: Option<Duration>,
     port: u16,
     runtime_executable: PathBuf,
     project_path: &Path,
@@ -29,6 +31,7 @@ pub async fn start(
 )> {
     let port = &port.to_string();
     let environment = &environment.to_string();
+    let drain_timeout = drain_timeout.map(|timeout| timeout.as_secs().to_string());
 
     let args = if wasm {
         vec!["--port", port]
@@ -46,6 +49,10 @@ pub async fn start(
             args.append(&mut vec!["--auth-uri", auth_uri]);
         }
 
+        if let Some(drain_timeout) = &drain_timeout {
+            args.append(&mut vec!["--drain-timeout", drain_timeout]);
+        }
+
         args
     };
 
@@ -0,0 +1,128 @@
+use std::future::{pending, poll_fn};
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+
+#[derive(Debug, Default)]
+struct State {
+    fired: bool,
+    wakers: Vec<Waker>,
+}
+
+impl State {
+    fn fire(&mut self) {
+        self.fired = true;
+
+        for waker in self.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// The runtime's end of a [`Shutdown`] signal.
+///
+/// The signal fires on [`ShutdownSender::send`], or when this sender is dropped.
+#[derive(Debug)]
+pub struct ShutdownSender {
+    state: Arc<Mutex<State>>,
+}
+
+impl ShutdownSender {
+    /// Fire the signal for every clone of its [`Shutdown`]
+    pub fn send(&self) {
+        self.state.lock().unwrap().fire();
+    }
+}
+
+impl Drop for ShutdownSender {
+    fn drop(&mut self) {
+        self.send();
+    }
+}
+
+/// A signal that resolves once the runtime wants the service to shut down gracefully.
+///
+/// It is handed to [`crate::Service::bind_with_shutdown`] and can be cloned freely, for example to
+/// pass it to multiple servers running in the same service.
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    state: Option<Arc<Mutex<State>>>,
+}
+
+impl Shutdown {
+    /// Create a new signal together with the sender that triggers it.
+    pub fn new() -> (ShutdownSender, Self) {
+        let state = Arc::new(Mutex::new(State::default()));
+
+        (
+            ShutdownSender {
+                state: state.clone(),
+            },
+            Self { state: Some(state) },
+        )
+    }
+
+    /// A signal that never fires. Useful to implement [`crate::Service::bind`] in terms of
+    /// [`crate::Service::bind_with_shutdown`].
+    pub fn never() -> Self {
+        Self { state: None }
+    }
+
+    /// Wait for the signal to fire
+    pub async fn recv(self) {
+        let Some(state) = self.state else {
+            return pending().await;
+        };
+
+        poll_fn(|cx| {
+            let mut state = state.lock().unwrap();
+
+            if state.fired {
+                Poll::Ready(())
+            } else {
+                if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    state.wakers.push(cx.waker().clone());
+                }
+
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Shutdown;
+
+    #[tokio::test]
+    async fn fires_on_send() {
+        let (tx, shutdown) = Shutdown::new();
+        let waiter = tokio::spawn(shutdown.clone().recv());
+
+        tx.send();
+
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("signal to fire")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn fires_on_drop() {
+        let (tx, shutdown) = Shutdown::new();
+        drop(tx);
+
+        tokio::time::timeout(Duration::from_secs(1), shutdown.recv())
+            .await
+            .expect("signal to fire");
+    }
+
+    #[tokio::test]
+    async fn never_fires() {
+        let res = tokio::time::timeout(Duration::from_millis(50), Shutdown::never().recv()).await;
+
+        assert!(res.is_err(), "signal should never fire");
+    }
+}
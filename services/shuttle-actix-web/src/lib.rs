@@ -12,6 +12,16 @@ where
     F: FnOnce(&mut actix_web::web::ServiceConfig) + Send + Clone + 'static,
 {
     async fn bind(mut self, addr: SocketAddr) -> Result<(), shuttle_runtime::Error> {
+        self.bind_with_shutdown(addr, shuttle_runtime::Shutdown::never())
+            .await
+    }
+
+    /// Same as [`ActixWebService::bind`], but gracefully stops the server once the shutdown signal fires.
+    async fn bind_with_shutdown(
+        self,
+        addr: SocketAddr,
+        shutdown: shuttle_runtime::Shutdown,
+    ) -> Result<(), shuttle_runtime::Error> {
         // Start a worker for each cpu, but no more than 4.
         let worker_count = num_cpus::get().min(4);
 
@@ -21,6 +31,12 @@ where
                 .bind(addr)?
                 .run();
 
+        let handle = server.handle();
+        shuttle_runtime::tokio::spawn(async move {
+            shutdown.recv().await;
+            handle.stop(true).await;
+        });
+
         server.await.map_err(shuttle_runtime::CustomError::new)?;
 
         Ok(())
@@ -8,8 +8,18 @@ where
     T: poem::Endpoint + Send + 'static,
 {
     async fn bind(mut self, addr: std::net::SocketAddr) -> Result<(), shuttle_runtime::Error> {
+        self.bind_with_shutdown(addr, shuttle_runtime::Shutdown::never())
+            .await
+    }
+
+    /// Same as [`PoemService::bind`], but gracefully stops the server once the shutdown signal fires.
+    async fn bind_with_shutdown(
+        self,
+        addr: std::net::SocketAddr,
+        shutdown: shuttle_runtime::Shutdown,
+    ) -> Result<(), shuttle_runtime::Error> {
         poem::Server::new(poem::listener::TcpListener::bind(addr))
-            .run(self.0)
+            .run_with_graceful_shutdown(self.0, shutdown.recv(), None)
             .await
             .map_err(shuttle_runtime::CustomError::new)?;
 
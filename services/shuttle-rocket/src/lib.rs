@@ -9,6 +9,16 @@ impl shuttle_runtime::Service for RocketService {
     /// Takes the router that is returned by the user in their [shuttle_runtime::main] function
     /// and binds to an address passed in by shuttle.
     async fn bind(mut self, addr: SocketAddr) -> Result<(), shuttle_runtime::Error> {
+        self.bind_with_shutdown(addr, shuttle_runtime::Shutdown::never())
+            .await
+    }
+
+    /// Same as [`RocketService::bind`], but notifies rocket to shut down gracefully once the shutdown signal fires.
+    async fn bind_with_shutdown(
+        self,
+        addr: SocketAddr,
+        shutdown_signal: shuttle_runtime::Shutdown,
+    ) -> Result<(), shuttle_runtime::Error> {
         let shutdown = rocket::config::Shutdown {
             ctrlc: false,
             ..rocket::config::Shutdown::default()
@@ -23,9 +33,20 @@ impl shuttle_runtime::Service for RocketService {
             .merge((rocket::Config::LOG_LEVEL, rocket::config::LogLevel::Off))
             .merge((rocket::Config::SHUTDOWN, shutdown));
 
-        let _rocket = self
+        let rocket = self
             .0
             .configure(config)
+            .ignite()
+            .await
+            .map_err(shuttle_runtime::CustomError::new)?;
+
+        let handle = rocket.shutdown();
+        shuttle_runtime::tokio::spawn(async move {
+            shutdown_signal.recv().await;
+            handle.notify();
+        });
+
+        let _rocket = rocket
             .launch()
             .await
             .map_err(shuttle_runtime::CustomError::new)?;
@@ -1,5 +1,5 @@
 #![doc = include_str!("../README.md")]
-use shuttle_runtime::{CustomError, Error};
+use shuttle_runtime::{CustomError, Error, Shutdown};
 use std::net::SocketAddr;
 
 #[cfg(feature = "serenity")]
@@ -14,11 +14,34 @@ pub struct SerenityService(pub Client);
 impl shuttle_runtime::Service for SerenityService {
     /// Takes the client that is returned by the user in their [shuttle_runtime::main] function
     /// and starts it.
-    async fn bind(mut self, _addr: SocketAddr) -> Result<(), Error> {
+    async fn bind(mut self, addr: SocketAddr) -> Result<(), Error> {
+        self.bind_with_shutdown(addr, Shutdown::never()).await
+    }
+
+    /// Same as [`SerenityService::bind`], but closes all gateway sessions cleanly once the shutdown signal fires.
+    async fn bind_with_shutdown(
+        mut self,
+        _addr: SocketAddr,
+        shutdown: Shutdown,
+    ) -> Result<(), Error> {
+        let shard_manager = self.0.shard_manager.clone();
+        shuttle_runtime::tokio::spawn(async move {
+            shutdown.recv().await;
+            #[cfg(feature = "serenity")]
+            shard_manager.shutdown_all().await;
+            #[cfg(feature = "serenity-0-11")]
+            shard_manager.lock().await.shutdown_all().await;
+        });
+
         self.0.start_autosharded().await.map_err(CustomError::new)?;
 
         Ok(())
     }
+
+    /// Bots only talk to the Discord gateway, so nothing ever connects to the address.
+    fn accepts_connections(&self) -> bool {
+        false
+    }
 }
 
 impl From<Client> for SerenityService {
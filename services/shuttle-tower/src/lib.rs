@@ -1,5 +1,5 @@
 #![doc = include_str!("../README.md")]
-use shuttle_runtime::{CustomError, Error};
+use shuttle_runtime::{CustomError, Error, Shutdown};
 use std::net::SocketAddr;
 
 /// A wrapper type for [tower::Service] so we can implement [shuttle_runtime::Service] for it.
@@ -19,9 +19,16 @@ where
     /// Takes the service that is returned by the user in their [shuttle_runtime::main] function
     /// and binds to an address passed in by shuttle.
     async fn bind(mut self, addr: SocketAddr) -> Result<(), Error> {
+        self.bind_with_shutdown(addr, Shutdown::never()).await
+    }
+
+    /// Same as [`TowerService::bind`], but stops accepting new connections once the shutdown
+    /// signal fires and waits for in-flight requests to finish.
+    async fn bind_with_shutdown(self, addr: SocketAddr, shutdown: Shutdown) -> Result<(), Error> {
         let shared = tower::make::Shared::new(self.0);
         hyper::Server::bind(&addr)
             .serve(shared)
+            .with_graceful_shutdown(shutdown.recv())
             .await
             .map_err(CustomError::new)?;
 
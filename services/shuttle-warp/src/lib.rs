@@ -1,5 +1,5 @@
 #![doc = include_str!("../README.md")]
-use shuttle_runtime::Error;
+use shuttle_runtime::{Error, Shutdown};
 use std::net::SocketAddr;
 use std::ops::Deref;
 
@@ -15,7 +15,15 @@ where
     /// Takes the router that is returned by the user in their [shuttle_runtime::main] function
     /// and binds to an address passed in by shuttle.
     async fn bind(mut self, addr: SocketAddr) -> Result<(), Error> {
-        warp::serve((*self).clone()).run(addr).await;
+        self.bind_with_shutdown(addr, Shutdown::never()).await
+    }
+
+    /// Same as [`WarpService::bind`], but stops accepting new connections once the shutdown
+    /// signal fires and waits for in-flight requests to finish.
+    async fn bind_with_shutdown(self, addr: SocketAddr, shutdown: Shutdown) -> Result<(), Error> {
+        let (_, server) =
+            warp::serve((*self).clone()).bind_with_graceful_shutdown(addr, shutdown.recv());
+        server.await;
         Ok(())
     }
 }